@@ -6,13 +6,14 @@ use crate::success::{Success, FAIL, OK};
 use crate::yaml::{size_t, yaml_char_t};
 use crate::{
     libc, PointerExt, YamlAliasEvent, YamlAliasToken, YamlAnchorToken,
-    YamlAnyEncoding, YamlBreakT, YamlDocumentEndEvent,
-    YamlDocumentStartEvent, YamlDocumentT, YamlEmitterStateT,
-    YamlEmitterT, YamlEncodingT, YamlEventT, YamlMappingEndEvent,
-    YamlMappingNode, YamlMappingStartEvent, YamlMappingStyleT,
-    YamlMarkT, YamlNodeItemT, YamlNodePairT, YamlNodeT,
-    YamlParserStateT, YamlParserT, YamlReadHandlerT, YamlScalarEvent,
-    YamlScalarNode, YamlScalarStyleT, YamlScalarToken,
+    YamlAnyEncoding, YamlBreakT, YamlCommentToken,
+    YamlDocumentEndEvent, YamlDocumentStartEvent, YamlDocumentT,
+    YamlEmitterStateT, YamlEmitterT, YamlEncodingT, YamlEventCommentsT,
+    YamlEventT, YamlMappingEndEvent, YamlMappingNode,
+    YamlMappingStartEvent, YamlMappingStyleT, YamlMarkT, YamlNodeItemT,
+    YamlNodePairT, YamlNodeT, YamlParserStateT, YamlParserT,
+    YamlReadHandlerT, YamlScalarEvent, YamlScalarNode,
+    YamlScalarStyleT, YamlScalarToken, YamlSchemaT,
     YamlSequenceEndEvent, YamlSequenceNode, YamlSequenceStartEvent,
     YamlSequenceStyleT, YamlSimpleKeyT, YamlStreamEndEvent,
     YamlStreamStartEvent, YamlTagDirectiveT, YamlTagDirectiveToken,
@@ -24,7 +25,10 @@ use core::ptr::{self, addr_of_mut};
 const INPUT_RAW_BUFFER_SIZE: usize = 16384;
 const INPUT_BUFFER_SIZE: usize = INPUT_RAW_BUFFER_SIZE * 3;
 const OUTPUT_BUFFER_SIZE: usize = 16384;
-const OUTPUT_RAW_BUFFER_SIZE: usize = OUTPUT_BUFFER_SIZE * 2 + 2;
+// Every byte of the output buffer may become a UTF-32 character.
+const OUTPUT_RAW_BUFFER_SIZE: usize = OUTPUT_BUFFER_SIZE * 4 + 4;
+const MAX_DEPTH: size_t = 1000;
+const MAX_ALIAS_EXPANSION: size_t = 1_000_000;
 
 /// Allocate memory using the system's `malloc` function.
 ///
@@ -326,11 +330,19 @@ pub unsafe fn yaml_parser_initialize(
     BUFFER_INIT!((*parser).raw_buffer, INPUT_RAW_BUFFER_SIZE);
     BUFFER_INIT!((*parser).buffer, INPUT_BUFFER_SIZE);
     QUEUE_INIT!((*parser).tokens, YamlTokenT);
+    QUEUE_INIT!((*parser).pending_comments, YamlTokenT);
     STACK_INIT!((*parser).indents, libc::c_int);
     STACK_INIT!((*parser).simple_keys, YamlSimpleKeyT);
     STACK_INIT!((*parser).states, YamlParserStateT);
     STACK_INIT!((*parser).marks, YamlMarkT);
     STACK_INIT!((*parser).tag_directives, YamlTagDirectiveT);
+    (*parser).max_input_size = size_t::MAX;
+    (*parser).max_queued_tokens = size_t::MAX;
+    (*parser).max_scalar_length = size_t::MAX;
+    (*parser).max_name_length = size_t::MAX;
+    (*parser).max_depth = MAX_DEPTH;
+    (*parser).allow_aliases = true;
+    (*parser).max_alias_expansion = MAX_ALIAS_EXPANSION;
     OK
 }
 
@@ -354,6 +366,14 @@ pub unsafe fn yaml_parser_delete(parser: *mut YamlParserT) {
         yaml_token_delete(addr_of_mut!(DEQUEUE!((*parser).tokens)));
     }
     QUEUE_DEL!((*parser).tokens);
+    while !QUEUE_EMPTY!((*parser).pending_comments) {
+        yaml_token_delete(addr_of_mut!(DEQUEUE!(
+            (*parser).pending_comments
+        )));
+    }
+    QUEUE_DEL!((*parser).pending_comments);
+    yaml_free((*parser).comment_before as *mut libc::c_void);
+    yaml_free((*parser).comment_inline as *mut libc::c_void);
     STACK_DEL!((*parser).indents);
     STACK_DEL!((*parser).simple_keys);
     STACK_DEL!((*parser).states);
@@ -483,6 +503,296 @@ pub unsafe fn yaml_parser_set_encoding(
     (*parser).encoding = encoding;
 }
 
+/// Set if comment tokens should be produced.
+///
+/// Comments are skipped by default. When enabled, yaml_parser_scan() also
+/// produces a YamlCommentToken for every comment, classified as leading,
+/// trailing or document-level, and yaml_parser_parse() attaches them to
+/// the neighbouring events (see YamlEventCommentsT). yaml_parser_load()
+/// discards them.
+///
+/// # Safety
+///
+/// - `parser` must be a valid, non-null pointer to a properly initialized `YamlParserT` struct.
+/// - The function must be called before the first token is scanned.
+///
+pub unsafe fn yaml_parser_set_comments(
+    parser: *mut YamlParserT,
+    comments: bool,
+) {
+    __assert!(!parser.is_null());
+    (*parser).comments = comments;
+}
+
+/// Set if the YAML 1.2 rules are followed.
+///
+/// By default, the parser keeps libyaml's behaviour, which follows YAML
+/// 1.1 where the two versions differ. In strict mode, it follows YAML
+/// 1.2.2 instead:
+///
+/// - anchors and aliases may use any character but spaces and flow
+///   indicators, such as `&a:` or `*😀`;
+/// - keys may be empty, as in `: value` or `[ : value ]`;
+/// - the keys of flow mappings may span several lines, with the `:` on
+///   a later line;
+/// - in flow collections, `:` is only a value indicator before a space,
+///   a flow indicator, or right after a quoted or flow collection key,
+///   as in `{"key":value}`;
+/// - tabs may separate tokens on a line, as in `-\tvalue`;
+/// - block scalars may be unindented at the top level;
+/// - a bare document may follow a `...` document end marker;
+/// - reserved directives are ignored, and `%YAML 1.x` is accepted for
+///   any minor version.
+///
+/// It also rejects documents libyaml lets through: comments must follow
+/// a space, directives must follow a `...` marker unless they open the
+/// stream, the lines of quoted scalars and flow collections must be
+/// indented more than the block collection around them, and the leading
+/// empty lines of a block scalar may not be indented more than its
+/// content.
+///
+/// # Safety
+///
+/// - `parser` must be a valid, non-null pointer to a properly initialized `YamlParserT` struct.
+/// - The function must be called before the first token is scanned.
+///
+pub unsafe fn yaml_parser_set_strict(
+    parser: *mut YamlParserT,
+    strict: bool,
+) {
+    __assert!(!parser.is_null());
+    (*parser).strict = strict;
+}
+
+/// Set the schema resolving untagged scalars.
+///
+/// yaml_parser_load() tags every scalar without a tag, or with the `!`
+/// non-specific tag, as a string by default, which is what the failsafe
+/// schema does. With another schema, the untagged plain scalars matching
+/// one of its null, boolean, integer or floating-point forms are tagged
+/// accordingly instead. See the resolver module for the forms of each
+/// schema and for constructing their values.
+///
+/// # Safety
+///
+/// - `parser` must be a valid, non-null pointer to a properly initialized `YamlParserT` struct.
+///
+pub unsafe fn yaml_parser_set_schema(
+    parser: *mut YamlParserT,
+    schema: YamlSchemaT,
+) {
+    __assert!(!parser.is_null());
+    (*parser).schema = schema;
+}
+
+/// Set the maximum number of bytes read from the input.
+///
+/// Once the input grows past `max_input_size` bytes, the parser fails with
+/// a YamlReaderError and the "input size limit exceeded" problem, at the
+/// offset of the first byte over the limit. The input is unlimited by
+/// default.
+///
+/// # Safety
+///
+/// - `parser` must be a valid, non-null pointer to a properly initialized `YamlParserT` struct.
+///
+pub unsafe fn yaml_parser_set_max_input_size(
+    parser: *mut YamlParserT,
+    max_input_size: size_t,
+) {
+    __assert!(!parser.is_null());
+    (*parser).max_input_size = max_input_size;
+}
+
+/// Set the maximum length of a scalar.
+///
+/// A scalar whose value grows past `max_scalar_length` bytes fails the
+/// scanner with a YamlScannerError and the "scalar length limit exceeded"
+/// problem, marked where the text that did not fit starts. Scalars are
+/// unlimited by default.
+///
+/// # Safety
+///
+/// - `parser` must be a valid, non-null pointer to a properly initialized `YamlParserT` struct.
+///
+pub unsafe fn yaml_parser_set_max_scalar_length(
+    parser: *mut YamlParserT,
+    max_scalar_length: size_t,
+) {
+    __assert!(!parser.is_null());
+    (*parser).max_scalar_length = max_scalar_length;
+}
+
+/// Set the maximum length of anchors and tags.
+///
+/// The limit applies to anchor and alias names, and to tag handles and
+/// suffixes on their own. Going past `max_name_length` bytes fails the
+/// scanner with a YamlScannerError and the "anchor length limit exceeded"
+/// or "tag length limit exceeded" problem. Names are unlimited by
+/// default.
+///
+/// # Safety
+///
+/// - `parser` must be a valid, non-null pointer to a properly initialized `YamlParserT` struct.
+///
+pub unsafe fn yaml_parser_set_max_name_length(
+    parser: *mut YamlParserT,
+    max_name_length: size_t,
+) {
+    __assert!(!parser.is_null());
+    (*parser).max_name_length = max_name_length;
+}
+
+/// Set the maximum number of queued tokens.
+///
+/// The scanner reads ahead of the parser while it looks for the end of
+/// simple keys. Once more than `max_queued_tokens` tokens wait in the
+/// queue, it fails with a YamlScannerError and the "token queue limit
+/// exceeded" problem. The queue is unlimited by default.
+///
+/// # Safety
+///
+/// - `parser` must be a valid, non-null pointer to a properly initialized `YamlParserT` struct.
+///
+pub unsafe fn yaml_parser_set_max_queued_tokens(
+    parser: *mut YamlParserT,
+    max_queued_tokens: size_t,
+) {
+    __assert!(!parser.is_null());
+    (*parser).max_queued_tokens = max_queued_tokens;
+}
+
+/// Set the maximum nesting depth.
+///
+/// Every sequence or mapping opened inside another one adds a level.
+/// Going deeper than `max_depth` levels fails with a YamlParserError and
+/// the "recursion limit exceeded" problem, which guards the parser and
+/// loader stacks against hostile input such as `[[[[...`. The default
+/// limit is 1000; a limit of 0 only allows scalars and aliases.
+///
+/// # Safety
+///
+/// - `parser` must be a valid, non-null pointer to a properly initialized `YamlParserT` struct.
+///
+pub unsafe fn yaml_parser_set_max_depth(
+    parser: *mut YamlParserT,
+    max_depth: size_t,
+) {
+    __assert!(!parser.is_null());
+    (*parser).max_depth = max_depth;
+}
+
+/// Set if yaml_parser_load() accepts aliases.
+///
+/// Aliases are allowed by default. When they are not, the first alias in
+/// a document fails the load with a YamlComposerError. Anchors are still
+/// accepted.
+///
+/// # Safety
+///
+/// - `parser` must be a valid, non-null pointer to a properly initialized `YamlParserT` struct.
+///
+pub unsafe fn yaml_parser_set_allow_aliases(
+    parser: *mut YamlParserT,
+    allow_aliases: bool,
+) {
+    __assert!(!parser.is_null());
+    (*parser).allow_aliases = allow_aliases;
+}
+
+/// Set the alias expansion budget of yaml_parser_load().
+///
+/// The loader counts the nodes every alias stands for, as if it were
+/// replaced by a copy of the anchored node with its own aliases expanded
+/// in turn. Once the count for a document goes past
+/// `max_alias_expansion`, the load fails with a YamlComposerError and the
+/// "alias expansion limit exceeded" problem. This stops documents such as
+/// the "billion laughs" from blowing up in consumers that expand the
+/// tree. The default budget is 1000000 nodes; an alias to a collection
+/// that is still being loaded counts as a single node.
+///
+/// # Safety
+///
+/// - `parser` must be a valid, non-null pointer to a properly initialized `YamlParserT` struct.
+///
+pub unsafe fn yaml_parser_set_max_alias_expansion(
+    parser: *mut YamlParserT,
+    max_alias_expansion: size_t,
+) {
+    __assert!(!parser.is_null());
+    (*parser).max_alias_expansion = max_alias_expansion;
+}
+
+/// Set if yaml_parser_load() applies merge keys.
+///
+/// Merge keys are loaded as ordinary keys by default. When they are
+/// applied, following the YAML 1.1 merge type, a plain `<<` key (or any
+/// key tagged `!!merge`) whose value is a mapping, or a sequence of
+/// mappings, is replaced with the pairs of those mappings. The keys of
+/// the mapping itself take precedence over merged ones, and the mappings
+/// earlier in a sequence take precedence over later ones. Merged pairs
+/// are marked in YamlNodePairT. Merging anything else fails the load
+/// with a YamlComposerError.
+///
+/// # Safety
+///
+/// - `parser` must be a valid, non-null pointer to a properly initialized `YamlParserT` struct.
+///
+pub unsafe fn yaml_parser_set_merge_keys(
+    parser: *mut YamlParserT,
+    merge_keys: bool,
+) {
+    __assert!(!parser.is_null());
+    (*parser).merge_keys = merge_keys;
+}
+
+/// Set if yaml_parser_load() rejects duplicate mapping keys.
+///
+/// Duplicate keys are loaded as they are by default. When they are
+/// rejected, a mapping with two equal keys fails the load with a
+/// YamlComposerError pointing at the second key, with the first one as
+/// the context. Scalar keys are equal if they resolve to the same tag
+/// and value under the parser's schema, so `0x10` and `16` are the same
+/// key under the core schema, and collection keys are equal if their
+/// contents are. The keys are checked before any merge keys are
+/// applied.
+///
+/// # Safety
+///
+/// - `parser` must be a valid, non-null pointer to a properly initialized `YamlParserT` struct.
+///
+pub unsafe fn yaml_parser_set_unique_keys(
+    parser: *mut YamlParserT,
+    unique_keys: bool,
+) {
+    __assert!(!parser.is_null());
+    (*parser).unique_keys = unique_keys;
+}
+
+/// Set if scalar values may point into the input string.
+///
+/// With a string input, set by yaml_parser_set_input_string(), in UTF-8,
+/// the plain and quoted scalars that appear in the input as they are, on
+/// a single line and without escapes, are not copied. Their tokens and
+/// events point into the input instead and are marked as borrowed, so
+/// yaml_token_delete() and yaml_event_delete() leave their values alone.
+/// Borrowed values are not NUL-terminated; use their length instead.
+/// yaml_parser_load() still copies them into the document. Other inputs
+/// and scalars are not affected.
+///
+/// # Safety
+///
+/// - `parser` must be a valid, non-null pointer to a properly initialized `YamlParserT` struct.
+/// - The input string must remain valid and unmodified until the borrowed tokens and events are deleted.
+///
+pub unsafe fn yaml_parser_set_borrow_scalars(
+    parser: *mut YamlParserT,
+    borrow_scalars: bool,
+) {
+    __assert!(!parser.is_null());
+    (*parser).borrow_scalars = borrow_scalars;
+}
+
 /// Initialize an emitter.
 ///
 /// This function creates a new emitter object. An application is responsible
@@ -541,6 +851,8 @@ pub unsafe fn yaml_emitter_delete(emitter: *mut YamlEmitterT) {
     }
     STACK_DEL!((*emitter).tag_directives);
     yaml_free((*emitter).anchors as *mut libc::c_void);
+    yaml_free((*emitter).comment as *mut libc::c_void);
+    yaml_free((*emitter).comments_after as *mut libc::c_void);
     memset(
         emitter as *mut libc::c_void,
         0,
@@ -790,9 +1102,12 @@ pub unsafe fn yaml_token_delete(token: *mut YamlTokenT) {
             yaml_free((*token).data.tag.handle as *mut libc::c_void);
             yaml_free((*token).data.tag.suffix as *mut libc::c_void);
         }
-        YamlScalarToken => {
+        YamlScalarToken if !(*token).data.scalar.borrowed => {
             yaml_free((*token).data.scalar.value as *mut libc::c_void);
         }
+        YamlCommentToken => {
+            yaml_free((*token).data.comment.value as *mut libc::c_void);
+        }
         _ => {}
     }
     memset(
@@ -882,6 +1197,8 @@ pub unsafe fn yaml_stream_start_event_initialize(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     __assert!(!event.is_null());
     memset(
@@ -913,6 +1230,8 @@ pub unsafe fn yaml_stream_end_event_initialize(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     __assert!(!event.is_null());
     memset(
@@ -952,6 +1271,8 @@ pub unsafe fn yaml_document_start_event_initialize(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     let mut version_directive_copy: *mut YamlVersionDirectiveT =
         ptr::null_mut::<YamlVersionDirectiveT>();
@@ -1080,6 +1401,8 @@ pub unsafe fn yaml_document_end_event_initialize(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     __assert!(!event.is_null());
     memset(
@@ -1111,6 +1434,8 @@ pub unsafe fn yaml_alias_event_initialize(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     __assert!(!event.is_null());
     __assert!(!anchor.is_null());
@@ -1197,6 +1522,8 @@ pub unsafe fn yaml_scalar_event_initialize(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     let mut anchor_copy: *mut yaml_char_t =
         ptr::null_mut::<yaml_char_t>();
@@ -1323,6 +1650,8 @@ pub unsafe fn yaml_sequence_start_event_initialize(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     let mut anchor_copy: *mut yaml_char_t =
         ptr::null_mut::<yaml_char_t>();
@@ -1403,6 +1732,8 @@ pub unsafe fn yaml_sequence_end_event_initialize(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     __assert!(!event.is_null());
     memset(
@@ -1445,6 +1776,8 @@ pub unsafe fn yaml_mapping_start_event_initialize(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     let mut anchor_copy: *mut yaml_char_t =
         ptr::null_mut::<yaml_char_t>();
@@ -1525,6 +1858,8 @@ pub unsafe fn yaml_mapping_end_event_initialize(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     __assert!(!event.is_null());
     memset(
@@ -1538,6 +1873,56 @@ pub unsafe fn yaml_mapping_end_event_initialize(
     OK
 }
 
+/// Attach comments to an event.
+///
+/// Each comment is either null or a null-terminated string of comment
+/// lines separated by '\n', each line being the text to write after the
+/// '#' indicator. Comments already attached to the event are replaced.
+///
+/// The emitter writes the comments of node events, DOCUMENT-START,
+/// DOCUMENT-END and STREAM-END events, and ignores the others.
+///
+/// # Safety
+///
+/// - `event` must be a valid, non-null pointer to an initialized `YamlEventT` struct.
+/// - `before`, `inline` and `after`, if not null, must be valid pointers to null-terminated UTF-8 strings.
+///
+pub unsafe fn yaml_event_set_comments(
+    event: *mut YamlEventT,
+    before: *const yaml_char_t,
+    inline: *const yaml_char_t,
+    after: *const yaml_char_t,
+) -> Success {
+    let mut comments = YamlEventCommentsT {
+        before: ptr::null_mut::<yaml_char_t>(),
+        inline: ptr::null_mut::<yaml_char_t>(),
+        after: ptr::null_mut::<yaml_char_t>(),
+    };
+    __assert!(!event.is_null());
+    for (comment, copy) in [
+        (before, addr_of_mut!(comments.before)),
+        (inline, addr_of_mut!(comments.inline)),
+        (after, addr_of_mut!(comments.after)),
+    ] {
+        if comment.is_null() {
+            continue;
+        }
+        if yaml_check_utf8(
+            comment,
+            strlen(comment as *mut libc::c_char),
+        )
+        .fail
+        {
+            yaml_event_comments_delete(addr_of_mut!(comments));
+            return FAIL;
+        }
+        *copy = yaml_strdup(comment);
+    }
+    yaml_event_comments_delete(addr_of_mut!((*event).comments));
+    (*event).comments = comments;
+    OK
+}
+
 /// Free any memory allocated for an event object.
 ///
 /// This function frees the dynamically allocated memory associated with a `YamlEventT` struct,
@@ -1578,7 +1963,11 @@ pub unsafe fn yaml_event_delete(event: *mut YamlEventT) {
         YamlScalarEvent => {
             yaml_free((*event).data.scalar.anchor as *mut libc::c_void);
             yaml_free((*event).data.scalar.tag as *mut libc::c_void);
-            yaml_free((*event).data.scalar.value as *mut libc::c_void);
+            if !(*event).data.scalar.borrowed {
+                yaml_free(
+                    (*event).data.scalar.value as *mut libc::c_void,
+                );
+            }
         }
         YamlSequenceStartEvent => {
             yaml_free(
@@ -1599,6 +1988,7 @@ pub unsafe fn yaml_event_delete(event: *mut YamlEventT) {
         }
         _ => {}
     }
+    yaml_event_comments_delete(addr_of_mut!((*event).comments));
     memset(
         event as *mut libc::c_void,
         0,
@@ -1606,6 +1996,17 @@ pub unsafe fn yaml_event_delete(event: *mut YamlEventT) {
     );
 }
 
+pub(crate) unsafe fn yaml_event_comments_delete(
+    comments: *mut YamlEventCommentsT,
+) {
+    yaml_free((*comments).before as *mut libc::c_void);
+    yaml_free((*comments).inline as *mut libc::c_void);
+    yaml_free((*comments).after as *mut libc::c_void);
+    (*comments).before = ptr::null_mut::<yaml_char_t>();
+    (*comments).inline = ptr::null_mut::<yaml_char_t>();
+    (*comments).after = ptr::null_mut::<yaml_char_t>();
+}
+
 /// Create a YAML document.
 ///
 /// This function initializes a `YamlDocumentT` struct with the provided version directive,
@@ -1660,6 +2061,8 @@ pub unsafe fn yaml_document_initialize(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     __assert!(!document.is_null());
     __assert!(
@@ -1897,6 +2300,8 @@ pub unsafe fn yaml_document_add_scalar(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     let mut tag_copy: *mut yaml_char_t = ptr::null_mut::<yaml_char_t>();
     let mut value_copy: *mut yaml_char_t =
@@ -1978,6 +2383,8 @@ pub unsafe fn yaml_document_add_sequence(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     let mut tag_copy: *mut yaml_char_t = ptr::null_mut::<yaml_char_t>();
     struct Items {
@@ -2054,6 +2461,8 @@ pub unsafe fn yaml_document_add_mapping(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     let mut tag_copy: *mut yaml_char_t = ptr::null_mut::<yaml_char_t>();
     struct Pairs {
@@ -2194,7 +2603,11 @@ pub unsafe fn yaml_document_append_mapping_pair(
                 .wrapping_offset(value as isize)
                 <= (*document).nodes.top
     );
-    let pair = YamlNodePairT { key, value };
+    let pair = YamlNodePairT {
+        key,
+        value,
+        merged: false,
+    };
     PUSH!(
         (*((*document).nodes.start)
             .wrapping_offset((mapping - 1) as isize))
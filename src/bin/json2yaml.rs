@@ -0,0 +1,324 @@
+//! Pretty-prints JSON as block-style YAML.
+//!
+//! ```text
+//! json2yaml [--comments] [--indent N] [FILE]
+//! ```
+//!
+//! FILE, or the standard input when there is none, is read as a JSON
+//! text and written as a YAML document:
+//!
+//! - Arrays and objects become block sequences and block mappings,
+//!   except for empty ones, which are written as `[]` and `{}`.
+//! - Strings are written as plain scalars where the core schema reads
+//!   them back as strings, and quoted otherwise, so `"1"` and `"true"`
+//!   keep their quotes. Numbers, booleans and null are written as they
+//!   are.
+//! - `--comments` lets the `//` and `/* */` comments of JSON5 through,
+//!   and drops them. `--indent` sets the indentation, 2 by default.
+//!
+//! Errors are reported as `FILE:LINE:COLUMN: message`.
+
+#![allow(missing_docs)]
+#![warn(clippy::pedantic)]
+#![allow(
+    clippy::cast_lossless,
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss,
+    clippy::items_after_statements,
+    clippy::let_underscore_untyped,
+    clippy::missing_errors_doc,
+    clippy::missing_safety_doc,
+    clippy::too_many_lines,
+    clippy::uninlined_format_args
+)]
+
+mod cstr;
+
+use self::cstr::CStr;
+use libyml::json::{yaml_parser_set_json, JsonSyntax};
+use libyml::resolver::{self, STR_TAG};
+use libyml::{
+    yaml_emitter_delete, yaml_emitter_emit, yaml_emitter_initialize,
+    yaml_emitter_set_indent, yaml_emitter_set_output,
+    yaml_emitter_set_unicode, yaml_parser_delete,
+    yaml_parser_initialize, yaml_parser_parse, yaml_parser_set_input,
+    YamlAnyScalarStyle, YamlBlockMappingStyle, YamlBlockSequenceStyle,
+    YamlCoreSchema, YamlDoubleQuotedScalarStyle, YamlEmitterT,
+    YamlEventT, YamlMappingStartEvent, YamlMarkT, YamlParserT,
+    YamlPlainScalarStyle, YamlScalarEvent, YamlSequenceStartEvent,
+    YamlStreamEndEvent,
+};
+use std::env;
+use std::ffi::c_void;
+use std::fmt::{self, Display, Write as _};
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::mem::MaybeUninit;
+use std::process::ExitCode;
+use std::ptr::{self, addr_of_mut};
+use std::{slice, str};
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Options {
+    pub(crate) syntax: JsonSyntax,
+    pub(crate) indent: i32,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            syntax: JsonSyntax::Strict,
+            indent: 2,
+        }
+    }
+}
+
+/// A conversion error, located in the input unless it is an I/O or an
+/// emitter error.
+#[derive(Debug)]
+pub(crate) struct Failure {
+    pub(crate) mark: Option<YamlMarkT>,
+    pub(crate) message: String,
+}
+
+impl Display for Failure {
+    /// Formats the error as `LINE:COLUMN: message`, one-based, or as
+    /// just the message.
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(mark) = self.mark {
+            write!(
+                formatter,
+                "{}:{}: ",
+                mark.line.wrapping_add(1),
+                mark.column.wrapping_add(1),
+            )?;
+        }
+        formatter.write_str(&self.message)
+    }
+}
+
+impl From<String> for Failure {
+    fn from(message: String) -> Self {
+        Failure {
+            mark: None,
+            message,
+        }
+    }
+}
+
+/// Turns an event of the JSON text into the one written for it.
+unsafe fn restyle(event: *mut YamlEventT) {
+    match (*event).type_ {
+        YamlSequenceStartEvent => {
+            (*event).data.sequence_start.style = YamlBlockSequenceStyle;
+        }
+        YamlMappingStartEvent => {
+            (*event).data.mapping_start.style = YamlBlockMappingStyle;
+        }
+        YamlScalarEvent => {
+            let scalar = &mut (*event).data.scalar;
+            if scalar.style != YamlDoubleQuotedScalarStyle {
+                return;
+            }
+            // The JSON scanner only produces UTF-8.
+            let value = str::from_utf8(slice::from_raw_parts(
+                scalar.value,
+                scalar.length as usize,
+            ))
+            .unwrap_or_default();
+            scalar.plain_implicit = resolver::resolve(
+                YamlCoreSchema,
+                None,
+                value,
+                YamlPlainScalarStyle,
+            )
+            .is_ok_and(|resolved| resolved.tag == STR_TAG);
+            scalar.style = YamlAnyScalarStyle;
+        }
+        _ => {}
+    }
+}
+
+/// Converts the JSON text read from `input` and writes it to `output`.
+pub(crate) unsafe fn convert(
+    mut input: &mut dyn Read,
+    mut output: &mut dyn Write,
+    options: &Options,
+) -> Result<(), Failure> {
+    let mut parser = MaybeUninit::<YamlParserT>::uninit();
+    let parser = parser.as_mut_ptr();
+    if yaml_parser_initialize(parser).fail {
+        return Err(Failure {
+            mark: None,
+            message: "Could not initialize the parser object".into(),
+        });
+    }
+    let mut emitter = MaybeUninit::<YamlEmitterT>::uninit();
+    let emitter = emitter.as_mut_ptr();
+    if yaml_emitter_initialize(emitter).fail {
+        yaml_parser_delete(parser);
+        return Err(Failure {
+            mark: None,
+            message: "Could not initialize the emitter object".into(),
+        });
+    }
+
+    unsafe fn read_from_input(
+        data: *mut c_void,
+        buffer: *mut u8,
+        size: u64,
+        size_read: *mut u64,
+    ) -> i32 {
+        let input: *mut &mut dyn Read = data.cast();
+        ptr::write_bytes(buffer, 0, size as usize);
+        let slice = slice::from_raw_parts_mut(buffer, size as usize);
+        match (*input).read(slice) {
+            Ok(n) => {
+                *size_read = n as u64;
+                1
+            }
+            Err(_) => 0,
+        }
+    }
+
+    unsafe fn write_to_output(
+        data: *mut c_void,
+        buffer: *mut u8,
+        size: u64,
+    ) -> i32 {
+        let output: *mut &mut dyn Write = data.cast();
+        let bytes = slice::from_raw_parts(buffer, size as usize);
+        match (*output).write_all(bytes) {
+            Ok(()) => 1,
+            Err(_) => 0,
+        }
+    }
+
+    yaml_parser_set_input(
+        parser,
+        read_from_input,
+        addr_of_mut!(input).cast(),
+    );
+    yaml_parser_set_json(parser, options.syntax);
+    yaml_emitter_set_output(
+        emitter,
+        write_to_output,
+        addr_of_mut!(output).cast(),
+    );
+    yaml_emitter_set_indent(emitter, options.indent);
+    yaml_emitter_set_unicode(emitter, true);
+
+    let mut event = MaybeUninit::<YamlEventT>::uninit();
+    let event = event.as_mut_ptr();
+    let result = loop {
+        if yaml_parser_parse(parser, event).fail {
+            let parser = &*parser;
+            // Memory errors come without a problem.
+            let mut error = Failure {
+                mark: Some(parser.problem_mark),
+                message: if parser.problem.is_null() {
+                    "parser error".into()
+                } else {
+                    CStr::from_ptr(parser.problem).to_string()
+                },
+            };
+            if !parser.context.is_null() {
+                let _ = write!(
+                    error.message,
+                    " ({} at {}:{})",
+                    CStr::from_ptr(parser.context),
+                    parser.context_mark.line.wrapping_add(1),
+                    parser.context_mark.column.wrapping_add(1),
+                );
+            }
+            break Err(error);
+        }
+        let type_ = (*event).type_;
+        restyle(event);
+        // The emitter takes the event over, even when it fails.
+        if yaml_emitter_emit(emitter, event).fail {
+            let emitter = &*emitter;
+            if emitter.problem.is_null() {
+                break Err("emitter error".to_owned().into());
+            }
+            break Err(CStr::from_ptr(emitter.problem)
+                .to_string()
+                .into());
+        }
+        if type_ == YamlStreamEndEvent {
+            break Ok(());
+        }
+    };
+    yaml_emitter_delete(emitter);
+    yaml_parser_delete(parser);
+    result
+}
+
+fn parse_args(
+    args: impl Iterator<Item = String>,
+) -> Result<(Options, Option<String>), String> {
+    let mut options = Options::default();
+    let mut files = Vec::new();
+    let mut args = args.peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--comments" => options.syntax = JsonSyntax::Comments,
+            "--indent" => {
+                options.indent = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .filter(|n| (2..=9).contains(n))
+                    .ok_or("invalid --indent, not from 2 to 9")?;
+            }
+            "--" => files.extend(args.by_ref()),
+            _ if arg.starts_with("--") => {
+                return Err(format!("unknown option {}", arg));
+            }
+            _ => files.push(arg),
+        }
+    }
+    if files.len() > 1 {
+        return Err("more than one FILE".into());
+    }
+    Ok((options, files.pop()))
+}
+
+fn main() -> ExitCode {
+    let (options, file) = match parse_args(env::args().skip(1)) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            let _ = writeln!(
+                io::stderr(),
+                "{}\nUsage: json2yaml [--comments] [--indent N] [FILE]",
+                error,
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    let name = file.unwrap_or_else(|| String::from("-"));
+    let result = if name == "-" {
+        unsafe {
+            convert(&mut io::stdin().lock(), &mut stdout, &options)
+        }
+    } else {
+        match File::open(&name) {
+            Ok(mut file) => unsafe {
+                convert(&mut file, &mut stdout, &options)
+            },
+            Err(error) => Err(error.to_string().into()),
+        }
+    };
+    let flushed = stdout.flush();
+    if let Err(error) = result {
+        let separator = if error.mark.is_some() { ":" } else { ": " };
+        let _ =
+            writeln!(io::stderr(), "{}{}{}", name, separator, error);
+        return ExitCode::FAILURE;
+    }
+    if flushed.is_err() {
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
@@ -43,8 +43,9 @@ use libyml::{
     yaml_stream_start_event_initialize, YamlAnyScalarStyle,
     YamlBlockMappingStyle, YamlBlockSequenceStyle,
     YamlDoubleQuotedScalarStyle, YamlEmitterError, YamlEmitterT,
-    YamlEventT, YamlFoldedScalarStyle, YamlLiteralScalarStyle,
-    YamlMemoryError, YamlPlainScalarStyle, YamlScalarStyleT,
+    YamlEventT, YamlFlowMappingStyle, YamlFlowSequenceStyle,
+    YamlFoldedScalarStyle, YamlLiteralScalarStyle, YamlMemoryError,
+    YamlPlainScalarStyle, YamlScalarStyleT,
     YamlSingleQuotedScalarStyle, YamlTagDirectiveT, YamlUtf8Encoding,
     YamlVersionDirectiveT, YamlWriterError,
 };
@@ -113,7 +114,11 @@ pub(crate) unsafe fn unsafe_main(
                 get_anchor(b'&', line, anchor.as_mut_ptr()),
                 get_tag(line, tag.as_mut_ptr()),
                 false,
-                YamlBlockMappingStyle,
+                if line[4..].starts_with(b" {}") {
+                    YamlFlowMappingStyle
+                } else {
+                    YamlBlockMappingStyle
+                },
             )
         } else if line.starts_with(b"-MAP") {
             yaml_mapping_end_event_initialize(event)
@@ -123,7 +128,11 @@ pub(crate) unsafe fn unsafe_main(
                 get_anchor(b'&', line, anchor.as_mut_ptr()),
                 get_tag(line, tag.as_mut_ptr()),
                 false,
-                YamlBlockSequenceStyle,
+                if line[4..].starts_with(b" []") {
+                    YamlFlowSequenceStyle
+                } else {
+                    YamlBlockSequenceStyle
+                },
             )
         } else if line.starts_with(b"-SEQ") {
             yaml_sequence_end_event_initialize(event)
@@ -18,14 +18,15 @@ mod cstr;
 use self::cstr::CStr;
 use libyml::{
     yaml_event_delete, yaml_parser_delete, yaml_parser_initialize,
-    yaml_parser_parse, yaml_parser_set_input, YamlAliasEvent,
-    YamlDocumentEndEvent, YamlDocumentStartEvent,
+    yaml_parser_parse, yaml_parser_set_input, yaml_parser_set_strict,
+    YamlAliasEvent, YamlDocumentEndEvent, YamlDocumentStartEvent,
     YamlDoubleQuotedScalarStyle, YamlEventT, YamlEventTypeT,
-    YamlFoldedScalarStyle, YamlLiteralScalarStyle, YamlMappingEndEvent,
-    YamlMappingStartEvent, YamlNoEvent, YamlParserT,
-    YamlPlainScalarStyle, YamlScalarEvent, YamlSequenceEndEvent,
-    YamlSequenceStartEvent, YamlSingleQuotedScalarStyle,
-    YamlStreamEndEvent, YamlStreamStartEvent,
+    YamlFlowMappingStyle, YamlFlowSequenceStyle, YamlFoldedScalarStyle,
+    YamlLiteralScalarStyle, YamlMappingEndEvent, YamlMappingStartEvent,
+    YamlNoEvent, YamlParserT, YamlPlainScalarStyle, YamlScalarEvent,
+    YamlSequenceEndEvent, YamlSequenceStartEvent,
+    YamlSingleQuotedScalarStyle, YamlStreamEndEvent,
+    YamlStreamStartEvent,
 };
 use std::env;
 use std::error::Error;
@@ -39,8 +40,19 @@ use std::ptr::addr_of_mut;
 use std::slice;
 
 pub(crate) unsafe fn unsafe_main(
+    stdin: &mut dyn Read,
+    stdout: &mut dyn Write,
+) -> Result<(), Box<dyn Error>> {
+    // The suite follows YAML 1.2.
+    print_events(stdin, stdout, true)
+}
+
+/// Writes the events of the input as the suite's test.event files do,
+/// parsing it in strict mode or not.
+pub(crate) unsafe fn print_events(
     mut stdin: &mut dyn Read,
     stdout: &mut dyn Write,
+    strict: bool,
 ) -> Result<(), Box<dyn Error>> {
     let mut parser = MaybeUninit::<YamlParserT>::uninit();
     let parser = parser.as_mut_ptr();
@@ -71,6 +83,7 @@ pub(crate) unsafe fn unsafe_main(
         read_from_stdio,
         addr_of_mut!(stdin).cast(),
     );
+    yaml_parser_set_strict(parser, strict);
 
     let mut event = MaybeUninit::<YamlEventT>::uninit();
     let event = event.as_mut_ptr();
@@ -115,6 +128,10 @@ pub(crate) unsafe fn unsafe_main(
             let _ = writeln!(stdout);
         } else if type_ == YamlMappingStartEvent {
             let _ = write!(stdout, "+MAP");
+            if (*event).data.mapping_start.style == YamlFlowMappingStyle
+            {
+                let _ = write!(stdout, " {{}}");
+            }
             if !(*event).data.mapping_start.anchor.is_null() {
                 let _ = write!(
                     stdout,
@@ -138,6 +155,11 @@ pub(crate) unsafe fn unsafe_main(
             let _ = writeln!(stdout, "-MAP");
         } else if type_ == YamlSequenceStartEvent {
             let _ = write!(stdout, "+SEQ");
+            if (*event).data.sequence_start.style
+                == YamlFlowSequenceStyle
+            {
+                let _ = write!(stdout, " []");
+            }
             if !(*event).data.sequence_start.anchor.is_null() {
                 let _ = write!(
                     stdout,
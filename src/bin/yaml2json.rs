@@ -0,0 +1,617 @@
+//! Converts YAML streams to JSON.
+//!
+//! ```text
+//! yaml2json [--ndjson | --array] [--pretty] [--schema SCHEMA]
+//!           [--max-alias-nodes N] [FILE]...
+//! ```
+//!
+//! Each FILE, or the standard input when there is none, is read as a
+//! YAML stream and written as JSON:
+//!
+//! - A stream of exactly one document is written as its value, and any
+//!   other stream as an array of its documents. `--array` always writes
+//!   an array, and `--ndjson` writes one document per line instead.
+//! - Untagged plain scalars resolve according to SCHEMA, one of
+//!   `failsafe`, `json`, `core` (the default) or `yaml11`, and become
+//!   JSON numbers, booleans and nulls. Tagged scalars resolve according
+//!   to their tag. Integers are written in decimal at any size.
+//!   Infinite and NaN floats have no JSON form and fail the conversion.
+//! - Aliases are replaced with a copy of the node they refer to. They
+//!   may stand for at most N nodes per document, 1000000 by default,
+//!   which keeps a few nested aliases from exhausting the memory.
+//! - Mapping keys that resolve to a number, a boolean or null are
+//!   written as the string of their JSON form, so `1: a` becomes
+//!   `{"1":"a"}` and `~: b` becomes `{"null":"b"}`. Sequences and
+//!   mappings used as keys fail the conversion. Duplicate keys are all
+//!   written, in order.
+//!
+//! Errors are reported as `FILE:LINE:COLUMN: message`.
+
+#![allow(missing_docs)]
+#![warn(clippy::pedantic)]
+#![allow(
+    clippy::cast_lossless,
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss,
+    clippy::items_after_statements,
+    clippy::let_underscore_untyped,
+    clippy::missing_errors_doc,
+    clippy::missing_safety_doc,
+    clippy::too_many_lines,
+    clippy::uninlined_format_args
+)]
+
+mod cstr;
+
+use self::cstr::CStr;
+use libyml::resolver::{self, Value};
+use libyml::{
+    yaml_event_delete, yaml_parser_delete, yaml_parser_initialize,
+    yaml_parser_parse, yaml_parser_set_input, YamlAliasEvent,
+    YamlCoreSchema, YamlDocumentEndEvent, YamlEventT,
+    YamlFailsafeSchema, YamlJsonSchema, YamlMappingEndEvent,
+    YamlMappingStartEvent, YamlMarkT, YamlParserT, YamlScalarEvent,
+    YamlSchemaT, YamlSequenceEndEvent, YamlSequenceStartEvent,
+    YamlStreamEndEvent, YamlYaml11Schema,
+};
+use std::collections::HashMap;
+use std::env;
+use std::ffi::c_void;
+use std::fmt::{self, Display, Write as _};
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::mem::MaybeUninit;
+use std::process::ExitCode;
+use std::ptr::{self, addr_of_mut};
+use std::{slice, str};
+
+/// How the documents of a stream are written.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Output {
+    /// The only document as is, or an array of the documents.
+    Auto,
+    /// An array of the documents.
+    Array,
+    /// One document per line.
+    Ndjson,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Options {
+    pub(crate) output: Output,
+    pub(crate) pretty: bool,
+    pub(crate) schema: YamlSchemaT,
+    pub(crate) max_alias_nodes: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            output: Output::Auto,
+            pretty: false,
+            schema: YamlCoreSchema,
+            max_alias_nodes: 1_000_000,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Int(i64),
+    // The decimal digits of an integer too wide for an `i64`.
+    BigInt(String),
+    Float(f64),
+    Str(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// The number of nodes, counting this one.
+    fn nodes(&self) -> usize {
+        match self {
+            Json::Array(items) => {
+                1 + items.iter().map(Json::nodes).sum::<usize>()
+            }
+            Json::Object(pairs) => {
+                1 + pairs
+                    .iter()
+                    .map(|(_, value)| 1 + value.nodes())
+                    .sum::<usize>()
+            }
+            _ => 1,
+        }
+    }
+}
+
+/// A collection being built.
+struct Frame {
+    anchor: Option<String>,
+    start_mark: YamlMarkT,
+    kind: FrameKind,
+}
+
+enum FrameKind {
+    Sequence(Vec<Json>),
+    // The key waiting for its value, if any.
+    Mapping(Vec<(String, Json)>, Option<String>),
+}
+
+/// Builds the documents of a stream out of its events.
+struct Builder<'a> {
+    options: &'a Options,
+    stack: Vec<Frame>,
+    anchors: HashMap<String, Json>,
+    alias_nodes: usize,
+    root: Option<Json>,
+}
+
+impl Builder<'_> {
+    unsafe fn event(
+        &mut self,
+        event: *const YamlEventT,
+    ) -> Result<(), Failure> {
+        let mark = (*event).start_mark;
+        match (*event).type_ {
+            YamlScalarEvent => {
+                let scalar = &(*event).data.scalar;
+                let value = str::from_utf8(slice::from_raw_parts(
+                    scalar.value,
+                    scalar.length as usize,
+                ))
+                .map_err(|error| located(mark, &error.to_string()))?;
+                let tag = optional_str(scalar.tag);
+                let resolved = resolver::resolve(
+                    self.options.schema,
+                    tag.as_deref(),
+                    value,
+                    scalar.style,
+                )
+                .map_err(|error| located(mark, error.message()))?;
+                let json = match resolved.value {
+                    Value::Null => Json::Null,
+                    Value::Bool(bool) => Json::Bool(bool),
+                    Value::Int(int) => Json::Int(int),
+                    Value::BigInt(int) => Json::BigInt(int.to_string()),
+                    Value::Float(float) if float.is_finite() => {
+                        Json::Float(float)
+                    }
+                    Value::Float(_) => {
+                        return Err(located(
+                            mark,
+                            &format!("{:?} has no JSON form", value),
+                        ));
+                    }
+                    Value::Str(str) => Json::Str(str.to_owned()),
+                    _ => Json::Str(value.to_owned()),
+                };
+                self.node(json, optional_str(scalar.anchor), mark)
+            }
+            YamlAliasEvent => {
+                let anchor = optional_str((*event).data.alias.anchor)
+                    .unwrap_or_default();
+                let Some(json) = self.anchors.get(&anchor) else {
+                    return Err(located(
+                        mark,
+                        &format!("unknown anchor {:?}", anchor),
+                    ));
+                };
+                self.alias_nodes += json.nodes();
+                if self.alias_nodes > self.options.max_alias_nodes {
+                    return Err(located(
+                        mark,
+                        &format!(
+                            "aliases stand for more than {} nodes",
+                            self.options.max_alias_nodes,
+                        ),
+                    ));
+                }
+                let json = json.clone();
+                self.node(json, None, mark)
+            }
+            YamlSequenceStartEvent => {
+                self.stack.push(Frame {
+                    anchor: optional_str(
+                        (*event).data.sequence_start.anchor,
+                    ),
+                    start_mark: mark,
+                    kind: FrameKind::Sequence(Vec::new()),
+                });
+                Ok(())
+            }
+            YamlMappingStartEvent => {
+                self.stack.push(Frame {
+                    anchor: optional_str(
+                        (*event).data.mapping_start.anchor,
+                    ),
+                    start_mark: mark,
+                    kind: FrameKind::Mapping(Vec::new(), None),
+                });
+                Ok(())
+            }
+            YamlSequenceEndEvent | YamlMappingEndEvent => {
+                let frame = self.stack.pop().expect("open collection");
+                let json = match frame.kind {
+                    FrameKind::Sequence(items) => Json::Array(items),
+                    FrameKind::Mapping(pairs, _) => Json::Object(pairs),
+                };
+                self.node(json, frame.anchor, frame.start_mark)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Adds a complete node to the collection it is in.
+    fn node(
+        &mut self,
+        json: Json,
+        anchor: Option<String>,
+        mark: YamlMarkT,
+    ) -> Result<(), Failure> {
+        if let Some(anchor) = anchor {
+            self.anchors.insert(anchor, json.clone());
+        }
+        let Some(frame) = self.stack.last_mut() else {
+            self.root = Some(json);
+            return Ok(());
+        };
+        match &mut frame.kind {
+            FrameKind::Sequence(items) => items.push(json),
+            FrameKind::Mapping(pairs, key) => match key.take() {
+                Some(key) => pairs.push((key, json)),
+                None => *key = Some(key_string(json, mark)?),
+            },
+        }
+        Ok(())
+    }
+
+    /// Takes the root node of the document that just ended.
+    fn document(&mut self) -> Json {
+        self.anchors.clear();
+        self.alias_nodes = 0;
+        self.root.take().unwrap_or(Json::Null)
+    }
+}
+
+/// The string a mapping key is written as.
+fn key_string(key: Json, mark: YamlMarkT) -> Result<String, Failure> {
+    match key {
+        Json::Str(string) => Ok(string),
+        Json::Array(_) | Json::Object(_) => Err(located(
+            mark,
+            "a sequence or a mapping cannot be a JSON object key",
+        )),
+        scalar => {
+            let mut string = String::new();
+            write_json(&mut string, &scalar, None);
+            Ok(string)
+        }
+    }
+}
+
+/// A conversion error, located in the input unless it is an I/O error.
+#[derive(Debug)]
+pub(crate) struct Failure {
+    pub(crate) mark: Option<YamlMarkT>,
+    pub(crate) message: String,
+}
+
+impl Display for Failure {
+    /// Formats the error as `LINE:COLUMN: message`, one-based, or as
+    /// just the message.
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(mark) = self.mark {
+            write!(
+                formatter,
+                "{}:{}: ",
+                mark.line.wrapping_add(1),
+                mark.column.wrapping_add(1),
+            )?;
+        }
+        formatter.write_str(&self.message)
+    }
+}
+
+impl From<io::Error> for Failure {
+    fn from(error: io::Error) -> Self {
+        Failure {
+            mark: None,
+            message: error.to_string(),
+        }
+    }
+}
+
+fn located(mark: YamlMarkT, message: &str) -> Failure {
+    Failure {
+        mark: Some(mark),
+        message: message.to_owned(),
+    }
+}
+
+unsafe fn optional_str(ptr: *const u8) -> Option<String> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(ptr.cast()).to_string())
+    }
+}
+
+/// Writes `json` into `out`, indented by `indent` levels of two spaces,
+/// or on a single line when `indent` is `None`.
+fn write_json(out: &mut String, json: &Json, indent: Option<usize>) {
+    let newline = |out: &mut String, level: usize| {
+        if let Some(indent) = indent {
+            out.push('\n');
+            out.extend(std::iter::repeat("  ").take(indent + level));
+        }
+    };
+    match json {
+        Json::Null => out.push_str("null"),
+        Json::Bool(bool) => {
+            let _ = write!(out, "{}", bool);
+        }
+        Json::Int(int) => {
+            let _ = write!(out, "{}", int);
+        }
+        Json::BigInt(digits) => out.push_str(digits),
+        Json::Float(float) => {
+            let _ = write!(out, "{:?}", float);
+        }
+        Json::Str(string) => write_string(out, string),
+        Json::Array(items) if items.is_empty() => out.push_str("[]"),
+        Json::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i != 0 {
+                    out.push(',');
+                }
+                newline(out, 1);
+                write_json(out, item, indent.map(|indent| indent + 1));
+            }
+            newline(out, 0);
+            out.push(']');
+        }
+        Json::Object(pairs) if pairs.is_empty() => out.push_str("{}"),
+        Json::Object(pairs) => {
+            out.push('{');
+            for (i, (key, value)) in pairs.iter().enumerate() {
+                if i != 0 {
+                    out.push(',');
+                }
+                newline(out, 1);
+                write_string(out, key);
+                out.push_str(if indent.is_some() { ": " } else { ":" });
+                write_json(out, value, indent.map(|indent| indent + 1));
+            }
+            newline(out, 0);
+            out.push('}');
+        }
+    }
+}
+
+fn write_string(out: &mut String, string: &str) {
+    out.push('"');
+    for ch in string.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            ch if ch < ' ' => {
+                let _ = write!(out, "\\u{:04x}", ch as u32);
+            }
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+}
+
+/// Converts the YAML stream read from `input` and writes it to `output`.
+pub(crate) unsafe fn convert(
+    mut input: &mut dyn Read,
+    output: &mut dyn Write,
+    options: &Options,
+) -> Result<(), Failure> {
+    let mut parser = MaybeUninit::<YamlParserT>::uninit();
+    let parser = parser.as_mut_ptr();
+    if yaml_parser_initialize(parser).fail {
+        return Err(Failure {
+            mark: None,
+            message: "Could not initialize the parser object".into(),
+        });
+    }
+
+    unsafe fn read_from_input(
+        data: *mut c_void,
+        buffer: *mut u8,
+        size: u64,
+        size_read: *mut u64,
+    ) -> i32 {
+        let input: *mut &mut dyn Read = data.cast();
+        ptr::write_bytes(buffer, 0, size as usize);
+        let slice = slice::from_raw_parts_mut(buffer, size as usize);
+        match (*input).read(slice) {
+            Ok(n) => {
+                *size_read = n as u64;
+                1
+            }
+            Err(_) => 0,
+        }
+    }
+
+    yaml_parser_set_input(
+        parser,
+        read_from_input,
+        addr_of_mut!(input).cast(),
+    );
+
+    let mut builder = Builder {
+        options,
+        stack: Vec::new(),
+        anchors: HashMap::new(),
+        alias_nodes: 0,
+        root: None,
+    };
+    let mut documents = Vec::new();
+    let mut event = MaybeUninit::<YamlEventT>::uninit();
+    let event = event.as_mut_ptr();
+    let result = loop {
+        if yaml_parser_parse(parser, event).fail {
+            let parser = &*parser;
+            // Memory errors come without a problem.
+            let mut error = located(
+                parser.problem_mark,
+                &if parser.problem.is_null() {
+                    "parser error".to_owned()
+                } else {
+                    CStr::from_ptr(parser.problem).to_string()
+                },
+            );
+            if !parser.context.is_null() {
+                let _ = write!(
+                    error.message,
+                    " ({} at {}:{})",
+                    CStr::from_ptr(parser.context),
+                    parser.context_mark.line.wrapping_add(1),
+                    parser.context_mark.column.wrapping_add(1),
+                );
+            }
+            break Err(error);
+        }
+        let type_ = (*event).type_;
+        let result = builder.event(event);
+        yaml_event_delete(event);
+        if let Err(error) = result {
+            break Err(error);
+        }
+        if type_ == YamlDocumentEndEvent {
+            let document = builder.document();
+            if options.output == Output::Ndjson {
+                let mut line = String::new();
+                write_json(&mut line, &document, None);
+                line.push('\n');
+                if let Err(error) = output.write_all(line.as_bytes()) {
+                    break Err(error.into());
+                }
+            } else {
+                documents.push(document);
+            }
+        } else if type_ == YamlStreamEndEvent {
+            break Ok(());
+        }
+    };
+    yaml_parser_delete(parser);
+    result?;
+
+    if options.output == Output::Ndjson {
+        return Ok(());
+    }
+    let json = if options.output == Output::Auto && documents.len() == 1
+    {
+        documents.pop().unwrap()
+    } else {
+        Json::Array(documents)
+    };
+    let mut text = String::new();
+    write_json(&mut text, &json, options.pretty.then_some(0));
+    text.push('\n');
+    output.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+fn parse_args(
+    args: impl Iterator<Item = String>,
+) -> Result<(Options, Vec<String>), String> {
+    let mut options = Options::default();
+    let mut files = Vec::new();
+    let mut args = args.peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ndjson" => options.output = Output::Ndjson,
+            "--array" => options.output = Output::Array,
+            "--pretty" => options.pretty = true,
+            "--schema" => {
+                options.schema = match args.next().as_deref() {
+                    Some("failsafe") => YamlFailsafeSchema,
+                    Some("json") => YamlJsonSchema,
+                    Some("core") => YamlCoreSchema,
+                    Some("yaml11") => YamlYaml11Schema,
+                    _ => return Err("unknown schema".into()),
+                };
+            }
+            "--max-alias-nodes" => {
+                options.max_alias_nodes = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or("invalid --max-alias-nodes")?;
+            }
+            "--" => files.extend(args.by_ref()),
+            _ if arg.starts_with("--") => {
+                return Err(format!("unknown option {}", arg));
+            }
+            _ => files.push(arg),
+        }
+    }
+    if options.pretty && options.output == Output::Ndjson {
+        return Err("--pretty does not apply to --ndjson".into());
+    }
+    Ok((options, files))
+}
+
+fn main() -> ExitCode {
+    let (options, files) = match parse_args(env::args().skip(1)) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            let _ = writeln!(
+                io::stderr(),
+                "{}\nUsage: yaml2json [--ndjson | --array] [--pretty] \
+                 [--schema SCHEMA] [--max-alias-nodes N] [FILE]...",
+                error,
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    let inputs = if files.is_empty() {
+        vec![String::from("-")]
+    } else {
+        files
+    };
+    for name in inputs {
+        let result = if name == "-" {
+            unsafe {
+                convert(&mut io::stdin().lock(), &mut stdout, &options)
+            }
+        } else {
+            match File::open(&name) {
+                Ok(mut file) => unsafe {
+                    convert(&mut file, &mut stdout, &options)
+                },
+                Err(error) => Err(error.into()),
+            }
+        };
+        if let Err(error) = result {
+            let _ = stdout.flush();
+            let separator =
+                if error.mark.is_some() { ":" } else { ": " };
+            let _ = writeln!(
+                io::stderr(),
+                "{}{}{}",
+                name,
+                separator,
+                error
+            );
+            return ExitCode::FAILURE;
+        }
+    }
+    if stdout.flush().is_err() {
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
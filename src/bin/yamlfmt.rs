@@ -0,0 +1,400 @@
+//! Formats YAML files.
+//!
+//! ```text
+//! yamlfmt [--indent N] [--width N] [--line-break lf|crlf|cr]
+//!         [--ascii] [--check] [FILE]...
+//! ```
+//!
+//! Each FILE is parsed and emitted again, and rewritten in place if that
+//! changes it. The new content goes to a temporary file next to it, which
+//! then replaces the original, so a failure never leaves a file half
+//! written. With no FILE, the standard input is formatted to the standard
+//! output.
+//!
+//! - `--indent` sets the indentation, 2 by default, and `--width` the
+//!   preferred line width, 80 by default or unlimited when negative.
+//! - `--line-break` sets the line breaks, `lf` by default.
+//! - `--ascii` escapes non-ASCII characters instead of writing them as
+//!   they are, which puts the scalars that contain them in double
+//!   quotes.
+//! - `--check` writes nothing, lists the files that would change, and
+//!   exits with 1 if there are any.
+//!
+//! Scalars keep their style wherever the emitter allows it, documents
+//! keep their `---` and `...` markers, and collections their block or
+//! flow style, so formatting the output again leaves it as it is. Comments
+//! are kept next to the nodes they belong to.
+//!
+//! Errors are reported as `FILE:LINE:COLUMN: message`, and make the exit
+//! code 2.
+
+#![allow(missing_docs)]
+#![warn(clippy::pedantic)]
+#![allow(
+    clippy::cast_lossless,
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss,
+    clippy::items_after_statements,
+    clippy::let_underscore_untyped,
+    clippy::missing_errors_doc,
+    clippy::missing_safety_doc,
+    clippy::too_many_lines,
+    clippy::uninlined_format_args
+)]
+
+mod cstr;
+
+use self::cstr::CStr;
+use libyml::resolver;
+use libyml::{
+    yaml_emitter_delete, yaml_emitter_emit, yaml_emitter_initialize,
+    yaml_emitter_set_break, yaml_emitter_set_indent,
+    yaml_emitter_set_output, yaml_emitter_set_unicode,
+    yaml_emitter_set_width, yaml_parser_delete, yaml_parser_initialize,
+    yaml_parser_parse, yaml_parser_set_comments,
+    yaml_parser_set_input_string, YamlBreakT, YamlCoreSchema,
+    YamlCrBreak, YamlCrlnBreak, YamlEmitterT, YamlEventT, YamlLnBreak,
+    YamlMarkT, YamlParserT, YamlPlainScalarStyle, YamlScalarEvent,
+    YamlStreamEndEvent, YamlYaml11Schema,
+};
+use std::env;
+use std::ffi::c_void;
+use std::fmt::{self, Display, Write as _};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::mem::MaybeUninit;
+use std::path::Path;
+use std::process::{self, ExitCode};
+use std::ptr::addr_of_mut;
+use std::slice;
+use std::str;
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Options {
+    pub(crate) indent: i32,
+    pub(crate) width: i32,
+    pub(crate) line_break: YamlBreakT,
+    pub(crate) unicode: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            indent: 2,
+            width: 80,
+            line_break: YamlLnBreak,
+            unicode: true,
+        }
+    }
+}
+
+/// A formatting error, located in the input unless it is an I/O or an
+/// emitter error.
+#[derive(Debug)]
+pub(crate) struct Failure {
+    pub(crate) mark: Option<YamlMarkT>,
+    pub(crate) message: String,
+}
+
+impl Display for Failure {
+    /// Formats the error as `LINE:COLUMN: message`, one-based, or as
+    /// just the message.
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(mark) = self.mark {
+            write!(
+                formatter,
+                "{}:{}: ",
+                mark.line.wrapping_add(1),
+                mark.column.wrapping_add(1),
+            )?;
+        }
+        formatter.write_str(&self.message)
+    }
+}
+
+impl From<io::Error> for Failure {
+    fn from(error: io::Error) -> Self {
+        Failure {
+            mark: None,
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Formats the YAML stream `input`.
+pub(crate) unsafe fn format(
+    input: &[u8],
+    options: &Options,
+) -> Result<Vec<u8>, Failure> {
+    let mut parser = MaybeUninit::<YamlParserT>::uninit();
+    let parser = parser.as_mut_ptr();
+    if yaml_parser_initialize(parser).fail {
+        return Err(Failure {
+            mark: None,
+            message: "Could not initialize the parser object".into(),
+        });
+    }
+    let mut emitter = MaybeUninit::<YamlEmitterT>::uninit();
+    let emitter = emitter.as_mut_ptr();
+    if yaml_emitter_initialize(emitter).fail {
+        yaml_parser_delete(parser);
+        return Err(Failure {
+            mark: None,
+            message: "Could not initialize the emitter object".into(),
+        });
+    }
+
+    unsafe fn write_to_vec(
+        data: *mut c_void,
+        buffer: *mut u8,
+        size: u64,
+    ) -> i32 {
+        let output = &mut *data.cast::<Vec<u8>>();
+        output.extend_from_slice(slice::from_raw_parts(
+            buffer,
+            size as usize,
+        ));
+        1
+    }
+
+    let mut output = Vec::new();
+    yaml_parser_set_input_string(
+        parser,
+        input.as_ptr(),
+        input.len() as u64,
+    );
+    yaml_parser_set_comments(parser, true);
+    yaml_emitter_set_output(
+        emitter,
+        write_to_vec,
+        addr_of_mut!(output).cast(),
+    );
+    yaml_emitter_set_indent(emitter, options.indent);
+    yaml_emitter_set_width(emitter, options.width);
+    yaml_emitter_set_break(emitter, options.line_break);
+    yaml_emitter_set_unicode(emitter, options.unicode);
+
+    // The events go through as the parser produces them: they carry the
+    // scalar and collection styles, and whether the document markers
+    // are implicit, which the emitter follows wherever it can.
+    let mut event = MaybeUninit::<YamlEventT>::uninit();
+    let event = event.as_mut_ptr();
+    let result = loop {
+        if yaml_parser_parse(parser, event).fail {
+            let parser = &*parser;
+            // Memory errors come without a problem.
+            let mut error = Failure {
+                mark: Some(parser.problem_mark),
+                message: if parser.problem.is_null() {
+                    "parser error".into()
+                } else {
+                    CStr::from_ptr(parser.problem).to_string()
+                },
+            };
+            if !parser.context.is_null() {
+                let _ = write!(
+                    error.message,
+                    " ({} at {}:{})",
+                    CStr::from_ptr(parser.context),
+                    parser.context_mark.line.wrapping_add(1),
+                    parser.context_mark.column.wrapping_add(1),
+                );
+            }
+            break Err(error);
+        }
+        let type_ = (*event).type_;
+        if type_ == YamlScalarEvent {
+            keep_untagged(event);
+        }
+        // The emitter takes the event over, even when it fails.
+        if yaml_emitter_emit(emitter, event).fail {
+            let emitter = &*emitter;
+            break Err(Failure {
+                mark: None,
+                message: if emitter.problem.is_null() {
+                    "emitter error".into()
+                } else {
+                    CStr::from_ptr(emitter.problem).to_string()
+                },
+            });
+        }
+        if type_ == YamlStreamEndEvent {
+            break Ok(());
+        }
+    };
+    yaml_emitter_delete(emitter);
+    yaml_parser_delete(parser);
+    result.map(|()| output)
+}
+
+/// Lets a plain scalar that the emitter has to quote, such as one with
+/// a character it escapes, go without the `!` tag that would otherwise
+/// mark it as a string. That is only the case for scalars that are
+/// strings already, under the core and YAML 1.1 schemas alike.
+unsafe fn keep_untagged(event: *mut YamlEventT) {
+    let scalar = &mut (*event).data.scalar;
+    if !scalar.tag.is_null()
+        || !scalar.plain_implicit
+        || scalar.quoted_implicit
+        || scalar.length == 0
+    {
+        return;
+    }
+    let value =
+        slice::from_raw_parts(scalar.value, scalar.length as usize);
+    let Ok(value) = str::from_utf8(value) else {
+        return;
+    };
+    scalar.quoted_implicit =
+        [YamlCoreSchema, YamlYaml11Schema].iter().all(|&schema| {
+            resolver::resolve(schema, None, value, YamlPlainScalarStyle)
+                .is_ok_and(|resolved| resolved.tag == resolver::STR_TAG)
+        });
+}
+
+/// Formats the file at `path`, rewriting it unless `check` is set, and
+/// tells whether its content changes.
+pub(crate) fn format_file(
+    path: &Path,
+    options: &Options,
+    check: bool,
+) -> Result<bool, Failure> {
+    let input = fs::read(path)?;
+    let output = unsafe { format(&input, options)? };
+    if output == input {
+        return Ok(false);
+    }
+    if !check {
+        replace(path, &output)?;
+    }
+    Ok(true)
+}
+
+/// Replaces the content of the file at `path` with `content`, through a
+/// temporary file in the same directory renamed over it.
+fn replace(path: &Path, content: &[u8]) -> io::Result<()> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temporary = path.with_file_name(format!(
+        ".{}.{}.yamlfmt",
+        name,
+        process::id()
+    ));
+    let result = (|| {
+        let mut file = File::create(&temporary)?;
+        file.write_all(content)?;
+        file.set_permissions(fs::metadata(path)?.permissions())?;
+        file.sync_all()?;
+        fs::rename(&temporary, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    result
+}
+
+fn parse_args(
+    args: impl Iterator<Item = String>,
+) -> Result<(Options, bool, Vec<String>), String> {
+    let mut options = Options::default();
+    let mut check = false;
+    let mut files = Vec::new();
+    let mut args = args.peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--indent" => {
+                options.indent = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .filter(|n| (2..=9).contains(n))
+                    .ok_or("invalid --indent, not from 2 to 9")?;
+            }
+            "--width" => {
+                options.width = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or("invalid --width")?;
+            }
+            "--line-break" => {
+                options.line_break = match args.next().as_deref() {
+                    Some("lf") => YamlLnBreak,
+                    Some("crlf") => YamlCrlnBreak,
+                    Some("cr") => YamlCrBreak,
+                    _ => return Err("unknown line break".into()),
+                };
+            }
+            "--ascii" => options.unicode = false,
+            "--check" => check = true,
+            "--" => files.extend(args.by_ref()),
+            _ if arg.starts_with("--") => {
+                return Err(format!("unknown option {}", arg));
+            }
+            _ => files.push(arg),
+        }
+    }
+    Ok((options, check, files))
+}
+
+fn main() -> ExitCode {
+    let (options, check, files) = match parse_args(env::args().skip(1))
+    {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            let _ = writeln!(
+                io::stderr(),
+                "{}\nUsage: yamlfmt [--indent N] [--width N] \
+                 [--line-break lf|crlf|cr] [--ascii] [--check] \
+                 [FILE]...",
+                error,
+            );
+            return ExitCode::from(2);
+        }
+    };
+    let report = |name: &str, error: Failure| {
+        let separator = if error.mark.is_some() { ":" } else { ": " };
+        let _ =
+            writeln!(io::stderr(), "{}{}{}", name, separator, error);
+    };
+
+    if files.is_empty() {
+        let mut input = Vec::new();
+        let result = io::stdin()
+            .read_to_end(&mut input)
+            .map_err(Failure::from)
+            .and_then(|_| unsafe { format(&input, &options) });
+        return match result {
+            Ok(output) if check => {
+                ExitCode::from(u8::from(output != input))
+            }
+            Ok(output) => match io::stdout().write_all(&output) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(_) => ExitCode::from(2),
+            },
+            Err(error) => {
+                report("-", error);
+                ExitCode::from(2)
+            }
+        };
+    }
+
+    let mut changed = false;
+    let mut failed = false;
+    for name in files {
+        match format_file(Path::new(&name), &options, check) {
+            Ok(true) if check => {
+                changed = true;
+                let _ = writeln!(io::stdout(), "{}", name);
+            }
+            Ok(_) => {}
+            Err(error) => {
+                failed = true;
+                report(&name, error);
+            }
+        }
+    }
+    if failed {
+        ExitCode::from(2)
+    } else {
+        ExitCode::from(u8::from(changed))
+    }
+}
@@ -0,0 +1,466 @@
+//! `Result`-returning counterparts of the raw `yaml_*` functions.
+//!
+//! Each function here calls the function of the same name in the crate
+//! root and, when it fails, copies the error state out of the parser or
+//! emitter into an [`Error`]. The safety requirements are those of the
+//! wrapped function.
+//!
+//! ```
+//! use core::mem::MaybeUninit;
+//! use libyml::checked::{yaml_parser_initialize, yaml_parser_parse};
+//! use libyml::error::ErrorKind;
+//! use libyml::{
+//!     yaml_event_delete, yaml_parser_delete,
+//!     yaml_parser_set_input_string, YamlStreamEndEvent,
+//! };
+//!
+//! unsafe {
+//!     let mut parser = MaybeUninit::uninit();
+//!     let parser = parser.as_mut_ptr();
+//!     yaml_parser_initialize(parser).unwrap();
+//!     let input = b"[1, 2";
+//!     yaml_parser_set_input_string(parser, input.as_ptr(), 5);
+//!     let mut event = MaybeUninit::uninit();
+//!     let event = event.as_mut_ptr();
+//!     let error = loop {
+//!         match yaml_parser_parse(parser, event) {
+//!             Ok(()) => {
+//!                 let done = (*event).type_ == YamlStreamEndEvent;
+//!                 yaml_event_delete(event);
+//!                 assert!(!done);
+//!             }
+//!             Err(error) => break error,
+//!         }
+//!     };
+//!     assert_eq!(error.kind(), ErrorKind::Parser);
+//!     yaml_parser_delete(parser);
+//! }
+//! ```
+
+use crate::api::ScalarEventData;
+use crate::error::{Error, ErrorKind};
+use crate::libc;
+use crate::success::Success;
+use crate::yaml::{
+    yaml_char_t, YamlDocumentT, YamlEmitterT, YamlEncodingT,
+    YamlEventT, YamlMappingStyleT, YamlParserT, YamlSequenceStyleT,
+    YamlTagDirectiveT, YamlTokenT, YamlVersionDirectiveT,
+};
+
+fn check(
+    success: Success,
+    error: impl FnOnce() -> Error,
+) -> Result<(), Error> {
+    if success.ok {
+        Ok(())
+    } else {
+        Err(error())
+    }
+}
+
+fn invalid_event() -> Error {
+    Error::new(ErrorKind::Emitter, "invalid event data")
+}
+
+/// See [`yaml_parser_initialize`](crate::yaml_parser_initialize).
+///
+/// # Safety
+///
+/// Same as [`yaml_parser_initialize`](crate::yaml_parser_initialize).
+pub unsafe fn yaml_parser_initialize(
+    parser: *mut YamlParserT,
+) -> Result<(), Error> {
+    check(crate::yaml_parser_initialize(parser), || {
+        Error::from_parser(parser)
+    })
+}
+
+/// See [`yaml_parser_scan`](crate::yaml_parser_scan).
+///
+/// # Safety
+///
+/// Same as [`yaml_parser_scan`](crate::yaml_parser_scan).
+pub unsafe fn yaml_parser_scan(
+    parser: *mut YamlParserT,
+    token: *mut YamlTokenT,
+) -> Result<(), Error> {
+    check(crate::yaml_parser_scan(parser, token), || {
+        Error::from_parser(parser)
+    })
+}
+
+/// See [`yaml_parser_parse`](crate::yaml_parser_parse).
+///
+/// # Safety
+///
+/// Same as [`yaml_parser_parse`](crate::yaml_parser_parse).
+pub unsafe fn yaml_parser_parse(
+    parser: *mut YamlParserT,
+    event: *mut YamlEventT,
+) -> Result<(), Error> {
+    check(crate::yaml_parser_parse(parser, event), || {
+        Error::from_parser(parser)
+    })
+}
+
+/// See [`yaml_parser_load`](crate::yaml_parser_load).
+///
+/// # Safety
+///
+/// Same as [`yaml_parser_load`](crate::yaml_parser_load).
+pub unsafe fn yaml_parser_load(
+    parser: *mut YamlParserT,
+    document: *mut YamlDocumentT,
+) -> Result<(), Error> {
+    check(crate::yaml_parser_load(parser, document), || {
+        Error::from_parser(parser)
+    })
+}
+
+/// See [`yaml_emitter_initialize`](crate::yaml_emitter_initialize).
+///
+/// # Safety
+///
+/// Same as [`yaml_emitter_initialize`](crate::yaml_emitter_initialize).
+pub unsafe fn yaml_emitter_initialize(
+    emitter: *mut YamlEmitterT,
+) -> Result<(), Error> {
+    check(crate::yaml_emitter_initialize(emitter), || {
+        Error::from_emitter(emitter)
+    })
+}
+
+/// See [`yaml_emitter_emit`](crate::yaml_emitter_emit).
+///
+/// # Safety
+///
+/// Same as [`yaml_emitter_emit`](crate::yaml_emitter_emit).
+pub unsafe fn yaml_emitter_emit(
+    emitter: *mut YamlEmitterT,
+    event: *mut YamlEventT,
+) -> Result<(), Error> {
+    check(crate::yaml_emitter_emit(emitter, event), || {
+        Error::from_emitter(emitter)
+    })
+}
+
+/// See [`yaml_emitter_flush`](crate::yaml_emitter_flush).
+///
+/// # Safety
+///
+/// Same as [`yaml_emitter_flush`](crate::yaml_emitter_flush).
+pub unsafe fn yaml_emitter_flush(
+    emitter: *mut YamlEmitterT,
+) -> Result<(), Error> {
+    check(crate::yaml_emitter_flush(emitter), || {
+        Error::from_emitter(emitter)
+    })
+}
+
+/// See [`yaml_emitter_open`](crate::yaml_emitter_open).
+///
+/// # Safety
+///
+/// Same as [`yaml_emitter_open`](crate::yaml_emitter_open).
+pub unsafe fn yaml_emitter_open(
+    emitter: *mut YamlEmitterT,
+) -> Result<(), Error> {
+    check(crate::yaml_emitter_open(emitter), || {
+        Error::from_emitter(emitter)
+    })
+}
+
+/// See [`yaml_emitter_close`](crate::yaml_emitter_close).
+///
+/// # Safety
+///
+/// Same as [`yaml_emitter_close`](crate::yaml_emitter_close).
+pub unsafe fn yaml_emitter_close(
+    emitter: *mut YamlEmitterT,
+) -> Result<(), Error> {
+    check(crate::yaml_emitter_close(emitter), || {
+        Error::from_emitter(emitter)
+    })
+}
+
+/// See [`yaml_emitter_dump`](crate::yaml_emitter_dump).
+///
+/// # Safety
+///
+/// Same as [`yaml_emitter_dump`](crate::yaml_emitter_dump).
+pub unsafe fn yaml_emitter_dump(
+    emitter: *mut YamlEmitterT,
+    document: *mut YamlDocumentT,
+) -> Result<(), Error> {
+    check(crate::yaml_emitter_dump(emitter, document), || {
+        Error::from_emitter(emitter)
+    })
+}
+
+/// See
+/// [`yaml_stream_start_event_initialize`](crate::yaml_stream_start_event_initialize).
+///
+/// # Safety
+///
+/// Same as
+/// [`yaml_stream_start_event_initialize`](crate::yaml_stream_start_event_initialize).
+pub unsafe fn yaml_stream_start_event_initialize(
+    event: *mut YamlEventT,
+    encoding: YamlEncodingT,
+) -> Result<(), Error> {
+    check(
+        crate::yaml_stream_start_event_initialize(event, encoding),
+        invalid_event,
+    )
+}
+
+/// See
+/// [`yaml_stream_end_event_initialize`](crate::yaml_stream_end_event_initialize).
+///
+/// # Safety
+///
+/// Same as
+/// [`yaml_stream_end_event_initialize`](crate::yaml_stream_end_event_initialize).
+pub unsafe fn yaml_stream_end_event_initialize(
+    event: *mut YamlEventT,
+) -> Result<(), Error> {
+    check(
+        crate::yaml_stream_end_event_initialize(event),
+        invalid_event,
+    )
+}
+
+/// See
+/// [`yaml_document_start_event_initialize`](crate::yaml_document_start_event_initialize).
+///
+/// # Safety
+///
+/// Same as
+/// [`yaml_document_start_event_initialize`](crate::yaml_document_start_event_initialize).
+pub unsafe fn yaml_document_start_event_initialize(
+    event: *mut YamlEventT,
+    version_directive: *mut YamlVersionDirectiveT,
+    tag_directives_start: *mut YamlTagDirectiveT,
+    tag_directives_end: *mut YamlTagDirectiveT,
+    implicit: bool,
+) -> Result<(), Error> {
+    check(
+        crate::yaml_document_start_event_initialize(
+            event,
+            version_directive,
+            tag_directives_start,
+            tag_directives_end,
+            implicit,
+        ),
+        invalid_event,
+    )
+}
+
+/// See
+/// [`yaml_document_end_event_initialize`](crate::yaml_document_end_event_initialize).
+///
+/// # Safety
+///
+/// Same as
+/// [`yaml_document_end_event_initialize`](crate::yaml_document_end_event_initialize).
+pub unsafe fn yaml_document_end_event_initialize(
+    event: *mut YamlEventT,
+    implicit: bool,
+) -> Result<(), Error> {
+    check(
+        crate::yaml_document_end_event_initialize(event, implicit),
+        invalid_event,
+    )
+}
+
+/// See [`yaml_alias_event_initialize`](crate::yaml_alias_event_initialize).
+///
+/// # Safety
+///
+/// Same as
+/// [`yaml_alias_event_initialize`](crate::yaml_alias_event_initialize).
+pub unsafe fn yaml_alias_event_initialize(
+    event: *mut YamlEventT,
+    anchor: *const yaml_char_t,
+) -> Result<(), Error> {
+    check(
+        crate::yaml_alias_event_initialize(event, anchor),
+        invalid_event,
+    )
+}
+
+/// See [`yaml_scalar_event_initialize`](crate::yaml_scalar_event_initialize).
+///
+/// # Safety
+///
+/// Same as
+/// [`yaml_scalar_event_initialize`](crate::yaml_scalar_event_initialize).
+pub unsafe fn yaml_scalar_event_initialize(
+    event: *mut YamlEventT,
+    data: ScalarEventData<'_>,
+) -> Result<(), Error> {
+    check(
+        crate::yaml_scalar_event_initialize(event, data),
+        invalid_event,
+    )
+}
+
+/// See
+/// [`yaml_sequence_start_event_initialize`](crate::yaml_sequence_start_event_initialize).
+///
+/// # Safety
+///
+/// Same as
+/// [`yaml_sequence_start_event_initialize`](crate::yaml_sequence_start_event_initialize).
+pub unsafe fn yaml_sequence_start_event_initialize(
+    event: *mut YamlEventT,
+    anchor: *const yaml_char_t,
+    tag: *const yaml_char_t,
+    implicit: bool,
+    style: YamlSequenceStyleT,
+) -> Result<(), Error> {
+    check(
+        crate::yaml_sequence_start_event_initialize(
+            event, anchor, tag, implicit, style,
+        ),
+        invalid_event,
+    )
+}
+
+/// See
+/// [`yaml_sequence_end_event_initialize`](crate::yaml_sequence_end_event_initialize).
+///
+/// # Safety
+///
+/// Same as
+/// [`yaml_sequence_end_event_initialize`](crate::yaml_sequence_end_event_initialize).
+pub unsafe fn yaml_sequence_end_event_initialize(
+    event: *mut YamlEventT,
+) -> Result<(), Error> {
+    check(
+        crate::yaml_sequence_end_event_initialize(event),
+        invalid_event,
+    )
+}
+
+/// See
+/// [`yaml_mapping_start_event_initialize`](crate::yaml_mapping_start_event_initialize).
+///
+/// # Safety
+///
+/// Same as
+/// [`yaml_mapping_start_event_initialize`](crate::yaml_mapping_start_event_initialize).
+pub unsafe fn yaml_mapping_start_event_initialize(
+    event: *mut YamlEventT,
+    anchor: *const yaml_char_t,
+    tag: *const yaml_char_t,
+    implicit: bool,
+    style: YamlMappingStyleT,
+) -> Result<(), Error> {
+    check(
+        crate::yaml_mapping_start_event_initialize(
+            event, anchor, tag, implicit, style,
+        ),
+        invalid_event,
+    )
+}
+
+/// See
+/// [`yaml_mapping_end_event_initialize`](crate::yaml_mapping_end_event_initialize).
+///
+/// # Safety
+///
+/// Same as
+/// [`yaml_mapping_end_event_initialize`](crate::yaml_mapping_end_event_initialize).
+pub unsafe fn yaml_mapping_end_event_initialize(
+    event: *mut YamlEventT,
+) -> Result<(), Error> {
+    check(
+        crate::yaml_mapping_end_event_initialize(event),
+        invalid_event,
+    )
+}
+
+/// See [`yaml_event_set_comments`](crate::yaml_event_set_comments).
+///
+/// # Safety
+///
+/// Same as [`yaml_event_set_comments`](crate::yaml_event_set_comments).
+pub unsafe fn yaml_event_set_comments(
+    event: *mut YamlEventT,
+    before: *const yaml_char_t,
+    inline: *const yaml_char_t,
+    after: *const yaml_char_t,
+) -> Result<(), Error> {
+    check(
+        crate::yaml_event_set_comments(event, before, inline, after),
+        invalid_event,
+    )
+}
+
+/// See [`yaml_document_initialize`](crate::yaml_document_initialize).
+///
+/// # Safety
+///
+/// Same as [`yaml_document_initialize`](crate::yaml_document_initialize).
+pub unsafe fn yaml_document_initialize(
+    document: *mut YamlDocumentT,
+    version_directive: *mut YamlVersionDirectiveT,
+    tag_directives_start: *mut YamlTagDirectiveT,
+    tag_directives_end: *mut YamlTagDirectiveT,
+    start_implicit: bool,
+    end_implicit: bool,
+) -> Result<(), Error> {
+    check(
+        crate::yaml_document_initialize(
+            document,
+            version_directive,
+            tag_directives_start,
+            tag_directives_end,
+            start_implicit,
+            end_implicit,
+        ),
+        || Error::new(ErrorKind::Emitter, "invalid document data"),
+    )
+}
+
+/// See
+/// [`yaml_document_append_sequence_item`](crate::yaml_document_append_sequence_item).
+///
+/// # Safety
+///
+/// Same as
+/// [`yaml_document_append_sequence_item`](crate::yaml_document_append_sequence_item).
+pub unsafe fn yaml_document_append_sequence_item(
+    document: *mut YamlDocumentT,
+    sequence: libc::c_int,
+    item: libc::c_int,
+) -> Result<(), Error> {
+    check(
+        crate::yaml_document_append_sequence_item(
+            document, sequence, item,
+        ),
+        || Error::new(ErrorKind::Memory, "out of memory"),
+    )
+}
+
+/// See
+/// [`yaml_document_append_mapping_pair`](crate::yaml_document_append_mapping_pair).
+///
+/// # Safety
+///
+/// Same as
+/// [`yaml_document_append_mapping_pair`](crate::yaml_document_append_mapping_pair).
+pub unsafe fn yaml_document_append_mapping_pair(
+    document: *mut YamlDocumentT,
+    mapping: libc::c_int,
+    key: libc::c_int,
+    value: libc::c_int,
+) -> Result<(), Error> {
+    check(
+        crate::yaml_document_append_mapping_pair(
+            document, mapping, key, value,
+        ),
+        || Error::new(ErrorKind::Memory, "out of memory"),
+    )
+}
@@ -0,0 +1,1166 @@
+//! Deserialization of YAML into Rust types with Serde.
+//!
+//! The [`Deserializer`] is driven by the events of a [`Parser`]: it
+//! reads the events of a document and walks them as the type being
+//! deserialized asks for its fields, without building a node tree.
+//!
+//! - Untagged plain scalars resolve according to the schema of the
+//!   parser, the core schema unless the deserializer is created with
+//!   [`Deserializer::from_parser`]. Scalars tagged with a type resolve
+//!   according to their tag, and strings accept any scalar.
+//! - An enum variant is given by a local tag on its value, as in
+//!   `!Circle {radius: 1}`, or by its name, as a plain scalar for a unit
+//!   variant or as the key of a mapping with a single pair.
+//! - Aliases stand for a copy of the node they refer to. They may stand
+//!   for at most [`max_alias_expansion`](Parser::max_alias_expansion)
+//!   nodes per document, and nodes, aliases included, may nest 128 deep.
+//! - [`from_str`], [`from_slice`] and [`from_reader`] read a stream of at
+//!   most one document, an empty stream being a null document. Iterating
+//!   over a [`Deserializer`] yields one deserializer per document.
+//! - Errors carry the position of the node they are about and its path
+//!   from the root, such as `servers[1].port`.
+//!
+//! ```
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize, Debug, PartialEq)]
+//! enum Shape {
+//!     Circle { radius: f64 },
+//!     Point,
+//! }
+//!
+//! #[derive(Deserialize, Debug, PartialEq)]
+//! struct Drawing {
+//!     name: String,
+//!     shapes: Vec<Shape>,
+//! }
+//!
+//! let input = "
+//! name: logo
+//! shapes:
+//! - !Circle {radius: 1.5}
+//! - Point
+//! ";
+//! let drawing: Drawing = libyml::from_str(input).unwrap();
+//! assert_eq!(
+//!     drawing.shapes,
+//!     [Shape::Circle { radius: 1.5 }, Shape::Point],
+//! );
+//!
+//! let error = libyml::from_str::<Drawing>("name: [logo]\n").unwrap_err();
+//! assert_eq!(error.path(), Some("name"));
+//! assert_eq!(
+//!     error.to_string(),
+//!     "name: invalid type: sequence, expected a string at line 1 \
+//!      column 7",
+//! );
+//! ```
+
+use crate::error::{Error, ErrorKind};
+use crate::resolver::{self, BigInt, Value};
+use crate::safe::{Event, Parser};
+use crate::yaml::{YamlMarkT, YamlScalarStyleT, YamlSchemaT};
+use crate::{YamlCoreSchema, YamlPlainScalarStyle};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display};
+use serde::de::value::StrDeserializer;
+use serde::de::{
+    self, Deserialize, DeserializeSeed, Expected, IgnoredAny,
+    IntoDeserializer, Visitor,
+};
+
+/// How deeply nodes may nest, counting the nodes aliases stand for.
+const MAX_DEPTH: usize = 128;
+
+/// Deserializes an instance of `T` from the YAML stream `input`, which
+/// must hold at most one document.
+pub fn from_str<'de, T: Deserialize<'de>>(
+    input: &'de str,
+) -> Result<T, Error> {
+    T::deserialize(Deserializer::from_str(input))
+}
+
+/// Deserializes an instance of `T` from the YAML stream `input`, which
+/// must hold at most one document.
+pub fn from_slice<'de, T: Deserialize<'de>>(
+    input: &'de [u8],
+) -> Result<T, Error> {
+    T::deserialize(Deserializer::from_slice(input))
+}
+
+/// Deserializes an instance of `T` from the YAML stream read from
+/// `reader`, which must hold at most one document.
+#[cfg(feature = "std")]
+pub fn from_reader<R: std::io::Read, T: de::DeserializeOwned>(
+    reader: R,
+) -> Result<T, Error> {
+    T::deserialize(Deserializer::from_reader(reader))
+}
+
+/// A Serde deserializer reading the documents of a YAML stream.
+///
+/// Deserializing from it reads a stream of at most one document. A
+/// stream of several documents is read by iterating over it:
+///
+/// ```
+/// use libyml::Deserializer;
+/// use serde::Deserialize;
+///
+/// let mut values = Vec::new();
+/// for document in Deserializer::from_str("--- 1\n--- 2\n") {
+///     values.push(u32::deserialize(document).unwrap());
+/// }
+/// assert_eq!(values, [1, 2]);
+/// ```
+pub struct Deserializer<'de> {
+    progress: Progress<'de>,
+}
+
+enum Progress<'de> {
+    Stream(Box<Parser<'de>>),
+    Document(Document<'de>),
+    Fail(Error),
+}
+
+impl<'de> Deserializer<'de> {
+    /// Creates a deserializer reading from `input`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(input: &'de str) -> Self {
+        Deserializer::from_slice(input.as_bytes())
+    }
+
+    /// Creates a deserializer reading from `input`.
+    pub fn from_slice(input: &'de [u8]) -> Self {
+        let mut parser = Parser::new(input);
+        parser.set_schema(YamlCoreSchema);
+        Deserializer::from_parser(parser)
+    }
+
+    /// Creates a deserializer pulling its input from `reader`.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: std::io::Read + 'de>(reader: R) -> Self {
+        let mut parser = Parser::from_reader(reader);
+        parser.set_schema(YamlCoreSchema);
+        Deserializer::from_parser(parser)
+    }
+
+    /// Creates a deserializer reading the events of `parser`, with its
+    /// schema and its alias expansion limit.
+    pub fn from_parser(parser: Parser<'de>) -> Self {
+        Deserializer {
+            progress: Progress::Stream(Box::new(parser)),
+        }
+    }
+
+    fn de<T>(
+        self,
+        f: impl for<'document> FnOnce(
+            &mut DeserializerFromEvents<'de, 'document>,
+        ) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        match self.progress {
+            Progress::Stream(mut parser) => {
+                let document = match load_document(&mut parser)? {
+                    Some(document) => document,
+                    None => Document::empty(&parser),
+                };
+                let value = document.deserialize(f)?;
+                match parser.next_event() {
+                    Some(Ok(Event::DocumentStart(_))) => Err(Error::new(
+                        ErrorKind::Composer,
+                        "deserializing from YAML containing more than \
+                         one document is not supported",
+                    )
+                    .locate(parser.start_mark(), None)),
+                    Some(Err(error)) => Err(error),
+                    _ => Ok(value),
+                }
+            }
+            Progress::Document(document) => document.deserialize(f),
+            Progress::Fail(error) => Err(error),
+        }
+    }
+}
+
+impl Iterator for Deserializer<'_> {
+    type Item = Self;
+
+    /// Reads the next document of the stream. A deserializer yields no
+    /// documents once the stream fails, or if it reads a document
+    /// itself.
+    fn next(&mut self) -> Option<Self> {
+        let Progress::Stream(parser) = &mut self.progress else {
+            return None;
+        };
+        let progress = match load_document(parser) {
+            Ok(Some(document)) => Progress::Document(document),
+            Ok(None) => return None,
+            Err(error) => Progress::Fail(error),
+        };
+        Some(Deserializer { progress })
+    }
+}
+
+impl Debug for Deserializer<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("Deserializer")
+            .finish_non_exhaustive()
+    }
+}
+
+/// The events of a document, as far as deserializing needs them.
+struct Document<'de> {
+    items: Vec<(Item<'de>, YamlMarkT)>,
+    schema: YamlSchemaT,
+    max_alias_expansion: u64,
+}
+
+enum Item<'de> {
+    /// An alias, to the node starting at the given index.
+    Alias(usize),
+    Scalar {
+        value: Cow<'de, str>,
+        tag: Option<Cow<'de, str>>,
+        style: YamlScalarStyleT,
+    },
+    SequenceStart {
+        tag: Option<Cow<'de, str>>,
+    },
+    SequenceEnd,
+    MappingStart {
+        tag: Option<Cow<'de, str>>,
+    },
+    MappingEnd,
+}
+
+/// Reads the events of the next document of the stream, or returns
+/// `None` once it has ended.
+fn load_document<'de>(
+    parser: &mut Parser<'de>,
+) -> Result<Option<Document<'de>>, Error> {
+    let mut items = Vec::new();
+    let mut anchors = BTreeMap::new();
+    loop {
+        let event = match parser.next_event() {
+            Some(event) => event?,
+            None => return Ok(None),
+        };
+        let mark = parser.start_mark();
+        let (item, anchor) = match event {
+            Event::StreamStart(_) | Event::DocumentStart(_) => continue,
+            Event::StreamEnd => return Ok(None),
+            Event::DocumentEnd(_) => break,
+            Event::Alias(alias) => match anchors.get(&alias.anchor) {
+                Some(&index) => (Item::Alias(index), None),
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::Composer,
+                        "found undefined alias",
+                    )
+                    .locate(mark, None));
+                }
+            },
+            Event::Scalar(scalar) => (
+                Item::Scalar {
+                    value: scalar.value,
+                    tag: scalar.tag,
+                    style: scalar.style,
+                },
+                scalar.anchor,
+            ),
+            Event::SequenceStart(start) => {
+                (Item::SequenceStart { tag: start.tag }, start.anchor)
+            }
+            Event::SequenceEnd => (Item::SequenceEnd, None),
+            Event::MappingStart(start) => {
+                (Item::MappingStart { tag: start.tag }, start.anchor)
+            }
+            Event::MappingEnd => (Item::MappingEnd, None),
+        };
+        // A later anchor of the same name hides the earlier one.
+        if let Some(anchor) = anchor {
+            anchors.insert(anchor, items.len());
+        }
+        items.push((item, mark));
+    }
+    Ok(Some(Document {
+        items,
+        schema: parser.schema(),
+        max_alias_expansion: parser.max_alias_expansion(),
+    }))
+}
+
+impl<'de> Document<'de> {
+    /// The null document an empty stream stands for.
+    fn empty(parser: &Parser<'de>) -> Self {
+        let scalar = Item::Scalar {
+            value: Cow::Borrowed(""),
+            tag: None,
+            style: YamlPlainScalarStyle,
+        };
+        Document {
+            items: Vec::from([(scalar, parser.start_mark())]),
+            schema: parser.schema(),
+            max_alias_expansion: parser.max_alias_expansion(),
+        }
+    }
+
+    fn deserialize<T>(
+        &self,
+        f: impl for<'document> FnOnce(
+            &mut DeserializerFromEvents<'de, 'document>,
+        ) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let mut pos = 0;
+        let mut expanded = 0;
+        f(&mut DeserializerFromEvents {
+            document: self,
+            pos: &mut pos,
+            expanded: &mut expanded,
+            path: Path::Root,
+            remaining_depth: MAX_DEPTH,
+            alias: None,
+            untagged: false,
+        })
+    }
+}
+
+/// Where a node is in the document.
+#[derive(Copy, Clone)]
+enum Path<'a> {
+    Root,
+    Seq { parent: &'a Path<'a>, index: usize },
+    Map { parent: &'a Path<'a>, key: &'a str },
+    Alias { parent: &'a Path<'a> },
+}
+
+impl Path<'_> {
+    fn is_root(&self) -> bool {
+        match self {
+            Path::Root => true,
+            Path::Alias { parent } => parent.is_root(),
+            Path::Seq { .. } | Path::Map { .. } => false,
+        }
+    }
+}
+
+impl Display for Path<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Path::Root => Ok(()),
+            Path::Seq { parent, index } => {
+                write!(formatter, "{}[{}]", parent, index)
+            }
+            Path::Map { parent, key } if parent.is_root() => {
+                formatter.write_str(key)
+            }
+            Path::Map { parent, key } => {
+                write!(formatter, "{}.{}", parent, key)
+            }
+            Path::Alias { parent } => Display::fmt(parent, formatter),
+        }
+    }
+}
+
+/// Deserializes the node starting at `*pos`, moving `*pos` past it.
+struct DeserializerFromEvents<'de, 'document> {
+    document: &'document Document<'de>,
+    pos: &'document mut usize,
+    /// How many nodes aliases have stood for so far.
+    expanded: &'document mut u64,
+    path: Path<'document>,
+    remaining_depth: usize,
+    /// Where the outermost alias the node is read for is, if any.
+    alias: Option<YamlMarkT>,
+    /// Has the tag of the node been read as an enum variant already?
+    untagged: bool,
+}
+
+impl<'de, 'document> DeserializerFromEvents<'de, 'document> {
+    fn peek_item(
+        &self,
+    ) -> Result<(&'document Item<'de>, YamlMarkT), Error> {
+        let document = self.document;
+        match document.items.get(*self.pos) {
+            Some((item, mark)) => Ok((item, *mark)),
+            None => Err(Error::new(
+                ErrorKind::Composer,
+                "unexpected end of document",
+            )),
+        }
+    }
+
+    fn next_item(
+        &mut self,
+    ) -> Result<(&'document Item<'de>, YamlMarkT), Error> {
+        let (item, mark) = self.peek_item()?;
+        *self.pos += 1;
+        if let Some(alias) = self.alias {
+            if let Item::Scalar { .. }
+            | Item::SequenceStart { .. }
+            | Item::MappingStart { .. } = item
+            {
+                *self.expanded += 1;
+                if *self.expanded > self.document.max_alias_expansion {
+                    let error = Error::new(
+                        ErrorKind::Composer,
+                        "alias expansion limit exceeded",
+                    );
+                    return Err(self.fix(error, alias));
+                }
+            }
+        }
+        Ok((item, mark))
+    }
+
+    /// A deserializer for the node an alias at `mark` stands for, which
+    /// starts at `*pos`. Going over the expansion limit is reported at
+    /// the outermost alias being expanded.
+    fn jump<'a>(
+        &'a mut self,
+        pos: &'a mut usize,
+        mark: YamlMarkT,
+    ) -> DeserializerFromEvents<'de, 'a> {
+        DeserializerFromEvents {
+            document: self.document,
+            pos,
+            expanded: &mut *self.expanded,
+            path: Path::Alias { parent: &self.path },
+            remaining_depth: self.remaining_depth,
+            alias: Some(self.alias.unwrap_or(mark)),
+            untagged: false,
+        }
+    }
+
+    /// Places an error about the node at `mark` that has no position
+    /// yet.
+    fn fix(&self, error: Error, mark: YamlMarkT) -> Error {
+        if error.end_mark().is_some() {
+            return error;
+        }
+        let path = if self.path.is_root() {
+            None
+        } else {
+            Some(self.path.to_string())
+        };
+        error.locate(mark, path)
+    }
+
+    fn tag<'a>(
+        &self,
+        tag: &'a Option<Cow<'de, str>>,
+    ) -> Option<&'a str> {
+        if self.untagged {
+            None
+        } else {
+            tag.as_deref()
+        }
+    }
+
+    fn is_null(
+        &self,
+        value: &str,
+        tag: &Option<Cow<'de, str>>,
+        style: YamlScalarStyleT,
+    ) -> bool {
+        let tag = self.tag(tag);
+        matches!(
+            resolver::resolve(self.document.schema, tag, value, style),
+            Ok(resolved) if resolved.value == Value::Null
+        )
+    }
+
+    fn recursion_check<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let previous = self.remaining_depth;
+        self.remaining_depth = match previous.checked_sub(1) {
+            Some(remaining_depth) => remaining_depth,
+            None => {
+                return Err(Error::new(
+                    ErrorKind::Composer,
+                    "recursion limit exceeded",
+                ));
+            }
+        };
+        let result = f(self);
+        self.remaining_depth = previous;
+        result
+    }
+
+    fn visit_scalar<V: Visitor<'de>>(
+        &self,
+        visitor: V,
+        value: &'document Cow<'de, str>,
+        tag: &'document Option<Cow<'de, str>>,
+        style: YamlScalarStyleT,
+    ) -> Result<V::Value, Error> {
+        let tag = self.tag(tag);
+        let schema = self.document.schema;
+        let resolved = resolver::resolve(schema, tag, value, style)?;
+        match resolved.value {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(value) => visitor.visit_bool(value),
+            Value::Int(value) => visitor.visit_i64(value),
+            Value::BigInt(int) => visit_wide_int(visitor, int)
+                .unwrap_or_else(|| {
+                    Err(Error::new(
+                        ErrorKind::Composer,
+                        format!("integer {:?} out of range", value),
+                    ))
+                }),
+            Value::Float(value) => visitor.visit_f64(value),
+            _ => visit_str(visitor, value),
+        }
+    }
+
+    fn visit_sequence<V: Visitor<'de>>(
+        &mut self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let (value, len) = self.recursion_check(|de| {
+            let mut seq = SeqAccess { de, len: 0 };
+            let value = visitor.visit_seq(&mut seq)?;
+            Ok((value, seq.len))
+        })?;
+        self.end_sequence(len)?;
+        Ok(value)
+    }
+
+    fn visit_mapping<V: Visitor<'de>>(
+        &mut self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let (value, len) = self.recursion_check(|de| {
+            let mut map = MapAccess {
+                de,
+                len: 0,
+                key: None,
+            };
+            let value = visitor.visit_map(&mut map)?;
+            Ok((value, map.len))
+        })?;
+        self.end_mapping(len)?;
+        Ok(value)
+    }
+
+    /// Skips the items the visitor left, and fails if there were any.
+    fn end_sequence(&mut self, len: usize) -> Result<(), Error> {
+        let total = {
+            let mut seq = SeqAccess { de: self, len };
+            while de::SeqAccess::next_element::<IgnoredAny>(&mut seq)?
+                .is_some()
+            {}
+            seq.len
+        };
+        self.next_item()?;
+        if total == len {
+            Ok(())
+        } else {
+            Err(de::Error::invalid_length(total, &ExpectedSeq(len)))
+        }
+    }
+
+    /// Skips the pairs the visitor left, and fails if there were any.
+    fn end_mapping(&mut self, len: usize) -> Result<(), Error> {
+        let total = {
+            let mut map = MapAccess {
+                de: self,
+                len,
+                key: None,
+            };
+            while de::MapAccess::next_entry::<IgnoredAny, IgnoredAny>(
+                &mut map,
+            )?
+            .is_some()
+            {}
+            map.len
+        };
+        self.next_item()?;
+        if total == len {
+            Ok(())
+        } else {
+            Err(de::Error::invalid_length(total, &ExpectedMap(len)))
+        }
+    }
+
+    /// Skips the node, without following aliases.
+    fn ignore_any(&mut self) {
+        let mut depth = 0_usize;
+        while let Some((item, _)) = self.document.items.get(*self.pos) {
+            *self.pos += 1;
+            match item {
+                Item::SequenceStart { .. }
+                | Item::MappingStart { .. } => {
+                    depth += 1;
+                }
+                Item::SequenceEnd | Item::MappingEnd => depth -= 1,
+                Item::Alias(_) | Item::Scalar { .. } => {}
+            }
+            if depth == 0 {
+                break;
+            }
+        }
+    }
+}
+
+fn visit_str<'de, V: Visitor<'de>>(
+    visitor: V,
+    value: &Cow<'de, str>,
+) -> Result<V::Value, Error> {
+    match value {
+        Cow::Borrowed(value) => visitor.visit_borrowed_str(value),
+        Cow::Owned(value) => visitor.visit_str(value),
+    }
+}
+
+/// Visits an integer too wide for an `i64`, as the serializer writes
+/// large `u64`, `i128` and `u128` values, in any base of the schema.
+fn visit_wide_int<'de, V: Visitor<'de>>(
+    visitor: V,
+    int: BigInt,
+) -> Option<Result<V::Value, Error>> {
+    let mut digits = String::new();
+    if int.is_negative() {
+        digits.push('-');
+    }
+    digits.extend(int.digits().chars().filter(|&ch| ch != '_'));
+    let radix = int.radix();
+    if let Ok(value) = u64::from_str_radix(&digits, radix) {
+        Some(visitor.visit_u64(value))
+    } else if let Ok(value) = i128::from_str_radix(&digits, radix) {
+        Some(visitor.visit_i128(value))
+    } else if let Ok(value) = u128::from_str_radix(&digits, radix) {
+        Some(visitor.visit_u128(value))
+    } else {
+        None
+    }
+}
+
+/// The variant of a local tag, such as `Circle` for `!Circle`.
+fn variant_tag(tag: &str) -> Option<&str> {
+    tag.strip_prefix('!').filter(|variant| {
+        !variant.is_empty() && !variant.starts_with('!')
+    })
+}
+
+impl<'de> de::Deserializer<'de>
+    for &mut DeserializerFromEvents<'de, '_>
+{
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let (item, mark) = self.next_item()?;
+        let result = match item {
+            Item::Alias(pos) => {
+                let mut pos = *pos;
+                self.jump(&mut pos, mark).deserialize_any(visitor)
+            }
+            Item::Scalar { value, tag, style } => {
+                self.visit_scalar(visitor, value, tag, *style)
+            }
+            Item::SequenceStart { .. } => self.visit_sequence(visitor),
+            Item::MappingStart { .. } => self.visit_mapping(visitor),
+            Item::SequenceEnd | Item::MappingEnd => Err(Error::new(
+                ErrorKind::Composer,
+                "unexpected end of a collection",
+            )),
+        };
+        result.map_err(|error| self.fix(error, mark))
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let (item, mark) = self.peek_item()?;
+        let result = match item {
+            Item::Alias(pos) => self.next_item().and_then(|_| {
+                let mut pos = *pos;
+                self.jump(&mut pos, mark).deserialize_str(visitor)
+            }),
+            Item::Scalar { value, .. } => {
+                self.next_item().and_then(|_| visit_str(visitor, value))
+            }
+            _ => return self.deserialize_any(visitor),
+        };
+        result.map_err(|error| self.fix(error, mark))
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let (item, mark) = self.peek_item()?;
+        let result = match item {
+            Item::Alias(pos) => self.next_item().and_then(|_| {
+                let mut pos = *pos;
+                self.jump(&mut pos, mark).deserialize_option(visitor)
+            }),
+            Item::Scalar { value, tag, style }
+                if self.is_null(value, tag, *style) =>
+            {
+                self.next_item().and_then(|_| visitor.visit_none())
+            }
+            _ => return visitor.visit_some(self),
+        };
+        result.map_err(|error| self.fix(error, mark))
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        // An empty value, as in `key:`, stands for an empty mapping.
+        let (item, mark) = self.peek_item()?;
+        if let Item::Scalar { value, tag, style } = item {
+            if value.is_empty()
+                && self.tag(tag).is_none()
+                && *style == YamlPlainScalarStyle
+            {
+                return self
+                    .next_item()
+                    .and_then(|_| visitor.visit_map(EmptyMap))
+                    .map_err(|error| self.fix(error, mark));
+            }
+        }
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let (item, mark) = self.peek_item()?;
+        let tag = match item {
+            Item::Alias(pos) => {
+                return self
+                    .next_item()
+                    .and_then(|_| {
+                        let mut pos = *pos;
+                        self.jump(&mut pos, mark)
+                            .deserialize_enum(name, variants, visitor)
+                    })
+                    .map_err(|error| self.fix(error, mark));
+            }
+            Item::Scalar { tag, .. }
+            | Item::SequenceStart { tag }
+            | Item::MappingStart { tag } => {
+                self.tag(tag).and_then(variant_tag)
+            }
+            Item::SequenceEnd | Item::MappingEnd => None,
+        };
+        let result = match (tag, item) {
+            (Some(tag), _) => visitor.visit_enum(TaggedEnum {
+                tag,
+                de: &mut *self,
+            }),
+            (None, Item::Scalar { value, .. }) => {
+                self.next_item().and_then(|_| {
+                    let variant: StrDeserializer<'_, Error> =
+                        value.as_ref().into_deserializer();
+                    visitor.visit_enum(variant)
+                })
+            }
+            (None, Item::MappingStart { .. }) => {
+                self.next_item().and_then(|_| {
+                    let value = self.recursion_check(|de| {
+                        visitor.visit_enum(UntaggedEnum { de })
+                    })?;
+                    self.end_mapping(1)?;
+                    Ok(value)
+                })
+            }
+            (None, _) => return self.deserialize_any(visitor),
+        };
+        result.map_err(|error| self.fix(error, mark))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.ignore_any();
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 unit
+        unit_struct seq tuple tuple_struct
+    }
+}
+
+struct SeqAccess<'de, 'document, 'seq> {
+    de: &'seq mut DeserializerFromEvents<'de, 'document>,
+    len: usize,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'de, '_, '_> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if let (Item::SequenceEnd, _) = self.de.peek_item()? {
+            return Ok(None);
+        }
+        let mut element = DeserializerFromEvents {
+            document: self.de.document,
+            pos: &mut *self.de.pos,
+            expanded: &mut *self.de.expanded,
+            path: Path::Seq {
+                parent: &self.de.path,
+                index: self.len,
+            },
+            remaining_depth: self.de.remaining_depth,
+            alias: self.de.alias,
+            untagged: false,
+        };
+        self.len += 1;
+        seed.deserialize(&mut element).map(Some)
+    }
+}
+
+struct MapAccess<'de, 'document, 'map> {
+    de: &'map mut DeserializerFromEvents<'de, 'document>,
+    len: usize,
+    /// The key of the pair being read, if it is a scalar.
+    key: Option<&'document str>,
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess<'de, '_, '_> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        self.key = match self.de.peek_item()? {
+            (Item::MappingEnd, _) => return Ok(None),
+            (Item::Scalar { value, .. }, _) => Some(value),
+            _ => None,
+        };
+        self.len += 1;
+        seed.deserialize(&mut DeserializerFromEvents {
+            document: self.de.document,
+            pos: &mut *self.de.pos,
+            expanded: &mut *self.de.expanded,
+            path: self.de.path,
+            remaining_depth: self.de.remaining_depth,
+            alias: self.de.alias,
+            untagged: false,
+        })
+        .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Error> {
+        seed.deserialize(&mut DeserializerFromEvents {
+            document: self.de.document,
+            pos: &mut *self.de.pos,
+            expanded: &mut *self.de.expanded,
+            path: Path::Map {
+                parent: &self.de.path,
+                key: self.key.unwrap_or("?"),
+            },
+            remaining_depth: self.de.remaining_depth,
+            alias: self.de.alias,
+            untagged: false,
+        })
+    }
+}
+
+/// The mapping an empty value stands for.
+struct EmptyMap;
+
+impl<'de> de::MapAccess<'de> for EmptyMap {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        _seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        Ok(None)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        _seed: V,
+    ) -> Result<V::Value, Error> {
+        Err(de::Error::custom("an empty mapping has no values"))
+    }
+}
+
+/// An enum variant given by the local tag of its value.
+struct TaggedEnum<'de, 'document, 'a> {
+    tag: &'document str,
+    de: &'a mut DeserializerFromEvents<'de, 'document>,
+}
+
+impl<'de, 'a> de::EnumAccess<'de> for TaggedEnum<'de, '_, 'a> {
+    type Error = Error;
+    type Variant = DeserializerFromEvents<'de, 'a>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Error> {
+        let variant: StrDeserializer<'_, Error> =
+            self.tag.into_deserializer();
+        let value = seed.deserialize(variant)?;
+        let de = self.de;
+        Ok((
+            value,
+            DeserializerFromEvents {
+                document: de.document,
+                pos: &mut *de.pos,
+                expanded: &mut *de.expanded,
+                path: de.path,
+                remaining_depth: de.remaining_depth,
+                alias: de.alias,
+                untagged: true,
+            },
+        ))
+    }
+}
+
+/// An enum variant given by the key of a mapping with a single pair.
+struct UntaggedEnum<'de, 'document, 'a> {
+    de: &'a mut DeserializerFromEvents<'de, 'document>,
+}
+
+impl<'de, 'a> de::EnumAccess<'de> for UntaggedEnum<'de, '_, 'a> {
+    type Error = Error;
+    type Variant = DeserializerFromEvents<'de, 'a>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Error> {
+        let de = self.de;
+        let key = match de.peek_item()? {
+            (Item::MappingEnd, _) => {
+                return Err(de::Error::invalid_length(
+                    0,
+                    &ExpectedMap(1),
+                ));
+            }
+            (Item::Scalar { value, .. }, _) => Some(value.as_ref()),
+            _ => None,
+        };
+        let value = seed.deserialize(&mut DeserializerFromEvents {
+            document: de.document,
+            pos: &mut *de.pos,
+            expanded: &mut *de.expanded,
+            path: de.path,
+            remaining_depth: de.remaining_depth,
+            alias: de.alias,
+            untagged: false,
+        })?;
+        Ok((
+            value,
+            DeserializerFromEvents {
+                document: de.document,
+                pos: &mut *de.pos,
+                expanded: &mut *de.expanded,
+                path: Path::Map {
+                    parent: &de.path,
+                    key: key.unwrap_or("?"),
+                },
+                remaining_depth: de.remaining_depth,
+                alias: de.alias,
+                untagged: false,
+            },
+        ))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for DeserializerFromEvents<'de, '_> {
+    type Error = Error;
+
+    fn unit_variant(mut self) -> Result<(), Error> {
+        Deserialize::deserialize(&mut self)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        mut self,
+        seed: T,
+    ) -> Result<T::Value, Error> {
+        seed.deserialize(&mut self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        mut self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_seq(&mut self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        mut self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_struct(
+            &mut self, "", fields, visitor,
+        )
+    }
+}
+
+struct ExpectedSeq(usize);
+
+impl Expected for ExpectedSeq {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 == 1 {
+            formatter.write_str("sequence of 1 element")
+        } else {
+            write!(formatter, "sequence of {} elements", self.0)
+        }
+    }
+}
+
+struct ExpectedMap(usize);
+
+impl Expected for ExpectedMap {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 == 1 {
+            formatter.write_str("map containing 1 entry")
+        } else {
+            write!(formatter, "map containing {} entries", self.0)
+        }
+    }
+}
+
+macro_rules! deserialize_document {
+    ($($method:ident($($arg:ident: $type:ty),*))*) => {
+        $(
+            fn $method<V: Visitor<'de>>(
+                self,
+                $($arg: $type,)*
+                visitor: V,
+            ) -> Result<V::Value, Error> {
+                self.de(|state| {
+                    de::Deserializer::$method(state, $($arg,)* visitor)
+                })
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    deserialize_document! {
+        deserialize_any()
+        deserialize_bool()
+        deserialize_i8()
+        deserialize_i16()
+        deserialize_i32()
+        deserialize_i64()
+        deserialize_i128()
+        deserialize_u8()
+        deserialize_u16()
+        deserialize_u32()
+        deserialize_u64()
+        deserialize_u128()
+        deserialize_f32()
+        deserialize_f64()
+        deserialize_char()
+        deserialize_str()
+        deserialize_string()
+        deserialize_bytes()
+        deserialize_byte_buf()
+        deserialize_option()
+        deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_seq()
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_map()
+        deserialize_struct(
+            name: &'static str,
+            fields: &'static [&'static str]
+        )
+        deserialize_enum(
+            name: &'static str,
+            variants: &'static [&'static str]
+        )
+        deserialize_identifier()
+        deserialize_ignored_any()
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(message: T) -> Self {
+        Error::new(ErrorKind::Composer, message.to_string())
+    }
+}
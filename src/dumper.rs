@@ -35,6 +35,8 @@ pub unsafe fn yaml_emitter_open(emitter: *mut YamlEmitterT) -> Success {
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     __assert!(!emitter.is_null());
     __assert!(!(*emitter).opened);
@@ -73,6 +75,8 @@ pub unsafe fn yaml_emitter_close(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     __assert!(!emitter.is_null());
     __assert!((*emitter).opened);
@@ -118,6 +122,8 @@ pub unsafe fn yaml_emitter_dump(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
 
     __assert!(!emitter.is_null());
@@ -375,6 +381,8 @@ unsafe fn yaml_emitter_dump_alias(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     memset(
         event as *mut libc::c_void,
@@ -399,6 +407,8 @@ unsafe fn yaml_emitter_dump_scalar(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     let plain_implicit = strcmp(
         (*node).tag as *mut libc::c_char,
@@ -437,6 +447,8 @@ unsafe fn yaml_emitter_dump_sequence(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     let implicit = strcmp(
         (*node).tag as *mut libc::c_char,
@@ -487,6 +499,8 @@ unsafe fn yaml_emitter_dump_mapping(
         index: 0_u64,
         line: 0_u64,
         column: 0_u64,
+        offset: 0_u64,
+        utf16_column: 0_u64,
     };
     let implicit = strcmp(
         (*node).tag as *mut libc::c_char,
@@ -162,11 +162,27 @@ pub unsafe fn yaml_emitter_emit(
         {
             return FAIL;
         }
+        if yaml_emitter_process_comments_after(
+            emitter,
+            (*emitter).events.head,
+        )
+        .fail
+        {
+            return FAIL;
+        }
         if yaml_emitter_state_machine(emitter, (*emitter).events.head)
             .fail
         {
             return FAIL;
         }
+        if yaml_emitter_process_comments(
+            emitter,
+            (*emitter).events.head,
+        )
+        .fail
+        {
+            return FAIL;
+        }
         yaml_event_delete(addr_of_mut!(DEQUEUE!((*emitter).events)));
     }
     OK
@@ -565,6 +581,9 @@ unsafe fn yaml_emitter_emit_document_start(
         if yaml_emitter_check_empty_document(emitter) {
             implicit = false;
         }
+        if yaml_emitter_write_comments_before(emitter, event).fail {
+            return FAIL;
+        }
         if !implicit {
             if yaml_emitter_write_indent(emitter).fail {
                 return FAIL;
@@ -607,6 +626,14 @@ unsafe fn yaml_emitter_emit_document_start(
                 return FAIL;
             }
         }
+        if yaml_emitter_write_comments_before(emitter, event).fail {
+            return FAIL;
+        }
+        if !(*emitter).comment.is_null()
+            && yaml_emitter_write_indent(emitter).fail
+        {
+            return FAIL;
+        }
         if yaml_emitter_flush(emitter).fail {
             return FAIL;
         }
@@ -636,6 +663,18 @@ unsafe fn yaml_emitter_emit_document_end(
         if yaml_emitter_write_indent(emitter).fail {
             return FAIL;
         }
+        // The comments that end the document are kept apart from its
+        // content by a blank line, which is how they are read again.
+        if (!(*emitter).comments_after.is_null()
+            || !(*event).comments.before.is_null())
+            && (put_break(emitter).fail
+                || yaml_emitter_write_comments_after(emitter).fail
+                || yaml_emitter_write_comments_before(emitter, event)
+                    .fail
+                || yaml_emitter_write_indent(emitter).fail)
+        {
+            return FAIL;
+        }
         if !(*event).data.document_end.implicit {
             if yaml_emitter_write_indicator(
                 emitter,
@@ -649,6 +688,9 @@ unsafe fn yaml_emitter_emit_document_end(
                 return FAIL;
             }
             (*emitter).open_ended = 0;
+            if yaml_emitter_set_comment(emitter, event).fail {
+                return FAIL;
+            }
             if yaml_emitter_write_indent(emitter).fail {
                 return FAIL;
             }
@@ -694,6 +736,12 @@ unsafe fn yaml_emitter_emit_flow_sequence_item(
         *fresh12 += 1;
     }
     if (*event).type_ == YamlSequenceEndEvent {
+        if !first
+            && !(*emitter).comment.is_null()
+            && yaml_emitter_write_indent(emitter).fail
+        {
+            return FAIL;
+        }
         let fresh13 = addr_of_mut!((*emitter).flow_level);
         *fresh13 -= 1;
         (*emitter).indent = POP!((*emitter).indents);
@@ -740,7 +788,8 @@ unsafe fn yaml_emitter_emit_flow_sequence_item(
         return FAIL;
     }
     if ((*emitter).canonical
-        || (*emitter).column > (*emitter).best_width)
+        || (*emitter).column > (*emitter).best_width
+        || !(*emitter).comment.is_null())
         && yaml_emitter_write_indent(emitter).fail
     {
         return FAIL;
@@ -774,6 +823,12 @@ unsafe fn yaml_emitter_emit_flow_mapping_key(
         if STACK_EMPTY!((*emitter).indents) {
             return FAIL;
         }
+        if !first
+            && !(*emitter).comment.is_null()
+            && yaml_emitter_write_indent(emitter).fail
+        {
+            return FAIL;
+        }
         let fresh19 = addr_of_mut!((*emitter).flow_level);
         *fresh19 -= 1;
         (*emitter).indent = POP!((*emitter).indents);
@@ -820,7 +875,8 @@ unsafe fn yaml_emitter_emit_flow_mapping_key(
         return FAIL;
     }
     if ((*emitter).canonical
-        || (*emitter).column > (*emitter).best_width)
+        || (*emitter).column > (*emitter).best_width
+        || !(*emitter).comment.is_null())
         && yaml_emitter_write_indent(emitter).fail
     {
         return FAIL;
@@ -866,7 +922,8 @@ unsafe fn yaml_emitter_emit_flow_mapping_value(
         }
     } else {
         if ((*emitter).canonical
-            || (*emitter).column > (*emitter).best_width)
+            || (*emitter).column > (*emitter).best_width
+            || !(*emitter).comment.is_null())
             && yaml_emitter_write_indent(emitter).fail
         {
             return FAIL;
@@ -893,10 +950,14 @@ unsafe fn yaml_emitter_emit_block_sequence_item(
     first: bool,
 ) -> Success {
     if first {
+        // Comments above the first item would be read again as the
+        // comments of the sequence, which indent it.
         yaml_emitter_increase_indent(
             emitter,
             false,
-            (*emitter).mapping_context && !(*emitter).indention,
+            (*emitter).mapping_context
+                && !(*emitter).indention
+                && (*event).comments.before.is_null(),
         );
     }
     if (*event).type_ == YamlSequenceEndEvent {
@@ -907,6 +968,9 @@ unsafe fn yaml_emitter_emit_block_sequence_item(
     if yaml_emitter_write_indent(emitter).fail {
         return FAIL;
     }
+    if yaml_emitter_write_comments_before(emitter, event).fail {
+        return FAIL;
+    }
     if yaml_emitter_write_indicator(
         emitter,
         b"-\0" as *const u8 as *const libc::c_char,
@@ -938,6 +1002,9 @@ unsafe fn yaml_emitter_emit_block_mapping_key(
     if yaml_emitter_write_indent(emitter).fail {
         return FAIL;
     }
+    if yaml_emitter_write_comments_before(emitter, event).fail {
+        return FAIL;
+    }
     if yaml_emitter_check_simple_key(emitter) {
         PUSH!((*emitter).states, YamlEmitBlockMappingSimpleValueState);
         yaml_emitter_emit_node(emitter, event, false, false, true, true)
@@ -1009,6 +1076,27 @@ unsafe fn yaml_emitter_emit_node(
     (*emitter).sequence_context = sequence;
     (*emitter).mapping_context = mapping;
     (*emitter).simple_key_context = simple_key;
+    if !(*event).comments.before.is_null() {
+        // The inline comment of a block collection ends the line before
+        // it, above the comments before it.
+        if yaml_emitter_check_block_collection(emitter, event)
+            && yaml_emitter_set_comment(emitter, event).fail
+        {
+            return FAIL;
+        }
+        // The node does not start a line, so its comments go on the
+        // lines above it, indented as its contents would be.
+        let block = (*emitter).flow_level == 0;
+        if block {
+            yaml_emitter_increase_indent(emitter, false, false);
+        }
+        if yaml_emitter_write_comments_before(emitter, event).fail {
+            return FAIL;
+        }
+        if block {
+            (*emitter).indent = POP!((*emitter).indents);
+        }
+    }
     match (*event).type_ {
         YamlAliasEvent => yaml_emitter_emit_alias(emitter, event),
         YamlScalarEvent => yaml_emitter_emit_scalar(emitter, event),
@@ -1050,6 +1138,21 @@ unsafe fn yaml_emitter_emit_scalar(
         return FAIL;
     }
     yaml_emitter_increase_indent(emitter, true, false);
+    if (*emitter).scalar_data.style == YamlLiteralScalarStyle
+        || (*emitter).scalar_data.style == YamlFoldedScalarStyle
+    {
+        // Written on the header line.
+        if yaml_emitter_set_comment(emitter, event).fail {
+            return FAIL;
+        }
+    } else if !(*emitter).comment.is_null()
+        && yaml_emitter_check_scalar_breaks(emitter)
+    {
+        // A comment must not end a line inside the scalar.
+        if yaml_emitter_write_indent(emitter).fail {
+            return FAIL;
+        }
+    }
     if yaml_emitter_process_scalar(emitter).fail {
         return FAIL;
     }
@@ -1108,6 +1211,27 @@ unsafe fn yaml_emitter_check_empty_document(
     false
 }
 
+/// Does the event start a collection written in block style?
+unsafe fn yaml_emitter_check_block_collection(
+    emitter: *mut YamlEmitterT,
+    event: *mut YamlEventT,
+) -> bool {
+    if (*emitter).flow_level != 0 || (*emitter).canonical {
+        return false;
+    }
+    match (*event).type_ {
+        YamlSequenceStartEvent => {
+            (*event).data.sequence_start.style != YamlFlowSequenceStyle
+                && !yaml_emitter_check_empty_sequence(emitter)
+        }
+        YamlMappingStartEvent => {
+            (*event).data.mapping_start.style != YamlFlowMappingStyle
+                && !yaml_emitter_check_empty_mapping(emitter)
+        }
+        _ => false,
+    }
+}
+
 unsafe fn yaml_emitter_check_empty_sequence(
     emitter: *mut YamlEmitterT,
 ) -> bool {
@@ -1141,6 +1265,9 @@ unsafe fn yaml_emitter_check_simple_key(
 ) -> bool {
     let event: *mut YamlEventT = (*emitter).events.head;
     let mut length: size_t = 0_u64;
+    if !(*event).comments.after.is_null() {
+        return false;
+    }
     match (*event).type_ {
         YamlAliasEvent => {
             length =
@@ -1182,6 +1309,16 @@ unsafe fn yaml_emitter_check_simple_key(
     true
 }
 
+unsafe fn yaml_emitter_check_scalar_breaks(
+    emitter: *mut YamlEmitterT,
+) -> bool {
+    (*emitter).scalar_data.multiline
+        || (*emitter).scalar_data.style != YamlPlainScalarStyle
+        || ((*emitter).column as size_t)
+            .force_add((*emitter).scalar_data.length)
+            >= (*emitter).best_width as size_t
+}
+
 unsafe fn yaml_emitter_select_scalar_style(
     emitter: *mut YamlEmitterT,
     event: *mut YamlEventT,
@@ -1217,9 +1354,15 @@ unsafe fn yaml_emitter_select_scalar_style(
         {
             style = YamlSingleQuotedScalarStyle;
         }
+        // An empty plain scalar reads back as such only as a block node,
+        // as the value of a flow mapping pair, as in `{a: }`, or as a
+        // key with an anchor or a tag before it, as in `&a : b`.
         if (*emitter).scalar_data.length == 0
             && ((*emitter).flow_level != 0
-                || (*emitter).simple_key_context)
+                && !(*emitter).mapping_context
+                || (*emitter).simple_key_context
+                    && no_tag
+                    && (*emitter).anchor_data.anchor.is_null())
         {
             style = YamlSingleQuotedScalarStyle;
         }
@@ -1394,6 +1537,127 @@ unsafe fn yaml_emitter_process_scalar(
     FAIL
 }
 
+/// Keeps the inline comment of an event for the end of the line.
+unsafe fn yaml_emitter_set_comment(
+    emitter: *mut YamlEmitterT,
+    event: *mut YamlEventT,
+) -> Success {
+    if (*event).comments.inline.is_null() {
+        return OK;
+    }
+    if !(*emitter).comment.is_null()
+        && yaml_emitter_write_indent(emitter).fail
+    {
+        return FAIL;
+    }
+    (*emitter).comment = (*event).comments.inline;
+    (*event).comments.inline = ptr::null_mut::<yaml_char_t>();
+    OK
+}
+
+/// Writes the comments before an event on lines of their own, then
+/// starts the line of the event.
+unsafe fn yaml_emitter_write_comments_before(
+    emitter: *mut YamlEmitterT,
+    event: *mut YamlEventT,
+) -> Success {
+    let comment: *mut yaml_char_t = (*event).comments.before;
+    if comment.is_null() {
+        return OK;
+    }
+    (*event).comments.before = ptr::null_mut::<yaml_char_t>();
+    let success = yaml_emitter_write_comment(emitter, comment, false);
+    yaml_free(comment as *mut libc::c_void);
+    if success.fail {
+        return FAIL;
+    }
+    yaml_emitter_write_indent(emitter)
+}
+
+/// Handles the comments an event still carries once it is emitted: the
+/// inline one waits for the end of the line, and the ones after it are
+/// written below in flow collections, or held back for the next event
+/// outside of them.
+unsafe fn yaml_emitter_process_comments(
+    emitter: *mut YamlEmitterT,
+    event: *mut YamlEventT,
+) -> Success {
+    if yaml_emitter_set_comment(emitter, event).fail {
+        return FAIL;
+    }
+    if (*event).comments.after.is_null() {
+        return OK;
+    }
+    if (*emitter).flow_level != 0 {
+        if yaml_emitter_write_comment(
+            emitter,
+            (*event).comments.after,
+            false,
+        )
+        .fail
+        {
+            return FAIL;
+        }
+        return yaml_emitter_write_indent(emitter);
+    }
+    (*emitter).comments_after = (*event).comments.after;
+    (*emitter).comments_after_indent = (*emitter).indent;
+    (*event).comments.after = ptr::null_mut::<yaml_char_t>();
+    OK
+}
+
+/// Writes the comments held back after a block node once the next event
+/// is known not to end the document, followed by a blank line. Empty
+/// collection ends go by without writing anything, and the end of the
+/// document writes them itself.
+unsafe fn yaml_emitter_process_comments_after(
+    emitter: *mut YamlEmitterT,
+    event: *mut YamlEventT,
+) -> Success {
+    if (*emitter).comments_after.is_null() {
+        return OK;
+    }
+    let comments = (*event).comments;
+    match (*event).type_ {
+        YamlDocumentEndEvent => return OK,
+        YamlSequenceEndEvent | YamlMappingEndEvent
+            if comments.before.is_null()
+                && comments.inline.is_null()
+                && comments.after.is_null() =>
+        {
+            return OK;
+        }
+        _ => {}
+    }
+    if yaml_emitter_write_comments_after(emitter).fail
+        || put_break(emitter).fail
+        || put_break(emitter).fail
+    {
+        return FAIL;
+    }
+    (*emitter).whitespace = true;
+    (*emitter).indention = true;
+    OK
+}
+
+/// Writes the comments held back after a block node, indented as the
+/// node was.
+unsafe fn yaml_emitter_write_comments_after(
+    emitter: *mut YamlEmitterT,
+) -> Success {
+    let comment: *mut yaml_char_t = (*emitter).comments_after;
+    if comment.is_null() {
+        return OK;
+    }
+    (*emitter).comments_after = ptr::null_mut::<yaml_char_t>();
+    let indent = (*emitter).indent;
+    (*emitter).indent = (*emitter).comments_after_indent;
+    let success = yaml_emitter_write_comment(emitter, comment, false);
+    (*emitter).indent = indent;
+    yaml_free(comment as *mut libc::c_void);
+    success
+}
+
 unsafe fn yaml_emitter_analyze_version_directive(
     emitter: *mut YamlEmitterT,
     version_directive: YamlVersionDirectiveT,
@@ -1578,10 +1842,13 @@ unsafe fn yaml_emitter_analyze_scalar(
     (*emitter).scalar_data.length = length;
     if string.start == string.end {
         (*emitter).scalar_data.multiline = false;
-        (*emitter).scalar_data.flow_plain_allowed = false;
+        // Where an empty plain scalar may go depends on the context,
+        // which yaml_emitter_select_scalar_style() checks.
+        (*emitter).scalar_data.flow_plain_allowed = true;
         (*emitter).scalar_data.block_plain_allowed = true;
         (*emitter).scalar_data.single_quoted_allowed = true;
-        (*emitter).scalar_data.block_allowed = false;
+        // An empty literal or folded scalar is written as `|-` or `>-`.
+        (*emitter).scalar_data.block_allowed = true;
         return OK;
     }
     if CHECK_AT!(string, b'-', 0)
@@ -1868,6 +2135,9 @@ unsafe fn yaml_emitter_write_indent(
     } else {
         0
     };
+    if yaml_emitter_write_pending_comment(emitter).fail {
+        return FAIL;
+    }
     if (!(*emitter).indention
         || (*emitter).column > indent
         || (*emitter).column == indent && !(*emitter).whitespace)
@@ -1916,6 +2186,56 @@ unsafe fn yaml_emitter_write_indicator(
     OK
 }
 
+/// Writes the comment lines in `comment`, each on a line of its own
+/// unless `inline` puts the first one at the end of the current line.
+unsafe fn yaml_emitter_write_comment(
+    emitter: *mut YamlEmitterT,
+    comment: *mut yaml_char_t,
+    inline: bool,
+) -> Success {
+    let mut string =
+        STRING_ASSIGN!(comment, strlen(comment as *mut libc::c_char));
+    let mut first = true;
+    loop {
+        if first && inline {
+            if (*emitter).column != 0 && put(emitter, b' ').fail {
+                return FAIL;
+            }
+        } else if yaml_emitter_write_indent(emitter).fail {
+            return FAIL;
+        }
+        first = false;
+        if put(emitter, b'#').fail {
+            return FAIL;
+        }
+        while string.pointer != string.end && !CHECK!(string, b'\n') {
+            if write!(emitter, string).fail {
+                return FAIL;
+            }
+        }
+        (*emitter).whitespace = false;
+        (*emitter).indention = false;
+        if string.pointer == string.end {
+            return OK;
+        }
+        string.pointer = string.pointer.wrapping_offset(1);
+    }
+}
+
+/// Writes the inline comment waiting for the end of the line, if any.
+unsafe fn yaml_emitter_write_pending_comment(
+    emitter: *mut YamlEmitterT,
+) -> Success {
+    let comment: *mut yaml_char_t = (*emitter).comment;
+    if comment.is_null() {
+        return OK;
+    }
+    (*emitter).comment = ptr::null_mut::<yaml_char_t>();
+    let success = yaml_emitter_write_comment(emitter, comment, true);
+    yaml_free(comment as *mut libc::c_void);
+    success
+}
+
 unsafe fn yaml_emitter_write_anchor(
     emitter: *mut YamlEmitterT,
     value: *mut yaml_char_t,
@@ -2045,7 +2365,9 @@ unsafe fn yaml_emitter_write_plain_scalar(
     let mut breaks = false;
     let mut string = STRING_ASSIGN!(value, length);
     if !(*emitter).whitespace
-        && (length != 0 || (*emitter).flow_level != 0)
+        && (length != 0
+            || (*emitter).flow_level != 0
+            || (*emitter).simple_key_context)
         && put(emitter, b' ').fail
     {
         return FAIL;
@@ -2411,7 +2733,9 @@ unsafe fn yaml_emitter_write_block_scalar_hints(
     let mut indent_hint: [libc::c_char; 2] = [0; 2];
     let mut chomp_hint: *const libc::c_char =
         ptr::null::<libc::c_char>();
-    if IS_SPACE!(string) || IS_BREAK!(string) {
+    if string.start != string.end
+        && (IS_SPACE!(string) || IS_BREAK!(string))
+    {
         indent_hint[0] = (b'0' as libc::c_int + (*emitter).best_indent)
             as libc::c_char;
         indent_hint[1] = '\0' as libc::c_char;
@@ -2488,6 +2812,9 @@ unsafe fn yaml_emitter_write_literal_scalar(
     if yaml_emitter_write_block_scalar_hints(emitter, string).fail {
         return FAIL;
     }
+    if yaml_emitter_write_pending_comment(emitter).fail {
+        return FAIL;
+    }
     if put_break(emitter).fail {
         return FAIL;
     }
@@ -2536,6 +2863,9 @@ unsafe fn yaml_emitter_write_folded_scalar(
     if yaml_emitter_write_block_scalar_hints(emitter, string).fail {
         return FAIL;
     }
+    if yaml_emitter_write_pending_comment(emitter).fail {
+        return FAIL;
+    }
     if put_break(emitter).fail {
         return FAIL;
     }
@@ -0,0 +1,410 @@
+//! Structured errors for the parser, loader, emitter and deserializer.
+//!
+//! The raw API reports failures through a `Success` value and leaves
+//! the details in the `error`, `problem` and `context` fields of the
+//! parser or emitter. [`Error`] copies those details out into an owned
+//! value that implements `Display` and `core::error::Error`.
+//!
+//! ```
+//! use libyml::error::ErrorKind;
+//! use libyml::safe::Parser;
+//!
+//! let source = "key: [unclosed\n";
+//! let error = Parser::new(source.as_bytes())
+//!     .find_map(Result::err)
+//!     .unwrap();
+//! assert_eq!(error.kind(), ErrorKind::Parser);
+//! assert_eq!(
+//!     error.snippet(source).to_string(),
+//!     "\
+//! error: did not find expected ',' or ']'
+//!  --> 2:1
+//!   |
+//! 2 |
+//!   | ^
+//!   |
+//!   = note: while parsing a flow sequence at 1:6
+//! ",
+//! );
+//! ```
+
+use crate::safe::owned_cstr;
+use crate::yaml::{
+    YamlEmitterT, YamlErrorTypeT, YamlMarkT, YamlParserT,
+};
+use crate::{
+    YamlComposerError, YamlEmitterError, YamlMemoryError, YamlNoError,
+    YamlParserError, YamlReaderError, YamlScannerError,
+    YamlWriterError,
+};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+#[cfg(feature = "serde")]
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use core::fmt::{self, Display, Write};
+
+/// The category of an [`Error`], mirroring `YamlErrorTypeT`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Cannot allocate or reallocate a block of memory.
+    Memory,
+    /// Cannot read or decode the input stream.
+    Reader,
+    /// Cannot scan the input stream.
+    Scanner,
+    /// Cannot parse the input stream.
+    Parser,
+    /// Cannot compose a YAML document.
+    Composer,
+    /// Cannot write to the output stream.
+    Writer,
+    /// Cannot emit a YAML stream.
+    Emitter,
+}
+
+/// An error reported by the parser, loader or emitter.
+///
+/// The span of the error runs from [`Error::start_mark`], the start of
+/// the construct described by [`Error::context`] if there is one, to
+/// [`Error::end_mark`], where the problem was detected.
+#[derive(Clone, Debug)]
+pub struct Error(Box<ErrorImpl>);
+
+#[derive(Clone, Debug)]
+struct ErrorImpl {
+    kind: ErrorKind,
+    message: Cow<'static, str>,
+    context: Option<Cow<'static, str>>,
+    start_mark: Option<YamlMarkT>,
+    end_mark: Option<YamlMarkT>,
+    path: Option<Cow<'static, str>>,
+    cause: Option<Arc<dyn core::error::Error + Send + Sync>>,
+}
+
+impl Error {
+    /// Creates an error without a position.
+    pub fn new(
+        kind: ErrorKind,
+        message: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Error(Box::new(ErrorImpl {
+            kind,
+            message: message.into(),
+            context: None,
+            start_mark: None,
+            end_mark: None,
+            path: None,
+            cause: None,
+        }))
+    }
+
+    /// Copies the error state out of a failed parser.
+    ///
+    /// # Safety
+    ///
+    /// - `parser` must be a valid, non-null pointer to an initialized
+    ///   `YamlParserT`.
+    pub unsafe fn from_parser(parser: *const YamlParserT) -> Self {
+        let kind = ErrorKind::from((*parser).error);
+        let message = owned_cstr((*parser).problem.cast())
+            .unwrap_or(Cow::Borrowed("libyml parser failed"));
+        if (*parser).error == YamlReaderError {
+            // The reader only knows the byte offset of the problem.
+            let mark = YamlMarkT {
+                index: (*parser).problem_offset,
+                line: 0,
+                column: 0,
+                offset: (*parser).problem_offset,
+                utf16_column: 0,
+            };
+            return Error(Box::new(ErrorImpl {
+                kind,
+                message,
+                context: None,
+                start_mark: Some(mark),
+                end_mark: Some(mark),
+                path: None,
+                cause: None,
+            }));
+        }
+        let context = owned_cstr((*parser).context.cast());
+        let end_mark = (*parser).problem_mark;
+        let start_mark = if context.is_some() {
+            (*parser).context_mark
+        } else {
+            end_mark
+        };
+        Error(Box::new(ErrorImpl {
+            kind,
+            message,
+            context,
+            start_mark: Some(start_mark),
+            end_mark: Some(end_mark),
+            path: None,
+            cause: None,
+        }))
+    }
+
+    /// Copies the error state out of a failed emitter.
+    ///
+    /// # Safety
+    ///
+    /// - `emitter` must be a valid, non-null pointer to an initialized
+    ///   `YamlEmitterT`.
+    pub unsafe fn from_emitter(emitter: *const YamlEmitterT) -> Self {
+        let message = owned_cstr((*emitter).problem.cast())
+            .unwrap_or(Cow::Borrowed("libyml emitter failed"));
+        Error::new(ErrorKind::from((*emitter).error), message)
+    }
+
+    /// The category of the error.
+    pub fn kind(&self) -> ErrorKind {
+        self.0.kind
+    }
+
+    /// A description of the problem.
+    pub fn message(&self) -> &str {
+        &self.0.message
+    }
+
+    /// A description of the construct being processed, if any.
+    pub fn context(&self) -> Option<&str> {
+        self.0.context.as_deref()
+    }
+
+    /// Where the erroneous construct starts, if known.
+    pub fn start_mark(&self) -> Option<YamlMarkT> {
+        self.0.start_mark
+    }
+
+    /// Where the problem was detected, if known.
+    pub fn end_mark(&self) -> Option<YamlMarkT> {
+        self.0.end_mark
+    }
+
+    /// Where in the document the error occurred, such as
+    /// `servers[1].port`, for errors raised while deserializing a node
+    /// other than the root.
+    pub fn path(&self) -> Option<&str> {
+        self.0.path.as_deref()
+    }
+
+    /// The I/O error that caused this error, if any.
+    #[cfg(feature = "std")]
+    pub fn io_error(&self) -> Option<&std::io::Error> {
+        self.0.cause.as_deref()?.downcast_ref()
+    }
+
+    /// Attaches the underlying error, reported by
+    /// [`source`](core::error::Error::source).
+    pub(crate) fn with_cause(
+        mut self,
+        cause: impl core::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        self.0.cause = Some(Arc::new(cause));
+        self
+    }
+
+    /// Places an error that has no position yet at `mark`, in the node
+    /// at `path`.
+    #[cfg(feature = "serde")]
+    pub(crate) fn locate(
+        mut self,
+        mark: YamlMarkT,
+        path: Option<String>,
+    ) -> Self {
+        if self.0.end_mark.is_none() {
+            self.0.start_mark = Some(mark);
+            self.0.end_mark = Some(mark);
+            self.0.path = path.map(Cow::Owned);
+        }
+        self
+    }
+
+    /// Renders the error together with the offending line of `source`,
+    /// in the style of rustc diagnostics.
+    ///
+    /// `source` must be the input the error was produced from.
+    pub fn snippet<'a>(&'a self, source: &'a str) -> Snippet<'a> {
+        Snippet {
+            error: self,
+            source,
+        }
+    }
+}
+
+impl From<YamlErrorTypeT> for ErrorKind {
+    fn from(error: YamlErrorTypeT) -> Self {
+        match error {
+            YamlReaderError => ErrorKind::Reader,
+            YamlScannerError => ErrorKind::Scanner,
+            YamlParserError => ErrorKind::Parser,
+            YamlComposerError => ErrorKind::Composer,
+            YamlWriterError => ErrorKind::Writer,
+            YamlEmitterError => ErrorKind::Emitter,
+            // A failure without an error type comes from an allocation
+            // helper that does not record one.
+            YamlNoError | YamlMemoryError => ErrorKind::Memory,
+        }
+    }
+}
+
+impl From<ErrorKind> for YamlErrorTypeT {
+    fn from(kind: ErrorKind) -> Self {
+        match kind {
+            ErrorKind::Memory => YamlMemoryError,
+            ErrorKind::Reader => YamlReaderError,
+            ErrorKind::Scanner => YamlScannerError,
+            ErrorKind::Parser => YamlParserError,
+            ErrorKind::Composer => YamlComposerError,
+            ErrorKind::Writer => YamlWriterError,
+            ErrorKind::Emitter => YamlEmitterError,
+        }
+    }
+}
+
+fn same_position(a: &YamlMarkT, b: &YamlMarkT) -> bool {
+    a.line == b.line && a.column == b.column
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(path) = &self.0.path {
+            write!(formatter, "{}: ", path)?;
+        }
+        formatter.write_str(&self.0.message)?;
+        if let Some(mark) = &self.0.end_mark {
+            if self.0.kind == ErrorKind::Reader {
+                write!(formatter, " at position {}", mark.index)?;
+            } else {
+                write!(
+                    formatter,
+                    " at line {} column {}",
+                    mark.line + 1,
+                    mark.column + 1,
+                )?;
+            }
+        }
+        if let Some(context) = &self.0.context {
+            write!(formatter, ", {}", context)?;
+            if let (Some(start), Some(end)) =
+                (&self.0.start_mark, &self.0.end_mark)
+            {
+                if self.0.kind != ErrorKind::Reader
+                    && !same_position(start, end)
+                {
+                    write!(
+                        formatter,
+                        " at line {} column {}",
+                        start.line + 1,
+                        start.column + 1,
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        let cause: &(dyn core::error::Error + 'static) =
+            self.0.cause.as_deref()?;
+        Some(cause)
+    }
+}
+
+/// An [`Error`] rendered against its source, created by
+/// [`Error::snippet`].
+#[derive(Copy, Clone, Debug)]
+pub struct Snippet<'a> {
+    error: &'a Error,
+    source: &'a str,
+}
+
+impl Snippet<'_> {
+    /// Finds the line and column of `mark` in the source.
+    ///
+    /// Reader errors only carry a byte offset, which is translated into a
+    /// line and column here.
+    fn locate(&self, mark: &YamlMarkT) -> (usize, usize) {
+        if self.error.0.kind != ErrorKind::Reader {
+            return (mark.line as usize, mark.column as usize);
+        }
+        let mut offset = mark.index as usize;
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let before = &self.source[..offset.min(self.source.len())];
+        let line = before.matches('\n').count();
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        (line, before[line_start..].chars().count())
+    }
+}
+
+impl Display for Snippet<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let error = self.error;
+        formatter.write_str("error: ")?;
+        if let Some(path) = &error.0.path {
+            write!(formatter, "{}: ", path)?;
+        }
+        writeln!(formatter, "{}", error.0.message)?;
+        let (line, column) = match &error.0.end_mark {
+            Some(end) => self.locate(end),
+            None => return Ok(()),
+        };
+        let number = line + 1;
+        let width = number.to_string().len();
+        let gutter = "";
+        writeln!(
+            formatter,
+            "{:width$}--> {}:{}",
+            gutter,
+            number,
+            column + 1
+        )?;
+        writeln!(formatter, "{:width$} |", gutter)?;
+        let text = self
+            .source
+            .lines()
+            .nth(line)
+            .unwrap_or_default()
+            .trim_end_matches('\r');
+        if text.is_empty() {
+            writeln!(formatter, "{} |", number)?;
+        } else {
+            writeln!(formatter, "{} | {}", number, text)?;
+        }
+        write!(formatter, "{:width$} | ", gutter)?;
+        // Tabs are kept so that the caret lines up with the source line.
+        for ch in text.chars().take(column) {
+            formatter.write_char(if ch == '\t' {
+                '\t'
+            } else {
+                ' '
+            })?;
+        }
+        writeln!(formatter, "^")?;
+        if let Some(context) = &error.0.context {
+            writeln!(formatter, "{:width$} |", gutter)?;
+            write!(formatter, "{:width$} = note: {}", gutter, context)?;
+            if let Some(start) = &error.0.start_mark {
+                let start = self.locate(start);
+                if start != (line, column) {
+                    write!(
+                        formatter,
+                        " at {}:{}",
+                        start.0 + 1,
+                        start.1 + 1
+                    )?;
+                }
+            }
+            writeln!(formatter)?;
+        }
+        Ok(())
+    }
+}
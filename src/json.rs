@@ -0,0 +1,814 @@
+//! JSON input.
+//!
+//! A JSON text is a YAML 1.2 flow node, which the YAML scanner reads as
+//! it is. With [`yaml_parser_set_json`], the parser reads its input with
+//! a JSON scanner instead, which lets nothing but JSON through and
+//! produces the same tokens for it. yaml_parser_parse() then produces
+//! the events it would for the JSON text read as YAML, with marks
+//! pointing into the JSON source, and yaml_parser_load() the same
+//! document:
+//!
+//! - a stream of a single implicit document holding the JSON value;
+//! - flow sequences and flow mappings for arrays and objects;
+//! - double-quoted scalars for strings, and plain scalars for numbers,
+//!   `true`, `false` and `null`, which the JSON and core schemas resolve
+//!   to the matching types.
+//!
+//! With [`JsonSyntax::Comments`], the `//` and `/* */` comments of JSON5
+//! are skipped like whitespace.
+//!
+//! ```
+//! use libyml::json::JsonSyntax;
+//! use libyml::safe::{Event, Parser};
+//!
+//! let mut parser = Parser::new(b"{\"a\": [1, true] /* note */}");
+//! parser.set_json(JsonSyntax::Comments);
+//! let mut values = Vec::new();
+//! for event in parser {
+//!     if let Event::Scalar(scalar) = event.unwrap() {
+//!         values.push(scalar.value.into_owned());
+//!     }
+//! }
+//! assert_eq!(values, ["a", "1", "true"]);
+//! ```
+
+use crate::api::{
+    yaml_free, yaml_malloc, yaml_queue_extend, yaml_stack_extend,
+    yaml_string_extend, yaml_string_join,
+};
+use crate::externs::memset;
+use crate::scanner::{
+    cache, read, skip, skip_line, yaml_parser_can_borrow,
+    yaml_parser_check_scalar_length, yaml_parser_fetch_stream_end,
+    yaml_parser_fetch_stream_start, yaml_parser_input_string,
+    yaml_parser_set_scanner_error,
+};
+use crate::success::{Success, FAIL, OK};
+use crate::yaml::{size_t, yaml_char_t, YamlStringT, NULL_STRING};
+use crate::{
+    libc, PointerExt, YamlDoubleQuotedScalarStyle, YamlFlowEntryToken,
+    YamlFlowMappingEndToken, YamlFlowMappingStartToken,
+    YamlFlowSequenceEndToken, YamlFlowSequenceStartToken, YamlKeyToken,
+    YamlMarkT, YamlMemoryError, YamlParserT, YamlPlainScalarStyle,
+    YamlScalarStyleT, YamlScalarToken, YamlTokenT, YamlTokenTypeT,
+    YamlValueToken,
+};
+use core::mem::{size_of, MaybeUninit};
+use core::ptr::{self, addr_of, addr_of_mut};
+
+/// The dialects of JSON the parser reads.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum JsonSyntax {
+    /// JSON as RFC 8259 defines it.
+    Strict,
+    /// JSON with the `//` line comments and `/* */` block comments of
+    /// JSON5.
+    Comments,
+}
+
+/// What the JSON scanner expects next.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub(crate) enum JsonExpect {
+    /// A value, first thing in the input or after ':' or the ',' of an
+    /// array.
+    Value = 0,
+    /// A value or ']', after '['.
+    FirstValue = 1,
+    /// A key or '}', after '{'.
+    FirstKey = 2,
+    /// A key, after the ',' of an object.
+    Key = 3,
+    /// The ':' after a key.
+    Colon = 4,
+    /// ',' or the closing bracket, after a value in an array or an
+    /// object.
+    Separator = 5,
+    /// The end of the input, after the top-level value.
+    End = 6,
+}
+
+/// Set the parser to read JSON instead of YAML.
+///
+/// The input must then hold exactly one JSON value, surrounded by
+/// whitespace and, with JsonSyntax::Comments, comments. See the json
+/// module for the events it is parsed into.
+///
+/// # Safety
+///
+/// - `parser` must be a valid, non-null pointer to a properly initialized `YamlParserT` struct.
+/// - The function must be called before the first token is scanned.
+///
+pub unsafe fn yaml_parser_set_json(
+    parser: *mut YamlParserT,
+    syntax: JsonSyntax,
+) {
+    __assert!(!parser.is_null());
+    (*parser).json = true;
+    (*parser).json_comments = syntax == JsonSyntax::Comments;
+}
+
+/// Fetches the next token of the JSON input, checking that it may
+/// follow the ones before.
+///
+/// The kinds of the arrays and objects the scanner is in are kept on the
+/// indents stack, which JSON has no other use for: 0 for an array and 1
+/// for an object.
+pub(crate) unsafe fn yaml_parser_fetch_json_token(
+    parser: *mut YamlParserT,
+) -> Success {
+    if !(*parser).stream_start_produced {
+        yaml_parser_fetch_stream_start(parser);
+        return OK;
+    }
+    if yaml_parser_skip_json_blanks(parser).fail {
+        return FAIL;
+    }
+    let object = (*parser).flow_level != 0
+        && *(*parser).indents.top.wrapping_offset(-1) != 0;
+    match (*parser).json_expect {
+        JsonExpect::FirstValue if CHECK!((*parser).buffer, b']') => {
+            yaml_parser_fetch_json_collection_end(
+                parser,
+                YamlFlowSequenceEndToken,
+            )
+        }
+        JsonExpect::Value | JsonExpect::FirstValue => {
+            yaml_parser_fetch_json_value(parser)
+        }
+        JsonExpect::FirstKey if CHECK!((*parser).buffer, b'}') => {
+            yaml_parser_fetch_json_collection_end(
+                parser,
+                YamlFlowMappingEndToken,
+            )
+        }
+        JsonExpect::FirstKey | JsonExpect::Key => {
+            if !CHECK!((*parser).buffer, b'"') {
+                return yaml_parser_set_json_error(
+                    parser,
+                    b"did not find expected string key\0" as *const u8
+                        as *const libc::c_char,
+                );
+            }
+            yaml_parser_fetch_json_indicator(parser, YamlKeyToken, 0);
+            if yaml_parser_fetch_json_string(parser).fail {
+                return FAIL;
+            }
+            (*parser).json_expect = JsonExpect::Colon;
+            OK
+        }
+        JsonExpect::Colon => {
+            if !CHECK!((*parser).buffer, b':') {
+                return yaml_parser_set_json_error(
+                    parser,
+                    b"did not find expected ':'\0" as *const u8
+                        as *const libc::c_char,
+                );
+            }
+            yaml_parser_fetch_json_indicator(parser, YamlValueToken, 1);
+            (*parser).json_expect = JsonExpect::Value;
+            OK
+        }
+        JsonExpect::Separator => {
+            if CHECK!((*parser).buffer, b',') {
+                yaml_parser_fetch_json_indicator(
+                    parser,
+                    YamlFlowEntryToken,
+                    1,
+                );
+                (*parser).json_expect = if object {
+                    JsonExpect::Key
+                } else {
+                    JsonExpect::Value
+                };
+                OK
+            } else if object && CHECK!((*parser).buffer, b'}') {
+                yaml_parser_fetch_json_collection_end(
+                    parser,
+                    YamlFlowMappingEndToken,
+                )
+            } else if !object && CHECK!((*parser).buffer, b']') {
+                yaml_parser_fetch_json_collection_end(
+                    parser,
+                    YamlFlowSequenceEndToken,
+                )
+            } else {
+                yaml_parser_set_json_error(
+                    parser,
+                    if object {
+                        b"did not find expected ',' or '}'\0"
+                            as *const u8
+                            as *const libc::c_char
+                    } else {
+                        b"did not find expected ',' or ']'\0"
+                            as *const u8
+                            as *const libc::c_char
+                    },
+                )
+            }
+        }
+        JsonExpect::End => {
+            if !IS_Z!((*parser).buffer) {
+                return yaml_parser_set_json_error(
+                    parser,
+                    b"found content after the JSON value\0" as *const u8
+                        as *const libc::c_char,
+                );
+            }
+            yaml_parser_fetch_stream_end(parser)
+        }
+    }
+}
+
+unsafe fn yaml_parser_set_json_error(
+    parser: *mut YamlParserT,
+    problem: *const libc::c_char,
+) -> Success {
+    yaml_parser_set_scanner_error(
+        parser,
+        ptr::null::<libc::c_char>(),
+        (*parser).mark,
+        problem,
+    );
+    FAIL
+}
+
+/// Skips whitespace, and comments if they are allowed, leaving at least
+/// one character in the buffer.
+unsafe fn yaml_parser_skip_json_blanks(
+    parser: *mut YamlParserT,
+) -> Success {
+    loop {
+        if cache(parser, 2_u64).fail {
+            return FAIL;
+        }
+        if IS_SPACE!((*parser).buffer) || IS_TAB!((*parser).buffer) {
+            skip(parser);
+        } else if CHECK!((*parser).buffer, b'\r')
+            || CHECK!((*parser).buffer, b'\n')
+        {
+            skip_line(parser);
+        } else if (*parser).json_comments
+            && CHECK_AT!((*parser).buffer, b'/', 0)
+            && CHECK_AT!((*parser).buffer, b'/', 1)
+        {
+            while !(IS_Z!((*parser).buffer)
+                || CHECK!((*parser).buffer, b'\r')
+                || CHECK!((*parser).buffer, b'\n'))
+            {
+                skip(parser);
+                if cache(parser, 1_u64).fail {
+                    return FAIL;
+                }
+            }
+        } else if (*parser).json_comments
+            && CHECK_AT!((*parser).buffer, b'/', 0)
+            && CHECK_AT!((*parser).buffer, b'*', 1)
+        {
+            let start_mark: YamlMarkT = (*parser).mark;
+            skip(parser);
+            skip(parser);
+            loop {
+                if cache(parser, 2_u64).fail {
+                    return FAIL;
+                }
+                if IS_Z!((*parser).buffer) {
+                    yaml_parser_set_scanner_error(
+                        parser,
+                        b"while scanning a comment\0" as *const u8
+                            as *const libc::c_char,
+                        start_mark,
+                        b"did not find the end of the comment\0"
+                            as *const u8
+                            as *const libc::c_char,
+                    );
+                    return FAIL;
+                }
+                if CHECK_AT!((*parser).buffer, b'*', 0)
+                    && CHECK_AT!((*parser).buffer, b'/', 1)
+                {
+                    skip(parser);
+                    skip(parser);
+                    break;
+                }
+                if CHECK!((*parser).buffer, b'\r')
+                    || CHECK!((*parser).buffer, b'\n')
+                {
+                    skip_line(parser);
+                } else {
+                    skip(parser);
+                }
+            }
+        } else {
+            return OK;
+        }
+    }
+}
+
+/// Enqueues a token of the given type for the next `width` characters,
+/// which are ASCII indicators, and moves past them.
+unsafe fn yaml_parser_fetch_json_indicator(
+    parser: *mut YamlParserT,
+    type_: YamlTokenTypeT,
+    width: usize,
+) {
+    let mut token = MaybeUninit::<YamlTokenT>::uninit();
+    let token = token.as_mut_ptr();
+    let start_mark: YamlMarkT = (*parser).mark;
+    for _ in 0..width {
+        skip(parser);
+    }
+    memset(
+        token as *mut libc::c_void,
+        0,
+        size_of::<YamlTokenT>() as libc::c_ulong,
+    );
+    (*token).type_ = type_;
+    (*token).start_mark = start_mark;
+    (*token).end_mark = (*parser).mark;
+    ENQUEUE!((*parser).tokens, *token);
+}
+
+/// Expects what may follow a complete value.
+unsafe fn yaml_parser_end_json_value(parser: *mut YamlParserT) {
+    (*parser).json_expect = if (*parser).flow_level == 0 {
+        JsonExpect::End
+    } else {
+        JsonExpect::Separator
+    };
+}
+
+unsafe fn yaml_parser_fetch_json_collection_end(
+    parser: *mut YamlParserT,
+    type_: YamlTokenTypeT,
+) -> Success {
+    let _ = POP!((*parser).indents);
+    (*parser).flow_level -= 1;
+    yaml_parser_fetch_json_indicator(parser, type_, 1);
+    yaml_parser_end_json_value(parser);
+    OK
+}
+
+unsafe fn yaml_parser_fetch_json_value(
+    parser: *mut YamlParserT,
+) -> Success {
+    if CHECK!((*parser).buffer, b'[') || CHECK!((*parser).buffer, b'{')
+    {
+        let object = CHECK!((*parser).buffer, b'{');
+        if (*parser).flow_level == libc::c_int::MAX {
+            (*parser).error = YamlMemoryError;
+            return FAIL;
+        }
+        PUSH!((*parser).indents, object as libc::c_int);
+        (*parser).flow_level += 1;
+        if object {
+            yaml_parser_fetch_json_indicator(
+                parser,
+                YamlFlowMappingStartToken,
+                1,
+            );
+            (*parser).json_expect = JsonExpect::FirstKey;
+        } else {
+            yaml_parser_fetch_json_indicator(
+                parser,
+                YamlFlowSequenceStartToken,
+                1,
+            );
+            (*parser).json_expect = JsonExpect::FirstValue;
+        }
+        return OK;
+    }
+    if CHECK!((*parser).buffer, b'"') {
+        return yaml_parser_fetch_json_string(parser);
+    }
+    if CHECK!((*parser).buffer, b'-') || IS_DIGIT!((*parser).buffer) {
+        return yaml_parser_fetch_json_scalar(
+            parser,
+            yaml_parser_scan_json_number,
+            YamlPlainScalarStyle,
+        );
+    }
+    if CHECK!((*parser).buffer, b't')
+        || CHECK!((*parser).buffer, b'f')
+        || CHECK!((*parser).buffer, b'n')
+    {
+        return yaml_parser_fetch_json_scalar(
+            parser,
+            yaml_parser_scan_json_literal,
+            YamlPlainScalarStyle,
+        );
+    }
+    yaml_parser_set_json_error(
+        parser,
+        b"did not find expected JSON value\0" as *const u8
+            as *const libc::c_char,
+    )
+}
+
+unsafe fn yaml_parser_fetch_json_string(
+    parser: *mut YamlParserT,
+) -> Success {
+    yaml_parser_fetch_json_scalar(
+        parser,
+        yaml_parser_scan_json_string,
+        YamlDoubleQuotedScalarStyle,
+    )
+}
+
+/// Scans the text of a scalar into `string`, starting at `start_mark`
+/// and past any opening quote. A borrowed scalar leaves `string` null
+/// until the text stops being verbatim, and is then copied into it.
+type JsonScan = unsafe fn(
+    parser: *mut YamlParserT,
+    string: *mut YamlStringT,
+    borrowed: *mut bool,
+    start_mark: YamlMarkT,
+) -> Success;
+
+/// Scans a scalar, of the given style, and enqueues its token.
+unsafe fn yaml_parser_fetch_json_scalar(
+    parser: *mut YamlParserT,
+    scan: JsonScan,
+    style: YamlScalarStyleT,
+) -> Success {
+    let mut token = MaybeUninit::<YamlTokenT>::uninit();
+    let token = token.as_mut_ptr();
+    let start_mark: YamlMarkT = (*parser).mark;
+    let quoted = style == YamlDoubleQuotedScalarStyle;
+    if quoted {
+        skip(parser);
+    }
+    let content_start = (*parser).mark.offset;
+    let mut string = NULL_STRING;
+    let mut borrowed = yaml_parser_can_borrow(parser);
+    if !borrowed {
+        STRING_INIT!(string);
+    }
+    if scan(
+        parser,
+        addr_of_mut!(string),
+        addr_of_mut!(borrowed),
+        start_mark,
+    )
+    .fail
+    {
+        yaml_free(string.start as *mut libc::c_void);
+        return FAIL;
+    }
+    let content_end = (*parser).mark.offset;
+    let value = if borrowed {
+        yaml_parser_input_string(parser, content_start, content_end)
+    } else {
+        string
+    };
+    if yaml_parser_check_scalar_length(
+        parser,
+        addr_of!(value),
+        if quoted {
+            b"while scanning a JSON string\0" as *const u8
+                as *const libc::c_char
+        } else {
+            b"while scanning a JSON value\0" as *const u8
+                as *const libc::c_char
+        },
+        start_mark,
+        (*parser).mark,
+    )
+    .fail
+    {
+        yaml_free(string.start as *mut libc::c_void);
+        return FAIL;
+    }
+    if quoted {
+        skip(parser);
+    }
+    memset(
+        token as *mut libc::c_void,
+        0,
+        size_of::<YamlTokenT>() as libc::c_ulong,
+    );
+    (*token).type_ = YamlScalarToken;
+    (*token).start_mark = start_mark;
+    (*token).end_mark = (*parser).mark;
+    (*token).data.scalar.value = value.start;
+    (*token).data.scalar.length =
+        value.pointer.c_offset_from(value.start) as size_t;
+    (*token).data.scalar.borrowed = borrowed;
+    (*token).data.scalar.style = style;
+    ENQUEUE!((*parser).tokens, *token);
+    yaml_parser_end_json_value(parser);
+    OK
+}
+
+/// Moves past the character ahead, copying it into `string` unless the
+/// scalar is borrowed.
+unsafe fn yaml_parser_take_json_char(
+    parser: *mut YamlParserT,
+    string: *mut YamlStringT,
+    borrowed: bool,
+) {
+    if borrowed {
+        skip(parser);
+    } else {
+        read(parser, string);
+    }
+}
+
+unsafe fn yaml_parser_scan_json_literal(
+    parser: *mut YamlParserT,
+    string: *mut YamlStringT,
+    borrowed: *mut bool,
+    _start_mark: YamlMarkT,
+) -> Success {
+    if cache(parser, 5_u64).fail {
+        return FAIL;
+    }
+    let literal: &[u8] = match *(*parser).buffer.pointer {
+        b't' => b"true",
+        b'f' => b"false",
+        _ => b"null",
+    };
+    // The characters before a mismatch are ASCII, so the bytes compared
+    // are all in the buffer.
+    if !literal.iter().enumerate().all(|(k, &octet)| {
+        CHECK_AT!((*parser).buffer, octet, k as isize)
+    }) {
+        return yaml_parser_set_json_error(
+            parser,
+            b"did not find expected JSON value\0" as *const u8
+                as *const libc::c_char,
+        );
+    }
+    for _ in literal {
+        yaml_parser_take_json_char(parser, string, *borrowed);
+    }
+    OK
+}
+
+unsafe fn yaml_parser_scan_json_number(
+    parser: *mut YamlParserT,
+    string: *mut YamlStringT,
+    borrowed: *mut bool,
+    start_mark: YamlMarkT,
+) -> Success {
+    if CHECK!((*parser).buffer, b'-') {
+        yaml_parser_take_json_char(parser, string, *borrowed);
+        if cache(parser, 1_u64).fail {
+            return FAIL;
+        }
+    }
+    // A leading zero is the whole integer part.
+    if CHECK!((*parser).buffer, b'0') {
+        yaml_parser_take_json_char(parser, string, *borrowed);
+        if cache(parser, 1_u64).fail {
+            return FAIL;
+        }
+    } else if yaml_parser_scan_json_digits(
+        parser, string, *borrowed, start_mark,
+    )
+    .fail
+    {
+        return FAIL;
+    }
+    if CHECK!((*parser).buffer, b'.') {
+        yaml_parser_take_json_char(parser, string, *borrowed);
+        if yaml_parser_scan_json_digits(
+            parser, string, *borrowed, start_mark,
+        )
+        .fail
+        {
+            return FAIL;
+        }
+    }
+    if CHECK!((*parser).buffer, b'e') || CHECK!((*parser).buffer, b'E')
+    {
+        yaml_parser_take_json_char(parser, string, *borrowed);
+        if cache(parser, 1_u64).fail {
+            return FAIL;
+        }
+        if CHECK!((*parser).buffer, b'+')
+            || CHECK!((*parser).buffer, b'-')
+        {
+            yaml_parser_take_json_char(parser, string, *borrowed);
+        }
+        if yaml_parser_scan_json_digits(
+            parser, string, *borrowed, start_mark,
+        )
+        .fail
+        {
+            return FAIL;
+        }
+    }
+    OK
+}
+
+/// Scans one or more digits, leaving the character after them in the
+/// buffer.
+unsafe fn yaml_parser_scan_json_digits(
+    parser: *mut YamlParserT,
+    string: *mut YamlStringT,
+    borrowed: bool,
+    start_mark: YamlMarkT,
+) -> Success {
+    if cache(parser, 1_u64).fail {
+        return FAIL;
+    }
+    if !IS_DIGIT!((*parser).buffer) {
+        yaml_parser_set_scanner_error(
+            parser,
+            b"while scanning a JSON number\0" as *const u8
+                as *const libc::c_char,
+            start_mark,
+            b"did not find expected digit\0" as *const u8
+                as *const libc::c_char,
+        );
+        return FAIL;
+    }
+    loop {
+        yaml_parser_take_json_char(parser, string, borrowed);
+        if cache(parser, 1_u64).fail {
+            return FAIL;
+        }
+        if !IS_DIGIT!((*parser).buffer) {
+            return OK;
+        }
+    }
+}
+
+unsafe fn yaml_parser_scan_json_string(
+    parser: *mut YamlParserT,
+    string: *mut YamlStringT,
+    borrowed: *mut bool,
+    start_mark: YamlMarkT,
+) -> Success {
+    let content_start = (*parser).mark.offset;
+    loop {
+        if cache(parser, 1_u64).fail {
+            return FAIL;
+        }
+        if CHECK!((*parser).buffer, b'"') {
+            return OK;
+        }
+        if IS_Z!((*parser).buffer) || *(*parser).buffer.pointer < b' ' {
+            yaml_parser_set_scanner_error(
+                parser,
+                b"while scanning a JSON string\0" as *const u8
+                    as *const libc::c_char,
+                start_mark,
+                if IS_Z!((*parser).buffer) {
+                    b"found unexpected end of stream\0" as *const u8
+                        as *const libc::c_char
+                } else {
+                    b"found unescaped control character\0" as *const u8
+                        as *const libc::c_char
+                },
+            );
+            return FAIL;
+        }
+        if !CHECK!((*parser).buffer, b'\\') {
+            yaml_parser_take_json_char(parser, string, *borrowed);
+            continue;
+        }
+        if *borrowed {
+            STRING_INIT!(*string);
+            let mut verbatim = yaml_parser_input_string(
+                parser,
+                content_start,
+                (*parser).mark.offset,
+            );
+            JOIN!(*string, verbatim);
+            *borrowed = false;
+        }
+        if yaml_parser_scan_json_escape(parser, string, start_mark).fail
+        {
+            return FAIL;
+        }
+    }
+}
+
+/// Scans the escape sequence ahead and appends the character it stands
+/// for to `string`.
+unsafe fn yaml_parser_scan_json_escape(
+    parser: *mut YamlParserT,
+    string: *mut YamlStringT,
+    start_mark: YamlMarkT,
+) -> Success {
+    if cache(parser, 2_u64).fail {
+        return FAIL;
+    }
+    let mut value: u32 = match *(*parser).buffer.pointer.wrapping_add(1)
+    {
+        b'"' => '"' as u32,
+        b'\\' => '\\' as u32,
+        b'/' => '/' as u32,
+        b'b' => 0x08,
+        b'f' => 0x0C,
+        b'n' => '\n' as u32,
+        b'r' => '\r' as u32,
+        b't' => '\t' as u32,
+        b'u' => 0,
+        _ => {
+            yaml_parser_set_scanner_error(
+                parser,
+                b"while scanning a JSON string\0" as *const u8
+                    as *const libc::c_char,
+                start_mark,
+                b"found unknown escape character\0" as *const u8
+                    as *const libc::c_char,
+            );
+            return FAIL;
+        }
+    };
+    if value != 0 {
+        skip(parser);
+        skip(parser);
+    } else {
+        if yaml_parser_scan_json_code_unit(
+            parser,
+            addr_of_mut!(value),
+            start_mark,
+        )
+        .fail
+        {
+            return FAIL;
+        }
+        // A high surrogate must be followed by the escape of a low one,
+        // the two of them standing for a single character.
+        let mut paired = !(0xD800..0xE000).contains(&value);
+        if (0xD800..0xDC00).contains(&value) {
+            if cache(parser, 2_u64).fail {
+                return FAIL;
+            }
+            let mut low: u32 = 0;
+            if CHECK_AT!((*parser).buffer, b'\\', 0)
+                && CHECK_AT!((*parser).buffer, b'u', 1)
+                && yaml_parser_scan_json_code_unit(
+                    parser,
+                    addr_of_mut!(low),
+                    start_mark,
+                )
+                .fail
+            {
+                return FAIL;
+            }
+            if (0xDC00..0xE000).contains(&low) {
+                value =
+                    0x10000 + ((value - 0xD800) << 10) + (low - 0xDC00);
+                paired = true;
+            }
+        }
+        if !paired {
+            yaml_parser_set_scanner_error(
+                parser,
+                b"while scanning a JSON string\0" as *const u8
+                    as *const libc::c_char,
+                start_mark,
+                b"found unpaired surrogate escape\0" as *const u8
+                    as *const libc::c_char,
+            );
+            return FAIL;
+        }
+    }
+    let character =
+        char::from_u32(value).unwrap_or(char::REPLACEMENT_CHARACTER);
+    let mut bytes = [0; 4];
+    STRING_EXTEND!(*string);
+    for &octet in character.encode_utf8(&mut bytes).as_bytes() {
+        *(*string).pointer = octet;
+        (*string).pointer = (*string).pointer.wrapping_add(1);
+    }
+    OK
+}
+
+/// Scans a `\uXXXX` escape into the UTF-16 code unit it stands for.
+unsafe fn yaml_parser_scan_json_code_unit(
+    parser: *mut YamlParserT,
+    value: *mut u32,
+    start_mark: YamlMarkT,
+) -> Success {
+    if cache(parser, 6_u64).fail {
+        return FAIL;
+    }
+    skip(parser);
+    skip(parser);
+    *value = 0;
+    for k in 0..4 {
+        if !IS_HEX_AT!((*parser).buffer, k) {
+            yaml_parser_set_scanner_error(
+                parser,
+                b"while scanning a JSON string\0" as *const u8
+                    as *const libc::c_char,
+                start_mark,
+                b"did not find expected hexadecimal number\0"
+                    as *const u8 as *const libc::c_char,
+            );
+            return FAIL;
+        }
+        *value = *value << 4 | AS_HEX_AT!((*parser).buffer, k) as u32;
+    }
+    for _ in 0..4 {
+        skip(parser);
+    }
+    OK
+}
@@ -40,7 +40,7 @@
 //!
 //! ### Rust Version Compatibility
 //!
-//! This library is compatible with Rust 1.60 and above.
+//! This library is compatible with Rust 1.81 and above.
 //!
 //! ## Features
 //!
@@ -152,6 +152,8 @@
 #![crate_type = "lib"]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 use core::mem::size_of;
 
@@ -234,18 +236,6 @@ mod externs {
         rust::dealloc(memory, layout);
     }
 
-    pub(crate) unsafe fn memcmp(
-        lhs: *const libc::c_void,
-        rhs: *const libc::c_void,
-        count: libc::c_ulong,
-    ) -> libc::c_int {
-        let lhs =
-            slice::from_raw_parts(lhs.cast::<u8>(), count as usize);
-        let rhs =
-            slice::from_raw_parts(rhs.cast::<u8>(), count as usize);
-        lhs.cmp(rhs) as libc::c_int
-    }
-
     pub(crate) unsafe fn memcpy(
         dest: *mut libc::c_void,
         src: *const libc::c_void,
@@ -423,13 +413,23 @@ mod macros;
 
 /// API module for LibYML
 pub mod api;
+pub mod checked;
+#[cfg(feature = "serde")]
+pub mod de;
 mod dumper;
 mod emitter;
+pub mod error;
+pub mod json;
 mod loader;
 mod ops;
 mod parser;
+mod push;
 mod reader;
+pub mod resolver;
+pub mod safe;
 mod scanner;
+#[cfg(feature = "serde")]
+pub mod ser;
 mod success;
 mod writer;
 /// YAML API module for LibYML
@@ -447,38 +447,55 @@ pub use crate::api::{
     yaml_emitter_set_canonical, yaml_emitter_set_encoding,
     yaml_emitter_set_indent, yaml_emitter_set_output,
     yaml_emitter_set_output_string, yaml_emitter_set_unicode,
-    yaml_emitter_set_width, yaml_event_delete,
+    yaml_emitter_set_width, yaml_event_delete, yaml_event_set_comments,
     yaml_mapping_end_event_initialize,
     yaml_mapping_start_event_initialize, yaml_parser_delete,
-    yaml_parser_initialize, yaml_parser_set_encoding,
-    yaml_parser_set_input, yaml_parser_set_input_string,
-    yaml_scalar_event_initialize, yaml_sequence_end_event_initialize,
+    yaml_parser_initialize, yaml_parser_set_allow_aliases,
+    yaml_parser_set_borrow_scalars, yaml_parser_set_comments,
+    yaml_parser_set_encoding, yaml_parser_set_input,
+    yaml_parser_set_input_string, yaml_parser_set_max_alias_expansion,
+    yaml_parser_set_max_depth, yaml_parser_set_max_input_size,
+    yaml_parser_set_max_name_length, yaml_parser_set_max_queued_tokens,
+    yaml_parser_set_max_scalar_length, yaml_parser_set_merge_keys,
+    yaml_parser_set_schema, yaml_parser_set_strict,
+    yaml_parser_set_unique_keys, yaml_scalar_event_initialize,
+    yaml_sequence_end_event_initialize,
     yaml_sequence_start_event_initialize,
     yaml_stream_end_event_initialize,
     yaml_stream_start_event_initialize, yaml_token_delete,
 };
+#[cfg(all(feature = "serde", feature = "std"))]
+pub use crate::de::from_reader;
+#[cfg(feature = "serde")]
+pub use crate::de::{from_slice, from_str, Deserializer};
 pub use crate::dumper::{
     yaml_emitter_close, yaml_emitter_dump, yaml_emitter_open,
 };
 pub use crate::emitter::yaml_emitter_emit;
+pub use crate::json::yaml_parser_set_json;
 pub use crate::loader::yaml_parser_load;
 pub use crate::parser::yaml_parser_parse;
 pub use crate::scanner::yaml_parser_scan;
+#[cfg(all(feature = "serde", feature = "std"))]
+pub use crate::ser::to_writer;
+#[cfg(feature = "serde")]
+pub use crate::ser::{to_string, to_vec, Serializer};
 pub use crate::writer::yaml_emitter_flush;
 pub use crate::yaml::{
-    YamlAliasDataT, YamlBreakT, YamlDocumentT, YamlEmitterStateT,
-    YamlEmitterT, YamlEncodingT, YamlErrorTypeT, YamlEventT,
-    YamlEventTypeT, YamlMappingStyleT, YamlMarkT, YamlNodeItemT,
-    YamlNodePairT, YamlNodeT, YamlNodeTypeT, YamlParserStateT,
-    YamlParserT, YamlReadHandlerT, YamlScalarStyleT,
-    YamlSequenceStyleT, YamlSimpleKeyT, YamlStackT, YamlTagDirectiveT,
-    YamlTokenT, YamlTokenTypeT, YamlVersionDirectiveT,
-    YamlWriteHandlerT,
+    YamlAliasDataT, YamlBreakT, YamlCommentTypeT, YamlDocumentT,
+    YamlEmitterStateT, YamlEmitterT, YamlEncodingT, YamlErrorTypeT,
+    YamlEventCommentsT, YamlEventT, YamlEventTypeT, YamlMappingStyleT,
+    YamlMarkT, YamlNodeItemT, YamlNodePairT, YamlNodeT, YamlNodeTypeT,
+    YamlParserStateT, YamlParserT, YamlReadHandlerT, YamlScalarStyleT,
+    YamlSchemaT, YamlSequenceStyleT, YamlSimpleKeyT, YamlStackT,
+    YamlTagDirectiveT, YamlTokenT, YamlTokenTypeT,
+    YamlVersionDirectiveT, YamlWriteHandlerT,
 };
 #[doc(hidden)]
 pub use crate::yaml::{
-    YamlBreakT::*, YamlEmitterStateT::*, YamlEncodingT::*,
-    YamlErrorTypeT::*, YamlEventTypeT::*, YamlMappingStyleT::*,
-    YamlNodeTypeT::*, YamlParserStateT::*, YamlScalarStyleT::*,
-    YamlSequenceStyleT::*, YamlTokenTypeT::*,
+    YamlBreakT::*, YamlCommentTypeT::*, YamlEmitterStateT::*,
+    YamlEncodingT::*, YamlErrorTypeT::*, YamlEventTypeT::*,
+    YamlMappingStyleT::*, YamlNodeTypeT::*, YamlParserStateT::*,
+    YamlScalarStyleT::*, YamlSchemaT::*, YamlSequenceStyleT::*,
+    YamlTokenTypeT::*,
 };
@@ -1,27 +1,49 @@
 use crate::api::{
-    yaml_free, yaml_malloc, yaml_stack_extend, yaml_strdup,
+    yaml_event_comments_delete, yaml_free, yaml_malloc,
+    yaml_stack_extend, yaml_strdup,
 };
+use crate::error::Error;
 use crate::externs::{memset, strcmp};
+use crate::ops::ForceAdd as _;
+use crate::resolver::{
+    resolve, resolve_plain, Resolved, Value, MERGE_TAG, STR_TAG,
+};
 use crate::success::{Success, FAIL, OK};
-use crate::yaml::yaml_char_t;
+use crate::yaml::{size_t, yaml_char_t};
 use crate::{
     libc, yaml_document_delete, yaml_parser_parse, PointerExt,
     YamlAliasDataT, YamlAliasEvent, YamlComposerError,
     YamlDocumentEndEvent, YamlDocumentStartEvent, YamlDocumentT,
     YamlEventT, YamlMappingEndEvent, YamlMappingNode,
     YamlMappingStartEvent, YamlMarkT, YamlMemoryError, YamlNodeItemT,
-    YamlNodePairT, YamlNodeT, YamlParserT, YamlScalarEvent,
-    YamlScalarNode, YamlSequenceEndEvent, YamlSequenceNode,
-    YamlSequenceStartEvent, YamlStreamEndEvent, YamlStreamStartEvent,
+    YamlNodePairT, YamlNodeT, YamlParserT, YamlPlainScalarStyle,
+    YamlScalarEvent, YamlScalarNode, YamlSequenceEndEvent,
+    YamlSequenceNode, YamlSequenceStartEvent, YamlStackT,
+    YamlStreamEndEvent, YamlStreamStartEvent,
 };
+use alloc::collections::BTreeMap;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::ffi::CStr;
 use core::mem::{size_of, MaybeUninit};
 use core::ptr::{self, addr_of_mut};
+use core::{slice, str};
 
 #[repr(C)]
 struct LoaderCtx {
-    start: *mut libc::c_int,
-    end: *mut libc::c_int,
-    top: *mut libc::c_int,
+    start: *mut LoaderNode,
+    end: *mut LoaderNode,
+    top: *mut LoaderNode,
+}
+
+/// A collection whose end has not been loaded yet.
+#[derive(Copy, Clone)]
+#[repr(C)]
+struct LoaderNode {
+    /// The node id.
+    index: libc::c_int,
+    /// The expanded node count of the document before the collection.
+    expanded: size_t,
 }
 
 /// Parse the input stream and produce the next YAML document.
@@ -62,7 +84,7 @@ pub unsafe fn yaml_parser_load(
     );
     STACK_INIT!((*document).nodes, YamlNodeT);
     if !(*parser).stream_start_produced {
-        if yaml_parser_parse(parser, event).fail {
+        if yaml_parser_load_event(parser, event).fail {
             current_block = 6234624449317607669;
         } else {
             __assert!((*event).type_ == YamlStreamStartEvent);
@@ -75,7 +97,7 @@ pub unsafe fn yaml_parser_load(
         if (*parser).stream_end_produced {
             return OK;
         }
-        if yaml_parser_parse(parser, event).ok {
+        if yaml_parser_load_event(parser, event).ok {
             if (*event).type_ == YamlStreamEndEvent {
                 return OK;
             }
@@ -97,6 +119,18 @@ pub unsafe fn yaml_parser_load(
     FAIL
 }
 
+unsafe fn yaml_parser_load_event(
+    parser: *mut YamlParserT,
+    event: *mut YamlEventT,
+) -> Success {
+    if yaml_parser_parse(parser, event).fail {
+        return FAIL;
+    }
+    // Documents have no place for comments.
+    yaml_event_comments_delete(addr_of_mut!((*event).comments));
+    OK
+}
+
 unsafe fn yaml_parser_set_composer_error(
     parser: *mut YamlParserT,
     problem: *const libc::c_char,
@@ -138,9 +172,9 @@ unsafe fn yaml_parser_load_document(
     event: *mut YamlEventT,
 ) -> Success {
     let mut ctx = LoaderCtx {
-        start: ptr::null_mut::<libc::c_int>(),
-        end: ptr::null_mut::<libc::c_int>(),
-        top: ptr::null_mut::<libc::c_int>(),
+        start: ptr::null_mut::<LoaderNode>(),
+        end: ptr::null_mut::<LoaderNode>(),
+        top: ptr::null_mut::<LoaderNode>(),
     };
     __assert!((*event).type_ == YamlDocumentStartEvent);
     let fresh16 = addr_of_mut!((*(*parser).document).version_directive);
@@ -154,7 +188,8 @@ unsafe fn yaml_parser_load_document(
     (*(*parser).document).start_implicit =
         (*event).data.document_start.implicit;
     (*(*parser).document).start_mark = (*event).start_mark;
-    STACK_INIT!(ctx, libc::c_int);
+    (*parser).expanded_nodes = 0;
+    STACK_INIT!(ctx, LoaderNode);
     if yaml_parser_load_nodes(parser, addr_of_mut!(ctx)).fail {
         STACK_DEL!(ctx);
         return FAIL;
@@ -170,7 +205,7 @@ unsafe fn yaml_parser_load_nodes(
     let mut event = MaybeUninit::<YamlEventT>::uninit();
     let event = event.as_mut_ptr();
     loop {
-        if yaml_parser_parse(parser, event).fail {
+        if yaml_parser_load_event(parser, event).fail {
             return FAIL;
         }
         match (*event).type_ {
@@ -226,6 +261,7 @@ unsafe fn yaml_parser_register_anchor(
     parser: *mut YamlParserT,
     index: libc::c_int,
     anchor: *mut yaml_char_t,
+    expanded: size_t,
 ) -> Success {
     let mut data = MaybeUninit::<YamlAliasDataT>::uninit();
     let data = data.as_mut_ptr();
@@ -235,6 +271,7 @@ unsafe fn yaml_parser_register_anchor(
     }
     (*data).anchor = anchor;
     (*data).index = index;
+    (*data).expanded = expanded;
     (*data).mark = (*(*(*parser).document)
         .nodes
         .start
@@ -273,7 +310,7 @@ unsafe fn yaml_parser_load_node_add(
         return OK;
     }
     let parent_index: libc::c_int =
-        *(*ctx).top.wrapping_offset(-1_isize);
+        (*(*ctx).top.wrapping_offset(-1_isize)).index;
     let parent: *mut YamlNodeT =
         addr_of_mut!(*((*(*parser).document).nodes.start)
             .wrapping_offset((parent_index - 1) as isize));
@@ -310,6 +347,7 @@ unsafe fn yaml_parser_load_node_add(
                 _ => {
                     (*pair).key = index;
                     (*pair).value = 0;
+                    (*pair).merged = false;
                     if STACK_LIMIT!(
                         parser,
                         (*parent).data.mapping.pairs
@@ -336,6 +374,15 @@ unsafe fn yaml_parser_load_alias(
 ) -> Success {
     let anchor: *mut yaml_char_t = (*event).data.alias.anchor;
     let mut alias_data: *mut YamlAliasDataT;
+    if !(*parser).allow_aliases {
+        yaml_free(anchor as *mut libc::c_void);
+        return yaml_parser_set_composer_error(
+            parser,
+            b"found alias, but aliases are not allowed\0" as *const u8
+                as *const libc::c_char,
+            (*event).start_mark,
+        );
+    }
     alias_data = (*parser).aliases.start;
     while alias_data != (*parser).aliases.top {
         if strcmp(
@@ -344,6 +391,22 @@ unsafe fn yaml_parser_load_alias(
         ) == 0
         {
             yaml_free(anchor as *mut libc::c_void);
+            // An alias inside its own anchored collection would expand
+            // forever, so it only counts as a reference.
+            let expanded = if (*alias_data).expanded == 0 {
+                1
+            } else {
+                (*alias_data).expanded
+            };
+            if yaml_parser_expand_alias(
+                parser,
+                expanded,
+                (*event).start_mark,
+            )
+            .fail
+            {
+                return FAIL;
+            }
             return yaml_parser_load_node_add(
                 parser,
                 ctx,
@@ -360,6 +423,53 @@ unsafe fn yaml_parser_load_alias(
     )
 }
 
+/// Counts the nodes an alias stands for, and fails once the aliases of
+/// the document stand for more nodes than the parser allows.
+unsafe fn yaml_parser_expand_alias(
+    parser: *mut YamlParserT,
+    expanded: size_t,
+    mark: YamlMarkT,
+) -> Success {
+    (*parser).expanded_nodes =
+        (*parser).expanded_nodes.saturating_add(expanded);
+    let nodes = (*(*parser).document)
+        .nodes
+        .top
+        .c_offset_from((*(*parser).document).nodes.start)
+        as size_t;
+    if (*parser).expanded_nodes - nodes > (*parser).max_alias_expansion
+    {
+        return yaml_parser_set_composer_error(
+            parser,
+            b"alias expansion limit exceeded\0" as *const u8
+                as *const libc::c_char,
+            mark,
+        );
+    }
+    OK
+}
+
+/// Records the expanded node count of a collection that just ended, for
+/// the aliases to its anchor.
+unsafe fn yaml_parser_finish_anchor(
+    parser: *mut YamlParserT,
+    node: LoaderNode,
+) {
+    let expanded = (*parser).expanded_nodes - node.expanded;
+    // Anchors are registered in node order, so the ones above this
+    // node's anchor belong to its descendants.
+    let mut alias_data = (*parser).aliases.top;
+    while alias_data != (*parser).aliases.start {
+        alias_data = alias_data.wrapping_offset(-1);
+        if (*alias_data).index <= node.index {
+            if (*alias_data).index == node.index {
+                (*alias_data).expanded = expanded;
+            }
+            break;
+        }
+    }
+}
+
 unsafe fn yaml_parser_load_scalar(
     parser: *mut YamlParserT,
     event: *mut YamlEventT,
@@ -370,19 +480,19 @@ unsafe fn yaml_parser_load_scalar(
     let node = node.as_mut_ptr();
     let index: libc::c_int;
     let mut tag: *mut yaml_char_t = (*event).data.scalar.tag;
-    if STACK_LIMIT!(parser, (*(*parser).document).nodes).ok {
+    if yaml_parser_own_scalar(parser, event).ok
+        && STACK_LIMIT!(parser, (*(*parser).document).nodes).ok
+    {
         if tag.is_null()
             || strcmp(
                 tag as *mut libc::c_char,
                 b"!\0" as *const u8 as *const libc::c_char,
             ) == 0
         {
+            let default_tag =
+                yaml_parser_default_scalar_tag(parser, event);
             yaml_free(tag as *mut libc::c_void);
-            tag = yaml_strdup(
-                b"tag:yaml.org,2002:str\0" as *const u8
-                    as *const libc::c_char
-                    as *mut yaml_char_t,
-            );
+            tag = default_tag;
             if tag.is_null() {
                 current_block = 10579931339944277179;
             } else {
@@ -410,10 +520,13 @@ unsafe fn yaml_parser_load_scalar(
                 .top
                 .c_offset_from((*(*parser).document).nodes.start)
                 as libc::c_int;
+            (*parser).expanded_nodes =
+                (*parser).expanded_nodes.saturating_add(1);
             if yaml_parser_register_anchor(
                 parser,
                 index,
                 (*event).data.scalar.anchor,
+                1,
             )
             .fail
             {
@@ -424,10 +537,80 @@ unsafe fn yaml_parser_load_scalar(
     }
     yaml_free(tag as *mut libc::c_void);
     yaml_free((*event).data.scalar.anchor as *mut libc::c_void);
-    yaml_free((*event).data.scalar.value as *mut libc::c_void);
+    if !(*event).data.scalar.borrowed {
+        yaml_free((*event).data.scalar.value as *mut libc::c_void);
+    }
     FAIL
 }
 
+/// Copies the value of a scalar event that borrows it from the input, as
+/// documents own their values.
+unsafe fn yaml_parser_own_scalar(
+    parser: *mut YamlParserT,
+    event: *mut YamlEventT,
+) -> Success {
+    let scalar = &mut (*event).data.scalar;
+    if !scalar.borrowed {
+        return OK;
+    }
+    let value =
+        yaml_malloc(scalar.length.force_add(1)) as *mut yaml_char_t;
+    if value.is_null() {
+        (*parser).error = YamlMemoryError;
+        return FAIL;
+    }
+    ptr::copy_nonoverlapping(
+        scalar.value,
+        value,
+        scalar.length as usize,
+    );
+    *value.add(scalar.length as usize) = b'\0';
+    scalar.value = value;
+    scalar.borrowed = false;
+    OK
+}
+
+/// Duplicates the tag of a scalar event without a specific tag: the one
+/// its value resolves to under the parser's schema if it is plain, or
+/// the string tag otherwise.
+unsafe fn yaml_parser_default_scalar_tag(
+    parser: *mut YamlParserT,
+    event: *mut YamlEventT,
+) -> *mut yaml_char_t {
+    let scalar = &(*event).data.scalar;
+    let resolved = if scalar.tag.is_null()
+        && scalar.style == YamlPlainScalarStyle
+    {
+        let value = if scalar.length == 0 {
+            ""
+        } else {
+            // The reader rejects invalid UTF-8.
+            str::from_utf8_unchecked(slice::from_raw_parts(
+                scalar.value,
+                scalar.length as usize,
+            ))
+        };
+        if (*parser).merge_keys && value == "<<" {
+            MERGE_TAG
+        } else {
+            resolve_plain((*parser).schema, value)
+        }
+    } else {
+        STR_TAG
+    };
+    let tag =
+        yaml_malloc(resolved.len() as size_t + 1) as *mut yaml_char_t;
+    if !tag.is_null() {
+        ptr::copy_nonoverlapping(
+            resolved.as_ptr(),
+            tag,
+            resolved.len(),
+        );
+        *tag.add(resolved.len()) = b'\0';
+    }
+    tag
+}
+
 unsafe fn yaml_parser_load_sequence(
     parser: *mut YamlParserT,
     event: *mut YamlEventT,
@@ -491,10 +674,14 @@ unsafe fn yaml_parser_load_sequence(
                 .top
                 .c_offset_from((*(*parser).document).nodes.start)
                 as libc::c_int;
+            let expanded = (*parser).expanded_nodes;
+            (*parser).expanded_nodes =
+                (*parser).expanded_nodes.saturating_add(1);
             if yaml_parser_register_anchor(
                 parser,
                 index,
                 (*event).data.sequence_start.anchor,
+                0,
             )
             .fail
             {
@@ -506,7 +693,7 @@ unsafe fn yaml_parser_load_sequence(
             if STACK_LIMIT!(parser, *ctx).fail {
                 return FAIL;
             }
-            PUSH!(*ctx, index);
+            PUSH!(*ctx, LoaderNode { index, expanded });
             return OK;
         }
     }
@@ -524,7 +711,8 @@ unsafe fn yaml_parser_load_sequence_end(
         ((*ctx).top).c_offset_from((*ctx).start) as libc::c_long
             > 0_i64
     );
-    let index: libc::c_int = *(*ctx).top.wrapping_offset(-1_isize);
+    let index: libc::c_int =
+        (*(*ctx).top.wrapping_offset(-1_isize)).index;
     __assert!(
         (*((*(*parser).document).nodes.start)
             .wrapping_offset((index - 1) as isize))
@@ -536,7 +724,7 @@ unsafe fn yaml_parser_load_sequence_end(
         .start
         .wrapping_offset((index - 1) as isize))
     .end_mark = (*event).end_mark;
-    let _ = POP!(*ctx);
+    yaml_parser_finish_anchor(parser, POP!(*ctx));
     OK
 }
 
@@ -603,10 +791,14 @@ unsafe fn yaml_parser_load_mapping(
                 .top
                 .c_offset_from((*(*parser).document).nodes.start)
                 as libc::c_int;
+            let expanded = (*parser).expanded_nodes;
+            (*parser).expanded_nodes =
+                (*parser).expanded_nodes.saturating_add(1);
             if yaml_parser_register_anchor(
                 parser,
                 index,
                 (*event).data.mapping_start.anchor,
+                0,
             )
             .fail
             {
@@ -618,7 +810,7 @@ unsafe fn yaml_parser_load_mapping(
             if STACK_LIMIT!(parser, *ctx).fail {
                 return FAIL;
             }
-            PUSH!(*ctx, index);
+            PUSH!(*ctx, LoaderNode { index, expanded });
             return OK;
         }
     }
@@ -636,7 +828,8 @@ unsafe fn yaml_parser_load_mapping_end(
         ((*ctx).top).c_offset_from((*ctx).start) as libc::c_long
             > 0_i64
     );
-    let index: libc::c_int = *(*ctx).top.wrapping_offset(-1_isize);
+    let index: libc::c_int =
+        (*(*ctx).top.wrapping_offset(-1_isize)).index;
     __assert!(
         (*((*(*parser).document).nodes.start)
             .wrapping_offset((index - 1) as isize))
@@ -648,6 +841,489 @@ unsafe fn yaml_parser_load_mapping_end(
         .start
         .wrapping_offset((index - 1) as isize))
     .end_mark = (*event).end_mark;
-    let _ = POP!(*ctx);
+    if (*parser).unique_keys
+        && yaml_parser_check_unique_keys(parser, ctx).fail
+    {
+        return FAIL;
+    }
+    if (*parser).merge_keys && yaml_parser_merge_keys(parser, ctx).fail
+    {
+        return FAIL;
+    }
+    yaml_parser_finish_anchor(parser, POP!(*ctx));
+    OK
+}
+
+/// Replaces the `<<` pairs of the mapping that just ended with the pairs
+/// of the mappings they merge, leaving out the keys the mapping has of
+/// its own and those already merged from an earlier mapping.
+unsafe fn yaml_parser_merge_keys(
+    parser: *mut YamlParserT,
+    ctx: *mut LoaderCtx,
+) -> Success {
+    let document = (*parser).document;
+    let mapping = yaml_document_node(
+        document,
+        (*(*ctx).top.wrapping_offset(-1_isize)).index,
+    );
+    let own = stack_slice(
+        (*mapping).data.mapping.pairs.start,
+        (*mapping).data.mapping.pairs.top,
+    );
+    if !own
+        .iter()
+        .any(|pair| yaml_document_is_merge_key(document, pair))
+    {
+        return OK;
+    }
+    let mut pairs = YamlStackT::<YamlNodePairT> {
+        start: ptr::null_mut(),
+        end: ptr::null_mut(),
+        top: ptr::null_mut(),
+    };
+    STACK_INIT!(pairs, YamlNodePairT);
+    let mut keys = KeyIndex::new();
+    for pair in own {
+        if !yaml_document_is_merge_key(document, pair) {
+            keys.insert(parser, pair.key);
+        }
+    }
+    if yaml_parser_merge_pairs(
+        parser, ctx, mapping, own, &mut keys, &mut pairs,
+    )
+    .fail
+    {
+        yaml_free(pairs.start as *mut libc::c_void);
+        return FAIL;
+    }
+    yaml_free((*mapping).data.mapping.pairs.start as *mut libc::c_void);
+    (*mapping).data.mapping.pairs = pairs;
+    OK
+}
+
+/// Pushes the pairs of the mapping onto `pairs`, with its `<<` pairs
+/// replaced by the merged pairs whose keys are not in `keys` yet. Those
+/// are added to `keys` as they are merged.
+unsafe fn yaml_parser_merge_pairs(
+    parser: *mut YamlParserT,
+    ctx: *mut LoaderCtx,
+    mapping: *mut YamlNodeT,
+    own: &[YamlNodePairT],
+    keys: &mut KeyIndex,
+    pairs: *mut YamlStackT<YamlNodePairT>,
+) -> Success {
+    let document = (*parser).document;
+    for pair in own {
+        if !yaml_document_is_merge_key(document, pair) {
+            if STACK_LIMIT!(parser, *pairs).fail {
+                return FAIL;
+            }
+            PUSH!(*pairs, *pair);
+            continue;
+        }
+        let value = yaml_document_node(document, pair.value);
+        let sources = match (*value).type_ {
+            YamlMappingNode => slice::from_ref(&pair.value),
+            YamlSequenceNode => stack_slice(
+                (*value).data.sequence.items.start,
+                (*value).data.sequence.items.top,
+            ),
+            _ => {
+                return yaml_parser_set_merge_error(
+                    parser,
+                    mapping,
+                    b"expected a mapping or a sequence of mappings\0",
+                    value,
+                );
+            }
+        };
+        for &source in sources {
+            let source = yaml_document_node(document, source);
+            if (*source).type_ != YamlMappingNode {
+                return yaml_parser_set_merge_error(
+                    parser,
+                    mapping,
+                    b"expected a mapping or a sequence of mappings\0",
+                    source,
+                );
+            }
+            if stack_slice((*ctx).start, (*ctx).top).iter().any(
+                |node| {
+                    yaml_document_node(document, node.index) == source
+                },
+            ) {
+                return yaml_parser_set_merge_error(
+                    parser,
+                    mapping,
+                    b"found a mapping merged into itself\0",
+                    source,
+                );
+            }
+            for merged in stack_slice(
+                (*source).data.mapping.pairs.start,
+                (*source).data.mapping.pairs.top,
+            ) {
+                if keys.insert(parser, merged.key).is_some() {
+                    continue;
+                }
+                if STACK_LIMIT!(parser, *pairs).fail {
+                    return FAIL;
+                }
+                PUSH!(
+                    *pairs,
+                    YamlNodePairT {
+                        key: merged.key,
+                        value: merged.value,
+                        merged: true,
+                    }
+                );
+            }
+        }
+    }
     OK
 }
+
+unsafe fn yaml_parser_set_merge_error(
+    parser: *mut YamlParserT,
+    mapping: *mut YamlNodeT,
+    problem: &'static [u8],
+    node: *mut YamlNodeT,
+) -> Success {
+    yaml_parser_set_composer_error_context(
+        parser,
+        b"while merging into a mapping\0" as *const u8
+            as *const libc::c_char,
+        (*mapping).start_mark,
+        problem.as_ptr() as *const libc::c_char,
+        (*node).start_mark,
+    )
+}
+
+unsafe fn yaml_document_node(
+    document: *mut YamlDocumentT,
+    index: libc::c_int,
+) -> *mut YamlNodeT {
+    (*document)
+        .nodes
+        .start
+        .wrapping_offset((index - 1) as isize)
+}
+
+/// Is the key of the pair a `!!merge` scalar?
+unsafe fn yaml_document_is_merge_key(
+    document: *mut YamlDocumentT,
+    pair: &YamlNodePairT,
+) -> bool {
+    let key = yaml_document_node(document, pair.key);
+    (*key).type_ == YamlScalarNode
+        && strcmp(
+            (*key).tag as *mut libc::c_char,
+            b"tag:yaml.org,2002:merge\0" as *const u8
+                as *const libc::c_char,
+        ) == 0
+}
+
+/// A pair of nodes being compared, with the comparison it is part of.
+struct Comparison<'a> {
+    lhs: libc::c_int,
+    rhs: libc::c_int,
+    outer: Option<&'a Comparison<'a>>,
+}
+
+/// Fails if two keys of the mapping that just ended are equal.
+unsafe fn yaml_parser_check_unique_keys(
+    parser: *mut YamlParserT,
+    ctx: *mut LoaderCtx,
+) -> Success {
+    let document = (*parser).document;
+    let mapping = yaml_document_node(
+        document,
+        (*(*ctx).top.wrapping_offset(-1_isize)).index,
+    );
+    let pairs = stack_slice(
+        (*mapping).data.mapping.pairs.start,
+        (*mapping).data.mapping.pairs.top,
+    );
+    let mut keys = KeyIndex::new();
+    for pair in pairs {
+        if let Some(first) = keys.insert(parser, pair.key) {
+            return yaml_parser_set_composer_error_context(
+                parser,
+                b"first occurrence of the key\0" as *const u8
+                    as *const libc::c_char,
+                (*yaml_document_node(document, first)).start_mark,
+                b"found duplicate key\0" as *const u8
+                    as *const libc::c_char,
+                (*yaml_document_node(document, pair.key)).start_mark,
+            );
+        }
+    }
+    OK
+}
+
+/// The keys of a mapping, put in buckets by a form that equal keys
+/// share, so that a key is only compared in full with the keys of its
+/// own bucket.
+struct KeyIndex {
+    buckets: BTreeMap<Vec<u8>, Vec<libc::c_int>>,
+}
+
+impl KeyIndex {
+    fn new() -> KeyIndex {
+        KeyIndex {
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a key, unless it is equal to one added before, which is
+    /// returned instead.
+    unsafe fn insert(
+        &mut self,
+        parser: *mut YamlParserT,
+        key: libc::c_int,
+    ) -> Option<libc::c_int> {
+        let mut form = Vec::new();
+        yaml_parser_node_form(parser, key, 1, &mut form);
+        let bucket = self.buckets.entry(form).or_default();
+        let first = bucket.iter().copied().find(|&first| {
+            yaml_parser_nodes_equal(parser, first, key, None)
+        });
+        if first.is_none() {
+            bucket.push(key);
+        }
+        first
+    }
+}
+
+/// Writes out the form of a node that all nodes equal to it share.
+/// Scalars are written as the value they resolve to, once. Collections
+/// are written as their size, followed by the forms of their contents
+/// down to `depth` levels, which bounds the work on nested aliases.
+unsafe fn yaml_parser_node_form(
+    parser: *mut YamlParserT,
+    index: libc::c_int,
+    depth: u32,
+    form: &mut Vec<u8>,
+) {
+    let node = yaml_document_node((*parser).document, index);
+    form.push((*node).type_ as u8);
+    form.extend_from_slice(
+        CStr::from_ptr((*node).tag as *const libc::c_char)
+            .to_bytes_with_nul(),
+    );
+    let bytes = |form: &mut Vec<u8>, bytes: &[u8]| {
+        form.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+        form.extend_from_slice(bytes);
+    };
+    match (*node).type_ {
+        YamlScalarNode => {
+            match yaml_parser_resolve_scalar(parser, node) {
+                (_, Ok(resolved)) => match resolved.value {
+                    Value::Null => form.push(b'n'),
+                    Value::Bool(value) => {
+                        form.extend_from_slice(&[b'b', value as u8]);
+                    }
+                    Value::Int(value) => {
+                        form.push(b'i');
+                        form.extend_from_slice(&value.to_le_bytes());
+                    }
+                    Value::BigInt(value) => {
+                        form.push(b'I');
+                        bytes(form, value.to_string().as_bytes());
+                    }
+                    // A NaN is only equal to itself.
+                    Value::Float(value) if value.is_nan() => {
+                        form.push(b'N');
+                        form.extend_from_slice(&index.to_le_bytes());
+                    }
+                    Value::Float(value) => {
+                        // Zero is equal to negative zero.
+                        let value =
+                            if value == 0.0 { 0.0 } else { value };
+                        form.push(b'f');
+                        form.extend_from_slice(&value.to_le_bytes());
+                    }
+                    Value::Str(value) => {
+                        form.push(b's');
+                        bytes(form, value.as_bytes());
+                    }
+                },
+                (value, Err(_)) => {
+                    form.push(b'r');
+                    bytes(form, value.as_bytes());
+                }
+            }
+        }
+        YamlSequenceNode => {
+            let items = stack_slice(
+                (*node).data.sequence.items.start,
+                (*node).data.sequence.items.top,
+            );
+            form.extend_from_slice(&(items.len() as u64).to_le_bytes());
+            if depth != 0 {
+                for &item in items {
+                    yaml_parser_node_form(
+                        parser,
+                        item,
+                        depth - 1,
+                        form,
+                    );
+                }
+            }
+        }
+        YamlMappingNode => {
+            let pairs = stack_slice(
+                (*node).data.mapping.pairs.start,
+                (*node).data.mapping.pairs.top,
+            );
+            form.extend_from_slice(&(pairs.len() as u64).to_le_bytes());
+            if depth != 0 {
+                // Equal mappings may list their pairs in any order.
+                let mut forms: Vec<Vec<u8>> = pairs
+                    .iter()
+                    .map(|pair| {
+                        let mut form = Vec::new();
+                        yaml_parser_node_form(
+                            parser,
+                            pair.key,
+                            depth - 1,
+                            &mut form,
+                        );
+                        yaml_parser_node_form(
+                            parser,
+                            pair.value,
+                            depth - 1,
+                            &mut form,
+                        );
+                        form
+                    })
+                    .collect();
+                forms.sort_unstable();
+                for pair in forms {
+                    bytes(form, &pair);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Are two nodes equal? Scalars are compared by the value they resolve
+/// to under the parser's schema, and collections by their contents.
+/// Collections that contain themselves through an alias are taken to be
+/// equal once the comparison comes back to the same pair of nodes.
+unsafe fn yaml_parser_nodes_equal(
+    parser: *mut YamlParserT,
+    lhs: libc::c_int,
+    rhs: libc::c_int,
+    outer: Option<&Comparison<'_>>,
+) -> bool {
+    if lhs == rhs {
+        return true;
+    }
+    let mut comparison = outer;
+    while let Some(pending) = comparison {
+        if pending.lhs == lhs && pending.rhs == rhs {
+            return true;
+        }
+        comparison = pending.outer;
+    }
+    let comparison = Comparison { lhs, rhs, outer };
+    let outer = Some(&comparison);
+    let document = (*parser).document;
+    let lhs = yaml_document_node(document, lhs);
+    let rhs = yaml_document_node(document, rhs);
+    if (*lhs).type_ != (*rhs).type_
+        || strcmp(
+            (*lhs).tag as *mut libc::c_char,
+            (*rhs).tag as *mut libc::c_char,
+        ) != 0
+    {
+        return false;
+    }
+    match (*lhs).type_ {
+        YamlScalarNode => yaml_parser_scalars_equal(parser, lhs, rhs),
+        YamlSequenceNode => {
+            let lhs = stack_slice(
+                (*lhs).data.sequence.items.start,
+                (*lhs).data.sequence.items.top,
+            );
+            let rhs = stack_slice(
+                (*rhs).data.sequence.items.start,
+                (*rhs).data.sequence.items.top,
+            );
+            lhs.len() == rhs.len()
+                && lhs.iter().zip(rhs).all(|(&lhs, &rhs)| {
+                    yaml_parser_nodes_equal(parser, lhs, rhs, outer)
+                })
+        }
+        YamlMappingNode => {
+            let lhs = stack_slice(
+                (*lhs).data.mapping.pairs.start,
+                (*lhs).data.mapping.pairs.top,
+            );
+            let rhs = stack_slice(
+                (*rhs).data.mapping.pairs.start,
+                (*rhs).data.mapping.pairs.top,
+            );
+            lhs.len() == rhs.len()
+                && lhs.iter().all(|lhs| {
+                    rhs.iter().any(|rhs| {
+                        yaml_parser_nodes_equal(
+                            parser, lhs.key, rhs.key, outer,
+                        ) && yaml_parser_nodes_equal(
+                            parser, lhs.value, rhs.value, outer,
+                        )
+                    })
+                })
+        }
+        _ => false,
+    }
+}
+
+/// Are two scalars with the same tag equal? Values that do not resolve
+/// are compared as they are.
+unsafe fn yaml_parser_scalars_equal(
+    parser: *mut YamlParserT,
+    lhs: *mut YamlNodeT,
+    rhs: *mut YamlNodeT,
+) -> bool {
+    match (
+        yaml_parser_resolve_scalar(parser, lhs),
+        yaml_parser_resolve_scalar(parser, rhs),
+    ) {
+        ((_, Ok(lhs)), (_, Ok(rhs))) => lhs.value == rhs.value,
+        ((lhs, _), (rhs, _)) => lhs == rhs,
+    }
+}
+
+/// Resolves a scalar node under the parser's schema, returning its
+/// value as it is written along with the result.
+unsafe fn yaml_parser_resolve_scalar<'a>(
+    parser: *mut YamlParserT,
+    node: *mut YamlNodeT,
+) -> (&'a str, Result<Resolved<'a>, Error>) {
+    // Tags and values are checked to be UTF-8 by the reader and the
+    // scanner, or by yaml_document_add_scalar().
+    let tag = str::from_utf8_unchecked(
+        CStr::from_ptr((*node).tag as *const libc::c_char).to_bytes(),
+    );
+    let scalar = &(*node).data.scalar;
+    let value = if scalar.length == 0 {
+        ""
+    } else {
+        str::from_utf8_unchecked(slice::from_raw_parts(
+            scalar.value,
+            scalar.length as usize,
+        ))
+    };
+    (
+        value,
+        resolve((*parser).schema, Some(tag), value, scalar.style),
+    )
+}
+
+/// Views the used part of a stack as a slice.
+unsafe fn stack_slice<'a, T>(start: *mut T, top: *mut T) -> &'a [T] {
+    slice::from_raw_parts(start, top.c_offset_from(start) as usize)
+}
@@ -322,6 +322,22 @@ macro_rules! IS_BLANKZ {
     };
 }
 
+macro_rules! IS_FLOW_INDICATOR_AT {
+    ($string:expr, $offset:expr) => {
+        CHECK_AT!($string, b',', $offset)
+            || CHECK_AT!($string, b'[', $offset)
+            || CHECK_AT!($string, b']', $offset)
+            || CHECK_AT!($string, b'{', $offset)
+            || CHECK_AT!($string, b'}', $offset)
+    };
+}
+
+macro_rules! IS_FLOW_INDICATOR {
+    ($string:expr) => {
+        IS_FLOW_INDICATOR_AT!($string, 0)
+    };
+}
+
 macro_rules! WIDTH_AT {
     ($string:expr, $offset:expr) => {
         if *$string.pointer.wrapping_offset($offset) & 0x80 == 0x00 {
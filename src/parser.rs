@@ -1,22 +1,25 @@
 use crate::api::{
-    yaml_free, yaml_malloc, yaml_stack_extend, yaml_strdup,
+    yaml_event_delete, yaml_free, yaml_malloc, yaml_stack_extend,
+    yaml_strdup, yaml_token_delete,
 };
 use crate::externs::{memcpy, memset, strcmp, strlen};
 use crate::ops::ForceAdd as _;
-use crate::scanner::yaml_parser_fetch_more_tokens;
+use crate::scanner::{
+    yaml_parser_fetch_more_tokens, yaml_parser_fetch_trailing_comment,
+};
 use crate::success::{Success, FAIL, OK};
 use crate::yaml::{size_t, yaml_char_t};
 use crate::{
     libc, YamlAliasEvent, YamlAliasToken, YamlAnchorToken,
     YamlBlockEndToken, YamlBlockEntryToken, YamlBlockMappingStartToken,
     YamlBlockMappingStyle, YamlBlockSequenceStartToken,
-    YamlBlockSequenceStyle, YamlDocumentEndEvent, YamlDocumentEndToken,
-    YamlDocumentStartEvent, YamlDocumentStartToken, YamlEventT,
-    YamlFlowEntryToken, YamlFlowMappingEndToken,
-    YamlFlowMappingStartToken, YamlFlowMappingStyle,
-    YamlFlowSequenceEndToken, YamlFlowSequenceStartToken,
-    YamlFlowSequenceStyle, YamlKeyToken, YamlMappingEndEvent,
-    YamlMappingStartEvent, YamlMarkT, YamlNoError,
+    YamlBlockSequenceStyle, YamlCommentToken, YamlDocumentComment,
+    YamlDocumentEndEvent, YamlDocumentEndToken, YamlDocumentStartEvent,
+    YamlDocumentStartToken, YamlEventT, YamlFlowEntryToken,
+    YamlFlowMappingEndToken, YamlFlowMappingStartToken,
+    YamlFlowMappingStyle, YamlFlowSequenceEndToken,
+    YamlFlowSequenceStartToken, YamlFlowSequenceStyle, YamlKeyToken,
+    YamlMappingEndEvent, YamlMappingStartEvent, YamlMarkT, YamlNoError,
     YamlParseBlockMappingFirstKeyState, YamlParseBlockMappingKeyState,
     YamlParseBlockMappingValueState,
     YamlParseBlockNodeOrIndentlessSequenceState,
@@ -38,20 +41,68 @@ use crate::{
     YamlScalarEvent, YamlScalarToken, YamlSequenceEndEvent,
     YamlSequenceStartEvent, YamlStreamEndEvent, YamlStreamEndToken,
     YamlStreamStartEvent, YamlStreamStartToken, YamlTagDirectiveT,
-    YamlTagDirectiveToken, YamlTagToken, YamlTokenT, YamlValueToken,
-    YamlVersionDirectiveT, YamlVersionDirectiveToken,
+    YamlTagDirectiveToken, YamlTagToken, YamlTokenT,
+    YamlTrailingComment, YamlValueToken, YamlVersionDirectiveT,
+    YamlVersionDirectiveToken,
 };
-use core::mem::size_of;
+use core::mem::{size_of, MaybeUninit};
 use core::ptr::{self, addr_of_mut};
 
 unsafe fn peek_token(parser: *mut YamlParserT) -> *mut YamlTokenT {
-    if (*parser).token_available
-        || yaml_parser_fetch_more_tokens(parser).ok
-    {
-        (*parser).tokens.head
+    loop {
+        if !(*parser).token_available
+            && yaml_parser_fetch_more_tokens(parser).fail
+        {
+            return ptr::null_mut::<YamlTokenT>();
+        }
+        let token = (*parser).tokens.head;
+        if (*token).type_ != YamlCommentToken {
+            return token;
+        }
+        // Comments take no part in the grammar; they wait for the next
+        // node event instead.
+        let comments =
+            if (*token).data.comment.type_ == YamlTrailingComment {
+                addr_of_mut!((*parser).comment_inline)
+            } else {
+                addr_of_mut!((*parser).comment_before)
+            };
+        yaml_parser_append_comment(comments, token);
+        yaml_token_delete(token);
+        skip_token(parser);
+    }
+}
+
+unsafe fn yaml_parser_append_comment(
+    comments: *mut *mut yaml_char_t,
+    token: *mut YamlTokenT,
+) {
+    let value: *mut yaml_char_t = (*token).data.comment.value;
+    let length: size_t = (*token).data.comment.length;
+    // The comments so far, each line ended by a '\n'.
+    let offset: size_t = if (*comments).is_null() {
+        0_u64
     } else {
-        ptr::null_mut::<YamlTokenT>()
+        strlen(*comments as *mut libc::c_char).force_add(1_u64)
+    };
+    let string = yaml_malloc(offset.force_add(length).force_add(1_u64))
+        as *mut yaml_char_t;
+    if !(*comments).is_null() {
+        memcpy(
+            string as *mut libc::c_void,
+            *comments as *const libc::c_void,
+            offset - 1,
+        );
+        *string.wrapping_offset(offset as isize - 1) = b'\n';
+        yaml_free(*comments as *mut libc::c_void);
     }
+    memcpy(
+        string.wrapping_offset(offset as isize) as *mut libc::c_void,
+        value as *const libc::c_void,
+        length,
+    );
+    *string.wrapping_offset(offset.force_add(length) as isize) = b'\0';
+    *comments = string;
 }
 
 unsafe fn skip_token(parser: *mut YamlParserT) {
@@ -102,7 +153,135 @@ pub unsafe fn yaml_parser_parse(
     {
         return OK;
     }
-    yaml_parser_state_machine(parser, event)
+    if yaml_parser_state_machine(parser, event).fail {
+        return FAIL;
+    }
+    if yaml_parser_track_depth(parser, event).fail {
+        yaml_event_delete(event);
+        return FAIL;
+    }
+    if (*parser).comments {
+        return yaml_parser_attach_comments(parser, event);
+    }
+    OK
+}
+
+/// Counts the collections the event opens or closes, and fails once
+/// they are nested deeper than the parser allows.
+unsafe fn yaml_parser_track_depth(
+    parser: *mut YamlParserT,
+    event: *mut YamlEventT,
+) -> Success {
+    match (*event).type_ {
+        YamlSequenceStartEvent | YamlMappingStartEvent => {
+            if (*parser).depth >= (*parser).max_depth {
+                yaml_parser_set_parser_error(
+                    parser,
+                    b"recursion limit exceeded\0" as *const u8
+                        as *const libc::c_char,
+                    (*event).start_mark,
+                );
+                return FAIL;
+            }
+            (*parser).depth += 1;
+        }
+        YamlSequenceEndEvent | YamlMappingEndEvent => {
+            (*parser).depth -= 1;
+        }
+        _ => {}
+    }
+    OK
+}
+
+/// Hands the comments collected so far to the event, together with the
+/// comments that follow it on its line and the detached ones below it.
+unsafe fn yaml_parser_attach_comments(
+    parser: *mut YamlParserT,
+    event: *mut YamlEventT,
+) -> Success {
+    let before = match (*event).type_ {
+        // These leave the comments to the next event.
+        YamlStreamStartEvent => return OK,
+        YamlDocumentStartEvent
+            if (*event).data.document_start.implicit =>
+        {
+            return OK
+        }
+        // The comments before a collection end belong to what follows.
+        YamlSequenceEndEvent | YamlMappingEndEvent => false,
+        // An empty scalar stands for a missing node and has no lines of
+        // its own.
+        YamlScalarEvent => {
+            (*event).start_mark.index != (*event).end_mark.index
+        }
+        _ => true,
+    };
+    if before {
+        (*event).comments.before = (*parser).comment_before;
+        (*parser).comment_before = ptr::null_mut::<yaml_char_t>();
+    }
+    (*event).comments.inline = (*parser).comment_inline;
+    (*parser).comment_inline = ptr::null_mut::<yaml_char_t>();
+    if (*event).type_ == YamlStreamEndEvent {
+        return OK;
+    }
+    loop {
+        if !(*parser).token_available
+            && yaml_parser_fetch_more_tokens(parser).fail
+        {
+            yaml_event_delete(event);
+            return FAIL;
+        }
+        let token = (*parser).tokens.head;
+        if (*token).type_ != YamlCommentToken {
+            // In a flow collection, the comment on the line of a node
+            // follows the ',' after it, and the comment of the
+            // collection itself follows its '[' or '{'.
+            if ((*token).type_ == YamlFlowEntryToken
+                || (*token).type_ == YamlFlowSequenceStartToken
+                || (*token).type_ == YamlFlowMappingStartToken)
+                && (*event).comments.inline.is_null()
+            {
+                let mut comment = MaybeUninit::<YamlTokenT>::uninit();
+                let comment = comment.as_mut_ptr();
+                if yaml_parser_fetch_trailing_comment(parser, comment)
+                    .fail
+                {
+                    yaml_event_delete(event);
+                    return FAIL;
+                }
+                if (*comment).type_ == YamlCommentToken {
+                    yaml_parser_append_comment(
+                        addr_of_mut!((*event).comments.inline),
+                        comment,
+                    );
+                    yaml_token_delete(comment);
+                }
+            }
+            return OK;
+        }
+        let comments = match (*token).data.comment.type_ {
+            YamlTrailingComment
+                if (*event).comments.inline.is_null() =>
+            {
+                addr_of_mut!((*event).comments.inline)
+            }
+            YamlDocumentComment => {
+                // The comments held back after a collection end came
+                // before these.
+                if (*event).comments.after.is_null() {
+                    (*event).comments.after = (*parser).comment_before;
+                    (*parser).comment_before =
+                        ptr::null_mut::<yaml_char_t>();
+                }
+                addr_of_mut!((*event).comments.after)
+            }
+            _ => return OK,
+        };
+        yaml_parser_append_comment(comments, token);
+        yaml_token_delete(token);
+        skip_token(parser);
+    }
 }
 
 unsafe fn yaml_parser_set_parser_error(
@@ -268,7 +447,7 @@ unsafe fn yaml_parser_parse_document_start(
     if token.is_null() {
         return FAIL;
     }
-    if !implicit {
+    if !implicit || (*parser).strict {
         while (*token).type_ == YamlDocumentEndToken {
             skip_token(parser);
             token = peek_token(parser);
@@ -438,13 +617,30 @@ unsafe fn yaml_parser_parse_document_end(
         end_mark = (*token).end_mark;
         skip_token(parser);
         implicit = false;
+    } else if (*parser).strict
+        && ((*token).type_ == YamlVersionDirectiveToken
+            || (*token).type_ == YamlTagDirectiveToken)
+    {
+        // YAML 1.2 only allows directives after a document end marker.
+        yaml_parser_set_parser_error(
+            parser,
+            b"did not find expected <document end>\0" as *const u8
+                as *const libc::c_char,
+            (*token).start_mark,
+        );
+        return FAIL;
     }
     while !STACK_EMPTY!((*parser).tag_directives) {
         let tag_directive = POP!((*parser).tag_directives);
         yaml_free(tag_directive.handle as *mut libc::c_void);
         yaml_free(tag_directive.prefix as *mut libc::c_void);
     }
-    (*parser).state = YamlParseDocumentStartState;
+    // In YAML 1.2, a bare document may follow a document end marker.
+    (*parser).state = if !implicit && (*parser).strict {
+        YamlParseImplicitDocumentStartState
+    } else {
+        YamlParseDocumentStartState
+    };
     memset(
         event as *mut libc::c_void,
         0,
@@ -477,6 +673,8 @@ unsafe fn yaml_parser_parse_node(
         index: 0,
         line: 0,
         column: 0,
+        offset: 0,
+        utf16_column: 0,
     };
     let implicit;
     token = peek_token(parser);
@@ -703,6 +901,8 @@ unsafe fn yaml_parser_parse_node(
                         quoted_implicit;
                     (*event).data.scalar.style =
                         (*token).data.scalar.style;
+                    (*event).data.scalar.borrowed =
+                        (*token).data.scalar.borrowed;
                     skip_token(parser);
                     return OK;
                 } else if (*token).type_ == YamlFlowSequenceStartToken {
@@ -1001,6 +1201,10 @@ unsafe fn yaml_parser_parse_block_mapping_key(
         (*event).end_mark = (*token).end_mark;
         skip_token(parser);
         OK
+    } else if (*token).type_ == YamlValueToken && (*parser).strict {
+        // YAML 1.2 allows a value with an empty key.
+        (*parser).state = YamlParseBlockMappingValueState;
+        yaml_parser_process_empty_scalar(event, (*token).start_mark)
     } else {
         yaml_parser_set_parser_error_context(
             parser,
@@ -1103,6 +1307,20 @@ unsafe fn yaml_parser_parse_flow_sequence_entry(
             (*event).data.mapping_start.style = YamlFlowMappingStyle;
             skip_token(parser);
             return OK;
+        } else if (*token).type_ == YamlValueToken && (*parser).strict {
+            // YAML 1.2 allows a single pair with an empty key.
+            (*parser).state = YamlParseFlowSequenceEntryMappingKeyState;
+            memset(
+                event as *mut libc::c_void,
+                0,
+                size_of::<YamlEventT>() as libc::c_ulong,
+            );
+            (*event).type_ = YamlMappingStartEvent;
+            (*event).start_mark = (*token).start_mark;
+            (*event).end_mark = (*token).start_mark;
+            (*event).data.mapping_start.implicit = true;
+            (*event).data.mapping_start.style = YamlFlowMappingStyle;
+            return OK;
         } else if (*token).type_ != YamlFlowSequenceEndToken {
             PUSH!((*parser).states, YamlParseFlowSequenceEntryState);
             return yaml_parser_parse_node(parser, event, false, false);
@@ -1139,6 +1357,10 @@ unsafe fn yaml_parser_parse_flow_sequence_entry_mapping_key(
             YamlParseFlowSequenceEntryMappingValueState
         );
         yaml_parser_parse_node(parser, event, false, false)
+    } else if (*parser).strict {
+        // YAML 1.2 leaves the indicator that ends an empty key in place.
+        (*parser).state = YamlParseFlowSequenceEntryMappingValueState;
+        yaml_parser_process_empty_scalar(event, (*token).start_mark)
     } else {
         let mark: YamlMarkT = (*token).end_mark;
         skip_token(parser);
@@ -1253,6 +1475,13 @@ unsafe fn yaml_parser_parse_flow_mapping_key(
                     (*token).start_mark,
                 );
             }
+        } else if (*token).type_ == YamlValueToken && (*parser).strict {
+            // YAML 1.2 allows a value with an empty key.
+            (*parser).state = YamlParseFlowMappingValueState;
+            return yaml_parser_process_empty_scalar(
+                event,
+                (*token).start_mark,
+            );
         } else if (*token).type_ != YamlFlowMappingEndToken {
             PUSH!(
                 (*parser).states,
@@ -1401,6 +1630,8 @@ unsafe fn yaml_parser_process_directives(
                 } else if (*token).data.version_directive.major != 1
                     || (*token).data.version_directive.minor != 1
                         && (*token).data.version_directive.minor != 2
+                        // YAML 1.2 processes later minor versions too.
+                        && !(*parser).strict
                 {
                     yaml_parser_set_parser_error(
                         parser,
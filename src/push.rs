@@ -0,0 +1,364 @@
+//! Suspending the scanner between the chunks of a pushed input.
+//!
+//! The input of a push parser is fed to it in chunks. Its read handler
+//! fails, marking the input as starved, once the chunks fed so far run
+//! out. The scanner is then rolled back to the state it was in before
+//! the token it was fetching, and fetches it again once the input fed
+//! for it has doubled, or is finished.
+//!
+//! The parser cannot be rolled back, as it takes the tokens it skips
+//! apart. It only runs once the scanner has fetched enough tokens ahead
+//! for it to produce an event without scanning any further.
+
+use crate::api::yaml_token_delete;
+use crate::json::JsonExpect;
+use crate::libc;
+use crate::scanner::{
+    yaml_parser_fetch_token, yaml_parser_simple_key_is_stale,
+};
+use crate::yaml::size_t;
+use crate::{
+    YamlCommentToken, YamlDocumentEndToken, YamlEncodingT,
+    YamlErrorTypeT, YamlMarkT, YamlParserT, YamlSimpleKeyT,
+    YamlStreamEndToken, YamlTagDirectiveToken, YamlTokenT,
+    YamlVersionDirectiveToken,
+};
+use alloc::vec::Vec;
+use core::{ptr, slice};
+
+/// The number of tokens kept ahead of the parser, leaving out comments,
+/// directives and DOCUMENT-END. Producing an event takes at most five.
+const LOOKAHEAD: usize = 8;
+
+/// The input fed to a push parser, along with the position its read
+/// handler has reached.
+#[derive(Debug, Default)]
+pub(crate) struct PushInput {
+    /// The bytes fed from `base` on.
+    data: Vec<u8>,
+    /// The input offset of the first byte of `data`.
+    base: u64,
+    /// The input offset of the next byte handed to the reader.
+    position: u64,
+    /// Has the whole input been fed?
+    finished: bool,
+    /// Did the reader run out of the bytes fed so far?
+    starved: bool,
+    /// The input offset the bytes fed have to reach before the scanner
+    /// starts over on the token it starved on.
+    wanted: u64,
+}
+
+impl PushInput {
+    pub(crate) fn feed(&mut self, bytes: &[u8]) {
+        assert!(!self.finished, "input fed after finish");
+        self.data.extend_from_slice(bytes);
+        if self.base + self.data.len() as u64 >= self.wanted {
+            self.starved = false;
+        }
+    }
+
+    pub(crate) fn finish(&mut self) {
+        self.finished = true;
+        self.starved = false;
+    }
+
+    /// Has the reader starved, with not enough fed since?
+    pub(crate) fn is_starved(&self) -> bool {
+        self.starved
+    }
+
+    /// Drops the bytes before `offset`, which the scanner is past.
+    fn release(&mut self, offset: u64) {
+        let consumed = (offset - self.base) as usize;
+        // Compacting only once most of the bytes are consumed keeps the
+        // cost of feeding linear.
+        if consumed > self.data.len() / 2 {
+            self.data.drain(..consumed);
+            self.base = offset;
+        }
+    }
+
+    /// Makes the reader start over from `offset`.
+    fn rewind(&mut self, offset: u64) {
+        self.position = offset;
+        if self.starved {
+            // Scanning a token again only once the bytes fed for it have
+            // doubled keeps the cost of a long token fed in small chunks
+            // linear.
+            let end = self.base + self.data.len() as u64;
+            self.wanted = end + (end - offset);
+        }
+    }
+}
+
+/// A `YamlReadHandlerT` serving the bytes of a [`PushInput`].
+///
+/// Until the input is finished, running out of bytes fails the read and
+/// marks the input as starved.
+pub(crate) unsafe fn push_read_handler(
+    data: *mut libc::c_void,
+    buffer: *mut libc::c_uchar,
+    size: size_t,
+    size_read: *mut size_t,
+) -> libc::c_int {
+    let input = &mut *data.cast::<PushInput>();
+    let start = (input.position - input.base) as usize;
+    let available = &input.data[start..];
+    if available.is_empty() && !input.finished {
+        input.starved = true;
+        return 0;
+    }
+    let length = available.len().min(size as usize);
+    ptr::copy_nonoverlapping(available.as_ptr(), buffer, length);
+    input.position += length as u64;
+    *size_read = length as size_t;
+    1
+}
+
+/// The outcome of fetching tokens ahead of the parser.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ScanAhead {
+    /// The parser can produce its next event.
+    Ready,
+    /// The input ran out before enough tokens were fetched.
+    NeedMoreInput,
+    /// The scanner failed. It is rolled back, for the parser to run into
+    /// the same error once it gets there.
+    Failed,
+}
+
+/// The scanner state saved before fetching a token.
+#[derive(Default)]
+pub(crate) struct ScannerState {
+    tokens: Vec<YamlTokenT>,
+    pending_comments: Vec<YamlTokenT>,
+    indents: Vec<libc::c_int>,
+    simple_keys: Vec<YamlSimpleKeyT>,
+    fields: Option<Fields>,
+    /// The number of the first token the parser needs the tokens of the
+    /// last fetch for.
+    last_fetch_needed_from: size_t,
+}
+
+/// The plain fields of the parser that the scanner updates.
+#[derive(Copy, Clone)]
+struct Fields {
+    error: YamlErrorTypeT,
+    problem: *const libc::c_char,
+    problem_offset: size_t,
+    problem_value: libc::c_int,
+    problem_mark: YamlMarkT,
+    context: *const libc::c_char,
+    context_mark: YamlMarkT,
+    encoding: YamlEncodingT,
+    mark: YamlMarkT,
+    stream_start_produced: bool,
+    stream_end_produced: bool,
+    flow_level: libc::c_int,
+    json_expect: JsonExpect,
+    token_available: bool,
+    token_end_mark: YamlMarkT,
+    indent: libc::c_int,
+    simple_key_allowed: bool,
+    not_simple_keys: libc::c_int,
+}
+
+impl ScannerState {
+    /// Fetches tokens until the parser can produce its next event, or
+    /// the input or the scanner fails.
+    pub(crate) unsafe fn scan_ahead(
+        &mut self,
+        parser: *mut YamlParserT,
+        input: *mut PushInput,
+    ) -> ScanAhead {
+        while !yaml_parser_tokens_ready(parser) {
+            self.save(parser);
+            let needed_from = yaml_parser_fetch_needed_from(parser);
+            if yaml_parser_fetch_token(parser).ok {
+                (*input).release((*parser).mark.offset);
+                self.last_fetch_needed_from = needed_from;
+                continue;
+            }
+            let starved = (*input).starved;
+            self.restore(parser);
+            (*input).rewind((*parser).offset);
+            if starved {
+                return ScanAhead::NeedMoreInput;
+            }
+            // Fetching the tokens as the parser needs them, the scanner
+            // would have failed only once the parser got this far.
+            (*parser).stale_keys_from = self.last_fetch_needed_from;
+            return ScanAhead::Failed;
+        }
+        ScanAhead::Ready
+    }
+
+    unsafe fn save(&mut self, parser: *mut YamlParserT) {
+        let queue = (*parser).tokens;
+        save(&mut self.tokens, queue.head, queue.tail);
+        let queue = (*parser).pending_comments;
+        save(&mut self.pending_comments, queue.head, queue.tail);
+        let stack = (*parser).indents;
+        save(&mut self.indents, stack.start, stack.top);
+        let stack = (*parser).simple_keys;
+        save(&mut self.simple_keys, stack.start, stack.top);
+        self.fields = Some(Fields {
+            error: (*parser).error,
+            problem: (*parser).problem,
+            problem_offset: (*parser).problem_offset,
+            problem_value: (*parser).problem_value,
+            problem_mark: (*parser).problem_mark,
+            context: (*parser).context,
+            context_mark: (*parser).context_mark,
+            encoding: (*parser).encoding,
+            mark: (*parser).mark,
+            stream_start_produced: (*parser).stream_start_produced,
+            stream_end_produced: (*parser).stream_end_produced,
+            flow_level: (*parser).flow_level,
+            json_expect: (*parser).json_expect,
+            token_available: (*parser).token_available,
+            token_end_mark: (*parser).token_end_mark,
+            indent: (*parser).indent,
+            simple_key_allowed: (*parser).simple_key_allowed,
+            not_simple_keys: (*parser).not_simple_keys,
+        });
+    }
+
+    /// Puts the scanner back in the saved state, deleting the tokens
+    /// fetched since, and empties the input buffers for the reader to
+    /// start over from the saved position.
+    unsafe fn restore(&self, parser: *mut YamlParserT) {
+        let fields = self.fields.expect("saved scanner state");
+        // The tokens fetched since start at the saved mark or after it,
+        // except for the KEY and BLOCK-MAPPING-START tokens inserted
+        // before their simple keys, which own no data.
+        for queue in [(*parser).tokens, (*parser).pending_comments] {
+            let mut token = queue.head;
+            while token != queue.tail {
+                if (*token).start_mark.index >= fields.mark.index {
+                    yaml_token_delete(token);
+                }
+                token = token.add(1);
+            }
+        }
+        // The queues and stacks only ever grow, so the saved elements
+        // fit back in.
+        let queue = &mut (*parser).tokens;
+        queue.head = queue.start;
+        queue.tail = restore(&self.tokens, queue.start);
+        let queue = &mut (*parser).pending_comments;
+        queue.head = queue.start;
+        queue.tail = restore(&self.pending_comments, queue.start);
+        let stack = &mut (*parser).indents;
+        stack.top = restore(&self.indents, stack.start);
+        let stack = &mut (*parser).simple_keys;
+        stack.top = restore(&self.simple_keys, stack.start);
+        (*parser).error = fields.error;
+        (*parser).problem = fields.problem;
+        (*parser).problem_offset = fields.problem_offset;
+        (*parser).problem_value = fields.problem_value;
+        (*parser).problem_mark = fields.problem_mark;
+        (*parser).context = fields.context;
+        (*parser).context_mark = fields.context_mark;
+        (*parser).encoding = fields.encoding;
+        (*parser).mark = fields.mark;
+        (*parser).stream_start_produced = fields.stream_start_produced;
+        (*parser).stream_end_produced = fields.stream_end_produced;
+        (*parser).flow_level = fields.flow_level;
+        (*parser).json_expect = fields.json_expect;
+        (*parser).token_available = fields.token_available;
+        (*parser).token_end_mark = fields.token_end_mark;
+        (*parser).indent = fields.indent;
+        (*parser).simple_key_allowed = fields.simple_key_allowed;
+        (*parser).not_simple_keys = fields.not_simple_keys;
+
+        let raw_buffer = &mut (*parser).raw_buffer;
+        raw_buffer.pointer = raw_buffer.start;
+        raw_buffer.last = raw_buffer.start;
+        let buffer = &mut (*parser).buffer;
+        buffer.pointer = buffer.start;
+        buffer.last = buffer.start;
+        (*parser).unread = 0;
+        (*parser).eof = false;
+        (*parser).offset = fields.mark.offset;
+    }
+}
+
+unsafe fn save<T: Copy>(
+    saved: &mut Vec<T>,
+    start: *mut T,
+    end: *mut T,
+) {
+    saved.clear();
+    saved.extend_from_slice(slice::from_raw_parts(
+        start,
+        end.offset_from(start) as usize,
+    ));
+}
+
+unsafe fn restore<T: Copy>(saved: &[T], start: *mut T) -> *mut T {
+    ptr::copy_nonoverlapping(saved.as_ptr(), start, saved.len());
+    start.add(saved.len())
+}
+
+/// The number of the first token the parser needs the next fetched
+/// tokens for: the first token of a possible simple key, which they may
+/// complete, or else the first of them.
+unsafe fn yaml_parser_fetch_needed_from(
+    parser: *mut YamlParserT,
+) -> size_t {
+    let queue = (*parser).tokens;
+    let mut number = (*parser)
+        .tokens_parsed
+        .wrapping_add(queue.tail.offset_from(queue.head) as size_t);
+    let mut simple_key = (*parser)
+        .simple_keys
+        .start
+        .add((*parser).not_simple_keys as usize);
+    while simple_key != (*parser).simple_keys.top {
+        if (*simple_key).possible
+            && !yaml_parser_simple_key_is_stale(parser, simple_key)
+        {
+            number = number.min((*simple_key).token_number);
+        }
+        simple_key = simple_key.add(1);
+    }
+    number
+}
+
+/// Can the parser produce its next event from the queued tokens alone?
+unsafe fn yaml_parser_tokens_ready(parser: *mut YamlParserT) -> bool {
+    let queue = (*parser).tokens;
+    let length = queue.tail.offset_from(queue.head) as size_t;
+    // A possible simple key may still turn into a KEY token inserted
+    // before the one it starts with.
+    let mut simple_key = (*parser)
+        .simple_keys
+        .start
+        .add((*parser).not_simple_keys as usize);
+    while simple_key != (*parser).simple_keys.top {
+        if (*simple_key).possible
+            && (*simple_key).token_number
+                < (*parser).tokens_parsed.wrapping_add(length)
+        {
+            return false;
+        }
+        simple_key = simple_key.add(1);
+    }
+    let mut count = 0;
+    let mut token = queue.head;
+    while token != queue.tail {
+        match (*token).type_ {
+            YamlStreamEndToken => return true,
+            // The parser goes through any number of these in a row.
+            YamlCommentToken
+            | YamlVersionDirectiveToken
+            | YamlTagDirectiveToken
+            | YamlDocumentEndToken => {}
+            _ => count += 1,
+        }
+        token = token.add(1);
+    }
+    count >= LOOKAHEAD
+}
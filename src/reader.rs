@@ -1,12 +1,14 @@
-use crate::externs::{memcmp, memmove};
+use crate::externs::memmove;
 use crate::ops::ForceAdd as _;
 use crate::success::{Success, FAIL, OK};
 use crate::yaml::{size_t, yaml_char_t};
 use crate::{
     libc, PointerExt, YamlAnyEncoding, YamlParserT, YamlReaderError,
-    YamlUtf16beEncoding, YamlUtf16leEncoding, YamlUtf8Encoding,
+    YamlUtf16beEncoding, YamlUtf16leEncoding, YamlUtf32beEncoding,
+    YamlUtf32leEncoding, YamlUtf8Encoding,
 };
 use core::ptr::addr_of_mut;
+use core::slice;
 
 unsafe fn yaml_parser_set_reader_error(
     parser: *mut YamlParserT,
@@ -22,13 +24,6 @@ unsafe fn yaml_parser_set_reader_error(
     FAIL
 }
 
-const BOM_UTF8: *const libc::c_char =
-    b"\xEF\xBB\xBF\0" as *const u8 as *const libc::c_char;
-const BOM_UTF16LE: *const libc::c_char =
-    b"\xFF\xFE\0" as *const u8 as *const libc::c_char;
-const BOM_UTF16BE: *const libc::c_char =
-    b"\xFE\xFF\0" as *const u8 as *const libc::c_char;
-
 unsafe fn yaml_parser_determine_encoding(
     parser: *mut YamlParserT,
 ) -> Success {
@@ -38,69 +33,66 @@ unsafe fn yaml_parser_determine_encoding(
             .last
             .c_offset_from((*parser).raw_buffer.pointer)
             as libc::c_long)
-            < 3_i64
+            < 4_i64
     {
         if yaml_parser_update_raw_buffer(parser).fail {
             return FAIL;
         }
     }
-    if (*parser)
-        .raw_buffer
-        .last
-        .c_offset_from((*parser).raw_buffer.pointer)
-        as libc::c_long
-        >= 2_i64
-        && memcmp(
-            (*parser).raw_buffer.pointer as *const libc::c_void,
-            BOM_UTF16LE as *const libc::c_void,
-            2_u64,
-        ) == 0
-    {
-        (*parser).encoding = YamlUtf16leEncoding;
-        let fresh1 = addr_of_mut!((*parser).raw_buffer.pointer);
-        *fresh1 = (*fresh1).wrapping_offset(2_isize);
-        let fresh2 = addr_of_mut!((*parser).offset);
-        *fresh2 = (*fresh2).force_add(3_u64);
-    } else if (*parser)
-        .raw_buffer
-        .last
-        .c_offset_from((*parser).raw_buffer.pointer)
-        as libc::c_long
-        >= 2_i64
-        && memcmp(
-            (*parser).raw_buffer.pointer as *const libc::c_void,
-            BOM_UTF16BE as *const libc::c_void,
-            2_u64,
-        ) == 0
-    {
-        (*parser).encoding = YamlUtf16beEncoding;
-        let fresh3 = addr_of_mut!((*parser).raw_buffer.pointer);
-        *fresh3 = (*fresh3).wrapping_offset(2_isize);
-        let fresh4 = addr_of_mut!((*parser).offset);
-        *fresh4 = (*fresh4).force_add(3_u64);
-    } else if (*parser)
-        .raw_buffer
-        .last
-        .c_offset_from((*parser).raw_buffer.pointer)
-        as libc::c_long
-        >= 3_i64
-        && memcmp(
-            (*parser).raw_buffer.pointer as *const libc::c_void,
-            BOM_UTF8 as *const libc::c_void,
-            3_u64,
-        ) == 0
-    {
-        (*parser).encoding = YamlUtf8Encoding;
-        let fresh5 = addr_of_mut!((*parser).raw_buffer.pointer);
-        *fresh5 = (*fresh5).wrapping_offset(3_isize);
-        let fresh6 = addr_of_mut!((*parser).offset);
-        *fresh6 = (*fresh6).force_add(3_u64);
-    } else {
-        (*parser).encoding = YamlUtf8Encoding;
-    }
+    let raw = slice::from_raw_parts(
+        (*parser).raw_buffer.pointer,
+        (*parser)
+            .raw_buffer
+            .last
+            .c_offset_from((*parser).raw_buffer.pointer)
+            as usize,
+    );
+    let (encoding, bom) = match *raw {
+        [0, 0, 0xFE, 0xFF, ..] => (YamlUtf32beEncoding, 4),
+        [0xFF, 0xFE, 0, 0, ..] => (YamlUtf32leEncoding, 4),
+        [0xFE, 0xFF, ..] => (YamlUtf16beEncoding, 2),
+        [0xFF, 0xFE, ..] => (YamlUtf16leEncoding, 2),
+        [0xEF, 0xBB, 0xBF, ..] => (YamlUtf8Encoding, 3),
+        // Without a BOM, the null bytes around an ASCII first character
+        // give UTF-32 away.
+        [0, 0, 0, first, ..] if first != 0 => (YamlUtf32beEncoding, 0),
+        [first, 0, 0, 0, ..] if first != 0 => (YamlUtf32leEncoding, 0),
+        _ => (YamlUtf8Encoding, 0),
+    };
+    (*parser).encoding = encoding;
+    let fresh1 = addr_of_mut!((*parser).raw_buffer.pointer);
+    *fresh1 = (*fresh1).wrapping_add(bom);
+    let fresh2 = addr_of_mut!((*parser).offset);
+    *fresh2 = (*fresh2).force_add(bom as u64);
+    // Marks count the input bytes from its start, BOM included.
+    (*parser).mark.offset = (*parser).offset;
     OK
 }
 
+/// Returns how many bytes of the input `chars` characters were decoded
+/// from, given that they are `width` bytes long in the UTF-8 buffer.
+///
+/// In UTF-16 a single character four bytes long in the buffer came from
+/// a surrogate pair, and any other character from one code unit. Runs
+/// of several characters are only line breaks, which never need a
+/// surrogate pair.
+pub(crate) unsafe fn yaml_parser_input_width(
+    parser: *mut YamlParserT,
+    width: usize,
+    chars: usize,
+) -> size_t {
+    match (*parser).encoding {
+        YamlUtf16leEncoding | YamlUtf16beEncoding if width == 4 => 4,
+        YamlUtf16leEncoding | YamlUtf16beEncoding => {
+            2 * chars as size_t
+        }
+        YamlUtf32leEncoding | YamlUtf32beEncoding => {
+            4 * chars as size_t
+        }
+        _ => width as size_t,
+    }
+}
+
 unsafe fn yaml_parser_update_raw_buffer(
     parser: *mut YamlParserT,
 ) -> Success {
@@ -155,6 +147,22 @@ unsafe fn yaml_parser_update_raw_buffer(
     }
     let fresh9 = addr_of_mut!((*parser).raw_buffer.last);
     *fresh9 = (*fresh9).wrapping_offset(size_read as isize);
+    if (*parser).offset.force_add(
+        (*parser)
+            .raw_buffer
+            .last
+            .c_offset_from((*parser).raw_buffer.pointer)
+            as size_t,
+    ) > (*parser).max_input_size
+    {
+        return yaml_parser_set_reader_error(
+            parser,
+            b"input size limit exceeded\0" as *const u8
+                as *const libc::c_char,
+            (*parser).max_input_size,
+            -1,
+        );
+    }
     if size_read == 0 {
         (*parser).eof = true;
     }
@@ -419,6 +427,47 @@ pub(crate) unsafe fn yaml_parser_update_buffer(
                         }
                     }
                 }
+                YamlUtf32leEncoding | YamlUtf32beEncoding => {
+                    if raw_unread < 4_u64 {
+                        if (*parser).eof {
+                            return yaml_parser_set_reader_error(
+                                parser,
+                                b"incomplete UTF-32 character\0"
+                                    as *const u8
+                                    as *const libc::c_char,
+                                (*parser).offset,
+                                -1,
+                            );
+                        }
+                        incomplete = true;
+                    } else {
+                        let mut bytes = [0; 4];
+                        bytes.copy_from_slice(slice::from_raw_parts(
+                            (*parser).raw_buffer.pointer,
+                            4,
+                        ));
+                        value = if (*parser).encoding
+                            == YamlUtf32leEncoding
+                        {
+                            u32::from_le_bytes(bytes)
+                        } else {
+                            u32::from_be_bytes(bytes)
+                        };
+                        if (0xD800..=0xDFFF).contains(&value)
+                            || value > 0x10FFFF
+                        {
+                            return yaml_parser_set_reader_error(
+                                parser,
+                                b"invalid Unicode character\0"
+                                    as *const u8
+                                    as *const libc::c_char,
+                                (*parser).offset,
+                                value as libc::c_int,
+                            );
+                        }
+                        width = 4;
+                    }
+                }
                 _ => {}
             }
             if incomplete {
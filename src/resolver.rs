@@ -0,0 +1,518 @@
+//! Tag resolution and construction of scalar values.
+//!
+//! The parser reports scalars as strings. A [`YamlSchemaT`] decides
+//! which tag an untagged plain scalar gets, and [`resolve`] turns the
+//! scalar into a typed [`Value`] according to that tag:
+//!
+//! | Type   | JSON           | Core                       | YAML 1.1                          |
+//! |--------|----------------|----------------------------|-----------------------------------|
+//! | null   | `null`         | `null`, `~`, empty         | `null`, `~`, empty                |
+//! | bool   | `true`,`false` | `true`, `False`, `TRUE`... | also `yes`, `no`, `on`, `off`, `y`, `n` |
+//! | int    | `-12`          | `+012`, `0o14`, `0xC`      | `0b1100`, `014`, `1_000`, `3:25:45` |
+//! | float  | `-1.5e3`       | `.5`, `1.`, `.inf`, `.nan` | `1_000.5`, `3:25:45.5`, `.inf`    |
+//!
+//! The failsafe schema resolves every scalar to a string. Quoted and
+//! block scalars are strings under any schema, and plain scalars
+//! matching no form are strings too, including under the JSON schema.
+//!
+//! ```
+//! use libyml::resolver::{self, Value, INT_TAG};
+//! use libyml::{YamlCoreSchema, YamlPlainScalarStyle};
+//!
+//! let style = YamlPlainScalarStyle;
+//! let resolved =
+//!     resolver::resolve(YamlCoreSchema, None, "0x1F", style).unwrap();
+//! assert_eq!(resolved.tag, INT_TAG);
+//! assert_eq!(resolved.value, Value::Int(31));
+//! ```
+
+use crate::error::{Error, ErrorKind};
+use crate::yaml::{YamlScalarStyleT, YamlSchemaT};
+use crate::{
+    YamlCoreSchema, YamlFailsafeSchema, YamlJsonSchema,
+    YamlPlainScalarStyle, YamlYaml11Schema,
+};
+use alloc::format;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Write as _};
+
+/// The tag of null scalars.
+pub const NULL_TAG: &str = "tag:yaml.org,2002:null";
+/// The tag of boolean scalars.
+pub const BOOL_TAG: &str = "tag:yaml.org,2002:bool";
+/// The tag of integer scalars.
+pub const INT_TAG: &str = "tag:yaml.org,2002:int";
+/// The tag of floating-point scalars.
+pub const FLOAT_TAG: &str = "tag:yaml.org,2002:float";
+/// The tag of string scalars.
+pub const STR_TAG: &str = "tag:yaml.org,2002:str";
+/// The tag of `<<` merge keys, which the loader resolves plain `<<`
+/// scalars to when it applies them.
+pub const MERGE_TAG: &str = "tag:yaml.org,2002:merge";
+
+/// A scalar value constructed according to its tag.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Value<'a> {
+    /// A null value.
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// An integer.
+    Int(i64),
+    /// An integer that does not fit in an `i64`.
+    BigInt(BigInt<'a>),
+    /// A floating-point number.
+    Float(f64),
+    /// A string, or the value of a tag the schema does not know.
+    Str(&'a str),
+}
+
+/// An integer too wide for an `i64`, kept as the digits it is written
+/// with.
+///
+/// It compares by value and displays in decimal.
+///
+/// ```
+/// use libyml::resolver::{self, Value};
+/// use libyml::{YamlCoreSchema, YamlPlainScalarStyle};
+///
+/// let style = YamlPlainScalarStyle;
+/// let value = "0x10000000000000000";
+/// let resolved =
+///     resolver::resolve(YamlCoreSchema, None, value, style).unwrap();
+/// let Value::BigInt(int) = resolved.value else { panic!() };
+/// assert_eq!((int.radix(), int.digits()), (16, "10000000000000000"));
+/// assert_eq!(int.to_string(), "18446744073709551616");
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct BigInt<'a> {
+    negative: bool,
+    digits: &'a str,
+    radix: u32,
+}
+
+impl<'a> BigInt<'a> {
+    /// Is the integer below zero?
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// The digits without sign or prefix, which YAML 1.1 integers may
+    /// separate with `_`.
+    pub fn digits(&self) -> &'a str {
+        self.digits
+    }
+
+    /// The base of the digits: 2, 8, 10 or 16.
+    pub fn radix(&self) -> u32 {
+        self.radix
+    }
+
+    /// The digits without `_` separators and leading zeros.
+    fn significant_digits(&self) -> impl Iterator<Item = u32> + 'a {
+        self.digits
+            .chars()
+            .filter_map(|ch| ch.to_digit(16))
+            .skip_while(|&digit| digit == 0)
+    }
+
+    /// The magnitude in base 10^9, least significant limb first and
+    /// without leading zero limbs.
+    fn decimal(&self) -> Vec<u32> {
+        // As many digits at a time as keep the multiplier within a
+        // limb.
+        let mut chunk = 1;
+        while u64::from(self.radix).pow(chunk + 1) < DECIMAL_BASE {
+            chunk += 1;
+        }
+        let mut decimal: Vec<u32> = Vec::new();
+        let mut digits = self.significant_digits().peekable();
+        while digits.peek().is_some() {
+            let (mut multiplier, mut carry) = (1, 0);
+            for digit in digits.by_ref().take(chunk as usize) {
+                multiplier *= u64::from(self.radix);
+                carry =
+                    carry * u64::from(self.radix) + u64::from(digit);
+            }
+            for limb in &mut decimal {
+                let sum = u64::from(*limb) * multiplier + carry;
+                *limb = (sum % DECIMAL_BASE) as u32;
+                carry = sum / DECIMAL_BASE;
+            }
+            while carry != 0 {
+                decimal.push((carry % DECIMAL_BASE) as u32);
+                carry /= DECIMAL_BASE;
+            }
+        }
+        decimal
+    }
+}
+
+/// The base of the limbs of `BigInt::decimal`.
+const DECIMAL_BASE: u64 = 1_000_000_000;
+
+/// The most significant digits an integer may have, which bounds the
+/// work of comparing and displaying a `BigInt`.
+const MAX_INT_DIGITS: usize = 4096;
+
+impl PartialEq for BigInt<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.negative != other.negative {
+            false
+        } else if self.radix == other.radix {
+            self.significant_digits().eq(other.significant_digits())
+        } else {
+            self.decimal() == other.decimal()
+        }
+    }
+}
+
+impl Eq for BigInt<'_> {}
+
+impl Display for BigInt<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negative {
+            formatter.write_char('-')?;
+        }
+        let decimal = self.decimal();
+        let mut limbs = decimal.iter().rev();
+        write!(formatter, "{}", limbs.next().unwrap_or(&0))?;
+        for limb in limbs {
+            write!(formatter, "{limb:09}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A scalar with its tag resolved.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Resolved<'a> {
+    /// The explicit tag of the scalar, or the one it resolved to.
+    pub tag: &'a str,
+    /// The typed value.
+    pub value: Value<'a>,
+}
+
+/// Resolves the tag of a scalar and constructs its value.
+///
+/// `tag` is the tag of a scalar event, `None` or `!` when it has none,
+/// or the tag of a loaded scalar node. Untagged plain scalars resolve
+/// according to `schema` and other untagged scalars are strings.
+///
+/// Scalars tagged with a type of the schema must match one of its
+/// forms; a [`Composer`](ErrorKind::Composer) error is returned when
+/// they don't, when a YAML 1.1 base 60 integer does not fit in an
+/// `i64`, or when an integer has more than 4096 significant digits.
+/// Other integers too wide for an `i64` are
+/// [`BigInt`](Value::BigInt)s. The values of other tags are kept as
+/// strings.
+pub fn resolve<'a>(
+    schema: YamlSchemaT,
+    tag: Option<&'a str>,
+    value: &'a str,
+    style: YamlScalarStyleT,
+) -> Result<Resolved<'a>, Error> {
+    let tag = match tag {
+        Some(tag) if tag != "!" => tag,
+        Some(_) => STR_TAG,
+        None if style == YamlPlainScalarStyle => {
+            resolve_plain(schema, value)
+        }
+        None => STR_TAG,
+    };
+    let constructed = match tag {
+        _ if schema == YamlFailsafeSchema => Some(Value::Str(value)),
+        NULL_TAG => is_null(schema, value).then_some(Value::Null),
+        BOOL_TAG => parse_bool(schema, value).map(Value::Bool),
+        INT_TAG => match parse_int(schema, value) {
+            Some(Some(int)) => Some(int),
+            Some(None) => {
+                return Err(Error::new(
+                    ErrorKind::Composer,
+                    format!("integer {:?} out of range", value),
+                ));
+            }
+            None => None,
+        },
+        FLOAT_TAG => parse_float(schema, value).map(Value::Float),
+        _ => Some(Value::Str(value)),
+    };
+    match constructed {
+        Some(value) => Ok(Resolved { tag, value }),
+        None => Err(Error::new(
+            ErrorKind::Composer,
+            format!("invalid value {:?} for tag {}", value, tag),
+        )),
+    }
+}
+
+/// The tag an untagged plain scalar resolves to.
+pub(crate) fn resolve_plain(
+    schema: YamlSchemaT,
+    value: &str,
+) -> &'static str {
+    if schema == YamlFailsafeSchema {
+        STR_TAG
+    } else if is_null(schema, value) {
+        NULL_TAG
+    } else if parse_bool(schema, value).is_some() {
+        BOOL_TAG
+    } else if parse_int(schema, value).is_some() {
+        INT_TAG
+    } else if parse_float(schema, value).is_some() {
+        FLOAT_TAG
+    } else {
+        STR_TAG
+    }
+}
+
+fn is_null(schema: YamlSchemaT, value: &str) -> bool {
+    match value {
+        "null" => true,
+        "" | "~" | "Null" | "NULL" => schema != YamlJsonSchema,
+        _ => false,
+    }
+}
+
+fn parse_bool(schema: YamlSchemaT, value: &str) -> Option<bool> {
+    match (schema, value) {
+        (_, "true") => Some(true),
+        (_, "false") => Some(false),
+        (YamlJsonSchema, _) => None,
+        (_, "True" | "TRUE") => Some(true),
+        (_, "False" | "FALSE") => Some(false),
+        (YamlCoreSchema, _) => None,
+        (_, "y" | "Y" | "yes" | "Yes" | "YES" | "on" | "On" | "ON") => {
+            Some(true)
+        }
+        (_, "n" | "N" | "no" | "No" | "NO" | "off" | "Off" | "OFF") => {
+            Some(false)
+        }
+        _ => None,
+    }
+}
+
+/// Matches `value` against the integer forms of the schema, returning
+/// `Some(None)` for base 60 integers that do not fit in an `i64` and
+/// integers with too many digits for a `BigInt`.
+fn parse_int(
+    schema: YamlSchemaT,
+    value: &str,
+) -> Option<Option<Value<'_>>> {
+    match schema {
+        YamlJsonSchema => {
+            let digits = value.strip_prefix('-').unwrap_or(value);
+            if digits.starts_with('0') && digits != "0"
+                || !is_digits(digits, 10)
+            {
+                return None;
+            }
+            Some(int_value(value.starts_with('-'), digits, 10))
+        }
+        YamlCoreSchema => {
+            for (prefix, radix) in [("0o", 8), ("0x", 16)] {
+                if let Some(digits) = value.strip_prefix(prefix) {
+                    return is_digits(digits, radix)
+                        .then(|| int_value(false, digits, radix));
+                }
+            }
+            let (negative, digits) = split_sign(value);
+            is_digits(digits, 10)
+                .then(|| int_value(negative, digits, 10))
+        }
+        YamlYaml11Schema => {
+            let (negative, number) = split_sign(value);
+            let (digits, radix) = if let Some(digits) =
+                number.strip_prefix("0b")
+            {
+                (digits, 2)
+            } else if let Some(digits) = number.strip_prefix("0x") {
+                (digits, 16)
+            } else if number.contains(':') {
+                if number.starts_with('0') {
+                    return None;
+                }
+                return parse_sexagesimal(number).map(|int| {
+                    int.map(|int| {
+                        Value::Int(if negative { -int } else { int })
+                    })
+                });
+            } else if number.len() > 1 && number.starts_with('0') {
+                (&number[1..], 8)
+            } else if number.starts_with(|ch: char| ch.is_ascii_digit())
+            {
+                (number, 10)
+            } else {
+                return None;
+            };
+            is_separated_digits(digits, radix)
+                .then(|| int_value(negative, digits, radix))
+        }
+        _ => None,
+    }
+}
+
+/// Matches the integer part of the YAML 1.1 base 60 numbers, such as
+/// `3:25:45`.
+fn parse_sexagesimal(number: &str) -> Option<Option<i64>> {
+    let mut parts = number.split(':');
+    let first = parts.next()?;
+    if !first.starts_with(|ch: char| ch.is_ascii_digit())
+        || !is_separated_digits(first, 10)
+    {
+        return None;
+    }
+    let mut int = to_int(false, first, 10);
+    for part in parts {
+        if part.len() > 2 || !is_digits(part, 10) {
+            return None;
+        }
+        let digits = to_int(false, part, 10)?;
+        if digits >= 60 {
+            return None;
+        }
+        int = int
+            .and_then(|int| int.checked_mul(60)?.checked_add(digits));
+    }
+    Some(int)
+}
+
+fn parse_float(schema: YamlSchemaT, value: &str) -> Option<f64> {
+    let yaml11 = schema == YamlYaml11Schema;
+    let (negative, number) = match schema {
+        YamlJsonSchema => {
+            let number = value.strip_prefix('-');
+            (number.is_some(), number.unwrap_or(value))
+        }
+        YamlCoreSchema | YamlYaml11Schema => {
+            if let Some(special) = parse_special_float(value) {
+                return Some(special);
+            }
+            split_sign(value)
+        }
+        _ => return None,
+    };
+    let (mantissa, exponent) = split_exponent(number, yaml11)?;
+    let (int, fraction) = match mantissa.split_once('.') {
+        Some(parts) => parts,
+        None if yaml11 => return None,
+        None => (mantissa, ""),
+    };
+    let valid = if yaml11 {
+        let int_valid = if int.contains(':') {
+            exponent.is_none() && parse_sexagesimal(int).is_some()
+        } else {
+            int.is_empty()
+                || int.starts_with(|ch: char| ch.is_ascii_digit())
+                    && is_separated_digits(int, 10)
+        };
+        int_valid
+            && (fraction.is_empty()
+                || is_separated_digits(fraction, 10))
+            && mantissa.contains(|ch: char| ch.is_ascii_digit())
+    } else if schema == YamlJsonSchema {
+        is_digits(int, 10)
+            && (int == "0" || !int.starts_with('0'))
+            && (fraction.is_empty() || is_digits(fraction, 10))
+    } else {
+        (int.is_empty() || is_digits(int, 10))
+            && (fraction.is_empty() || is_digits(fraction, 10))
+            && !(int.is_empty() && fraction.is_empty())
+    };
+    if !valid {
+        return None;
+    }
+    let float = if int.contains(':') {
+        let int = parse_sexagesimal(int)??;
+        let fraction = format!("0.{}", fraction.replace('_', ""));
+        int as f64 + fraction.parse::<f64>().ok()?
+    } else {
+        number.replace('_', "").parse::<f64>().ok()?
+    };
+    Some(if negative { -float } else { float })
+}
+
+/// Matches `.inf`, `-.inf` and `.nan` in their three spellings.
+fn parse_special_float(value: &str) -> Option<f64> {
+    if let ".nan" | ".NaN" | ".NAN" = value {
+        return Some(f64::NAN);
+    }
+    let (negative, number) = split_sign(value);
+    matches!(number, ".inf" | ".Inf" | ".INF").then_some(if negative {
+        f64::NEG_INFINITY
+    } else {
+        f64::INFINITY
+    })
+}
+
+/// Splits off the exponent of a number, which YAML 1.1 requires to be
+/// signed.
+fn split_exponent(
+    number: &str,
+    signed: bool,
+) -> Option<(&str, Option<&str>)> {
+    let Some(at) = number.find(['e', 'E']) else {
+        return Some((number, None));
+    };
+    let exponent = &number[at + 1..];
+    let digits = exponent.strip_prefix(['-', '+']);
+    if signed && digits.is_none()
+        || !is_digits(digits.unwrap_or(exponent), 10)
+    {
+        return None;
+    }
+    Some((&number[..at], Some(exponent)))
+}
+
+fn split_sign(value: &str) -> (bool, &str) {
+    if let Some(number) = value.strip_prefix('-') {
+        (true, number)
+    } else {
+        (false, value.strip_prefix('+').unwrap_or(value))
+    }
+}
+
+fn is_digits(digits: &str, radix: u32) -> bool {
+    !digits.is_empty() && digits.chars().all(|ch| ch.is_digit(radix))
+}
+
+/// Like `is_digits`, but also allows the `_` separators of YAML 1.1.
+fn is_separated_digits(digits: &str, radix: u32) -> bool {
+    digits.chars().any(|ch| ch.is_digit(radix))
+        && digits.chars().all(|ch| ch == '_' || ch.is_digit(radix))
+}
+
+/// Converts validated digits into an `Int`, or a `BigInt` if they
+/// overflow, returning `None` if there are too many of them.
+fn int_value(
+    negative: bool,
+    digits: &str,
+    radix: u32,
+) -> Option<Value<'_>> {
+    if let Some(int) = to_int(negative, digits, radix) {
+        return Some(Value::Int(int));
+    }
+    let int = BigInt {
+        negative,
+        digits,
+        radix,
+    };
+    (int.significant_digits().count() <= MAX_INT_DIGITS)
+        .then_some(Value::BigInt(int))
+}
+
+/// Converts validated digits, returning `None` on overflow.
+fn to_int(negative: bool, digits: &str, radix: u32) -> Option<i64> {
+    let mut int: i64 = 0;
+    for digit in digits.chars().filter_map(|ch| ch.to_digit(radix)) {
+        let digit = i64::from(digit);
+        int = int.checked_mul(i64::from(radix))?;
+        int = if negative {
+            int.checked_sub(digit)?
+        } else {
+            int.checked_add(digit)?
+        };
+    }
+    Some(int)
+}
@@ -0,0 +1,587 @@
+use super::{Parser, TagDirective, VersionDirective};
+use crate::error::Error;
+use crate::externs::strlen;
+use crate::libc;
+use crate::resolver::{self, Resolved};
+use crate::yaml::{
+    yaml_char_t, YamlDocumentT, YamlMappingStyleT, YamlMarkT,
+    YamlNodePairT, YamlNodeT, YamlScalarStyleT, YamlSchemaT,
+    YamlSequenceStyleT,
+};
+use crate::{
+    yaml_document_delete, yaml_document_get_node,
+    yaml_document_get_root_node, yaml_parser_load, YamlMappingNode,
+    YamlScalarNode, YamlSequenceNode,
+};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use core::iter::FusedIterator;
+use core::mem::MaybeUninit;
+use core::{ptr, slice, str};
+
+/// Loads a stream of YAML documents.
+///
+/// Each item is a complete [`Document`], with its aliases resolved into
+/// references to the anchored nodes.
+///
+/// ```
+/// use libyml::safe::{Loader, Node};
+///
+/// let input = b"name: libyml\ntags: [yaml, c2rust]\n";
+/// let mut loader = Loader::new(input);
+/// let document = loader.next().unwrap().unwrap();
+/// let root = document.root().unwrap().as_mapping().unwrap();
+/// let name = root.get("name").and_then(Node::as_str);
+/// assert_eq!(name, Some("libyml"));
+/// let tags: Vec<_> = root
+///     .get("tags")
+///     .and_then(Node::as_sequence)
+///     .unwrap()
+///     .iter()
+///     .filter_map(Node::as_str)
+///     .collect();
+/// assert_eq!(tags, ["yaml", "c2rust"]);
+/// assert!(loader.next().is_none());
+/// ```
+#[derive(Debug)]
+pub struct Loader<'input> {
+    parser: Parser<'input>,
+    done: bool,
+}
+
+impl<'input> Loader<'input> {
+    /// Creates a loader reading from `input`.
+    pub fn new(input: &'input [u8]) -> Self {
+        Loader::from_parser(Parser::new(input))
+    }
+
+    /// Creates a loader reading documents from a parser.
+    ///
+    /// The parser must not have produced any events yet.
+    pub fn from_parser(parser: Parser<'input>) -> Self {
+        Loader {
+            parser,
+            done: false,
+        }
+    }
+
+    /// Loads the next document, or returns `None` once the stream has
+    /// ended.
+    pub fn next_document(&mut self) -> Option<Result<Document, Error>> {
+        if self.done {
+            return None;
+        }
+        let mut sys = MaybeUninit::<YamlDocumentT>::uninit();
+        unsafe {
+            let parser = self.parser.as_mut_ptr();
+            let document = sys.as_mut_ptr();
+            if yaml_parser_load(parser, document).fail {
+                self.done = true;
+                return Some(Err(self.parser.error()));
+            }
+            let document = Document {
+                sys: sys.assume_init(),
+                schema: (*parser).schema,
+            };
+            if document.root().is_none() {
+                // An empty document marks the end of the stream.
+                self.done = true;
+                return None;
+            }
+            Some(Ok(document))
+        }
+    }
+}
+
+impl Iterator for Loader<'_> {
+    type Item = Result<Document, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_document()
+    }
+}
+
+impl FusedIterator for Loader<'_> {}
+
+/// A safe, owned YAML document.
+///
+/// The nodes of the document are reached through [`Document::root`]. The
+/// underlying `YamlDocumentT` is released when the document is dropped.
+pub struct Document {
+    sys: YamlDocumentT,
+    schema: YamlSchemaT,
+}
+
+impl Document {
+    /// The root node, or `None` if the document is empty.
+    pub fn root(&self) -> Option<Node<'_>> {
+        unsafe {
+            let node = yaml_document_get_root_node(self.as_ptr());
+            Node::from_raw(self, node)
+        }
+    }
+
+    /// The `%YAML` directive, if any.
+    pub fn version_directive(&self) -> Option<VersionDirective> {
+        unsafe {
+            self.sys.version_directive.as_ref().map(|version| {
+                VersionDirective {
+                    major: version.major,
+                    minor: version.minor,
+                }
+            })
+        }
+    }
+
+    /// The `%TAG` directives.
+    pub fn tag_directives(&self) -> Vec<TagDirective<'_>> {
+        let directives = &self.sys.tag_directives;
+        let mut tag_directives = Vec::new();
+        let mut tag_directive = directives.start;
+        while tag_directive != directives.end {
+            unsafe {
+                tag_directives.push(TagDirective {
+                    handle: Cow::Borrowed(
+                        borrowed_cstr((*tag_directive).handle)
+                            .unwrap_or_default(),
+                    ),
+                    prefix: Cow::Borrowed(
+                        borrowed_cstr((*tag_directive).prefix)
+                            .unwrap_or_default(),
+                    ),
+                });
+            }
+            tag_directive = tag_directive.wrapping_offset(1);
+        }
+        tag_directives
+    }
+
+    /// Is the document start indicator implicit?
+    pub fn start_implicit(&self) -> bool {
+        self.sys.start_implicit
+    }
+
+    /// Is the document end indicator implicit?
+    pub fn end_implicit(&self) -> bool {
+        self.sys.end_implicit
+    }
+
+    /// The beginning of the document.
+    pub fn start_mark(&self) -> YamlMarkT {
+        self.sys.start_mark
+    }
+
+    /// The end of the document.
+    pub fn end_mark(&self) -> YamlMarkT {
+        self.sys.end_mark
+    }
+
+    /// The underlying `YamlDocumentT`.
+    ///
+    /// `yaml_document_get_node` and `yaml_document_get_root_node` take
+    /// a mutable pointer but only read through it.
+    fn as_ptr(&self) -> *mut YamlDocumentT {
+        ptr::addr_of!(self.sys).cast_mut()
+    }
+
+    fn node(&self, index: libc::c_int) -> Option<Node<'_>> {
+        unsafe {
+            let node = yaml_document_get_node(self.as_ptr(), index);
+            Node::from_raw(self, node)
+        }
+    }
+}
+
+impl Drop for Document {
+    fn drop(&mut self) {
+        unsafe { yaml_document_delete(&mut self.sys) }
+    }
+}
+
+impl Debug for Document {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("Document")
+            .field("root", &self.root())
+            .finish()
+    }
+}
+
+/// A view of a node in a [`Document`].
+#[derive(Copy, Clone)]
+#[non_exhaustive]
+pub enum Node<'doc> {
+    /// A scalar node.
+    Scalar(ScalarNode<'doc>),
+    /// A sequence node.
+    Sequence(SequenceNode<'doc>),
+    /// A mapping node.
+    Mapping(MappingNode<'doc>),
+}
+
+/// A view of a scalar node.
+#[derive(Copy, Clone)]
+pub struct ScalarNode<'doc> {
+    node: &'doc YamlNodeT,
+    schema: YamlSchemaT,
+}
+
+/// A view of a sequence node.
+#[derive(Copy, Clone)]
+pub struct SequenceNode<'doc> {
+    document: &'doc Document,
+    node: &'doc YamlNodeT,
+}
+
+/// A view of a mapping node.
+#[derive(Copy, Clone)]
+pub struct MappingNode<'doc> {
+    document: &'doc Document,
+    node: &'doc YamlNodeT,
+}
+
+impl<'doc> Node<'doc> {
+    unsafe fn from_raw(
+        document: &'doc Document,
+        node: *const YamlNodeT,
+    ) -> Option<Self> {
+        let node = node.as_ref()?;
+        Some(match node.type_ {
+            YamlScalarNode => Node::Scalar(ScalarNode {
+                node,
+                schema: document.schema,
+            }),
+            YamlSequenceNode => {
+                Node::Sequence(SequenceNode { document, node })
+            }
+            YamlMappingNode => {
+                Node::Mapping(MappingNode { document, node })
+            }
+            _ => return None,
+        })
+    }
+
+    fn raw(&self) -> &'doc YamlNodeT {
+        match self {
+            Node::Scalar(scalar) => scalar.node,
+            Node::Sequence(sequence) => sequence.node,
+            Node::Mapping(mapping) => mapping.node,
+        }
+    }
+
+    /// The resolved tag of the node, if any.
+    pub fn tag(&self) -> Option<&'doc str> {
+        unsafe { borrowed_cstr(self.raw().tag) }
+    }
+
+    /// The beginning of the node.
+    pub fn start_mark(&self) -> YamlMarkT {
+        self.raw().start_mark
+    }
+
+    /// The end of the node.
+    pub fn end_mark(&self) -> YamlMarkT {
+        self.raw().end_mark
+    }
+
+    /// Returns the scalar view if this is a scalar node.
+    pub fn as_scalar(self) -> Option<ScalarNode<'doc>> {
+        match self {
+            Node::Scalar(scalar) => Some(scalar),
+            _ => None,
+        }
+    }
+
+    /// Returns the sequence view if this is a sequence node.
+    pub fn as_sequence(self) -> Option<SequenceNode<'doc>> {
+        match self {
+            Node::Sequence(sequence) => Some(sequence),
+            _ => None,
+        }
+    }
+
+    /// Returns the mapping view if this is a mapping node.
+    pub fn as_mapping(self) -> Option<MappingNode<'doc>> {
+        match self {
+            Node::Mapping(mapping) => Some(mapping),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is a scalar node.
+    pub fn as_str(self) -> Option<&'doc str> {
+        self.as_scalar().map(|scalar| scalar.value())
+    }
+}
+
+impl<'doc> ScalarNode<'doc> {
+    /// The scalar value.
+    pub fn value(&self) -> &'doc str {
+        let scalar = &self.node.data.scalar;
+        if scalar.length == 0 {
+            return "";
+        }
+        unsafe {
+            let bytes = slice::from_raw_parts(
+                scalar.value,
+                scalar.length as usize,
+            );
+            // The reader and `yaml_document_add_scalar` both reject
+            // invalid UTF-8.
+            str::from_utf8_unchecked(bytes)
+        }
+    }
+
+    /// The scalar style.
+    pub fn style(&self) -> YamlScalarStyleT {
+        self.node.data.scalar.style
+    }
+
+    /// The resolved tag of the node, if any.
+    pub fn tag(&self) -> Option<&'doc str> {
+        unsafe { borrowed_cstr(self.node.tag) }
+    }
+
+    /// Constructs the value of the node according to its tag, with the
+    /// schema of the parser it was loaded by.
+    ///
+    /// ```
+    /// use libyml::resolver::{Value, BOOL_TAG};
+    /// use libyml::safe::{Loader, Parser};
+    /// use libyml::YamlYaml11Schema;
+    ///
+    /// let mut parser = Parser::new(b"enabled: yes\n");
+    /// parser.set_schema(YamlYaml11Schema);
+    /// let document = Loader::from_parser(parser).next().unwrap().unwrap();
+    /// let root = document.root().unwrap().as_mapping().unwrap();
+    /// let enabled = root.get("enabled").unwrap().as_scalar().unwrap();
+    /// assert_eq!(enabled.tag(), Some(BOOL_TAG));
+    /// assert_eq!(enabled.resolve().unwrap().value, Value::Bool(true));
+    /// ```
+    pub fn resolve(&self) -> Result<Resolved<'doc>, Error> {
+        resolver::resolve(
+            self.schema,
+            self.tag(),
+            self.value(),
+            self.style(),
+        )
+    }
+}
+
+impl<'doc> SequenceNode<'doc> {
+    /// The number of items in the sequence.
+    pub fn len(&self) -> usize {
+        self.items().len()
+    }
+
+    /// Is the sequence empty?
+    pub fn is_empty(&self) -> bool {
+        self.items().is_empty()
+    }
+
+    /// The item at `index`, starting from 0.
+    pub fn get(&self, index: usize) -> Option<Node<'doc>> {
+        let item = *self.items().get(index)?;
+        self.document.node(item)
+    }
+
+    /// An iterator over the items of the sequence.
+    pub fn iter(&self) -> Items<'doc> {
+        Items {
+            document: self.document,
+            items: self.items().iter(),
+        }
+    }
+
+    /// The sequence style.
+    pub fn style(&self) -> YamlSequenceStyleT {
+        self.node.data.sequence.style
+    }
+
+    /// The resolved tag of the node, if any.
+    pub fn tag(&self) -> Option<&'doc str> {
+        unsafe { borrowed_cstr(self.node.tag) }
+    }
+
+    fn items(&self) -> &'doc [libc::c_int] {
+        let items = &self.node.data.sequence.items;
+        unsafe { stack_slice(items.start, items.top) }
+    }
+}
+
+impl<'doc> IntoIterator for SequenceNode<'doc> {
+    type Item = Node<'doc>;
+    type IntoIter = Items<'doc>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'doc> MappingNode<'doc> {
+    /// The number of pairs in the mapping.
+    pub fn len(&self) -> usize {
+        self.pairs().len()
+    }
+
+    /// Is the mapping empty?
+    pub fn is_empty(&self) -> bool {
+        self.pairs().is_empty()
+    }
+
+    /// The value of the first pair whose key is a scalar equal to
+    /// `key`.
+    pub fn get(&self, key: &str) -> Option<Node<'doc>> {
+        self.iter()
+            .find(|(k, _)| k.as_str() == Some(key))
+            .map(|(_, value)| value)
+    }
+
+    /// Was the first pair whose key is a scalar equal to `key` merged in
+    /// by a `<<` key?
+    pub fn is_merged(&self, key: &str) -> bool {
+        self.pairs()
+            .iter()
+            .find(|pair| {
+                self.document.node(pair.key).and_then(Node::as_str)
+                    == Some(key)
+            })
+            .is_some_and(|pair| pair.merged)
+    }
+
+    /// An iterator over the key-value pairs of the mapping.
+    pub fn iter(&self) -> Pairs<'doc> {
+        Pairs {
+            document: self.document,
+            pairs: self.pairs().iter(),
+        }
+    }
+
+    /// The mapping style.
+    pub fn style(&self) -> YamlMappingStyleT {
+        self.node.data.mapping.style
+    }
+
+    /// The resolved tag of the node, if any.
+    pub fn tag(&self) -> Option<&'doc str> {
+        unsafe { borrowed_cstr(self.node.tag) }
+    }
+
+    fn pairs(&self) -> &'doc [YamlNodePairT] {
+        let pairs = &self.node.data.mapping.pairs;
+        unsafe { stack_slice(pairs.start, pairs.top) }
+    }
+}
+
+impl<'doc> IntoIterator for MappingNode<'doc> {
+    type Item = (Node<'doc>, Node<'doc>);
+    type IntoIter = Pairs<'doc>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the items of a [`SequenceNode`].
+#[derive(Clone, Debug)]
+pub struct Items<'doc> {
+    document: &'doc Document,
+    items: slice::Iter<'doc, libc::c_int>,
+}
+
+impl<'doc> Iterator for Items<'doc> {
+    type Item = Node<'doc>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = *self.items.next()?;
+        self.document.node(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.items.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Items<'_> {}
+
+impl FusedIterator for Items<'_> {}
+
+/// An iterator over the key-value pairs of a [`MappingNode`].
+#[derive(Clone, Debug)]
+pub struct Pairs<'doc> {
+    document: &'doc Document,
+    pairs: slice::Iter<'doc, YamlNodePairT>,
+}
+
+impl<'doc> Iterator for Pairs<'doc> {
+    type Item = (Node<'doc>, Node<'doc>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pair = self.pairs.next()?;
+        Some((
+            self.document.node(pair.key)?,
+            self.document.node(pair.value)?,
+        ))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.pairs.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Pairs<'_> {}
+
+impl FusedIterator for Pairs<'_> {}
+
+impl Debug for Node<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::Scalar(scalar) => Debug::fmt(scalar, formatter),
+            Node::Sequence(sequence) => Debug::fmt(sequence, formatter),
+            Node::Mapping(mapping) => Debug::fmt(mapping, formatter),
+        }
+    }
+}
+
+impl Debug for ScalarNode<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self.value(), formatter)
+    }
+}
+
+impl Debug for SequenceNode<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl Debug for MappingNode<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Views the used part of a `YamlStackT` as a slice.
+unsafe fn stack_slice<'a, T>(
+    start: *const T,
+    top: *const T,
+) -> &'a [T] {
+    if start.is_null() || start == top {
+        return &[];
+    }
+    slice::from_raw_parts(start, top.offset_from(start) as usize)
+}
+
+/// Borrows a NUL-terminated string, mapping null pointers to `None`.
+unsafe fn borrowed_cstr<'a>(
+    ptr: *const yaml_char_t,
+) -> Option<&'a str> {
+    if ptr.is_null() {
+        return None;
+    }
+    let length = strlen(ptr.cast::<libc::c_char>()) as usize;
+    let bytes = slice::from_raw_parts(ptr, length);
+    // Tags and directives are validated as UTF-8 before they are stored.
+    Some(str::from_utf8_unchecked(bytes))
+}
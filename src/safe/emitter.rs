@@ -0,0 +1,391 @@
+use super::Event;
+use crate::error::{Error, ErrorKind};
+use crate::libc;
+use crate::yaml::{size_t, YamlBreakT, YamlEmitterT, YamlEventT};
+use crate::{
+    yaml_emitter_delete, yaml_emitter_emit, yaml_emitter_flush,
+    yaml_emitter_initialize, yaml_emitter_set_break,
+    yaml_emitter_set_canonical, yaml_emitter_set_indent,
+    yaml_emitter_set_output, yaml_emitter_set_unicode,
+    yaml_emitter_set_width, YamlAnyBreak,
+};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::fmt::{self, Debug};
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::ptr::addr_of_mut;
+use core::{slice, str};
+
+/// A destination for the bytes produced by an [`Emitter`].
+///
+/// Besides `Vec<u8>`, the built-in sinks are [`FmtSink`] for any
+/// `fmt::Write` and, with the `std` feature, [`IoSink`](super::IoSink)
+/// for any `io::Write`.
+pub trait Sink {
+    /// The error returned when a write fails.
+    ///
+    /// It is reported as the [`source`](core::error::Error::source) of
+    /// the emitter's writer error.
+    type Error: core::error::Error + Send + Sync + 'static;
+
+    /// Writes all of `bytes` to the sink.
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+
+    /// Flushes the sink, if it buffers its output.
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl Sink for Vec<u8> {
+    type Error = Infallible;
+
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+impl<S: Sink + ?Sized> Sink for &mut S {
+    type Error = S::Error;
+
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        (**self).write_all(bytes)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        (**self).flush()
+    }
+}
+
+/// A [`Sink`] writing into a `fmt::Write`, such as a `String`.
+///
+/// The output must be UTF-8, which is the default encoding.
+///
+/// ```
+/// use libyml::safe::{Emitter, FmtSink};
+///
+/// let emitter = Emitter::new(FmtSink::new(String::new()));
+/// let output: String = emitter.into_inner().into_inner();
+/// # assert!(output.is_empty());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FmtSink<W> {
+    writer: W,
+    // A UTF-8 sequence split across two writes.
+    pending: Vec<u8>,
+}
+
+impl<W: fmt::Write> FmtSink<W> {
+    /// Creates a sink writing into `writer`.
+    pub fn new(writer: W) -> Self {
+        FmtSink {
+            writer,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: fmt::Write> Sink for FmtSink<W> {
+    type Error = fmt::Error;
+
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        let mut joined;
+        let bytes = if self.pending.is_empty() {
+            bytes
+        } else {
+            joined = core::mem::take(&mut self.pending);
+            joined.extend_from_slice(bytes);
+            &joined[..]
+        };
+        let valid = match str::from_utf8(bytes) {
+            Ok(valid) => valid,
+            Err(error) if error.error_len().is_none() => {
+                let (valid, rest) = bytes.split_at(error.valid_up_to());
+                self.pending.extend_from_slice(rest);
+                unsafe { str::from_utf8_unchecked(valid) }
+            }
+            Err(_) => return Err(fmt::Error),
+        };
+        self.writer.write_str(valid)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        if self.pending.is_empty() {
+            Ok(())
+        } else {
+            Err(fmt::Error)
+        }
+    }
+}
+
+/// Configures and creates an [`Emitter`].
+///
+/// Every option defaults to the value `yaml_emitter_initialize` would
+/// pick.
+///
+/// ```
+/// use libyml::safe::EmitterBuilder;
+/// use libyml::YamlCrlnBreak;
+///
+/// let emitter = EmitterBuilder::new()
+///     .indent(4)
+///     .width(-1)
+///     .line_break(YamlCrlnBreak)
+///     .build(Vec::new());
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct EmitterBuilder {
+    canonical: bool,
+    indent: i32,
+    width: i32,
+    unicode: bool,
+    line_break: YamlBreakT,
+}
+
+impl EmitterBuilder {
+    /// Creates a builder with the default options.
+    pub fn new() -> Self {
+        EmitterBuilder {
+            canonical: false,
+            indent: 2,
+            width: 80,
+            unicode: false,
+            line_break: YamlAnyBreak,
+        }
+    }
+
+    /// Sets whether the output should be in the canonical format.
+    pub fn canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// Sets the indentation increment, between 2 and 9.
+    pub fn indent(mut self, indent: i32) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Sets the preferred line width. A negative width means unlimited.
+    pub fn width(mut self, width: i32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets whether unescaped non-ASCII characters are allowed.
+    pub fn unicode(mut self, unicode: bool) -> Self {
+        self.unicode = unicode;
+        self
+    }
+
+    /// Sets the preferred line break.
+    pub fn line_break(mut self, line_break: YamlBreakT) -> Self {
+        self.line_break = line_break;
+        self
+    }
+
+    /// Creates an emitter writing to `output`.
+    pub fn build<W: Sink>(self, output: W) -> Emitter<W> {
+        let inner = Box::into_raw(Box::new(Inner {
+            sys: MaybeUninit::uninit(),
+            output,
+            error: None,
+        }));
+        unsafe {
+            let emitter =
+                addr_of_mut!((*inner).sys).cast::<YamlEmitterT>();
+            // Initialization only fails to allocate, which aborts.
+            let _ = yaml_emitter_initialize(emitter);
+            yaml_emitter_set_canonical(emitter, self.canonical);
+            yaml_emitter_set_indent(emitter, self.indent);
+            yaml_emitter_set_width(emitter, self.width);
+            yaml_emitter_set_unicode(emitter, self.unicode);
+            yaml_emitter_set_break(emitter, self.line_break);
+            yaml_emitter_set_output(
+                emitter,
+                write_handler::<W>,
+                inner.cast(),
+            );
+        }
+        Emitter { inner }
+    }
+}
+
+impl Default for EmitterBuilder {
+    fn default() -> Self {
+        EmitterBuilder::new()
+    }
+}
+
+/// A safe, owned YAML emitter.
+///
+/// The emitter owns its [`Sink`] and releases the underlying
+/// `YamlEmitterT` when dropped. Output is flushed to the sink at the end
+/// of every document, or explicitly through [`Emitter::flush`].
+///
+/// ```
+/// use libyml::safe::{
+///     DocumentEnd, DocumentStart, Emitter, Event, Scalar, StreamStart,
+/// };
+/// use libyml::{YamlPlainScalarStyle, YamlUtf8Encoding};
+///
+/// let mut emitter = Emitter::new(Vec::new());
+/// let events = [
+///     Event::StreamStart(StreamStart {
+///         encoding: YamlUtf8Encoding,
+///     }),
+///     Event::DocumentStart(DocumentStart {
+///         implicit: true,
+///         ..Default::default()
+///     }),
+///     Event::Scalar(Scalar {
+///         anchor: None,
+///         tag: None,
+///         value: "hello".into(),
+///         plain_implicit: true,
+///         quoted_implicit: false,
+///         style: YamlPlainScalarStyle,
+///     }),
+///     Event::DocumentEnd(DocumentEnd { implicit: true }),
+///     Event::StreamEnd,
+/// ];
+/// for event in &events {
+///     emitter.emit(event).unwrap();
+/// }
+/// assert_eq!(emitter.into_inner(), b"hello\n");
+/// ```
+pub struct Emitter<W: Sink> {
+    // Owned; the write handler keeps a pointer to it, so it must not
+    // move while the emitter is alive.
+    inner: *mut Inner<W>,
+}
+
+struct Inner<W: Sink> {
+    sys: MaybeUninit<YamlEmitterT>,
+    output: W,
+    // The error behind the last failed write.
+    error: Option<W::Error>,
+}
+
+impl<W: Sink> Emitter<W> {
+    /// Creates an emitter with the default options writing to `output`.
+    pub fn new(output: W) -> Self {
+        EmitterBuilder::new().build(output)
+    }
+
+    /// Emits an event.
+    ///
+    /// The event's strings are copied, so it can be dropped or reused
+    /// once this returns.
+    pub fn emit(&mut self, event: &Event<'_>) -> Result<(), Error> {
+        let mut raw = MaybeUninit::<YamlEventT>::uninit();
+        unsafe {
+            let raw = raw.as_mut_ptr();
+            if event.to_raw(raw).fail {
+                return Err(Error::new(
+                    ErrorKind::Emitter,
+                    "invalid event",
+                ));
+            }
+            // The emitter takes ownership of the event, even on failure.
+            if yaml_emitter_emit(self.as_mut_ptr(), raw).fail {
+                return Err(self.error());
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes any buffered output to the sink and flushes it.
+    ///
+    /// A failed write is reported as an [`ErrorKind::Writer`] error
+    /// whose [`source`](core::error::Error::source) is the sink's error.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        unsafe {
+            if yaml_emitter_flush(self.as_mut_ptr()).fail {
+                return Err(self.error());
+            }
+            if let Err(cause) = (*self.inner).output.flush() {
+                return Err(Error::new(
+                    ErrorKind::Writer,
+                    "flush error",
+                )
+                .with_cause(cause));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a reference to the sink.
+    pub fn get_ref(&self) -> &W {
+        unsafe { &(*self.inner).output }
+    }
+
+    /// Releases the emitter and returns its sink.
+    ///
+    /// Output still buffered in the emitter is discarded; call
+    /// [`Emitter::flush`] first if the stream was not closed.
+    pub fn into_inner(self) -> W {
+        let mut this = ManuallyDrop::new(self);
+        unsafe {
+            yaml_emitter_delete(this.as_mut_ptr());
+            Box::from_raw(this.inner).output
+        }
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut YamlEmitterT {
+        unsafe { addr_of_mut!((*self.inner).sys).cast() }
+    }
+
+    /// Copies the error state out of the failed emitter, together with
+    /// the sink error that caused it, if any.
+    unsafe fn error(&mut self) -> Error {
+        let error = Error::from_emitter(self.as_mut_ptr());
+        match (*self.inner).error.take() {
+            Some(cause) => error.with_cause(cause),
+            None => error,
+        }
+    }
+}
+
+impl<W: Sink> Drop for Emitter<W> {
+    fn drop(&mut self) {
+        unsafe {
+            yaml_emitter_delete(self.as_mut_ptr());
+            drop(Box::from_raw(self.inner));
+        }
+    }
+}
+
+impl<W: Sink> Debug for Emitter<W> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_struct("Emitter").finish_non_exhaustive()
+    }
+}
+
+unsafe fn write_handler<W: Sink>(
+    data: *mut libc::c_void,
+    buffer: *mut libc::c_uchar,
+    size: size_t,
+) -> libc::c_int {
+    let inner = data.cast::<Inner<W>>();
+    let bytes = slice::from_raw_parts(buffer, size as usize);
+    match (*inner).output.write_all(bytes) {
+        Ok(()) => 1,
+        Err(error) => {
+            (*inner).error = Some(error);
+            0
+        }
+    }
+}
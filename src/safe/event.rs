@@ -0,0 +1,517 @@
+use super::{input_cstr, input_str, nul_terminated, owned_cstr};
+use crate::api::ScalarEventData;
+use crate::error::Error;
+use crate::libc;
+use crate::resolver::{self, Resolved};
+use crate::success::{Success, FAIL};
+use crate::yaml::{
+    yaml_char_t, YamlEncodingT, YamlEventT, YamlMappingStyleT,
+    YamlScalarStyleT, YamlSchemaT, YamlSequenceStyleT,
+    YamlTagDirectiveT, YamlVersionDirectiveT,
+};
+use crate::{
+    yaml_alias_event_initialize, yaml_document_end_event_initialize,
+    yaml_document_start_event_initialize,
+    yaml_mapping_end_event_initialize,
+    yaml_mapping_start_event_initialize, yaml_scalar_event_initialize,
+    yaml_sequence_end_event_initialize,
+    yaml_sequence_start_event_initialize,
+    yaml_stream_end_event_initialize,
+    yaml_stream_start_event_initialize, YamlAliasEvent,
+    YamlDocumentEndEvent, YamlDocumentStartEvent, YamlMappingEndEvent,
+    YamlMappingStartEvent, YamlScalarEvent, YamlSequenceEndEvent,
+    YamlSequenceStartEvent, YamlStreamEndEvent, YamlStreamStartEvent,
+};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::ptr;
+
+/// A parsing or emitting event.
+///
+/// Unlike `YamlEventT`, an `Event` owns or borrows its anchors, tags and
+/// values, so it can be freely moved around and dropped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Event<'input> {
+    /// The start of the stream.
+    StreamStart(StreamStart),
+    /// The end of the stream.
+    StreamEnd,
+    /// The start of a document.
+    DocumentStart(DocumentStart<'input>),
+    /// The end of a document.
+    DocumentEnd(DocumentEnd),
+    /// A reference to a previously anchored node.
+    Alias(Alias<'input>),
+    /// A scalar value.
+    Scalar(Scalar<'input>),
+    /// The start of a sequence.
+    SequenceStart(SequenceStart<'input>),
+    /// The end of a sequence.
+    SequenceEnd,
+    /// The start of a mapping.
+    MappingStart(MappingStart<'input>),
+    /// The end of a mapping.
+    MappingEnd,
+}
+
+/// The data of a stream-start event.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StreamStart {
+    /// The stream encoding.
+    pub encoding: YamlEncodingT,
+}
+
+/// A `%YAML` directive.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VersionDirective {
+    /// The major version number.
+    pub major: i32,
+    /// The minor version number.
+    pub minor: i32,
+}
+
+/// A `%TAG` directive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TagDirective<'input> {
+    /// The tag handle, for example `!e!`.
+    pub handle: Cow<'input, str>,
+    /// The tag prefix the handle expands to.
+    pub prefix: Cow<'input, str>,
+}
+
+/// The data of a document-start event.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DocumentStart<'input> {
+    /// The `%YAML` directive, if any.
+    pub version_directive: Option<VersionDirective>,
+    /// The `%TAG` directives.
+    pub tag_directives: Vec<TagDirective<'input>>,
+    /// Is the document start indicator implicit?
+    pub implicit: bool,
+}
+
+/// The data of a document-end event.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DocumentEnd {
+    /// Is the document end indicator implicit?
+    pub implicit: bool,
+}
+
+/// The data of an alias event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alias<'input> {
+    /// The anchor being referred to.
+    pub anchor: Cow<'input, str>,
+}
+
+/// The data of a scalar event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scalar<'input> {
+    /// The anchor, if any.
+    pub anchor: Option<Cow<'input, str>>,
+    /// The resolved tag, if any.
+    pub tag: Option<Cow<'input, str>>,
+    /// The scalar value.
+    pub value: Cow<'input, str>,
+    /// Is the tag optional for the plain style?
+    pub plain_implicit: bool,
+    /// Is the tag optional for any non-plain style?
+    pub quoted_implicit: bool,
+    /// The scalar style.
+    pub style: YamlScalarStyleT,
+}
+
+/// The data of a sequence-start event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SequenceStart<'input> {
+    /// The anchor, if any.
+    pub anchor: Option<Cow<'input, str>>,
+    /// The resolved tag, if any.
+    pub tag: Option<Cow<'input, str>>,
+    /// Is the tag optional?
+    pub implicit: bool,
+    /// The sequence style.
+    pub style: YamlSequenceStyleT,
+}
+
+/// The data of a mapping-start event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MappingStart<'input> {
+    /// The anchor, if any.
+    pub anchor: Option<Cow<'input, str>>,
+    /// The resolved tag, if any.
+    pub tag: Option<Cow<'input, str>>,
+    /// Is the tag optional?
+    pub implicit: bool,
+    /// The mapping style.
+    pub style: YamlMappingStyleT,
+}
+
+impl Event<'_> {
+    /// Copies the data out of a raw event, borrowing the scalar values,
+    /// anchors and tags that appear as they are in `input` instead.
+    ///
+    /// Returns `None` for an empty event. The raw event keeps ownership of
+    /// its buffers and must still be released with `yaml_event_delete`.
+    pub(crate) unsafe fn from_raw(
+        event: *const YamlEventT,
+        input: &[u8],
+    ) -> Option<Event<'_>> {
+        let data = &(*event).data;
+        let mut end = (*event).end_mark.offset;
+        if (*event).type_ == YamlScalarEvent && data.scalar.borrowed {
+            // The anchor and tag come before the value.
+            end = (data.scalar.value as usize)
+                .wrapping_sub(input.as_ptr() as usize)
+                as u64;
+        }
+        let region =
+            input_region(input, (*event).start_mark.offset, end);
+        Some(match (*event).type_ {
+            YamlStreamStartEvent => Event::StreamStart(StreamStart {
+                encoding: data.stream_start.encoding,
+            }),
+            YamlStreamEndEvent => Event::StreamEnd,
+            YamlDocumentStartEvent => {
+                let document_start = &data.document_start;
+                let version_directive = document_start
+                    .version_directive
+                    .as_ref()
+                    .map(|version| VersionDirective {
+                        major: version.major,
+                        minor: version.minor,
+                    });
+                let mut tag_directives = Vec::new();
+                let mut tag_directive =
+                    document_start.tag_directives.start;
+                while tag_directive != document_start.tag_directives.end
+                {
+                    tag_directives.push(TagDirective {
+                        handle: owned_cstr((*tag_directive).handle)
+                            .unwrap_or_default(),
+                        prefix: owned_cstr((*tag_directive).prefix)
+                            .unwrap_or_default(),
+                    });
+                    tag_directive = tag_directive.wrapping_offset(1);
+                }
+                Event::DocumentStart(DocumentStart {
+                    version_directive,
+                    tag_directives,
+                    implicit: document_start.implicit,
+                })
+            }
+            YamlDocumentEndEvent => Event::DocumentEnd(DocumentEnd {
+                implicit: data.document_end.implicit,
+            }),
+            YamlAliasEvent => Event::Alias(Alias {
+                anchor: input_cstr(region, data.alias.anchor)
+                    .unwrap_or_default(),
+            }),
+            YamlScalarEvent => Event::Scalar(Scalar {
+                anchor: input_cstr(region, data.scalar.anchor),
+                tag: input_cstr(region, data.scalar.tag),
+                value: input_str(
+                    input,
+                    data.scalar.value,
+                    data.scalar.length as usize,
+                ),
+                plain_implicit: data.scalar.plain_implicit,
+                quoted_implicit: data.scalar.quoted_implicit,
+                style: data.scalar.style,
+            }),
+            YamlSequenceStartEvent => {
+                Event::SequenceStart(SequenceStart {
+                    anchor: input_cstr(
+                        region,
+                        data.sequence_start.anchor,
+                    ),
+                    tag: input_cstr(region, data.sequence_start.tag),
+                    implicit: data.sequence_start.implicit,
+                    style: data.sequence_start.style,
+                })
+            }
+            YamlSequenceEndEvent => Event::SequenceEnd,
+            YamlMappingStartEvent => {
+                Event::MappingStart(MappingStart {
+                    anchor: input_cstr(
+                        region,
+                        data.mapping_start.anchor,
+                    ),
+                    tag: input_cstr(region, data.mapping_start.tag),
+                    implicit: data.mapping_start.implicit,
+                    style: data.mapping_start.style,
+                })
+            }
+            YamlMappingEndEvent => Event::MappingEnd,
+            _ => return None,
+        })
+    }
+
+    /// Initializes a raw event with a copy of this event's data.
+    ///
+    /// Fails if an anchor, tag or directive contains a NUL byte, or if
+    /// libyml rejects the event. On success the raw event owns its
+    /// buffers and must be released with `yaml_event_delete` or handed
+    /// over to `yaml_emitter_emit`.
+    pub(crate) unsafe fn to_raw(
+        &self,
+        event: *mut YamlEventT,
+    ) -> Success {
+        match self {
+            Event::StreamStart(stream_start) => {
+                yaml_stream_start_event_initialize(
+                    event,
+                    stream_start.encoding,
+                )
+            }
+            Event::StreamEnd => yaml_stream_end_event_initialize(event),
+            Event::DocumentStart(document_start) => {
+                let mut version_directive = document_start
+                    .version_directive
+                    .map(|version| YamlVersionDirectiveT {
+                        major: version.major,
+                        minor: version.minor,
+                    });
+                let mut strings = Vec::new();
+                for tag_directive in &document_start.tag_directives {
+                    match (
+                        nul_terminated(&tag_directive.handle),
+                        nul_terminated(&tag_directive.prefix),
+                    ) {
+                        (Some(handle), Some(prefix)) => {
+                            strings.push((handle, prefix));
+                        }
+                        _ => return FAIL,
+                    }
+                }
+                let mut tag_directives: Vec<YamlTagDirectiveT> =
+                    strings
+                        .iter_mut()
+                        .map(|(handle, prefix)| YamlTagDirectiveT {
+                            handle: handle.as_mut_ptr(),
+                            prefix: prefix.as_mut_ptr(),
+                        })
+                        .collect();
+                let start = tag_directives.as_mut_ptr();
+                yaml_document_start_event_initialize(
+                    event,
+                    version_directive
+                        .as_mut()
+                        .map_or(ptr::null_mut(), |version| version),
+                    start,
+                    start.wrapping_add(tag_directives.len()),
+                    document_start.implicit,
+                )
+            }
+            Event::DocumentEnd(document_end) => {
+                yaml_document_end_event_initialize(
+                    event,
+                    document_end.implicit,
+                )
+            }
+            Event::Alias(alias) => {
+                match nul_terminated(&alias.anchor) {
+                    Some(anchor) => yaml_alias_event_initialize(
+                        event,
+                        anchor.as_ptr(),
+                    ),
+                    None => FAIL,
+                }
+            }
+            Event::Scalar(scalar) => {
+                let (anchor, tag) = match (
+                    optional(scalar.anchor.as_deref()),
+                    optional(scalar.tag.as_deref()),
+                ) {
+                    (Some(anchor), Some(tag)) => (anchor, tag),
+                    _ => return FAIL,
+                };
+                let length =
+                    match libc::c_int::try_from(scalar.value.len()) {
+                        Ok(length) => length,
+                        Err(_) => return FAIL,
+                    };
+                yaml_scalar_event_initialize(
+                    event,
+                    ScalarEventData {
+                        anchor: as_ptr(&anchor),
+                        tag: as_ptr(&tag),
+                        value: scalar.value.as_ptr(),
+                        length,
+                        plain_implicit: scalar.plain_implicit,
+                        quoted_implicit: scalar.quoted_implicit,
+                        style: scalar.style,
+                        _marker: PhantomData,
+                    },
+                )
+            }
+            Event::SequenceStart(sequence_start) => {
+                let (anchor, tag) = match (
+                    optional(sequence_start.anchor.as_deref()),
+                    optional(sequence_start.tag.as_deref()),
+                ) {
+                    (Some(anchor), Some(tag)) => (anchor, tag),
+                    _ => return FAIL,
+                };
+                yaml_sequence_start_event_initialize(
+                    event,
+                    as_ptr(&anchor),
+                    as_ptr(&tag),
+                    sequence_start.implicit,
+                    sequence_start.style,
+                )
+            }
+            Event::SequenceEnd => {
+                yaml_sequence_end_event_initialize(event)
+            }
+            Event::MappingStart(mapping_start) => {
+                let (anchor, tag) = match (
+                    optional(mapping_start.anchor.as_deref()),
+                    optional(mapping_start.tag.as_deref()),
+                ) {
+                    (Some(anchor), Some(tag)) => (anchor, tag),
+                    _ => return FAIL,
+                };
+                yaml_mapping_start_event_initialize(
+                    event,
+                    as_ptr(&anchor),
+                    as_ptr(&tag),
+                    mapping_start.implicit,
+                    mapping_start.style,
+                )
+            }
+            Event::MappingEnd => {
+                yaml_mapping_end_event_initialize(event)
+            }
+        }
+    }
+
+    /// Converts borrowed anchors, tags and values into owned ones.
+    pub fn into_owned(self) -> Event<'static> {
+        fn own(value: Cow<'_, str>) -> Cow<'static, str> {
+            Cow::Owned(value.into_owned())
+        }
+        match self {
+            Event::StreamStart(stream_start) => {
+                Event::StreamStart(stream_start)
+            }
+            Event::StreamEnd => Event::StreamEnd,
+            Event::DocumentStart(document_start) => {
+                Event::DocumentStart(DocumentStart {
+                    version_directive: document_start.version_directive,
+                    tag_directives: document_start
+                        .tag_directives
+                        .into_iter()
+                        .map(|tag_directive| TagDirective {
+                            handle: own(tag_directive.handle),
+                            prefix: own(tag_directive.prefix),
+                        })
+                        .collect(),
+                    implicit: document_start.implicit,
+                })
+            }
+            Event::DocumentEnd(document_end) => {
+                Event::DocumentEnd(document_end)
+            }
+            Event::Alias(alias) => Event::Alias(Alias {
+                anchor: own(alias.anchor),
+            }),
+            Event::Scalar(scalar) => Event::Scalar(Scalar {
+                anchor: scalar.anchor.map(own),
+                tag: scalar.tag.map(own),
+                value: own(scalar.value),
+                plain_implicit: scalar.plain_implicit,
+                quoted_implicit: scalar.quoted_implicit,
+                style: scalar.style,
+            }),
+            Event::SequenceStart(sequence_start) => {
+                Event::SequenceStart(SequenceStart {
+                    anchor: sequence_start.anchor.map(own),
+                    tag: sequence_start.tag.map(own),
+                    implicit: sequence_start.implicit,
+                    style: sequence_start.style,
+                })
+            }
+            Event::SequenceEnd => Event::SequenceEnd,
+            Event::MappingStart(mapping_start) => {
+                Event::MappingStart(MappingStart {
+                    anchor: mapping_start.anchor.map(own),
+                    tag: mapping_start.tag.map(own),
+                    implicit: mapping_start.implicit,
+                    style: mapping_start.style,
+                })
+            }
+            Event::MappingEnd => Event::MappingEnd,
+        }
+    }
+}
+
+impl Scalar<'_> {
+    /// Resolves the tag of the scalar under `schema` and constructs its
+    /// value.
+    ///
+    /// ```
+    /// use libyml::resolver::Value;
+    /// use libyml::safe::{Event, Parser};
+    /// use libyml::YamlCoreSchema;
+    ///
+    /// let values: Vec<_> = Parser::new(b"[1, '1', .inf, ~]\n")
+    ///     .filter_map(|event| match event.unwrap() {
+    ///         Event::Scalar(scalar) => Some(scalar),
+    ///         _ => None,
+    ///     })
+    ///     .collect();
+    /// let values: Vec<_> = values
+    ///     .iter()
+    ///     .map(|scalar| scalar.resolve(YamlCoreSchema).unwrap().value)
+    ///     .collect();
+    /// assert_eq!(
+    ///     values,
+    ///     [
+    ///         Value::Int(1),
+    ///         Value::Str("1"),
+    ///         Value::Float(f64::INFINITY),
+    ///         Value::Null,
+    ///     ],
+    /// );
+    /// ```
+    pub fn resolve(
+        &self,
+        schema: YamlSchemaT,
+    ) -> Result<Resolved<'_>, Error> {
+        resolver::resolve(
+            schema,
+            self.tag.as_deref(),
+            &self.value,
+            self.style,
+        )
+    }
+}
+
+/// The part of `input` between two offsets, clamped to its length.
+fn input_region(input: &[u8], start: u64, end: u64) -> &[u8] {
+    let end = usize::try_from(end)
+        .map_or(input.len(), |end| end.min(input.len()));
+    let start =
+        usize::try_from(start).map_or(end, |start| start.min(end));
+    &input[start..end]
+}
+
+/// Copies an optional anchor or tag into a NUL-terminated buffer.
+///
+/// Returns `None` if the value contains a NUL byte, and `Some(None)` if
+/// there is no value.
+fn optional(value: Option<&str>) -> Option<Option<Vec<u8>>> {
+    match value {
+        Some(value) => nul_terminated(value).map(Some),
+        None => Some(None),
+    }
+}
+
+fn as_ptr(buffer: &Option<Vec<u8>>) -> *const yaml_char_t {
+    buffer
+        .as_ref()
+        .map_or(ptr::null(), |buffer| buffer.as_ptr())
+}
@@ -0,0 +1,112 @@
+use super::Sink;
+use crate::libc;
+use crate::yaml::size_t;
+use alloc::boxed::Box;
+use core::{ptr, slice};
+use std::io::{self, Read, Write};
+
+/// An `io::Read` used as parser input, along with the last error it
+/// raised.
+pub(crate) struct ReadInput<'input> {
+    reader: Box<dyn Read + 'input>,
+    error: Option<io::Error>,
+}
+
+impl<'input> ReadInput<'input> {
+    /// Moves `reader` to the heap and leaks it as a handler data
+    /// pointer, to be released with [`ReadInput::free`].
+    pub(crate) fn new<R: Read + 'input>(reader: R) -> *mut Self {
+        Box::into_raw(Box::new(ReadInput {
+            reader: Box::new(reader),
+            error: None,
+        }))
+    }
+
+    /// Takes the error raised by the last read, if any.
+    pub(crate) unsafe fn take_error(
+        input: *mut Self,
+    ) -> Option<io::Error> {
+        if input.is_null() {
+            return None;
+        }
+        (*input).error.take()
+    }
+
+    pub(crate) unsafe fn free(input: *mut Self) {
+        if !input.is_null() {
+            drop(Box::from_raw(input));
+        }
+    }
+}
+
+/// A `YamlReadHandlerT` pulling from a [`ReadInput`].
+///
+/// Interrupted reads are retried. Any other error is kept in the input
+/// for the parser to report, and fails the read.
+pub(crate) unsafe fn read_handler(
+    data: *mut libc::c_void,
+    buffer: *mut libc::c_uchar,
+    size: size_t,
+    size_read: *mut size_t,
+) -> libc::c_int {
+    let input = data.cast::<ReadInput<'_>>();
+    // The raw buffer is not initialized, which `io::Read` requires.
+    ptr::write_bytes(buffer, 0, size as usize);
+    let buffer = slice::from_raw_parts_mut(buffer, size as usize);
+    loop {
+        match (*input).reader.read(buffer) {
+            Ok(n) => {
+                *size_read = n as size_t;
+                return 1;
+            }
+            Err(error)
+                if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => {
+                (*input).error = Some(error);
+                return 0;
+            }
+        }
+    }
+}
+
+/// A [`Sink`] writing into an `io::Write`.
+///
+/// ```
+/// use libyml::safe::{Emitter, IoSink};
+///
+/// let emitter = Emitter::new(IoSink::new(std::io::stdout()));
+/// # drop(emitter);
+/// ```
+#[derive(Debug, Default)]
+pub struct IoSink<W> {
+    writer: W,
+}
+
+impl<W: Write> IoSink<W> {
+    /// Creates a sink writing into `writer`.
+    pub fn new(writer: W) -> Self {
+        IoSink { writer }
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Sink for IoSink<W> {
+    type Error = io::Error;
+
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.writer.write_all(bytes)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,126 @@
+//! Safe wrappers around the parser, emitter and document APIs.
+//!
+//! The types in this module own the underlying `YamlParserT`,
+//! `YamlEmitterT` and `YamlDocumentT` structures, release them on drop,
+//! and expose their data through plain Rust enums and strings instead
+//! of raw pointers and unions.
+//!
+//! ```
+//! use libyml::safe::{Event, Parser};
+//!
+//! let mut values = Vec::new();
+//! for event in Parser::new(b"key: value\n") {
+//!     if let Event::Scalar(scalar) = event.unwrap() {
+//!         values.push(scalar.value.into_owned());
+//!     }
+//! }
+//! assert_eq!(values, ["key", "value"]);
+//! ```
+
+mod document;
+mod emitter;
+mod event;
+#[cfg(feature = "std")]
+mod io;
+mod parser;
+mod push;
+
+pub use self::document::{
+    Document, Items, Loader, MappingNode, Node, Pairs, ScalarNode,
+    SequenceNode,
+};
+pub use self::emitter::{Emitter, EmitterBuilder, FmtSink, Sink};
+pub use self::event::{
+    Alias, DocumentEnd, DocumentStart, Event, MappingStart, Scalar,
+    SequenceStart, StreamStart, TagDirective, VersionDirective,
+};
+#[cfg(feature = "std")]
+pub use self::io::IoSink;
+pub use self::parser::Parser;
+pub use self::push::{PushEvent, PushParser};
+
+use crate::externs::strlen;
+use crate::libc;
+use crate::yaml::yaml_char_t;
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::{slice, str};
+
+/// Copies `length` bytes starting at `ptr` into an owned string.
+///
+/// Invalid UTF-8 sequences, which the reader never lets through, are
+/// replaced rather than trusted.
+pub(crate) unsafe fn owned_str(
+    ptr: *const yaml_char_t,
+    length: usize,
+) -> Cow<'static, str> {
+    if length == 0 {
+        return Cow::Borrowed("");
+    }
+    let bytes = slice::from_raw_parts(ptr, length);
+    Cow::Owned(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Borrows the `length` bytes starting at `ptr` from `input`, or copies
+/// them if they do not lie in it.
+pub(crate) unsafe fn input_str(
+    input: &[u8],
+    ptr: *const yaml_char_t,
+    length: usize,
+) -> Cow<'_, str> {
+    let offset = (ptr as usize).wrapping_sub(input.as_ptr() as usize);
+    let bytes = offset
+        .checked_add(length)
+        .and_then(|end| input.get(offset..end));
+    match bytes.map(str::from_utf8) {
+        Some(Ok(value)) => Cow::Borrowed(value),
+        _ => owned_str(ptr, length),
+    }
+}
+
+/// Borrows a NUL-terminated anchor or tag from `region` of the input,
+/// if it appears there as it is, or copies it otherwise. Null pointers
+/// map to `None`.
+pub(crate) unsafe fn input_cstr<'input>(
+    region: &'input [u8],
+    ptr: *const yaml_char_t,
+) -> Option<Cow<'input, str>> {
+    if ptr.is_null() {
+        return None;
+    }
+    let length = strlen(ptr.cast::<libc::c_char>()) as usize;
+    let bytes = slice::from_raw_parts(ptr, length);
+    let found = region
+        .windows(length.max(1))
+        .find(|window| *window == bytes)
+        .and_then(|window| str::from_utf8(window).ok());
+    match found {
+        Some(value) => Some(Cow::Borrowed(value)),
+        None => Some(owned_str(ptr, length)),
+    }
+}
+
+/// Copies a NUL-terminated string into an owned string, mapping null
+/// pointers to `None`.
+pub(crate) unsafe fn owned_cstr(
+    ptr: *const yaml_char_t,
+) -> Option<Cow<'static, str>> {
+    if ptr.is_null() {
+        return None;
+    }
+    let length = strlen(ptr.cast::<libc::c_char>()) as usize;
+    Some(owned_str(ptr, length))
+}
+
+/// Copies `value` into a NUL-terminated buffer, or returns `None` if it
+/// already contains a NUL byte.
+pub(crate) fn nul_terminated(value: &str) -> Option<Vec<u8>> {
+    if value.as_bytes().contains(&0) {
+        return None;
+    }
+    let mut buffer = Vec::with_capacity(value.len() + 1);
+    buffer.extend_from_slice(value.as_bytes());
+    buffer.push(0);
+    Some(buffer)
+}
@@ -0,0 +1,287 @@
+#[cfg(feature = "std")]
+use super::io::{read_handler, ReadInput};
+use super::Event;
+use crate::error::Error;
+use crate::json::{yaml_parser_set_json, JsonSyntax};
+use crate::yaml::{YamlEventT, YamlMarkT, YamlParserT, YamlSchemaT};
+#[cfg(feature = "std")]
+use crate::yaml_parser_set_input;
+use crate::{
+    yaml_event_delete, yaml_parser_delete, yaml_parser_initialize,
+    yaml_parser_parse, yaml_parser_set_borrow_scalars,
+    yaml_parser_set_input_string, yaml_parser_set_max_alias_expansion,
+    yaml_parser_set_merge_keys, yaml_parser_set_schema,
+    yaml_parser_set_unique_keys,
+};
+use alloc::boxed::Box;
+use core::fmt::{self, Debug};
+use core::mem::MaybeUninit;
+#[cfg(feature = "std")]
+use core::ptr;
+
+/// A safe, owned YAML parser.
+///
+/// The parser borrows its input for `'input` and produces a stream of
+/// [`Event`]s through its `Iterator` implementation. The underlying
+/// `YamlParserT` is released when the parser is dropped.
+pub struct Parser<'input> {
+    sys: Box<YamlParserT>,
+    start_mark: YamlMarkT,
+    end_mark: YamlMarkT,
+    done: bool,
+    // Owned; the read handler keeps a pointer to it. Null when reading
+    // from a slice.
+    #[cfg(feature = "std")]
+    reader: *mut ReadInput<'input>,
+    // Empty when reading from a reader.
+    input: &'input [u8],
+}
+
+impl<'input> Parser<'input> {
+    /// Creates a parser reading from `input`.
+    ///
+    /// Scalar values, anchors and tags that appear in UTF-8 input as they
+    /// are borrow from it, and only the ones that need unescaping or
+    /// folding are allocated.
+    ///
+    /// ```
+    /// use libyml::safe::{Event, Parser};
+    /// use std::borrow::Cow;
+    ///
+    /// let input = "a: 'b'\nc: \"d\\n\"\n";
+    /// let values: Vec<_> = Parser::new(input.as_bytes())
+    ///     .filter_map(|event| match event.unwrap() {
+    ///         Event::Scalar(scalar) => Some(scalar.value),
+    ///         _ => None,
+    ///     })
+    ///     .collect();
+    /// assert!(matches!(values[1], Cow::Borrowed("b")));
+    /// assert!(matches!(values[3], Cow::Owned(_)));
+    /// ```
+    pub fn new(input: &'input [u8]) -> Self {
+        let mut parser = Parser::uninitialized();
+        parser.input = input;
+        unsafe {
+            yaml_parser_set_input_string(
+                parser.as_mut_ptr(),
+                input.as_ptr(),
+                input.len() as u64,
+            );
+            yaml_parser_set_borrow_scalars(parser.as_mut_ptr(), true);
+        }
+        parser
+    }
+
+    /// Creates a parser pulling its input from `reader`.
+    ///
+    /// An `io::Error` raised by the reader fails the parser with a
+    /// reader error whose [`source`](core::error::Error::source) is the
+    /// original `io::Error`.
+    ///
+    /// ```
+    /// use libyml::safe::{Event, Parser};
+    /// use std::io::Cursor;
+    ///
+    /// let reader = Cursor::new("- a\n- b\n");
+    /// let values: Vec<_> = Parser::from_reader(reader)
+    ///     .filter_map(|event| match event.unwrap() {
+    ///         Event::Scalar(scalar) => Some(scalar.value),
+    ///         _ => None,
+    ///     })
+    ///     .collect();
+    /// assert_eq!(values, ["a", "b"]);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: std::io::Read + 'input>(reader: R) -> Self {
+        let mut parser = Parser::uninitialized();
+        parser.reader = ReadInput::new(reader);
+        unsafe {
+            yaml_parser_set_input(
+                parser.as_mut_ptr(),
+                read_handler,
+                parser.reader.cast(),
+            );
+        }
+        parser
+    }
+
+    /// Creates a parser that still needs its input to be set.
+    pub(super) fn uninitialized() -> Self {
+        let mut sys = Box::new(MaybeUninit::<YamlParserT>::uninit());
+        let sys = unsafe {
+            // Initialization only fails to allocate, which aborts.
+            let _ = yaml_parser_initialize(sys.as_mut_ptr());
+            Box::from_raw(Box::into_raw(sys).cast::<YamlParserT>())
+        };
+        let mark = YamlMarkT {
+            index: 0,
+            line: 0,
+            column: 0,
+            offset: 0,
+            utf16_column: 0,
+        };
+        Parser {
+            sys,
+            start_mark: mark,
+            end_mark: mark,
+            done: false,
+            #[cfg(feature = "std")]
+            reader: ptr::null_mut(),
+            input: &[],
+        }
+    }
+
+    /// Makes the parser read its input as JSON, in the given syntax,
+    /// rather than as YAML. It must be called before the first event.
+    ///
+    /// JSON is parsed into the events of the same text read as YAML;
+    /// see the [`json`](crate::json) module.
+    pub fn set_json(&mut self, syntax: JsonSyntax) {
+        unsafe { yaml_parser_set_json(self.as_mut_ptr(), syntax) }
+    }
+
+    /// Sets the schema the loader resolves untagged scalars with, the
+    /// failsafe schema by default.
+    ///
+    /// Scalar events are not affected; pass [`schema`](Self::schema) to
+    /// [`Scalar::resolve`](super::Scalar::resolve) to resolve them.
+    pub fn set_schema(&mut self, schema: YamlSchemaT) {
+        unsafe { yaml_parser_set_schema(self.as_mut_ptr(), schema) }
+    }
+
+    /// The schema resolving untagged scalars.
+    pub fn schema(&self) -> YamlSchemaT {
+        self.sys.schema
+    }
+
+    /// Sets whether the loader applies `<<` merge keys, which it does
+    /// not by default.
+    ///
+    /// Merged pairs can be told apart with
+    /// [`MappingNode::is_merged`](super::MappingNode::is_merged).
+    pub fn set_merge_keys(&mut self, merge_keys: bool) {
+        unsafe {
+            yaml_parser_set_merge_keys(self.as_mut_ptr(), merge_keys)
+        }
+    }
+
+    /// Sets whether the loader fails on mappings with duplicate keys,
+    /// which it does not by default.
+    ///
+    /// Scalar keys are compared by the value they resolve to under the
+    /// [`schema`](Self::schema).
+    pub fn set_unique_keys(&mut self, unique_keys: bool) {
+        unsafe {
+            yaml_parser_set_unique_keys(self.as_mut_ptr(), unique_keys)
+        }
+    }
+
+    /// Sets how many nodes the aliases of a document may stand for in
+    /// the loader, and in the Serde deserializer, 1000000 by default.
+    ///
+    /// See `yaml_parser_set_max_alias_expansion`.
+    pub fn set_max_alias_expansion(
+        &mut self,
+        max_alias_expansion: u64,
+    ) {
+        unsafe {
+            yaml_parser_set_max_alias_expansion(
+                self.as_mut_ptr(),
+                max_alias_expansion,
+            );
+        }
+    }
+
+    /// How many nodes the aliases of a document may stand for.
+    pub fn max_alias_expansion(&self) -> u64 {
+        self.sys.max_alias_expansion
+    }
+
+    /// The position where the most recently produced event starts.
+    pub fn start_mark(&self) -> YamlMarkT {
+        self.start_mark
+    }
+
+    /// The position where the most recently produced event ends.
+    pub fn end_mark(&self) -> YamlMarkT {
+        self.end_mark
+    }
+
+    /// Produces the next event, or `None` once the stream has ended.
+    pub fn next_event(
+        &mut self,
+    ) -> Option<Result<Event<'input>, Error>> {
+        if self.done {
+            return None;
+        }
+        let mut event = MaybeUninit::<YamlEventT>::uninit();
+        unsafe {
+            let parser = self.as_mut_ptr();
+            let event = event.as_mut_ptr();
+            if yaml_parser_parse(parser, event).fail {
+                self.done = true;
+                return Some(Err(self.error()));
+            }
+            self.start_mark = (*event).start_mark;
+            self.end_mark = (*event).end_mark;
+            let next = Event::from_raw(event, self.input);
+            yaml_event_delete(event);
+            match next {
+                Some(Event::StreamEnd) | None => self.done = true,
+                Some(_) => {}
+            }
+            next.map(Ok)
+        }
+    }
+
+    /// Has the stream ended, or the parser failed?
+    pub(super) fn is_done(&self) -> bool {
+        self.done
+    }
+
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut YamlParserT {
+        &mut *self.sys
+    }
+
+    /// Copies the error state out of the failed parser, together with
+    /// the I/O error that caused it, if any.
+    pub(crate) fn error(&mut self) -> Error {
+        let error = unsafe { Error::from_parser(self.as_mut_ptr()) };
+        #[cfg(feature = "std")]
+        if let Some(cause) =
+            unsafe { ReadInput::take_error(self.reader) }
+        {
+            return error.with_cause(cause);
+        }
+        error
+    }
+}
+
+impl<'input> Iterator for Parser<'input> {
+    type Item = Result<Event<'input>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event()
+    }
+}
+
+impl Drop for Parser<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            yaml_parser_delete(&mut *self.sys);
+            #[cfg(feature = "std")]
+            ReadInput::free(self.reader);
+        }
+    }
+}
+
+impl Debug for Parser<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("Parser")
+            .field("start_mark", &self.start_mark)
+            .field("end_mark", &self.end_mark)
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
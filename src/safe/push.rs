@@ -0,0 +1,155 @@
+use super::{Event, Parser};
+use crate::error::Error;
+use crate::json::JsonSyntax;
+use crate::push::{
+    push_read_handler, PushInput, ScanAhead, ScannerState,
+};
+use crate::yaml::YamlMarkT;
+use crate::yaml_parser_set_input;
+use alloc::boxed::Box;
+use core::fmt::{self, Debug};
+
+/// The outcome of [`PushParser::next_event`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PushEvent {
+    /// The next event.
+    Event(Event<'static>),
+    /// The next event needs more input, passed to
+    /// [`feed`](PushParser::feed), or the end of the input, signaled
+    /// with [`finish`](PushParser::finish).
+    NeedMoreInput,
+}
+
+/// A YAML parser its input is pushed to, for non-blocking I/O.
+///
+/// The input is fed in chunks as they arrive, and
+/// [`next_event`](Self::next_event) produces the events these complete.
+/// Only the bytes of the tokens not fully scanned yet are kept around,
+/// so the whole input never needs to be buffered.
+///
+/// ```
+/// use libyml::safe::{Event, PushEvent, PushParser};
+///
+/// let mut parser = PushParser::new();
+/// let mut values = Vec::new();
+/// let mut chunks = ["- one\n- t", "wo\n"].into_iter();
+/// while let Some(event) = parser.next_event() {
+///     match event.unwrap() {
+///         PushEvent::Event(Event::Scalar(scalar)) => {
+///             values.push(scalar.value)
+///         }
+///         PushEvent::Event(_) => {}
+///         PushEvent::NeedMoreInput => match chunks.next() {
+///             Some(chunk) => parser.feed(chunk.as_bytes()),
+///             None => parser.finish(),
+///         },
+///     }
+/// }
+/// assert_eq!(values, ["one", "two"]);
+/// ```
+pub struct PushParser {
+    parser: Parser<'static>,
+    // Owned; the read handler keeps a pointer to it.
+    input: *mut PushInput,
+    state: ScannerState,
+    // Set once the scanner failed ahead of the parser.
+    failed: bool,
+}
+
+impl PushParser {
+    /// Creates a parser waiting for its input to be fed.
+    pub fn new() -> Self {
+        let mut parser = Parser::uninitialized();
+        let input = Box::into_raw(Box::<PushInput>::default());
+        unsafe {
+            yaml_parser_set_input(
+                parser.as_mut_ptr(),
+                push_read_handler,
+                input.cast(),
+            );
+        }
+        PushParser {
+            parser,
+            input,
+            state: ScannerState::default(),
+            failed: false,
+        }
+    }
+
+    /// Makes the parser read its input as JSON, in the given syntax,
+    /// rather than as YAML. It must be called before the first event.
+    pub fn set_json(&mut self, syntax: JsonSyntax) {
+        self.parser.set_json(syntax);
+    }
+
+    /// Appends `bytes` to the input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the input is already [finished](Self::finish).
+    pub fn feed(&mut self, bytes: &[u8]) {
+        unsafe { (*self.input).feed(bytes) }
+    }
+
+    /// Signals the end of the input.
+    pub fn finish(&mut self) {
+        unsafe { (*self.input).finish() }
+    }
+
+    /// The position where the most recently produced event starts.
+    pub fn start_mark(&self) -> YamlMarkT {
+        self.parser.start_mark()
+    }
+
+    /// The position where the most recently produced event ends.
+    pub fn end_mark(&self) -> YamlMarkT {
+        self.parser.end_mark()
+    }
+
+    /// Produces the next event, [`PushEvent::NeedMoreInput`] if the
+    /// input fed so far is not enough for it, or `None` once the stream
+    /// has ended.
+    pub fn next_event(&mut self) -> Option<Result<PushEvent, Error>> {
+        if !self.failed && !self.parser.is_done() {
+            unsafe {
+                if (*self.input).is_starved() {
+                    return Some(Ok(PushEvent::NeedMoreInput));
+                }
+                let parser = self.parser.as_mut_ptr();
+                match self.state.scan_ahead(parser, self.input) {
+                    ScanAhead::Ready => {}
+                    ScanAhead::NeedMoreInput => {
+                        return Some(Ok(PushEvent::NeedMoreInput));
+                    }
+                    // The parser runs into the error on its own.
+                    ScanAhead::Failed => self.failed = true,
+                }
+            }
+        }
+        let event = self.parser.next_event()?;
+        Some(event.map(PushEvent::Event))
+    }
+}
+
+impl Default for PushParser {
+    fn default() -> Self {
+        PushParser::new()
+    }
+}
+
+impl Drop for PushParser {
+    fn drop(&mut self) {
+        unsafe { drop(Box::from_raw(self.input)) }
+    }
+}
+
+impl Debug for PushParser {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("PushParser")
+            .field("start_mark", &self.parser.start_mark())
+            .field("end_mark", &self.parser.end_mark())
+            .field("failed", &self.failed)
+            .finish_non_exhaustive()
+    }
+}
@@ -1,10 +1,13 @@
 use crate::api::{
     yaml_free, yaml_malloc, yaml_queue_extend, yaml_stack_extend,
-    yaml_string_extend, yaml_string_join,
+    yaml_string_extend, yaml_string_join, yaml_token_delete,
 };
 use crate::externs::{memcpy, memmove, memset, strcmp, strlen};
+use crate::json::yaml_parser_fetch_json_token;
 use crate::ops::{ForceAdd as _, ForceMul as _};
-use crate::reader::yaml_parser_update_buffer;
+use crate::reader::{
+    yaml_parser_input_width, yaml_parser_update_buffer,
+};
 use crate::success::{Success, FAIL, OK};
 use crate::yaml::{
     ptrdiff_t, size_t, yaml_char_t, YamlStringT, NULL_STRING,
@@ -12,22 +15,26 @@ use crate::yaml::{
 use crate::{
     libc, PointerExt, YamlAliasToken, YamlAnchorToken,
     YamlBlockEndToken, YamlBlockEntryToken, YamlBlockMappingStartToken,
-    YamlBlockSequenceStartToken, YamlDocumentEndToken,
-    YamlDocumentStartToken, YamlDoubleQuotedScalarStyle,
-    YamlFlowEntryToken, YamlFlowMappingEndToken,
-    YamlFlowMappingStartToken, YamlFlowSequenceEndToken,
-    YamlFlowSequenceStartToken, YamlFoldedScalarStyle, YamlKeyToken,
+    YamlBlockSequenceStartToken, YamlCommentToken, YamlCommentTypeT,
+    YamlDocumentComment, YamlDocumentEndToken, YamlDocumentStartToken,
+    YamlDoubleQuotedScalarStyle, YamlFlowEntryToken,
+    YamlFlowMappingEndToken, YamlFlowMappingStartToken,
+    YamlFlowSequenceEndToken, YamlFlowSequenceStartToken,
+    YamlFoldedScalarStyle, YamlKeyToken, YamlLeadingComment,
     YamlLiteralScalarStyle, YamlMarkT, YamlMemoryError, YamlNoError,
-    YamlParserT, YamlPlainScalarStyle, YamlScalarToken,
+    YamlNoToken, YamlParserT, YamlPlainScalarStyle, YamlScalarToken,
     YamlScannerError, YamlSimpleKeyT, YamlSingleQuotedScalarStyle,
     YamlStreamEndToken, YamlStreamStartToken, YamlTagDirectiveToken,
-    YamlTagToken, YamlTokenT, YamlTokenTypeT, YamlValueToken,
-    YamlVersionDirectiveToken,
+    YamlTagToken, YamlTokenT, YamlTokenTypeT, YamlTrailingComment,
+    YamlUtf8Encoding, YamlValueToken, YamlVersionDirectiveToken,
 };
 use core::mem::{size_of, MaybeUninit};
-use core::ptr::{self, addr_of_mut};
+use core::ptr::{self, addr_of, addr_of_mut};
 
-unsafe fn cache(parser: *mut YamlParserT, length: size_t) -> Success {
+pub(crate) unsafe fn cache(
+    parser: *mut YamlParserT,
+    length: size_t,
+) -> Success {
     if (*parser).unread >= length {
         OK
     } else {
@@ -35,41 +42,67 @@ unsafe fn cache(parser: *mut YamlParserT, length: size_t) -> Success {
     }
 }
 
-unsafe fn skip(parser: *mut YamlParserT) {
+/// Moves the mark past a character that is not a line break, `width`
+/// bytes long in the buffer.
+unsafe fn advance(parser: *mut YamlParserT, width: usize) {
+    let offset = yaml_parser_input_width(parser, width, 1);
+    let mark = &mut (*parser).mark;
+    mark.index = mark.index.force_add(width as u64);
+    mark.offset = mark.offset.force_add(offset);
+    mark.column = mark.column.force_add(1);
+    // Only characters outside the Basic Multilingual Plane, the ones
+    // four bytes long in UTF-8, take a surrogate pair in UTF-16.
+    let units = if width == 4 { 2 } else { 1 };
+    mark.utf16_column = mark.utf16_column.force_add(units);
+}
+
+/// Moves the mark past a line break of `chars` characters, `width`
+/// bytes long in the buffer.
+unsafe fn advance_line(
+    parser: *mut YamlParserT,
+    width: usize,
+    chars: usize,
+) {
+    let offset = yaml_parser_input_width(parser, width, chars);
+    let mark = &mut (*parser).mark;
+    mark.index = mark.index.force_add(width as u64);
+    mark.offset = mark.offset.force_add(offset);
+    mark.line = mark.line.force_add(1);
+    mark.column = 0;
+    mark.utf16_column = 0;
+}
+
+pub(crate) unsafe fn skip(parser: *mut YamlParserT) {
     let width = WIDTH!((*parser).buffer);
-    (*parser).mark.index = (*parser).mark.index.force_add(width as u64);
-    (*parser).mark.column = (*parser).mark.column.force_add(1);
+    advance(parser, width);
     (*parser).unread = (*parser).unread.wrapping_sub(1);
     (*parser).buffer.pointer =
-        (*parser).buffer.pointer.wrapping_offset(width as isize);
+        (*parser).buffer.pointer.wrapping_add(width);
 }
 
-unsafe fn skip_line(parser: *mut YamlParserT) {
+pub(crate) unsafe fn skip_line(parser: *mut YamlParserT) {
     if IS_CRLF!((*parser).buffer) {
-        (*parser).mark.index = (*parser).mark.index.force_add(2);
-        (*parser).mark.column = 0;
-        (*parser).mark.line = (*parser).mark.line.force_add(1);
+        advance_line(parser, 2, 2);
         (*parser).unread = (*parser).unread.wrapping_sub(2);
         (*parser).buffer.pointer =
             (*parser).buffer.pointer.wrapping_offset(2);
     } else if IS_BREAK!((*parser).buffer) {
         let width = WIDTH!((*parser).buffer);
-        (*parser).mark.index =
-            (*parser).mark.index.force_add(width as u64);
-        (*parser).mark.column = 0;
-        (*parser).mark.line = (*parser).mark.line.force_add(1);
+        advance_line(parser, width, 1);
         (*parser).unread = (*parser).unread.wrapping_sub(1);
         (*parser).buffer.pointer =
-            (*parser).buffer.pointer.wrapping_offset(width as isize);
+            (*parser).buffer.pointer.wrapping_add(width);
     };
 }
 
-unsafe fn read(parser: *mut YamlParserT, string: *mut YamlStringT) {
+pub(crate) unsafe fn read(
+    parser: *mut YamlParserT,
+    string: *mut YamlStringT,
+) {
     STRING_EXTEND!(*string);
     let width = WIDTH!((*parser).buffer);
     copy!(*string, (*parser).buffer);
-    (*parser).mark.index = (*parser).mark.index.force_add(width as u64);
-    (*parser).mark.column = (*parser).mark.column.force_add(1);
+    advance(parser, width);
     (*parser).unread = (*parser).unread.wrapping_sub(1);
 }
 
@@ -85,9 +118,7 @@ unsafe fn read_line(
         (*string).pointer = (*string).pointer.wrapping_offset(1);
         (*parser).buffer.pointer =
             (*parser).buffer.pointer.wrapping_offset(2);
-        (*parser).mark.index = (*parser).mark.index.force_add(2);
-        (*parser).mark.column = 0;
-        (*parser).mark.line = (*parser).mark.line.force_add(1);
+        advance_line(parser, 2, 2);
         (*parser).unread = (*parser).unread.wrapping_sub(2);
     } else if CHECK_AT!((*parser).buffer, b'\r', 0)
         || CHECK_AT!((*parser).buffer, b'\n', 0)
@@ -96,9 +127,7 @@ unsafe fn read_line(
         (*string).pointer = (*string).pointer.wrapping_offset(1);
         (*parser).buffer.pointer =
             (*parser).buffer.pointer.wrapping_offset(1);
-        (*parser).mark.index = (*parser).mark.index.force_add(1);
-        (*parser).mark.column = 0;
-        (*parser).mark.line = (*parser).mark.line.force_add(1);
+        advance_line(parser, 1, 1);
         (*parser).unread = (*parser).unread.wrapping_sub(1);
     } else if CHECK_AT!((*parser).buffer, b'\xC2', 0)
         && CHECK_AT!((*parser).buffer, b'\x85', 1)
@@ -107,9 +136,7 @@ unsafe fn read_line(
         (*string).pointer = (*string).pointer.wrapping_offset(1);
         (*parser).buffer.pointer =
             (*parser).buffer.pointer.wrapping_offset(2);
-        (*parser).mark.index = (*parser).mark.index.force_add(2);
-        (*parser).mark.column = 0;
-        (*parser).mark.line = (*parser).mark.line.force_add(1);
+        advance_line(parser, 2, 1);
         (*parser).unread = (*parser).unread.wrapping_sub(1);
     } else if CHECK_AT!((*parser).buffer, b'\xE2', 0)
         && CHECK_AT!((*parser).buffer, b'\x80', 1)
@@ -128,9 +155,7 @@ unsafe fn read_line(
         (*string).pointer = (*string).pointer.wrapping_offset(1);
         (*parser).buffer.pointer =
             (*parser).buffer.pointer.wrapping_offset(1);
-        (*parser).mark.index = (*parser).mark.index.force_add(3);
-        (*parser).mark.column = 0;
-        (*parser).mark.line = (*parser).mark.line.force_add(1);
+        advance_line(parser, 3, 1);
         (*parser).unread = (*parser).unread.wrapping_sub(1);
     };
 }
@@ -200,7 +225,7 @@ pub unsafe fn yaml_parser_scan(
     OK
 }
 
-unsafe fn yaml_parser_set_scanner_error(
+pub(crate) unsafe fn yaml_parser_set_scanner_error(
     parser: *mut YamlParserT,
     context: *const libc::c_char,
     context_mark: YamlMarkT,
@@ -215,6 +240,31 @@ unsafe fn yaml_parser_set_scanner_error(
     (*parser).problem_mark = (*parser).mark;
 }
 
+/// Fails once `string` holds more than `max` bytes, reporting `problem`
+/// at `mark`, where the text that did not fit starts.
+unsafe fn yaml_parser_check_length(
+    parser: *mut YamlParserT,
+    string: *const YamlStringT,
+    max: size_t,
+    context: *const libc::c_char,
+    context_mark: YamlMarkT,
+    problem: *const libc::c_char,
+    mark: YamlMarkT,
+) -> Success {
+    if (*string).pointer.c_offset_from((*string).start) as size_t <= max
+    {
+        return OK;
+    }
+    yaml_parser_set_scanner_error(
+        parser,
+        context,
+        context_mark,
+        problem,
+    );
+    (*parser).problem_mark = mark;
+    FAIL
+}
+
 pub(crate) unsafe fn yaml_parser_fetch_more_tokens(
     parser: *mut YamlParserT,
 ) -> Success {
@@ -225,7 +275,9 @@ pub(crate) unsafe fn yaml_parser_fetch_more_tokens(
             need_more_tokens = true;
         } else {
             let mut simple_key: *mut YamlSimpleKeyT;
-            if yaml_parser_stale_simple_keys(parser).fail {
+            if (*parser).tokens_parsed >= (*parser).stale_keys_from
+                && yaml_parser_stale_simple_keys(parser).fail
+            {
                 return FAIL;
             }
             simple_key = (*parser)
@@ -247,7 +299,7 @@ pub(crate) unsafe fn yaml_parser_fetch_more_tokens(
         if !need_more_tokens {
             break;
         }
-        if yaml_parser_fetch_next_token(parser).fail {
+        if yaml_parser_fetch_token(parser).fail {
             return FAIL;
         }
     }
@@ -255,12 +307,80 @@ pub(crate) unsafe fn yaml_parser_fetch_more_tokens(
     OK
 }
 
+/// Fetches the next token, or the few ones it completes, into the
+/// queue.
+pub(crate) unsafe fn yaml_parser_fetch_token(
+    parser: *mut YamlParserT,
+) -> Success {
+    if yaml_parser_fetch_next_token(parser).fail {
+        return FAIL;
+    }
+    if (*parser).tokens.tail.c_offset_from((*parser).tokens.head)
+        as size_t
+        > (*parser).max_queued_tokens
+    {
+        yaml_parser_set_scanner_error(
+            parser,
+            ptr::null::<libc::c_char>(),
+            (*parser).mark,
+            b"token queue limit exceeded\0" as *const u8
+                as *const libc::c_char,
+        );
+        return FAIL;
+    }
+    (*parser).token_end_mark =
+        (*(*parser).tokens.tail.wrapping_offset(-1)).end_mark;
+    OK
+}
+
+/// Moves the trailing comment right after the token at the head of the
+/// queue, if any, out of the queue and into `comment`. Otherwise the
+/// type of `comment` is left as YamlNoToken.
+///
+/// The head token must be available and must not be STREAM-END.
+pub(crate) unsafe fn yaml_parser_fetch_trailing_comment(
+    parser: *mut YamlParserT,
+    comment: *mut YamlTokenT,
+) -> Success {
+    memset(
+        comment as *mut libc::c_void,
+        0,
+        size_of::<YamlTokenT>() as libc::c_ulong,
+    );
+    if (*parser).tokens.head.wrapping_offset(1) == (*parser).tokens.tail
+    {
+        if yaml_parser_fetch_next_token(parser).fail {
+            return FAIL;
+        }
+        (*parser).token_end_mark =
+            (*(*parser).tokens.tail.wrapping_offset(-1)).end_mark;
+    }
+    // Fetching may have moved the queue.
+    let head: *mut YamlTokenT = (*parser).tokens.head;
+    let next: *mut YamlTokenT = head.wrapping_offset(1);
+    if (*next).type_ != YamlCommentToken
+        || (*next).data.comment.type_ != YamlTrailingComment
+    {
+        return OK;
+    }
+    *comment = *next;
+    // The head token takes the place of the comment, which then counts
+    // as parsed for the simple keys further on.
+    *next = *head;
+    (*parser).tokens.head = next;
+    (*parser).tokens_parsed = (*parser).tokens_parsed.wrapping_add(1);
+    OK
+}
+
 unsafe fn yaml_parser_fetch_next_token(
     parser: *mut YamlParserT,
 ) -> Success {
     if cache(parser, 1_u64).fail {
         return FAIL;
     }
+    if (*parser).json {
+        return yaml_parser_fetch_json_token(parser);
+    }
     if !(*parser).stream_start_produced {
         yaml_parser_fetch_stream_start(parser);
         return OK;
@@ -271,6 +391,27 @@ unsafe fn yaml_parser_fetch_next_token(
     if yaml_parser_stale_simple_keys(parser).fail {
         return FAIL;
     }
+    // YAML 1.2 requires the lines of a flow collection to be indented
+    // more than the block collection around it. Closing brackets are
+    // let through, as they commonly line up with the key.
+    if (*parser).strict
+        && (*parser).flow_level != 0
+        && (*parser).mark.line != (*parser).token_end_mark.line
+        && ((*parser).mark.column as libc::c_int) <= (*parser).indent
+        && !(IS_Z!((*parser).buffer)
+            || CHECK!((*parser).buffer, b']')
+            || CHECK!((*parser).buffer, b'}'))
+    {
+        yaml_parser_set_scanner_error(
+            parser,
+            b"while scanning for the next token\0" as *const u8
+                as *const libc::c_char,
+            (*parser).mark,
+            b"found a line with insufficient indentation\0" as *const u8
+                as *const libc::c_char,
+        );
+        return FAIL;
+    }
     yaml_parser_unroll_indent(
         parser,
         (*parser).mark.column as ptrdiff_t,
@@ -278,6 +419,9 @@ unsafe fn yaml_parser_fetch_next_token(
     if cache(parser, 4_u64).fail {
         return FAIL;
     }
+    if !QUEUE_EMPTY!((*parser).pending_comments) {
+        yaml_parser_fetch_pending_comments(parser);
+    }
     if IS_Z!((*parser).buffer) {
         return yaml_parser_fetch_stream_end(parser);
     }
@@ -346,8 +490,11 @@ unsafe fn yaml_parser_fetch_next_token(
         return yaml_parser_fetch_key(parser);
     }
     if CHECK!((*parser).buffer, b':')
-        && ((*parser).flow_level != 0
-            || IS_BLANKZ_AT!((*parser).buffer, 1))
+        && ((*parser).flow_level != 0 && !(*parser).strict
+            || IS_BLANKZ_AT!((*parser).buffer, 1)
+            || (*parser).flow_level != 0
+                && (IS_FLOW_INDICATOR_AT!((*parser).buffer, 1)
+                    || yaml_parser_adjacent_value_allowed(parser)))
     {
         return yaml_parser_fetch_value(parser);
     }
@@ -398,6 +545,7 @@ unsafe fn yaml_parser_fetch_next_token(
             && (CHECK!((*parser).buffer, b'?')
                 || CHECK!((*parser).buffer, b':'))
             && !IS_BLANKZ_AT!((*parser).buffer, 1)
+        || (*parser).strict && CHECK!((*parser).buffer, b':')
     {
         return yaml_parser_fetch_plain_scalar(parser);
     }
@@ -412,6 +560,47 @@ unsafe fn yaml_parser_fetch_next_token(
     FAIL
 }
 
+/// Checks if the ':' ahead follows a JSON-like key, that is a quoted
+/// scalar or a flow collection. In the flow context, YAML 1.2 then lets
+/// the value follow the ':' with no space in between.
+unsafe fn yaml_parser_adjacent_value_allowed(
+    parser: *mut YamlParserT,
+) -> bool {
+    let simple_key = (*parser).simple_keys.top.wrapping_offset(-1);
+    if !(*simple_key).possible {
+        return false;
+    }
+    let mut token = (*parser).tokens.tail;
+    while token != (*parser).tokens.head {
+        token = token.wrapping_offset(-1);
+        match (*token).type_ {
+            YamlCommentToken => {}
+            YamlScalarToken => {
+                return (*token).data.scalar.style
+                    == YamlSingleQuotedScalarStyle
+                    || (*token).data.scalar.style
+                        == YamlDoubleQuotedScalarStyle;
+            }
+            YamlFlowSequenceEndToken | YamlFlowMappingEndToken => {
+                return true;
+            }
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// Is the simple key too far behind the current position to be one?
+pub(crate) unsafe fn yaml_parser_simple_key_is_stale(
+    parser: *mut YamlParserT,
+    simple_key: *mut YamlSimpleKeyT,
+) -> bool {
+    (*simple_key).mark.line < (*parser).mark.line
+        && !(*simple_key).multiline
+        || (*simple_key).mark.index.force_add(1024_u64)
+            < (*parser).mark.index
+}
+
 unsafe fn yaml_parser_stale_simple_keys(
     parser: *mut YamlParserT,
 ) -> Success {
@@ -422,9 +611,7 @@ unsafe fn yaml_parser_stale_simple_keys(
         .add((*parser).not_simple_keys as usize);
     while simple_key != (*parser).simple_keys.top {
         if (*simple_key).possible
-            && ((*simple_key).mark.line < (*parser).mark.line
-                || (*simple_key).mark.index.force_add(1024_u64)
-                    < (*parser).mark.index)
+            && yaml_parser_simple_key_is_stale(parser, simple_key)
         {
             if (*simple_key).required {
                 yaml_parser_set_scanner_error(
@@ -470,6 +657,8 @@ unsafe fn yaml_parser_save_simple_key(
                     as libc::c_ulong,
             ),
             mark: (*parser).mark,
+            multiline: (*(*parser).simple_keys.top.wrapping_offset(-1))
+                .multiline,
         };
         if yaml_parser_remove_simple_key(parser).fail {
             return FAIL;
@@ -510,6 +699,7 @@ unsafe fn yaml_parser_remove_simple_key(
 
 unsafe fn yaml_parser_increase_flow_level(
     parser: *mut YamlParserT,
+    mapping: bool,
 ) -> Success {
     let empty_simple_key = YamlSimpleKeyT {
         possible: false,
@@ -519,7 +709,12 @@ unsafe fn yaml_parser_increase_flow_level(
             index: 0_u64,
             line: 0_u64,
             column: 0_u64,
+            offset: 0_u64,
+            utf16_column: 0_u64,
         },
+        // In YAML 1.2, the keys of a flow mapping may span several
+        // lines.
+        multiline: (*parser).strict && mapping,
     };
     PUSH!((*parser).simple_keys, empty_simple_key);
     if (*parser).flow_level == libc::c_int::MAX {
@@ -611,7 +806,9 @@ unsafe fn yaml_parser_unroll_indent(
     }
 }
 
-unsafe fn yaml_parser_fetch_stream_start(parser: *mut YamlParserT) {
+pub(crate) unsafe fn yaml_parser_fetch_stream_start(
+    parser: *mut YamlParserT,
+) {
     let simple_key = YamlSimpleKeyT {
         possible: false,
         required: false,
@@ -620,7 +817,10 @@ unsafe fn yaml_parser_fetch_stream_start(parser: *mut YamlParserT) {
             index: 0_u64,
             line: 0_u64,
             column: 0_u64,
+            offset: 0_u64,
+            utf16_column: 0_u64,
         },
+        multiline: false,
     };
     let mut token = MaybeUninit::<YamlTokenT>::uninit();
     let token = token.as_mut_ptr();
@@ -641,13 +841,14 @@ unsafe fn yaml_parser_fetch_stream_start(parser: *mut YamlParserT) {
     ENQUEUE!((*parser).tokens, *token);
 }
 
-unsafe fn yaml_parser_fetch_stream_end(
+pub(crate) unsafe fn yaml_parser_fetch_stream_end(
     parser: *mut YamlParserT,
 ) -> Success {
     let mut token = MaybeUninit::<YamlTokenT>::uninit();
     let token = token.as_mut_ptr();
     if (*parser).mark.column != 0_u64 {
         (*parser).mark.column = 0_u64;
+        (*parser).mark.utf16_column = 0_u64;
         let fresh22 = addr_of_mut!((*parser).mark.line);
         *fresh22 = (*fresh22).force_add(1);
     }
@@ -681,7 +882,9 @@ unsafe fn yaml_parser_fetch_directive(
     if yaml_parser_scan_directive(parser, token).fail {
         return FAIL;
     }
-    ENQUEUE!((*parser).tokens, *token);
+    if (*token).type_ != YamlNoToken {
+        ENQUEUE!((*parser).tokens, *token);
+    }
     OK
 }
 
@@ -722,7 +925,12 @@ unsafe fn yaml_parser_fetch_flow_collection_start(
     if yaml_parser_save_simple_key(parser).fail {
         return FAIL;
     }
-    if yaml_parser_increase_flow_level(parser).fail {
+    if yaml_parser_increase_flow_level(
+        parser,
+        type_ == YamlFlowMappingStartToken,
+    )
+    .fail
+    {
         return FAIL;
     }
     (*parser).simple_key_allowed = true;
@@ -995,14 +1203,22 @@ unsafe fn yaml_parser_fetch_block_scalar(
 ) -> Success {
     let mut token = MaybeUninit::<YamlTokenT>::uninit();
     let token = token.as_mut_ptr();
+    let mut comment = MaybeUninit::<YamlTokenT>::uninit();
+    let comment = comment.as_mut_ptr();
     if yaml_parser_remove_simple_key(parser).fail {
         return FAIL;
     }
     (*parser).simple_key_allowed = true;
-    if yaml_parser_scan_block_scalar(parser, token, literal).fail {
+    if yaml_parser_scan_block_scalar(parser, token, comment, literal)
+        .fail
+    {
         return FAIL;
     }
     ENQUEUE!((*parser).tokens, *token);
+    // A comment on the header line follows the scalar.
+    if (*comment).type_ == YamlCommentToken {
+        ENQUEUE!((*parser).tokens, *comment);
+    }
     OK
 }
 
@@ -1039,9 +1255,71 @@ unsafe fn yaml_parser_fetch_plain_scalar(
     OK
 }
 
+unsafe fn yaml_parser_fetch_comment(
+    parser: *mut YamlParserT,
+) -> Success {
+    let mut token = MaybeUninit::<YamlTokenT>::uninit();
+    let token = token.as_mut_ptr();
+    // Anything but whitespace before the comment on this line?
+    let trailing = (*parser).token_end_mark.line == (*parser).mark.line
+        && (*parser).token_end_mark.column != 0_u64;
+    if trailing {
+        if yaml_parser_scan_comment(parser, token, YamlTrailingComment)
+            .fail
+        {
+            return FAIL;
+        }
+        ENQUEUE!((*parser).tokens, *token);
+    } else {
+        if yaml_parser_scan_comment(parser, token, YamlLeadingComment)
+            .fail
+        {
+            return FAIL;
+        }
+        ENQUEUE!((*parser).pending_comments, *token);
+    }
+    OK
+}
+
+unsafe fn yaml_parser_fetch_pending_comments(parser: *mut YamlParserT) {
+    if IS_Z!((*parser).buffer)
+        || (*parser).mark.column == 0_u64
+            && (CHECK!((*parser).buffer, b'%')
+                || CHECK_AT!((*parser).buffer, b'-', 0)
+                    && CHECK_AT!((*parser).buffer, b'-', 1)
+                    && CHECK_AT!((*parser).buffer, b'-', 2)
+                    && IS_BLANKZ_AT!((*parser).buffer, 3)
+                || CHECK_AT!((*parser).buffer, b'.', 0)
+                    && CHECK_AT!((*parser).buffer, b'.', 1)
+                    && CHECK_AT!((*parser).buffer, b'.', 2)
+                    && IS_BLANKZ_AT!((*parser).buffer, 3))
+    {
+        // The comments close the document rather than lead a node.
+        yaml_parser_unroll_indent(parser, -1_i64);
+        yaml_parser_detach_pending_comments(parser);
+    }
+    while !QUEUE_EMPTY!((*parser).pending_comments) {
+        let comment: *mut YamlTokenT =
+            addr_of_mut!(DEQUEUE!((*parser).pending_comments));
+        ENQUEUE!((*parser).tokens, *comment);
+    }
+}
+
+unsafe fn yaml_parser_detach_pending_comments(
+    parser: *mut YamlParserT,
+) {
+    let mut comment: *mut YamlTokenT = (*parser).pending_comments.head;
+    while comment != (*parser).pending_comments.tail {
+        (*comment).data.comment.type_ = YamlDocumentComment;
+        comment = comment.wrapping_offset(1);
+    }
+}
+
 unsafe fn yaml_parser_scan_to_next_token(
     parser: *mut YamlParserT,
 ) -> Success {
+    // Is the whitespace ahead the indentation of a line?
+    let mut indentation = (*parser).mark.column == 0_u64;
     loop {
         if cache(parser, 1_u64).fail {
             return FAIL;
@@ -1054,9 +1332,11 @@ unsafe fn yaml_parser_scan_to_next_token(
         }
         let mut should_continue = true;
         while should_continue {
+            // YAML 1.2 separates tokens on a line with tabs too.
             if CHECK!((*parser).buffer, b' ')
                 || ((*parser).flow_level != 0
-                    || !(*parser).simple_key_allowed)
+                    || !(*parser).simple_key_allowed
+                    || (*parser).strict && !indentation)
                     && CHECK!((*parser).buffer, b'\t')
             {
                 skip(parser);
@@ -1068,12 +1348,41 @@ unsafe fn yaml_parser_scan_to_next_token(
             }
         }
         if CHECK!((*parser).buffer, b'#') {
-            while !IS_BREAKZ!((*parser).buffer) {
-                skip(parser);
-                if cache(parser, 1_u64).fail {
+            // YAML 1.2 requires a space between a token and a comment.
+            if (*parser).strict
+                && (*parser).mark.column != 0_u64
+                && (*parser).mark.index
+                    == (*parser).token_end_mark.index
+            {
+                yaml_parser_set_scanner_error(
+                    parser,
+                    b"while scanning a comment\0" as *const u8
+                        as *const libc::c_char,
+                    (*parser).mark,
+                    b"found a comment without a space before it\0"
+                        as *const u8
+                        as *const libc::c_char,
+                );
+                return FAIL;
+            }
+            if (*parser).comments {
+                if yaml_parser_fetch_comment(parser).fail {
                     return FAIL;
                 }
+            } else {
+                while !IS_BREAKZ!((*parser).buffer) {
+                    skip(parser);
+                    if cache(parser, 1_u64).fail {
+                        return FAIL;
+                    }
+                }
             }
+        } else if IS_BREAK!((*parser).buffer)
+            && !QUEUE_EMPTY!((*parser).pending_comments)
+        {
+            // A blank line separates the comments above it from the
+            // content.
+            yaml_parser_detach_pending_comments(parser);
         }
         if !IS_BREAK!((*parser).buffer) {
             break;
@@ -1082,6 +1391,7 @@ unsafe fn yaml_parser_scan_to_next_token(
             return FAIL;
         }
         skip_line(parser);
+        indentation = true;
         if (*parser).flow_level == 0 {
             (*parser).simple_key_allowed = true;
         }
@@ -1089,6 +1399,42 @@ unsafe fn yaml_parser_scan_to_next_token(
     OK
 }
 
+unsafe fn yaml_parser_scan_comment(
+    parser: *mut YamlParserT,
+    token: *mut YamlTokenT,
+    type_: YamlCommentTypeT,
+) -> Success {
+    let mut string = NULL_STRING;
+    STRING_INIT!(string);
+    let start_mark: YamlMarkT = (*parser).mark;
+    skip(parser);
+    if cache(parser, 1_u64).fail {
+        STRING_DEL!(string);
+        return FAIL;
+    }
+    while !IS_BREAKZ!((*parser).buffer) {
+        read!(parser, string);
+        if cache(parser, 1_u64).fail {
+            STRING_DEL!(string);
+            return FAIL;
+        }
+    }
+    memset(
+        token as *mut libc::c_void,
+        0,
+        size_of::<YamlTokenT>() as libc::c_ulong,
+    );
+    (*token).type_ = YamlCommentToken;
+    (*token).start_mark = start_mark;
+    (*token).end_mark = (*parser).mark;
+    let fresh = addr_of_mut!((*token).data.comment.value);
+    *fresh = string.start;
+    (*token).data.comment.length =
+        string.pointer.c_offset_from(string.start) as size_t;
+    (*token).data.comment.type_ = type_;
+    OK
+}
+
 unsafe fn yaml_parser_scan_directive(
     parser: *mut YamlParserT,
     token: *mut YamlTokenT,
@@ -1169,6 +1515,18 @@ unsafe fn yaml_parser_scan_directive(
                 *fresh113 = prefix;
                 current_block = 17407779659766490442;
             }
+        } else if (*parser).strict {
+            // YAML 1.2 reserves other directives and ignores them.
+            if yaml_parser_skip_reserved_directive(parser).fail {
+                current_block = 11397968426844348457;
+            } else {
+                memset(
+                    token as *mut libc::c_void,
+                    0,
+                    size_of::<YamlTokenT>() as libc::c_ulong,
+                );
+                current_block = 17407779659766490442;
+            }
         } else {
             yaml_parser_set_scanner_error(
                 parser,
@@ -1195,7 +1553,9 @@ unsafe fn yaml_parser_scan_directive(
                 }
             }
             if current_block != 11397968426844348457 {
-                if CHECK!((*parser).buffer, b'#') {
+                // A comment is left to be scanned as a token.
+                if CHECK!((*parser).buffer, b'#') && !(*parser).comments
+                {
                     loop {
                         if IS_BREAKZ!((*parser).buffer) {
                             current_block = 6669252993407410313;
@@ -1211,7 +1571,9 @@ unsafe fn yaml_parser_scan_directive(
                     current_block = 6669252993407410313;
                 }
                 if current_block != 11397968426844348457 {
-                    if !IS_BREAKZ!((*parser).buffer) {
+                    if !IS_BREAKZ!((*parser).buffer)
+                        && !CHECK!((*parser).buffer, b'#')
+                    {
                         yaml_parser_set_scanner_error(
                             parser,
                             b"while scanning a directive\0" as *const u8 as *const libc::c_char,
@@ -1245,6 +1607,35 @@ unsafe fn yaml_parser_scan_directive(
     FAIL
 }
 
+/// Skips the parameters of a reserved directive, up to a comment or the
+/// end of the line.
+unsafe fn yaml_parser_skip_reserved_directive(
+    parser: *mut YamlParserT,
+) -> Success {
+    loop {
+        if cache(parser, 1_u64).fail {
+            return FAIL;
+        }
+        while IS_BLANK!((*parser).buffer) {
+            skip(parser);
+            if cache(parser, 1_u64).fail {
+                return FAIL;
+            }
+        }
+        if IS_BREAKZ!((*parser).buffer)
+            || CHECK!((*parser).buffer, b'#')
+        {
+            return OK;
+        }
+        while !IS_BLANKZ!((*parser).buffer) {
+            skip(parser);
+            if cache(parser, 1_u64).fail {
+                return FAIL;
+            }
+        }
+    }
+}
+
 unsafe fn yaml_parser_scan_directive_name(
     parser: *mut YamlParserT,
     start_mark: YamlMarkT,
@@ -1360,7 +1751,7 @@ unsafe fn yaml_parser_scan_version_directive_number(
         }
         value =
             value.force_mul(10).force_add(AS_DIGIT!((*parser).buffer));
-        (*parser).buffer.next();
+        skip(parser);
         if cache(parser, 1_u64).fail {
             return FAIL;
         }
@@ -1500,12 +1891,39 @@ unsafe fn yaml_parser_scan_anchor(
     skip(parser);
     if cache(parser, 1_u64).ok {
         loop {
-            if !IS_ALPHA!((*parser).buffer) {
+            // YAML 1.2 allows any character but a space or a flow
+            // indicator in anchor names.
+            let end = if (*parser).strict {
+                IS_BLANKZ!((*parser).buffer)
+                    || IS_FLOW_INDICATOR!((*parser).buffer)
+            } else {
+                !IS_ALPHA!((*parser).buffer)
+            };
+            if end {
                 current_block = 2868539653012386629;
                 break;
             }
+            let mark = (*parser).mark;
             read!(parser, string);
-            if cache(parser, 1_u64).fail {
+            if yaml_parser_check_length(
+                parser,
+                addr_of!(string),
+                (*parser).max_name_length,
+                if type_ == YamlAnchorToken {
+                    b"while scanning an anchor\0" as *const u8
+                        as *const libc::c_char
+                } else {
+                    b"while scanning an alias\0" as *const u8
+                        as *const libc::c_char
+                },
+                start_mark,
+                b"anchor length limit exceeded\0" as *const u8
+                    as *const libc::c_char,
+                mark,
+            )
+            .fail
+                || cache(parser, 1_u64).fail
+            {
                 current_block = 5883759901342942623;
                 break;
             }
@@ -1738,8 +2156,18 @@ unsafe fn yaml_parser_scan_tag_handle(
                         current_block = 7651349459974463963;
                         break;
                     }
+                    let mark = (*parser).mark;
                     read!(parser, string);
-                    if cache(parser, 1_u64).fail {
+                    if yaml_parser_check_tag_length(
+                        parser,
+                        addr_of!(string),
+                        directive,
+                        start_mark,
+                        mark,
+                    )
+                    .fail
+                        || cache(parser, 1_u64).fail
+                    {
                         current_block = 1771849829115608806;
                         break;
                     }
@@ -1853,6 +2281,7 @@ unsafe fn yaml_parser_scan_tag_uri(
                                     || CHECK!((*parser).buffer, b'[')
                                     || CHECK!((*parser).buffer, b']')))
                     {
+                        let mark = (*parser).mark;
                         if CHECK!((*parser).buffer, b'%') {
                             STRING_EXTEND!(string);
                             if yaml_parser_scan_uri_escapes(
@@ -1870,7 +2299,16 @@ unsafe fn yaml_parser_scan_tag_uri(
                             read!(parser, string);
                         }
                         length = length.force_add(1);
-                        if cache(parser, 1_u64).fail {
+                        if yaml_parser_check_tag_length(
+                            parser,
+                            addr_of!(string),
+                            directive,
+                            start_mark,
+                            mark,
+                        )
+                        .fail
+                            || cache(parser, 1_u64).fail
+                        {
                             current_block = 15265153392498847348;
                             continue 'c_21953;
                         }
@@ -1903,6 +2341,31 @@ unsafe fn yaml_parser_scan_tag_uri(
     }
 }
 
+unsafe fn yaml_parser_check_tag_length(
+    parser: *mut YamlParserT,
+    string: *const YamlStringT,
+    directive: bool,
+    start_mark: YamlMarkT,
+    mark: YamlMarkT,
+) -> Success {
+    yaml_parser_check_length(
+        parser,
+        string,
+        (*parser).max_name_length,
+        if directive {
+            b"while scanning a %TAG directive\0" as *const u8
+                as *const libc::c_char
+        } else {
+            b"while scanning a tag\0" as *const u8
+                as *const libc::c_char
+        },
+        start_mark,
+        b"tag length limit exceeded\0" as *const u8
+            as *const libc::c_char,
+        mark,
+    )
+}
+
 unsafe fn yaml_parser_scan_uri_escapes(
     parser: *mut YamlParserT,
     directive: bool,
@@ -2000,6 +2463,7 @@ unsafe fn yaml_parser_scan_uri_escapes(
 unsafe fn yaml_parser_scan_block_scalar(
     parser: *mut YamlParserT,
     token: *mut YamlTokenT,
+    comment: *mut YamlTokenT,
     literal: bool,
 ) -> Success {
     let mut current_block: u64;
@@ -2009,12 +2473,18 @@ unsafe fn yaml_parser_scan_block_scalar(
     let mut trailing_breaks = NULL_STRING;
     let mut chomping: libc::c_int = 0;
     let mut increment: libc::c_int = 0;
-    let mut indent: libc::c_int = 0;
+    // The indentation of the content, or -1 until it is detected.
+    let mut indent: libc::c_int = -1;
     let mut leading_blank: libc::c_int = 0;
     let mut trailing_blank: libc::c_int;
     STRING_INIT!(string);
     STRING_INIT!(leading_break);
     STRING_INIT!(trailing_breaks);
+    memset(
+        comment as *mut libc::c_void,
+        0,
+        size_of::<YamlTokenT>() as libc::c_ulong,
+    );
     let start_mark: YamlMarkT = (*parser).mark;
     skip(parser);
     if cache(parser, 1_u64).ok {
@@ -2083,6 +2553,7 @@ unsafe fn yaml_parser_scan_block_scalar(
         } else {
             current_block = 11913429853522160501;
         }
+        let header_end: YamlMarkT = (*parser).mark;
         if current_block != 14984465786483313892
             && cache(parser, 1_u64).ok
         {
@@ -2098,7 +2569,27 @@ unsafe fn yaml_parser_scan_block_scalar(
                 }
             }
             if current_block != 14984465786483313892 {
-                if CHECK!((*parser).buffer, b'#') {
+                // YAML 1.2 requires a space before the comment.
+                let comment_allowed = !(*parser).strict
+                    || (*parser).mark.index != header_end.index;
+                if CHECK!((*parser).buffer, b'#')
+                    && comment_allowed
+                    && (*parser).comments
+                {
+                    current_block = if yaml_parser_scan_comment(
+                        parser,
+                        comment,
+                        YamlTrailingComment,
+                    )
+                    .ok
+                    {
+                        12997042908615822766
+                    } else {
+                        14984465786483313892
+                    };
+                } else if CHECK!((*parser).buffer, b'#')
+                    && comment_allowed
+                {
                     loop {
                         if IS_BREAKZ!((*parser).buffer) {
                             current_block = 12997042908615822766;
@@ -2162,6 +2653,47 @@ unsafe fn yaml_parser_scan_block_scalar(
                                             5793491756164225964;
                                         break;
                                     }
+                                    // Unindented content ends before
+                                    // a document marker.
+                                    if indent == 0 {
+                                        if cache(parser, 4_u64).fail {
+                                            current_block =
+                                                14984465786483313892;
+                                            break;
+                                        }
+                                        if (CHECK_AT!(
+                                            (*parser).buffer,
+                                            b'-',
+                                            0
+                                        ) && CHECK_AT!(
+                                            (*parser).buffer,
+                                            b'-',
+                                            1
+                                        ) && CHECK_AT!(
+                                            (*parser).buffer,
+                                            b'-',
+                                            2
+                                        ) || CHECK_AT!(
+                                            (*parser).buffer,
+                                            b'.',
+                                            0
+                                        ) && CHECK_AT!(
+                                            (*parser).buffer,
+                                            b'.',
+                                            1
+                                        ) && CHECK_AT!(
+                                            (*parser).buffer,
+                                            b'.',
+                                            2
+                                        )) && IS_BLANKZ_AT!(
+                                            (*parser).buffer,
+                                            3
+                                        ) {
+                                            current_block =
+                                                5793491756164225964;
+                                            break;
+                                        }
+                                    }
                                     trailing_blank =
                                         IS_BLANK!((*parser).buffer)
                                             as libc::c_int;
@@ -2193,8 +2725,20 @@ unsafe fn yaml_parser_scan_block_scalar(
                                             as libc::c_int;
                                     while !IS_BREAKZ!((*parser).buffer)
                                     {
+                                        let mark = (*parser).mark;
                                         read!(parser, string);
-                                        if cache(parser, 1_u64).fail {
+                                        if yaml_parser_check_scalar_length(
+                                            parser,
+                                            addr_of!(string),
+                                            b"while scanning a block scalar\0"
+                                                as *const u8
+                                                as *const libc::c_char,
+                                            start_mark,
+                                            mark,
+                                        )
+                                        .fail
+                                            || cache(parser, 1_u64).fail
+                                        {
                                             current_block =
                                                 14984465786483313892;
                                             break 's_281;
@@ -2238,37 +2782,49 @@ unsafe fn yaml_parser_scan_block_scalar(
                                                 trailing_breaks
                                             );
                                         }
-                                        memset(
-                                            token as *mut libc::c_void,
-                                            0,
-                                            size_of::<YamlTokenT>()
-                                                as libc::c_ulong,
-                                        );
-                                        (*token).type_ =
-                                            YamlScalarToken;
-                                        (*token).start_mark =
-                                            start_mark;
-                                        (*token).end_mark = end_mark;
-                                        let fresh479 = addr_of_mut!(
-                                            (*token).data.scalar.value
-                                        );
-                                        *fresh479 = string.start;
-                                        (*token).data.scalar.length =
-                                            string
-                                                .pointer
-                                                .c_offset_from(
-                                                    string.start,
-                                                )
-                                                as size_t;
-                                        (*token).data.scalar.style =
-                                            if literal {
-                                                YamlLiteralScalarStyle
-                                            } else {
-                                                YamlFoldedScalarStyle
-                                            };
-                                        STRING_DEL!(leading_break);
-                                        STRING_DEL!(trailing_breaks);
-                                        return OK;
+                                        if yaml_parser_check_scalar_length(
+                                            parser,
+                                            addr_of!(string),
+                                            b"while scanning a block scalar\0"
+                                                as *const u8
+                                                as *const libc::c_char,
+                                            start_mark,
+                                            end_mark,
+                                        )
+                                        .ok
+                                        {
+                                            memset(
+                                                token as *mut libc::c_void,
+                                                0,
+                                                size_of::<YamlTokenT>()
+                                                    as libc::c_ulong,
+                                            );
+                                            (*token).type_ =
+                                                YamlScalarToken;
+                                            (*token).start_mark =
+                                                start_mark;
+                                            (*token).end_mark = end_mark;
+                                            let fresh479 = addr_of_mut!(
+                                                (*token).data.scalar.value
+                                            );
+                                            *fresh479 = string.start;
+                                            (*token).data.scalar.length =
+                                                string
+                                                    .pointer
+                                                    .c_offset_from(
+                                                        string.start,
+                                                    )
+                                                    as size_t;
+                                            (*token).data.scalar.style =
+                                                if literal {
+                                                    YamlLiteralScalarStyle
+                                                } else {
+                                                    YamlFoldedScalarStyle
+                                                };
+                                            STRING_DEL!(leading_break);
+                                            STRING_DEL!(trailing_breaks);
+                                            return OK;
+                                        }
                                     }
                                 }
                             }
@@ -2281,9 +2837,126 @@ unsafe fn yaml_parser_scan_block_scalar(
     STRING_DEL!(string);
     STRING_DEL!(leading_break);
     STRING_DEL!(trailing_breaks);
+    yaml_token_delete(comment);
     FAIL
 }
 
+pub(crate) unsafe fn yaml_parser_check_scalar_length(
+    parser: *mut YamlParserT,
+    string: *const YamlStringT,
+    context: *const libc::c_char,
+    start_mark: YamlMarkT,
+    mark: YamlMarkT,
+) -> Success {
+    yaml_parser_check_length(
+        parser,
+        string,
+        (*parser).max_scalar_length,
+        context,
+        start_mark,
+        b"scalar length limit exceeded\0" as *const u8
+            as *const libc::c_char,
+        mark,
+    )
+}
+
+/// Whether scalar values may point into the input string instead of
+/// being copied.
+pub(crate) unsafe fn yaml_parser_can_borrow(
+    parser: *mut YamlParserT,
+) -> bool {
+    (*parser).borrow_scalars
+        && !(*parser).input.string.start.is_null()
+        && (*parser).encoding == YamlUtf8Encoding
+}
+
+/// A view of the input string between two offsets, for measuring and
+/// joining borrowed text like any other string.
+pub(crate) unsafe fn yaml_parser_input_string(
+    parser: *mut YamlParserT,
+    start: size_t,
+    end: size_t,
+) -> YamlStringT {
+    let input = (*parser).input.string.start as *mut yaml_char_t;
+    YamlStringT {
+        start: input.wrapping_add(start as usize),
+        end: input.wrapping_add(end as usize),
+        pointer: input.wrapping_add(end as usize),
+    }
+}
+
+/// Copies the borrowed text of a scalar, between two input offsets, into
+/// newly allocated strings, so that scanning can go on as if nothing had
+/// been borrowed.
+unsafe fn yaml_parser_own_scalar(
+    parser: *mut YamlParserT,
+    start: size_t,
+    end: size_t,
+    string: *mut YamlStringT,
+    leading_break: *mut YamlStringT,
+    trailing_breaks: *mut YamlStringT,
+    whitespaces: *mut YamlStringT,
+) {
+    STRING_INIT!(*string);
+    STRING_INIT!(*leading_break);
+    STRING_INIT!(*trailing_breaks);
+    STRING_INIT!(*whitespaces);
+    let mut input = yaml_parser_input_string(parser, start, end);
+    yaml_string_join(
+        addr_of_mut!((*string).start),
+        addr_of_mut!((*string).pointer),
+        addr_of_mut!((*string).end),
+        addr_of_mut!(input.start),
+        addr_of_mut!(input.pointer),
+        addr_of_mut!(input.end),
+    );
+}
+
+/// Copies the line breaks of a plain scalar, skipped between two input
+/// offsets while it was borrowed, as `read_line` would have read them.
+unsafe fn yaml_parser_own_breaks(
+    parser: *mut YamlParserT,
+    start: size_t,
+    end: size_t,
+    leading_break: *mut YamlStringT,
+    trailing_breaks: *mut YamlStringT,
+) {
+    let input = yaml_parser_input_string(parser, start, end);
+    let mut pointer = input.start;
+    while pointer < input.end {
+        let breaks =
+            if (*leading_break).start == (*leading_break).pointer {
+                leading_break
+            } else {
+                trailing_breaks
+            };
+        let width = match *pointer {
+            b' ' | b'\t' => {
+                pointer = pointer.wrapping_add(1);
+                continue;
+            }
+            b'\r' if *pointer.wrapping_add(1) == b'\n' => 2,
+            b'\r' | b'\n' => 1,
+            b'\xC2' => 2,
+            _ => {
+                // LS and PS are kept as they are.
+                STRING_EXTEND!(*breaks);
+                for _ in 0..3 {
+                    *(*breaks).pointer = *pointer;
+                    (*breaks).pointer =
+                        (*breaks).pointer.wrapping_add(1);
+                    pointer = pointer.wrapping_add(1);
+                }
+                continue;
+            }
+        };
+        STRING_EXTEND!(*breaks);
+        *(*breaks).pointer = b'\n';
+        (*breaks).pointer = (*breaks).pointer.wrapping_add(1);
+        pointer = pointer.wrapping_add(width);
+    }
+}
+
 unsafe fn yaml_parser_scan_block_scalar_breaks(
     parser: *mut YamlParserT,
     indent: *mut libc::c_int,
@@ -2297,7 +2970,7 @@ unsafe fn yaml_parser_scan_block_scalar_breaks(
         if cache(parser, 1_u64).fail {
             return FAIL;
         }
-        while (*indent == 0
+        while (*indent < 0
             || ((*parser).mark.column as libc::c_int) < *indent)
             && IS_SPACE!((*parser).buffer)
         {
@@ -2309,7 +2982,16 @@ unsafe fn yaml_parser_scan_block_scalar_breaks(
         if (*parser).mark.column as libc::c_int > max_indent {
             max_indent = (*parser).mark.column as libc::c_int;
         }
-        if (*indent == 0
+        // In YAML 1.2, a tab after the spaces of a line that sets the
+        // indentation is content.
+        if *indent < 0
+            && (*parser).strict
+            && IS_TAB!((*parser).buffer)
+            && (*parser).mark.column as libc::c_int > (*parser).indent
+        {
+            break;
+        }
+        if (*indent < 0
             || ((*parser).mark.column as libc::c_int) < *indent)
             && IS_TAB!((*parser).buffer)
         {
@@ -2323,6 +3005,26 @@ unsafe fn yaml_parser_scan_block_scalar_breaks(
             return FAIL;
         }
         if !IS_BREAK!((*parser).buffer) {
+            // YAML 1.2 does not let leading empty lines be indented
+            // more than the first content line.
+            if *indent < 0
+                && (*parser).strict
+                && ((*parser).mark.column as libc::c_int) < max_indent
+                && (*parser).mark.column as libc::c_int
+                    > (*parser).indent
+                && !IS_Z!((*parser).buffer)
+            {
+                yaml_parser_set_scanner_error(
+                    parser,
+                    b"while scanning a block scalar\0" as *const u8
+                        as *const libc::c_char,
+                    start_mark,
+                    b"found a leading empty line with more spaces than the first content line\0"
+                        as *const u8
+                        as *const libc::c_char,
+                );
+                return FAIL;
+            }
             break;
         }
         if cache(parser, 2_u64).fail {
@@ -2331,12 +3033,13 @@ unsafe fn yaml_parser_scan_block_scalar_breaks(
         read_line!(parser, *breaks);
         *end_mark = (*parser).mark;
     }
-    if *indent == 0 {
+    if *indent < 0 {
         *indent = max_indent;
         if *indent < (*parser).indent + 1 {
             *indent = (*parser).indent + 1;
         }
-        if *indent < 1 {
+        // YAML 1.2 allows unindented content at the top level.
+        if *indent < 1 && !((*parser).strict && (*parser).indent < 0) {
             *indent = 1;
         }
     }
@@ -2355,12 +3058,17 @@ unsafe fn yaml_parser_scan_flow_scalar(
     let mut trailing_breaks = NULL_STRING;
     let mut whitespaces = NULL_STRING;
     let mut leading_blanks;
-    STRING_INIT!(string);
-    STRING_INIT!(leading_break);
-    STRING_INIT!(trailing_breaks);
-    STRING_INIT!(whitespaces);
+    // A borrowed scalar is copied once it has an escape or a line break.
+    let mut borrowed = yaml_parser_can_borrow(parser);
+    if !borrowed {
+        STRING_INIT!(string);
+        STRING_INIT!(leading_break);
+        STRING_INIT!(trailing_breaks);
+        STRING_INIT!(whitespaces);
+    }
     let start_mark: YamlMarkT = (*parser).mark;
     skip(parser);
+    let content_start = (*parser).mark.offset;
     's_58: loop {
         if cache(parser, 4_u64).fail {
             current_block = 8114179180390253173;
@@ -2403,6 +3111,24 @@ unsafe fn yaml_parser_scan_flow_scalar(
             }
             leading_blanks = false;
             while !IS_BLANKZ!((*parser).buffer) {
+                let mark = (*parser).mark;
+                if borrowed
+                    && (single
+                        && CHECK_AT!((*parser).buffer, b'\'', 0)
+                        && CHECK_AT!((*parser).buffer, b'\'', 1)
+                        || !single && CHECK!((*parser).buffer, b'\\'))
+                {
+                    yaml_parser_own_scalar(
+                        parser,
+                        content_start,
+                        mark.offset,
+                        addr_of_mut!(string),
+                        addr_of_mut!(leading_break),
+                        addr_of_mut!(trailing_breaks),
+                        addr_of_mut!(whitespaces),
+                    );
+                    borrowed = false;
+                }
                 if single
                     && CHECK_AT!((*parser).buffer, b'\'', 0)
                     && CHECK_AT!((*parser).buffer, b'\'', 1)
@@ -2725,11 +3451,32 @@ unsafe fn yaml_parser_scan_flow_scalar(
                                 }
                             }
                         }
+                    } else if borrowed {
+                        skip(parser);
                     } else {
                         read!(parser, string);
                     }
                 }
-                if cache(parser, 2_u64).fail {
+                let value = if borrowed {
+                    yaml_parser_input_string(
+                        parser,
+                        content_start,
+                        (*parser).mark.offset,
+                    )
+                } else {
+                    string
+                };
+                if yaml_parser_check_scalar_length(
+                    parser,
+                    addr_of!(value),
+                    b"while scanning a quoted scalar\0" as *const u8
+                        as *const libc::c_char,
+                    start_mark,
+                    mark,
+                )
+                .fail
+                    || cache(parser, 2_u64).fail
+                {
                     current_block = 8114179180390253173;
                     break 's_58;
                 }
@@ -2749,11 +3496,12 @@ unsafe fn yaml_parser_scan_flow_scalar(
                 current_block = 8114179180390253173;
                 break;
             }
+            let content_end = (*parser).mark.offset;
             while IS_BLANK!((*parser).buffer)
                 || IS_BREAK!((*parser).buffer)
             {
                 if IS_BLANK!((*parser).buffer) {
-                    if !leading_blanks {
+                    if !leading_blanks && !borrowed {
                         read!(parser, whitespaces);
                     } else {
                         skip(parser);
@@ -2763,6 +3511,18 @@ unsafe fn yaml_parser_scan_flow_scalar(
                         current_block = 8114179180390253173;
                         break 's_58;
                     }
+                    if borrowed {
+                        yaml_parser_own_scalar(
+                            parser,
+                            content_start,
+                            content_end,
+                            addr_of_mut!(string),
+                            addr_of_mut!(leading_break),
+                            addr_of_mut!(trailing_breaks),
+                            addr_of_mut!(whitespaces),
+                        );
+                        borrowed = false;
+                    }
                     if !leading_blanks {
                         CLEAR!(whitespaces);
                         read_line!(parser, leading_break);
@@ -2776,6 +3536,26 @@ unsafe fn yaml_parser_scan_flow_scalar(
                     break 's_58;
                 }
             }
+            // YAML 1.2 requires the lines of a quoted scalar to be
+            // indented more than the block collection around it.
+            if leading_blanks
+                && (*parser).strict
+                && ((*parser).mark.column as libc::c_int)
+                    <= (*parser).indent
+                && !IS_Z!((*parser).buffer)
+            {
+                yaml_parser_set_scanner_error(
+                    parser,
+                    b"while scanning a quoted scalar\0" as *const u8
+                        as *const libc::c_char,
+                    start_mark,
+                    b"found a line with insufficient indentation\0"
+                        as *const u8
+                        as *const libc::c_char,
+                );
+                current_block = 8114179180390253173;
+                break;
+            }
             if leading_blanks {
                 if *leading_break.start == b'\n' {
                     if *trailing_breaks.start == b'\0' {
@@ -2795,13 +3575,36 @@ unsafe fn yaml_parser_scan_flow_scalar(
                     CLEAR!(leading_break);
                     CLEAR!(trailing_breaks);
                 }
-            } else {
+            } else if !borrowed {
                 JOIN!(string, whitespaces);
                 CLEAR!(whitespaces);
             }
+            let value = if borrowed {
+                yaml_parser_input_string(
+                    parser,
+                    content_start,
+                    (*parser).mark.offset,
+                )
+            } else {
+                string
+            };
+            if yaml_parser_check_scalar_length(
+                parser,
+                addr_of!(value),
+                b"while scanning a quoted scalar\0" as *const u8
+                    as *const libc::c_char,
+                start_mark,
+                (*parser).mark,
+            )
+            .fail
+            {
+                current_block = 8114179180390253173;
+                break;
+            }
         }
     }
     if current_block != 8114179180390253173 {
+        let content_end = (*parser).mark.offset;
         skip(parser);
         end_mark = (*parser).mark;
         memset(
@@ -2812,10 +3615,22 @@ unsafe fn yaml_parser_scan_flow_scalar(
         (*token).type_ = YamlScalarToken;
         (*token).start_mark = start_mark;
         (*token).end_mark = end_mark;
-        let fresh716 = addr_of_mut!((*token).data.scalar.value);
-        *fresh716 = string.start;
-        (*token).data.scalar.length =
-            string.pointer.c_offset_from(string.start) as size_t;
+        if borrowed {
+            let value = yaml_parser_input_string(
+                parser,
+                content_start,
+                content_end,
+            );
+            (*token).data.scalar.value = value.start;
+            (*token).data.scalar.length =
+                content_end.wrapping_sub(content_start);
+            (*token).data.scalar.borrowed = true;
+        } else {
+            let fresh716 = addr_of_mut!((*token).data.scalar.value);
+            *fresh716 = string.start;
+            (*token).data.scalar.length =
+                string.pointer.c_offset_from(string.start) as size_t;
+        }
         (*token).data.scalar.style = if single {
             YamlSingleQuotedScalarStyle
         } else {
@@ -2845,10 +3660,14 @@ unsafe fn yaml_parser_scan_plain_scalar(
     let mut whitespaces = NULL_STRING;
     let mut leading_blanks = false;
     let indent: libc::c_int = (*parser).indent + 1;
-    STRING_INIT!(string);
-    STRING_INIT!(leading_break);
-    STRING_INIT!(trailing_breaks);
-    STRING_INIT!(whitespaces);
+    // A borrowed scalar is copied once a line break has to be folded.
+    let mut borrowed = yaml_parser_can_borrow(parser);
+    if !borrowed {
+        STRING_INIT!(string);
+        STRING_INIT!(leading_break);
+        STRING_INIT!(trailing_breaks);
+        STRING_INIT!(whitespaces);
+    }
     end_mark = (*parser).mark;
     let start_mark: YamlMarkT = end_mark;
     's_57: loop {
@@ -2874,6 +3693,7 @@ unsafe fn yaml_parser_scan_plain_scalar(
         }
         while !IS_BLANKZ!((*parser).buffer) {
             if (*parser).flow_level != 0
+                && !(*parser).strict
                 && CHECK!((*parser).buffer, b':')
                 && (CHECK_AT!((*parser).buffer, b',', 1)
                     || CHECK_AT!((*parser).buffer, b'?', 1)
@@ -2893,8 +3713,15 @@ unsafe fn yaml_parser_scan_plain_scalar(
                 current_block = 16642808987012640029;
                 break 's_57;
             } else {
+                // In YAML 1.2, a ':' before a flow indicator is a value
+                // indicator.
                 if CHECK!((*parser).buffer, b':')
-                    && IS_BLANKZ_AT!((*parser).buffer, 1)
+                    && (IS_BLANKZ_AT!((*parser).buffer, 1)
+                        || (*parser).flow_level != 0
+                            && IS_FLOW_INDICATOR_AT!(
+                                (*parser).buffer,
+                                1
+                            ))
                     || (*parser).flow_level != 0
                         && (CHECK!((*parser).buffer, b',')
                             || CHECK!((*parser).buffer, b'[')
@@ -2907,6 +3734,25 @@ unsafe fn yaml_parser_scan_plain_scalar(
                 if leading_blanks
                     || whitespaces.start != whitespaces.pointer
                 {
+                    if borrowed {
+                        yaml_parser_own_scalar(
+                            parser,
+                            start_mark.offset,
+                            end_mark.offset,
+                            addr_of_mut!(string),
+                            addr_of_mut!(leading_break),
+                            addr_of_mut!(trailing_breaks),
+                            addr_of_mut!(whitespaces),
+                        );
+                        yaml_parser_own_breaks(
+                            parser,
+                            end_mark.offset,
+                            (*parser).mark.offset,
+                            addr_of_mut!(leading_break),
+                            addr_of_mut!(trailing_breaks),
+                        );
+                        borrowed = false;
+                    }
                     if leading_blanks {
                         if *leading_break.start == b'\n' {
                             if *trailing_breaks.start == b'\0' {
@@ -2932,9 +3778,30 @@ unsafe fn yaml_parser_scan_plain_scalar(
                         CLEAR!(whitespaces);
                     }
                 }
-                read!(parser, string);
+                let mark = (*parser).mark;
+                let value = if borrowed {
+                    skip(parser);
+                    yaml_parser_input_string(
+                        parser,
+                        start_mark.offset,
+                        (*parser).mark.offset,
+                    )
+                } else {
+                    read!(parser, string);
+                    string
+                };
                 end_mark = (*parser).mark;
-                if cache(parser, 2_u64).fail {
+                if yaml_parser_check_scalar_length(
+                    parser,
+                    addr_of!(value),
+                    b"while scanning a plain scalar\0" as *const u8
+                        as *const libc::c_char,
+                    start_mark,
+                    mark,
+                )
+                .fail
+                    || cache(parser, 2_u64).fail
+                {
                     current_block = 16642808987012640029;
                     break 's_57;
                 }
@@ -2965,7 +3832,7 @@ unsafe fn yaml_parser_scan_plain_scalar(
                     );
                     current_block = 16642808987012640029;
                     break 's_57;
-                } else if !leading_blanks {
+                } else if !leading_blanks && !borrowed {
                     read!(parser, whitespaces);
                 } else {
                     skip(parser);
@@ -2975,7 +3842,10 @@ unsafe fn yaml_parser_scan_plain_scalar(
                     current_block = 16642808987012640029;
                     break 's_57;
                 }
-                if !leading_blanks {
+                if borrowed {
+                    skip_line(parser);
+                    leading_blanks = true;
+                } else if !leading_blanks {
                     CLEAR!(whitespaces);
                     read_line!(parser, leading_break);
                     leading_blanks = true;
@@ -2988,7 +3858,10 @@ unsafe fn yaml_parser_scan_plain_scalar(
                 break 's_57;
             }
         }
-        if (*parser).flow_level == 0
+        // An underindented line in a flow collection is left for the
+        // YAML 1.2 indentation check.
+        if ((*parser).flow_level == 0
+            || (*parser).strict && leading_blanks)
             && ((*parser).mark.column as libc::c_int) < indent
         {
             current_block = 6281126495347172768;
@@ -3004,10 +3877,22 @@ unsafe fn yaml_parser_scan_plain_scalar(
         (*token).type_ = YamlScalarToken;
         (*token).start_mark = start_mark;
         (*token).end_mark = end_mark;
-        let fresh842 = addr_of_mut!((*token).data.scalar.value);
-        *fresh842 = string.start;
-        (*token).data.scalar.length =
-            string.pointer.c_offset_from(string.start) as size_t;
+        if borrowed {
+            let value = yaml_parser_input_string(
+                parser,
+                start_mark.offset,
+                end_mark.offset,
+            );
+            (*token).data.scalar.value = value.start;
+            (*token).data.scalar.length =
+                end_mark.offset.wrapping_sub(start_mark.offset);
+            (*token).data.scalar.borrowed = true;
+        } else {
+            let fresh842 = addr_of_mut!((*token).data.scalar.value);
+            *fresh842 = string.start;
+            (*token).data.scalar.length =
+                string.pointer.c_offset_from(string.start) as size_t;
+        }
         (*token).data.scalar.style = YamlPlainScalarStyle;
         if leading_blanks {
             (*parser).simple_key_allowed = true;
@@ -0,0 +1,674 @@
+//! Serialization of Rust types into YAML with Serde.
+//!
+//! The [`Serializer`] turns a value into events for an
+//! [`Emitter`], one document per value, leaving the layout to the
+//! emitter.
+//!
+//! - Strings that a plain scalar would not read back as, such as
+//!   `true`, `1.5` or an empty string, are single-quoted. So are the
+//!   strings YAML 1.1 reads as other types, such as `yes` and `1_000`.
+//!   Strings of several lines are written as literal block scalars where
+//!   the emitter allows it.
+//! - `None` and the unit types are written as `null`, and bytes as a
+//!   sequence of integers.
+//! - A unit variant is written as its name. Other variants are written
+//!   as their value with a local tag naming the variant, as in
+//!   `!Circle 1.5`, or as a mapping with a single pair when the value
+//!   already carries a tag of its own.
+//!
+//! What the serializer writes reads back through the
+//! [deserializer](crate::de) to the same value.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! enum Shape {
+//!     Circle { radius: f64 },
+//!     Point,
+//! }
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Drawing {
+//!     name: String,
+//!     version: String,
+//!     shapes: Vec<Shape>,
+//! }
+//!
+//! let drawing = Drawing {
+//!     name: "logo".to_owned(),
+//!     version: "1.0".to_owned(),
+//!     shapes: vec![Shape::Circle { radius: 1.5 }, Shape::Point],
+//! };
+//! let output = libyml::to_string(&drawing).unwrap();
+//! assert_eq!(
+//!     output,
+//!     "\
+//! name: logo
+//! version: '1.0'
+//! shapes:
+//! - !Circle
+//!   radius: 1.5
+//! - Point
+//! ",
+//! );
+//! assert_eq!(libyml::from_str::<Drawing>(&output).unwrap(), drawing);
+//! ```
+
+use crate::error::{Error, ErrorKind};
+use crate::resolver::{self, STR_TAG};
+use crate::safe::{
+    DocumentEnd, DocumentStart, Emitter, Event, FmtSink, MappingStart,
+    Scalar, SequenceStart, Sink, StreamStart,
+};
+use crate::yaml::YamlScalarStyleT;
+use crate::{
+    YamlAnyMappingStyle, YamlAnyScalarStyle, YamlAnySequenceStyle,
+    YamlCoreSchema, YamlLiteralScalarStyle, YamlPlainScalarStyle,
+    YamlSingleQuotedScalarStyle, YamlUtf8Encoding, YamlYaml11Schema,
+};
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display};
+use serde::ser::{self, Serialize};
+
+/// Serializes `value` as a YAML document into a string.
+pub fn to_string<T: ?Sized + Serialize>(
+    value: &T,
+) -> Result<String, Error> {
+    let mut serializer = Serializer::new(FmtSink::new(String::new()));
+    value.serialize(&mut serializer)?;
+    Ok(serializer.into_inner()?.into_inner())
+}
+
+/// Serializes `value` as a YAML document into a byte vector.
+pub fn to_vec<T: ?Sized + Serialize>(
+    value: &T,
+) -> Result<Vec<u8>, Error> {
+    let mut serializer = Serializer::new(Vec::new());
+    value.serialize(&mut serializer)?;
+    serializer.into_inner()
+}
+
+/// Serializes `value` as a YAML document into `writer`.
+#[cfg(feature = "std")]
+pub fn to_writer<W: std::io::Write, T: ?Sized + Serialize>(
+    writer: W,
+    value: &T,
+) -> Result<(), Error> {
+    let mut serializer =
+        Serializer::new(crate::safe::IoSink::new(writer));
+    value.serialize(&mut serializer)?;
+    serializer.into_inner()?;
+    Ok(())
+}
+
+/// A Serde serializer writing YAML documents through an [`Emitter`].
+///
+/// Every value serialized is written as a document of its own, so a
+/// serializer can write a stream of several documents:
+///
+/// ```
+/// use libyml::Serializer;
+/// use serde::Serialize;
+///
+/// let mut serializer = Serializer::new(Vec::new());
+/// for value in [1, 2] {
+///     value.serialize(&mut serializer).unwrap();
+/// }
+/// let output = serializer.into_inner().unwrap();
+/// assert_eq!(output, b"1\n--- 2\n");
+/// ```
+pub struct Serializer<W: Sink> {
+    emitter: Emitter<W>,
+    started: bool,
+    /// How deeply the collections being written nest.
+    depth: usize,
+    /// The variant to tag the next node with.
+    tag: Option<&'static str>,
+}
+
+impl<W: Sink> Serializer<W> {
+    /// Creates a serializer with the default emitter options writing to
+    /// `output`.
+    pub fn new(output: W) -> Self {
+        Serializer::from_emitter(Emitter::new(output))
+    }
+
+    /// Creates a serializer writing through `emitter`, which must not
+    /// have started a stream.
+    ///
+    /// ```
+    /// use libyml::safe::EmitterBuilder;
+    /// use libyml::Serializer;
+    /// use serde::Serialize;
+    ///
+    /// let emitter = EmitterBuilder::new().indent(4).build(Vec::new());
+    /// let mut serializer = Serializer::from_emitter(emitter);
+    /// [("a", [1])].serialize(&mut serializer).unwrap();
+    /// let output = serializer.into_inner().unwrap();
+    /// assert_eq!(output, b"-   - a\n    -   - 1\n");
+    /// ```
+    pub fn from_emitter(emitter: Emitter<W>) -> Self {
+        Serializer {
+            emitter,
+            started: false,
+            depth: 0,
+            tag: None,
+        }
+    }
+
+    /// Ends the stream and returns the sink.
+    pub fn into_inner(mut self) -> Result<W, Error> {
+        if self.started {
+            self.emitter.emit(&Event::StreamEnd)?;
+            self.emitter.flush()?;
+        }
+        Ok(self.emitter.into_inner())
+    }
+
+    /// Emits the event, wrapping each node at the top level in a
+    /// document.
+    fn emit(&mut self, event: Event<'_>) -> Result<(), Error> {
+        if self.depth == 0 {
+            if !self.started {
+                self.emitter.emit(&Event::StreamStart(
+                    StreamStart {
+                        encoding: YamlUtf8Encoding,
+                    },
+                ))?;
+                self.started = true;
+            }
+            self.emitter.emit(&Event::DocumentStart(
+                DocumentStart {
+                    implicit: true,
+                    ..DocumentStart::default()
+                },
+            ))?;
+        }
+        match event {
+            Event::SequenceStart(_) | Event::MappingStart(_) => {
+                self.depth += 1;
+            }
+            Event::SequenceEnd | Event::MappingEnd => self.depth -= 1,
+            _ => {}
+        }
+        self.emitter.emit(&event)?;
+        if self.depth == 0 {
+            self.emitter.emit(&Event::DocumentEnd(DocumentEnd {
+                implicit: true,
+            }))?;
+        }
+        Ok(())
+    }
+
+    /// The local tag of the variant the next node is the value of.
+    fn take_tag(&mut self) -> Option<Cow<'static, str>> {
+        self.tag
+            .take()
+            .map(|variant| Cow::Owned(format!("!{}", variant)))
+    }
+
+    fn emit_scalar(
+        &mut self,
+        value: &str,
+        style: YamlScalarStyleT,
+    ) -> Result<(), Error> {
+        let tag = self.take_tag();
+        let implicit = tag.is_none();
+        self.emit(Event::Scalar(Scalar {
+            anchor: None,
+            tag,
+            value: Cow::Borrowed(value),
+            plain_implicit: implicit,
+            quoted_implicit: implicit,
+            style,
+        }))
+    }
+
+    fn emit_sequence_start(&mut self) -> Result<(), Error> {
+        let tag = self.take_tag();
+        self.emit(Event::SequenceStart(SequenceStart {
+            anchor: None,
+            implicit: tag.is_none(),
+            tag,
+            style: YamlAnySequenceStyle,
+        }))
+    }
+
+    fn emit_mapping_start(&mut self) -> Result<(), Error> {
+        let tag = self.take_tag();
+        self.emit(Event::MappingStart(MappingStart {
+            anchor: None,
+            implicit: tag.is_none(),
+            tag,
+            style: YamlAnyMappingStyle,
+        }))
+    }
+
+    /// Tags the next node with `variant`. When a variant is pending
+    /// already, as for `Outer::A(Inner::B(1))`, the node is wrapped in
+    /// a mapping from the pending variant instead, and `true` is
+    /// returned: the mapping must be ended after the node.
+    fn tag_variant(
+        &mut self,
+        variant: &'static str,
+    ) -> Result<bool, Error> {
+        let wrapped = match self.tag.take() {
+            Some(outer) => {
+                self.emit_mapping_start()?;
+                ser::Serializer::serialize_str(&mut *self, outer)?;
+                true
+            }
+            None => false,
+        };
+        self.tag = Some(variant);
+        Ok(wrapped)
+    }
+
+    fn end_variant(&mut self, wrapped: bool) -> Result<(), Error> {
+        if wrapped {
+            self.emit(Event::MappingEnd)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Sink> Debug for Serializer<W> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_struct("Serializer").finish_non_exhaustive()
+    }
+}
+
+/// Does a plain scalar `value` read back as a string, with both the
+/// core schema and the YAML 1.1 types?
+fn is_plain_string(value: &str) -> bool {
+    [YamlCoreSchema, YamlYaml11Schema]
+        .into_iter()
+        .all(|schema| resolver::resolve_plain(schema, value) == STR_TAG)
+}
+
+/// Formats a float so that it reads back as one, keeping a fraction
+/// and a signed exponent for YAML 1.1.
+fn format_float(value: f64, debug: &dyn Debug) -> String {
+    if value.is_nan() {
+        return ".nan".to_string();
+    }
+    if value.is_infinite() {
+        let sign = if value < 0.0 { "-" } else { "" };
+        return format!("{}.inf", sign);
+    }
+    let formatted = format!("{:?}", debug);
+    match formatted.split_once('e') {
+        Some((mantissa, exponent)) => {
+            let fraction =
+                if mantissa.contains('.') { "" } else { ".0" };
+            let sign = if exponent.starts_with('-') { "" } else { "+" };
+            format!("{}{}e{}{}", mantissa, fraction, sign, exponent)
+        }
+        None => formatted,
+    }
+}
+
+impl<'a, W: Sink> ser::Serializer for &'a mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Compound<'a, W>;
+    type SerializeTuple = Compound<'a, W>;
+    type SerializeTupleStruct = Compound<'a, W>;
+    type SerializeTupleVariant = Compound<'a, W>;
+    type SerializeMap = Compound<'a, W>;
+    type SerializeStruct = Compound<'a, W>;
+    type SerializeStructVariant = Compound<'a, W>;
+
+    fn serialize_bool(self, value: bool) -> Result<(), Error> {
+        let value = if value { "true" } else { "false" };
+        self.emit_scalar(value, YamlPlainScalarStyle)
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<(), Error> {
+        self.serialize_i64(i64::from(value))
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<(), Error> {
+        self.serialize_i64(i64::from(value))
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<(), Error> {
+        self.serialize_i64(i64::from(value))
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<(), Error> {
+        self.emit_scalar(&value.to_string(), YamlPlainScalarStyle)
+    }
+
+    fn serialize_i128(self, value: i128) -> Result<(), Error> {
+        self.emit_scalar(&value.to_string(), YamlPlainScalarStyle)
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<(), Error> {
+        self.serialize_u64(u64::from(value))
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<(), Error> {
+        self.serialize_u64(u64::from(value))
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<(), Error> {
+        self.serialize_u64(u64::from(value))
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<(), Error> {
+        self.emit_scalar(&value.to_string(), YamlPlainScalarStyle)
+    }
+
+    fn serialize_u128(self, value: u128) -> Result<(), Error> {
+        self.emit_scalar(&value.to_string(), YamlPlainScalarStyle)
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<(), Error> {
+        let value = format_float(f64::from(value), &value);
+        self.emit_scalar(&value, YamlPlainScalarStyle)
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<(), Error> {
+        let value = format_float(value, &value);
+        self.emit_scalar(&value, YamlPlainScalarStyle)
+    }
+
+    fn serialize_char(self, value: char) -> Result<(), Error> {
+        self.serialize_str(value.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<(), Error> {
+        let style = if !is_plain_string(value) {
+            YamlSingleQuotedScalarStyle
+        } else if value.contains('\n') {
+            YamlLiteralScalarStyle
+        } else {
+            YamlAnyScalarStyle
+        };
+        self.emit_scalar(value, style)
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<(), Error> {
+        let mut seq = ser::Serializer::serialize_seq(self, None)?;
+        for byte in value {
+            ser::SerializeSeq::serialize_element(&mut seq, byte)?;
+        }
+        ser::SerializeSeq::end(seq)
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(
+        self,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        self.emit_scalar("null", YamlPlainScalarStyle)
+    }
+
+    fn serialize_unit_struct(
+        self,
+        _name: &'static str,
+    ) -> Result<(), Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        let wrapped = self.tag_variant(variant)?;
+        value.serialize(&mut *self)?;
+        self.end_variant(wrapped)
+    }
+
+    fn serialize_seq(
+        self,
+        _len: Option<usize>,
+    ) -> Result<Compound<'a, W>, Error> {
+        self.emit_sequence_start()?;
+        Ok(Compound {
+            ser: self,
+            wrapped: false,
+        })
+    }
+
+    fn serialize_tuple(
+        self,
+        len: usize,
+    ) -> Result<Compound<'a, W>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a, W>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a, W>, Error> {
+        let wrapped = self.tag_variant(variant)?;
+        let mut compound = self.serialize_seq(Some(len))?;
+        compound.wrapped = wrapped;
+        Ok(compound)
+    }
+
+    fn serialize_map(
+        self,
+        _len: Option<usize>,
+    ) -> Result<Compound<'a, W>, Error> {
+        self.emit_mapping_start()?;
+        Ok(Compound {
+            ser: self,
+            wrapped: false,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a, W>, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a, W>, Error> {
+        let wrapped = self.tag_variant(variant)?;
+        let mut compound = self.serialize_map(Some(len))?;
+        compound.wrapped = wrapped;
+        Ok(compound)
+    }
+}
+
+/// Serializes the items of a sequence or the pairs of a mapping.
+#[derive(Debug)]
+pub struct Compound<'a, W: Sink> {
+    ser: &'a mut Serializer<W>,
+    /// Is the collection the value of a variant wrapped in a mapping?
+    wrapped: bool,
+}
+
+impl<W: Sink> Compound<'_, W> {
+    fn end_sequence(self) -> Result<(), Error> {
+        self.ser.emit(Event::SequenceEnd)?;
+        self.ser.end_variant(self.wrapped)
+    }
+
+    fn end_mapping(self) -> Result<(), Error> {
+        self.ser.emit(Event::MappingEnd)?;
+        self.ser.end_variant(self.wrapped)
+    }
+}
+
+impl<W: Sink> ser::SerializeSeq for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.end_sequence()
+    }
+}
+
+impl<W: Sink> ser::SerializeTuple for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.end_sequence()
+    }
+}
+
+impl<W: Sink> ser::SerializeTupleStruct for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.end_sequence()
+    }
+}
+
+impl<W: Sink> ser::SerializeTupleVariant for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.end_sequence()
+    }
+}
+
+impl<W: Sink> ser::SerializeMap for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(
+        &mut self,
+        key: &T,
+    ) -> Result<(), Error> {
+        key.serialize(&mut *self.ser)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.end_mapping()
+    }
+}
+
+impl<W: Sink> ser::SerializeStruct for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        ser::Serializer::serialize_str(&mut *self.ser, key)?;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.end_mapping()
+    }
+}
+
+impl<W: Sink> ser::SerializeStructVariant for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        ser::Serializer::serialize_str(&mut *self.ser, key)?;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.end_mapping()
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(message: T) -> Self {
+        Error::new(ErrorKind::Emitter, message.to_string())
+    }
+}
@@ -4,9 +4,10 @@ use crate::{
     success::{Success, FAIL, OK},
     yaml::size_t,
     PointerExt, YamlAnyEncoding, YamlEmitterT, YamlUtf16leEncoding,
-    YamlUtf8Encoding, YamlWriterError,
+    YamlUtf32beEncoding, YamlUtf32leEncoding, YamlUtf8Encoding,
+    YamlWriterError,
 };
-use core::ptr::addr_of_mut;
+use core::ptr::{self, addr_of_mut};
 
 /// Sets the writer error for the emitter.
 ///
@@ -47,7 +48,8 @@ pub(crate) unsafe fn yaml_emitter_set_writer_error(
 ///  If the encoding is YamlUtf8Encoding, it writes the content of the buffer to the output stream.
 ///  If an error occurs during the write operation, it sets the error of the emitter and returns FAIL.
 ///
-///  If the encoding is not YamlUtf8Encoding, it writes the content of the buffer to the raw buffer.
+///  If the encoding is not YamlUtf8Encoding, it writes the content of the buffer to the raw buffer,
+///  encoded in UTF-16 or UTF-32.
 ///  It then writes the raw buffer to the output stream.
 ///  If an error occurs during the write operation, it sets the error of the emitter and returns FAIL.
 ///  If the write operation is successful, it returns OK.
@@ -154,7 +156,22 @@ pub unsafe fn yaml_emitter_flush(
         }
         let fresh5 = addr_of_mut!((*emitter).buffer.pointer);
         *fresh5 = (*fresh5).wrapping_offset(width as isize);
-        if value < 0x10000 {
+        if (*emitter).encoding == YamlUtf32leEncoding
+            || (*emitter).encoding == YamlUtf32beEncoding
+        {
+            let bytes = if (*emitter).encoding == YamlUtf32leEncoding {
+                value.to_le_bytes()
+            } else {
+                value.to_be_bytes()
+            };
+            ptr::copy_nonoverlapping(
+                bytes.as_ptr(),
+                (*emitter).raw_buffer.last,
+                4,
+            );
+            let fresh6 = addr_of_mut!((*emitter).raw_buffer.last);
+            *fresh6 = (*fresh6).wrapping_offset(4_isize);
+        } else if value < 0x10000 {
             *(*emitter)
                 .raw_buffer
                 .last
@@ -1,3 +1,4 @@
+use crate::json::JsonExpect;
 use crate::libc;
 use core::ops::Deref;
 use core::ptr::{self, addr_of};
@@ -44,6 +45,10 @@ pub enum YamlEncodingT {
     YamlUtf16leEncoding = 2,
     /// The UTF-16-BE encoding with BOM.
     YamlUtf16beEncoding = 3,
+    /// The UTF-32-LE encoding with BOM.
+    YamlUtf32leEncoding = 4,
+    /// The UTF-32-BE encoding with BOM.
+    YamlUtf32beEncoding = 5,
 }
 
 /// Line break type.
@@ -89,12 +94,16 @@ pub enum YamlErrorTypeT {
 #[repr(C)]
 #[non_exhaustive]
 pub struct YamlMarkT {
-    /// The position index.
+    /// The position index, in bytes of the input decoded to UTF-8.
     pub index: size_t,
     /// The position line.
     pub line: size_t,
-    /// The position column.
+    /// The position column, in characters.
     pub column: size_t,
+    /// The position offset, in bytes of the input as it was given.
+    pub offset: size_t,
+    /// The position column, in UTF-16 code units.
+    pub utf16_column: size_t,
 }
 
 /// Scalar styles.
@@ -142,6 +151,23 @@ pub enum YamlMappingStyleT {
     YamlFlowMappingStyle = 2,
 }
 
+/// Schemas for resolving the tags of untagged scalars.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum YamlSchemaT {
+    /// Every scalar is a string.
+    YamlFailsafeSchema = 0,
+    /// The YAML 1.2 JSON schema: null, booleans and numbers as spelled
+    /// in JSON.
+    YamlJsonSchema = 1,
+    /// The YAML 1.2 core schema, a superset of the JSON schema.
+    YamlCoreSchema = 2,
+    /// The YAML 1.1 types, with `yes`/`no` booleans, binary, octal and
+    /// sexagesimal numbers, and `_` separators.
+    YamlYaml11Schema = 3,
+}
+
 /// The token types.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 #[repr(u32)]
@@ -191,6 +217,22 @@ pub enum YamlTokenTypeT {
     YamlTagToken = 20,
     /// A scalar token.
     YamlScalarToken = 21,
+    /// A comment token.
+    YamlCommentToken = 22,
+}
+
+/// Comment types.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum YamlCommentTypeT {
+    /// A comment on its own line, before the next token.
+    YamlLeadingComment = 0,
+    /// A comment at the end of a line, after a token.
+    YamlTrailingComment = 1,
+    /// A block of comments separated from the content, by a blank line
+    /// or a document boundary.
+    YamlDocumentComment = 2,
 }
 
 /// The token structure.
@@ -235,6 +277,9 @@ pub struct YamlTokenT {
     ///         length: u64,
     ///         /// The scalar style.
     ///         style: YamlScalarStyleT,
+    ///         /// Does the value point into the input string instead of
+    ///         /// being owned by the token?
+    ///         borrowed: i32,
     ///     },
     ///     /// The version directive (for YamlVersionDirectiveToken).
     ///     version_directive: struct {
@@ -250,6 +295,15 @@ pub struct YamlTokenT {
     ///         /// The tag prefix.
     ///         prefix: *mut u8,
     ///     },
+    ///     /// The comment (for YamlCommentToken).
+    ///     comment: struct {
+    ///         /// The comment text, after the '#' indicator.
+    ///         value: *mut u8,
+    ///         /// The length of the comment text.
+    ///         length: u64,
+    ///         /// The comment type.
+    ///         type_: YamlCommentTypeT,
+    ///     },
     /// }
     /// # };
     /// ```
@@ -278,6 +332,8 @@ pub struct UnnamedYamlTokenTData {
     pub version_directive: UnnamedYamlTokenTdataVersionDirective,
     /// The tag directive (for YamlTagDirectiveToken).
     pub tag_directive: UnnamedYamlTokenTdataTagDirective,
+    /// The comment (for YamlCommentToken).
+    pub comment: UnnamedYamlTokenTdataComment,
 }
 
 /// Represents the start of a YAML data stream.
@@ -329,6 +385,9 @@ pub struct UnnamedYamlTokenTdataScalar {
     pub length: size_t,
     /// The scalar style.
     pub style: YamlScalarStyleT,
+    /// Does the value point into the input string instead of being owned
+    /// by the token?
+    pub borrowed: bool,
 }
 
 /// Represents the version directive in a YAML document.
@@ -353,6 +412,19 @@ pub struct UnnamedYamlTokenTdataTagDirective {
     pub prefix: *mut yaml_char_t,
 }
 
+/// Represents a comment in a YAML document.
+#[derive(Copy, Clone, Debug)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct UnnamedYamlTokenTdataComment {
+    /// The comment text, after the '#' indicator.
+    pub value: *mut yaml_char_t,
+    /// The length of the comment text.
+    pub length: size_t,
+    /// The comment type.
+    pub type_: YamlCommentTypeT,
+}
+
 /// Event types.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 #[repr(u32)]
@@ -439,6 +511,9 @@ pub struct YamlEventT {
     ///         quoted_implicit: i32,
     ///         /// The scalar style.
     ///         style: YamlScalarStyleT,
+    ///         /// Does the value point into the input string instead of
+    ///         /// being owned by the event?
+    ///         borrowed: i32,
     ///     },
     ///     /// The sequence parameters (for YamlSequenceStartEvent).
     ///     sequence_start: struct {
@@ -470,6 +545,27 @@ pub struct YamlEventT {
     pub start_mark: YamlMarkT,
     /// The end of the event.
     pub end_mark: YamlMarkT,
+    /// The comments attached to the event.
+    pub comments: YamlEventCommentsT,
+}
+
+/// The comments attached to an event.
+///
+/// Each field is null or a NUL-terminated UTF-8 string holding one or
+/// more comment lines separated by '\n'. A line is the text following
+/// the '#' indicator. The parser only fills these in when comments are
+/// enabled with yaml_parser_set_comments().
+#[derive(Copy, Clone, Debug)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct YamlEventCommentsT {
+    /// The comments on the lines before the event.
+    pub before: *mut yaml_char_t,
+    /// The comment at the end of the event's line.
+    pub inline: *mut yaml_char_t,
+    /// The comments on the lines after the event, set off from what
+    /// follows by a blank line.
+    pub after: *mut yaml_char_t,
 }
 
 /// Represents the data associated with a YAML event.
@@ -562,6 +658,9 @@ pub struct UnnamedYamlEventTdataScalar {
     pub quoted_implicit: bool,
     /// The scalar style.
     pub style: YamlScalarStyleT,
+    /// Does the value point into the input string instead of being owned
+    /// by the event?
+    pub borrowed: bool,
 }
 
 /// Represents the data associated with the start of a YAML sequence.
@@ -715,6 +814,8 @@ pub struct YamlNodePairT {
     pub key: libc::c_int,
     /// The value of the element.
     pub value: libc::c_int,
+    /// Was the element merged in from another mapping by a `<<` key?
+    pub merged: bool,
 }
 
 /// The document structure.
@@ -789,6 +890,8 @@ pub struct YamlSimpleKeyT {
     pub token_number: size_t,
     /// The position mark.
     pub mark: YamlMarkT,
+    /// May the key span several lines?
+    pub multiline: bool,
 }
 
 /// The states of the parser.
@@ -857,6 +960,9 @@ pub struct YamlAliasDataT {
     pub index: libc::c_int,
     /// The anchor mark.
     pub mark: YamlMarkT,
+    /// The number of nodes the anchored node stands for once its aliases
+    /// are expanded, or 0 while the node is still being loaded.
+    pub expanded: size_t,
 }
 
 /// The parser structure.
@@ -920,21 +1026,50 @@ pub struct YamlParserT {
     pub(crate) encoding: YamlEncodingT,
     /// The offset of the current position (in bytes).
     pub(crate) offset: size_t,
+    /// The maximum number of bytes read from the input.
+    pub(crate) max_input_size: size_t,
     /// The mark of the current position.
     pub(crate) mark: YamlMarkT,
     /// Have we started to scan the input stream?
     pub(crate) stream_start_produced: bool,
     /// Have we reached the end of the input stream?
     pub(crate) stream_end_produced: bool,
+    /// Are the YAML 1.2 rules followed instead of libyaml's 1.1-era ones?
+    pub(crate) strict: bool,
+    /// Is the input read as JSON?
+    pub(crate) json: bool,
+    /// Are comments skipped in JSON input?
+    pub(crate) json_comments: bool,
+    /// What the JSON scanner expects next.
+    pub(crate) json_expect: JsonExpect,
+    /// The schema resolving untagged scalars when loading documents.
+    pub(crate) schema: YamlSchemaT,
     /// The number of unclosed '[' and '{' indicators.
     pub(crate) flow_level: libc::c_int,
     /// The tokens queue.
     pub(crate) tokens: YamlQueueT<YamlTokenT>,
+    /// The maximum number of tokens in the queue.
+    pub(crate) max_queued_tokens: size_t,
+    /// The maximum length of a scalar value.
+    pub(crate) max_scalar_length: size_t,
+    /// The maximum length of an anchor, an alias or a tag part.
+    pub(crate) max_name_length: size_t,
     /// The number of tokens fetched from the queue.
     pub(crate) tokens_parsed: size_t,
     /// Does the tokens queue contain a token ready for dequeueing.
     pub(crate) token_available: bool,
-    /// The indentation levels stack.
+    /// The end of the last fetched token.
+    pub(crate) token_end_mark: YamlMarkT,
+    /// Are comment tokens produced?
+    pub(crate) comments: bool,
+    /// The comments waiting for the indentation to be unrolled.
+    pub(crate) pending_comments: YamlQueueT<YamlTokenT>,
+    /// The own-line comments waiting for the next node event.
+    pub(crate) comment_before: *mut yaml_char_t,
+    /// The trailing comments waiting for the next node event.
+    pub(crate) comment_inline: *mut yaml_char_t,
+    /// The indentation levels stack, or the kinds of the enclosing
+    /// arrays and objects in JSON input.
     pub(crate) indents: YamlStackT<libc::c_int>,
     /// The current indentation level.
     pub(crate) indent: libc::c_int,
@@ -944,16 +1079,38 @@ pub struct YamlParserT {
     pub(crate) simple_keys: YamlStackT<YamlSimpleKeyT>,
     /// At least this many leading elements of simple_keys have possible=0.
     pub(crate) not_simple_keys: libc::c_int,
+    /// The number of the token from which on the stale simple keys are
+    /// checked for. Once the scanner of a push parser fails ahead of the
+    /// parser, it is the first token that needs the last tokens fetched,
+    /// for the error to surface where it does in a pull parser.
+    pub(crate) stale_keys_from: size_t,
     /// The parser states stack.
     pub(crate) states: YamlStackT<YamlParserStateT>,
     /// The current parser state.
     pub(crate) state: YamlParserStateT,
+    /// The number of collections opened and not yet closed.
+    pub(crate) depth: size_t,
+    /// The maximum number of nested collections.
+    pub(crate) max_depth: size_t,
     /// The stack of marks.
     pub(crate) marks: YamlStackT<YamlMarkT>,
     /// The list of TAG directives.
     pub(crate) tag_directives: YamlStackT<YamlTagDirectiveT>,
     /// The alias data.
     pub(crate) aliases: YamlStackT<YamlAliasDataT>,
+    /// Are aliases allowed in loaded documents?
+    pub(crate) allow_aliases: bool,
+    /// The number of nodes loaded in the current document, counting every
+    /// alias as the nodes it stands for.
+    pub(crate) expanded_nodes: size_t,
+    /// The maximum number of nodes aliases may stand for in a document.
+    pub(crate) max_alias_expansion: size_t,
+    /// Are `<<` merge keys applied to loaded mappings?
+    pub(crate) merge_keys: bool,
+    /// Do the keys of loaded mappings have to be unique?
+    pub(crate) unique_keys: bool,
+    /// Do scalar values point into the input string when they can?
+    pub(crate) borrow_scalars: bool,
     /// The currently parsed document.
     pub(crate) document: *mut YamlDocumentT,
 }
@@ -1145,6 +1302,13 @@ pub struct YamlEmitterT {
     pub(crate) indention: bool,
     /// If an explicit document end is required?
     pub(crate) open_ended: libc::c_int,
+    /// The inline comment waiting for the end of the line.
+    pub(crate) comment: *mut yaml_char_t,
+    /// The comments after a block node, waiting for the next event to
+    /// tell whether they end the document.
+    pub(crate) comments_after: *mut yaml_char_t,
+    /// The indentation of the node the comments after it belong to.
+    pub(crate) comments_after_indent: libc::c_int,
     /// Anchor analysis.
     pub(crate) anchor_data: UnnamedYamlEmitterTAnchorData,
     /// Tag analysis.
@@ -1277,15 +1441,6 @@ impl<T> YamlBufferT<T> {
     pub(crate) fn is_empty(&self) -> bool {
         self.pointer == self.last
     }
-
-    /// Advances the buffer by one character.
-    pub(crate) fn next(&mut self) {
-        if !self.is_empty() {
-            unsafe {
-                self.pointer = self.pointer.add(1);
-            }
-        }
-    }
 }
 
 // impl<T> Copy for YamlBufferT<T> {}
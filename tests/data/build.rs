@@ -1,47 +1,39 @@
-#![allow(clippy::uninlined_format_args)]
+use std::env;
+use std::path::PathBuf;
 
-use anyhow::Result;
-use flate2::read::GzDecoder;
-use std::fs;
-use std::path::Path;
-use tar::Archive;
-
-const TAG: &str = "data-2020-02-11";
+/// The yaml-test-suite data release the tests are run against.
+const TAG: &str = "data-2022-01-17";
 
 fn main() {
-    let needs_clone = match fs::read_to_string("yaml-test-suite/COMMIT")
-    {
-        Err(_) => true,
-        Ok(contents) => contents.trim() != TAG,
+    // A checkout of the suite's data branch, either vendored next to
+    // this file or anywhere else on disk. Relative paths are taken
+    // from the workspace root.
+    let manifest_dir =
+        PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let dir = match env::var_os("YAML_TEST_SUITE") {
+        Some(dir) => manifest_dir.join("..").join("..").join(dir),
+        None => manifest_dir.join("yaml-test-suite"),
     };
-    if needs_clone {
-        download_and_unpack().unwrap();
-    }
-}
 
-fn download_and_unpack() -> Result<()> {
-    let url = format!("https://github.com/yaml/yaml-test-suite/archive/refs/tags/{TAG}.tar.gz");
-    let response = reqwest::blocking::get(url)?.error_for_status()?;
-    let decoder = GzDecoder::new(response);
-    let mut archive = Archive::new(decoder);
-    let prefix = format!("yaml-test-suite-{}", TAG);
+    println!("cargo:rerun-if-env-changed=YAML_TEST_SUITE");
+    println!("cargo:rerun-if-env-changed=CI");
+    println!("cargo:rerun-if-changed={}", dir.display());
+    println!("cargo:rustc-env=YAML_TEST_SUITE_DIR={}", dir.display());
+    println!("cargo:rustc-env=YAML_TEST_SUITE_TAG={}", TAG);
 
-    let yaml_test_suite = Path::new("yaml-test-suite");
-    if yaml_test_suite.exists() {
-        fs::remove_dir_all(yaml_test_suite)?;
-    }
-
-    for entry in archive.entries()? {
-        let mut entry = entry?;
-        let path = entry.path()?;
-        if path == Path::new("pax_global_header") {
-            continue;
+    // Without a checkout the conformance tests cannot run. That is
+    // allowed locally but never in CI, where it would pass without
+    // having run a single case.
+    if !dir.is_dir() {
+        let message = format!(
+            "yaml-test-suite not found at {}; check out its {} tag there \
+             or set YAML_TEST_SUITE to a local checkout",
+            dir.display(),
+            TAG,
+        );
+        if env::var_os("CI").is_some() {
+            panic!("{}", message);
         }
-        let relative = path.strip_prefix(&prefix)?;
-        let out = yaml_test_suite.join(relative);
-        entry.unpack(&out)?;
+        println!("cargo:warning={}", message);
     }
-
-    fs::write("yaml-test-suite/COMMIT", TAG)?;
-    Ok(())
 }
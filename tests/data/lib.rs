@@ -5,6 +5,9 @@ use std::fs::{self, File};
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
+const YAML_TEST_SUITE: &str = env!("YAML_TEST_SUITE_DIR");
+const TAG: &str = env!("YAML_TEST_SUITE_TAG");
+
 #[proc_macro]
 pub fn test_emitter(_input: TokenStream) -> TokenStream {
     test("libyaml-emitter", |dir| !dir.join("error").exists())
@@ -23,25 +26,85 @@ pub fn test_parser_error(_input: TokenStream) -> TokenStream {
 fn test(ignorelist: &str, check: fn(&Path) -> bool) -> TokenStream {
     let tests_dir = Path::new("tests");
 
+    // Entries are either a whole test such as `2G84`, or a single case
+    // of it such as `2G84/00`.
     let mut ignored_ids = Set::new();
     let ignorelist = tests_dir.join("ignorelist").join(ignorelist);
-    for line in BufReader::new(File::open(ignorelist).unwrap()).lines()
+    for line in BufReader::new(File::open(&ignorelist).unwrap()).lines()
     {
-        let mut line = line.unwrap();
-        line.truncate(4);
-        ignored_ids.insert(line);
+        let line = line.unwrap();
+        let id = line.split(':').next().unwrap().trim();
+        ignored_ids.insert(id.to_owned());
     }
 
-    let mut ids = Map::new();
-    let yaml_test_suite =
-        tests_dir.join("data").join("yaml-test-suite");
-    for entry in fs::read_dir(yaml_test_suite).unwrap() {
+    // Without a checkout the suite is skipped, leaving a single ignored
+    // test that says where it was looked for. The build script warns
+    // about it, and refuses it in CI.
+    let yaml_test_suite = Path::new(YAML_TEST_SUITE);
+    let entries = match fs::read_dir(yaml_test_suite) {
+        Ok(entries) => entries,
+        Err(_) => {
+            let message = format!(
+                "yaml-test-suite not found at {}; check out its {} tag \
+                 there or set YAML_TEST_SUITE to a local checkout",
+                YAML_TEST_SUITE, TAG,
+            );
+            return TokenStream::from(quote! {
+                #[test]
+                #[ignore = #message]
+                fn yaml_test_suite() {
+                    test(#YAML_TEST_SUITE);
+                }
+            });
+        }
+    };
+
+    let mut cases = Map::new();
+    for entry in entries {
         let entry = entry.unwrap();
-        if !entry.file_type().unwrap().is_dir() {
+        let file_name = entry.file_name();
+        let id = file_name.to_str().unwrap();
+        if !entry.file_type().unwrap().is_dir() || !is_id(id) {
             continue;
         }
 
+        // Tests with several cases keep each one in a numbered
+        // subdirectory.
         let path = entry.path();
+        if path.join("===").exists() {
+            cases.insert(id.to_owned(), path);
+            continue;
+        }
+        for entry in fs::read_dir(&path).unwrap() {
+            let entry = entry.unwrap();
+            if entry.file_type().unwrap().is_dir() {
+                let file_name = entry.file_name();
+                let case = file_name.to_str().unwrap();
+                cases.insert(format!("{id}/{case}"), entry.path());
+            }
+        }
+    }
+
+    // An entry that names no case of the release is left over from an
+    // older one, which means the list needs regenerating.
+    for id in &ignored_ids {
+        let known = cases.keys().any(|case| {
+            case == id || case.split('/').next() == Some(id)
+        });
+        assert!(
+            known,
+            "{} lists {}, which is not a case of yaml-test-suite {}; \
+             regenerate it from the tests failing against {}",
+            ignorelist.display(),
+            id,
+            TAG,
+            TAG,
+        );
+    }
+
+    let mut tests = proc_macro2::TokenStream::new();
+    let ignore = quote!(#[ignore]);
+    for (id, path) in cases {
         let description = path.join("===");
         let slug =
             if let Ok(description) = fs::read_to_string(description) {
@@ -54,23 +117,20 @@ fn test(ignorelist: &str, check: fn(&Path) -> bool) -> TokenStream {
             continue;
         }
 
-        let file_name = entry.file_name();
-        let id = file_name.to_str().unwrap().to_owned();
-        ids.insert(id, slug);
-    }
-
-    let mut tests = proc_macro2::TokenStream::new();
-    let ignore = quote!(#[ignore]);
-    for (id, slug) in ids {
-        let test_name = format_ident!("_{id}_{slug}");
-        let ignore = ignored_ids.contains(&id).then_some(&ignore);
+        let test_name =
+            format_ident!("_{}_{slug}", id.replace('/', "_"));
+        let test_id = id.split('/').next().unwrap();
+        let ignore = (ignored_ids.contains(&id)
+            || ignored_ids.contains(test_id))
+        .then_some(&ignore);
+        let dir = path.to_str().unwrap();
 
         tests.extend(quote! {
             #[test]
             #ignore
             #[allow(non_snake_case)]
             fn #test_name() {
-                test(#id);
+                test(#dir);
             }
         });
     }
@@ -78,6 +138,13 @@ fn test(ignorelist: &str, check: fn(&Path) -> bool) -> TokenStream {
     TokenStream::from(tests)
 }
 
+fn is_id(name: &str) -> bool {
+    name.len() == 4
+        && name
+            .bytes()
+            .all(|ch| ch.is_ascii_uppercase() || ch.is_ascii_digit())
+}
+
 fn description_to_slug(mut description: String) -> String {
     description = description
         .replace(|ch: char| !ch.is_ascii_alphanumeric(), "_");
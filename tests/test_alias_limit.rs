@@ -0,0 +1,110 @@
+use libyml::checked;
+use libyml::error::{Error, ErrorKind};
+use libyml::{
+    yaml_document_delete, yaml_parser_delete,
+    yaml_parser_set_allow_aliases, yaml_parser_set_input_string,
+    yaml_parser_set_max_alias_expansion, YamlDocumentT, YamlParserT,
+};
+use std::mem::MaybeUninit;
+
+fn load(
+    source: &str,
+    configure: impl FnOnce(*mut YamlParserT),
+) -> Result<(), Error> {
+    unsafe {
+        let mut parser = MaybeUninit::<YamlParserT>::uninit();
+        let parser = parser.as_mut_ptr();
+        checked::yaml_parser_initialize(parser).unwrap();
+        configure(parser);
+        yaml_parser_set_input_string(
+            parser,
+            source.as_ptr(),
+            source.len() as u64,
+        );
+        let mut document = MaybeUninit::<YamlDocumentT>::uninit();
+        let document = document.as_mut_ptr();
+        let result = checked::yaml_parser_load(parser, document);
+        if result.is_ok() {
+            yaml_document_delete(document);
+        }
+        yaml_parser_delete(parser);
+        result
+    }
+}
+
+fn billion_laughs() -> String {
+    let mut source = String::from("a: &a [lol, lol, lol, lol, lol]\n");
+    for (previous, name) in "abcdefghi".chars().zip("bcdefghij".chars())
+    {
+        let aliases = vec![format!("*{previous}"); 10].join(", ");
+        source += &format!("{}: &{} [{}]\n", name, name, aliases);
+    }
+    source
+}
+
+#[test]
+fn test_billion_laughs() {
+    let source = billion_laughs();
+    let error = load(&source, |_| {}).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Composer);
+    assert_eq!(error.message(), "alias expansion limit exceeded");
+    let mark = error.start_mark().unwrap();
+    assert_eq!(mark.line, 6);
+}
+
+#[test]
+fn test_expansion_budget() {
+    // Each alias stands for the sequence and its two items.
+    let source = "a: &a [x, y]\nb: *a\nc: *a\n";
+    load(source, |parser| unsafe {
+        yaml_parser_set_max_alias_expansion(parser, 6);
+    })
+    .unwrap();
+    let error = load(source, |parser| unsafe {
+        yaml_parser_set_max_alias_expansion(parser, 5);
+    })
+    .unwrap_err();
+    assert_eq!(error.message(), "alias expansion limit exceeded");
+    assert_eq!(error.start_mark().unwrap().line, 2);
+
+    // Nested aliases are expanded in turn.
+    let source = "a: &a [x]\nb: &b [*a, *a]\nc: *b\n";
+    load(source, |parser| unsafe {
+        yaml_parser_set_max_alias_expansion(parser, 9);
+    })
+    .unwrap();
+    assert!(load(source, |parser| unsafe {
+        yaml_parser_set_max_alias_expansion(parser, 8);
+    })
+    .is_err());
+}
+
+#[test]
+fn test_recursive_alias() {
+    load("&a [*a, *a]\n", |parser| unsafe {
+        yaml_parser_set_max_alias_expansion(parser, 2);
+    })
+    .unwrap();
+}
+
+#[test]
+fn test_aliases_not_allowed() {
+    let source = "a: &a x\nb: *a\n";
+    load(source, |_| {}).unwrap();
+    let error = load(source, |parser| unsafe {
+        yaml_parser_set_allow_aliases(parser, false);
+    })
+    .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Composer);
+    assert_eq!(
+        error.message(),
+        "found alias, but aliases are not allowed"
+    );
+    let mark = error.start_mark().unwrap();
+    assert_eq!((mark.line, mark.column), (1, 3));
+
+    load("a: &a x\n", |parser| unsafe {
+        yaml_parser_set_allow_aliases(parser, false);
+    })
+    .unwrap();
+}
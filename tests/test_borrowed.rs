@@ -0,0 +1,143 @@
+use libyml::safe::{Event, Loader, Parser, Scalar};
+use std::borrow::Cow;
+
+fn scalars(input: &[u8]) -> Vec<Scalar<'_>> {
+    Parser::new(input)
+        .filter_map(|event| match event.unwrap() {
+            Event::Scalar(scalar) => Some(scalar),
+            _ => None,
+        })
+        .collect()
+}
+
+fn is_borrowed(value: Option<&Cow<'_, str>>) -> bool {
+    matches!(value, Some(Cow::Borrowed(_)))
+}
+
+#[test]
+fn test_verbatim() {
+    let input =
+        "plain: one two\n'single': \"dou ble\"\n[ü, x y]: \"\"\n";
+    let scalars = scalars(input.as_bytes());
+    let values: Vec<_> = scalars
+        .iter()
+        .map(|scalar| {
+            (&*scalar.value, is_borrowed(Some(&scalar.value)))
+        })
+        .collect();
+    assert_eq!(
+        values,
+        [
+            ("plain", true),
+            ("one two", true),
+            ("single", true),
+            ("dou ble", true),
+            ("ü", true),
+            ("x y", true),
+            ("", true),
+        ],
+    );
+}
+
+#[test]
+fn test_copied() {
+    let input = b"\
+- a
+  b
+- 'it''s'
+- \"tab\\t\"
+- \"line\\
+  joined\"
+- 'folded
+  quote'
+- |
+  literal
+- >
+  folded
+";
+    let scalars = scalars(input);
+    let values: Vec<_> = scalars
+        .iter()
+        .map(|scalar| {
+            (&*scalar.value, is_borrowed(Some(&scalar.value)))
+        })
+        .collect();
+    assert_eq!(
+        values,
+        [
+            ("a b", false),
+            ("it's", false),
+            ("tab\t", false),
+            ("linejoined", false),
+            ("folded quote", false),
+            ("literal\n", false),
+            ("folded\n", false),
+        ],
+    );
+}
+
+#[test]
+fn test_anchors_and_tags() {
+    let input = b"- &a !local x\n- *a\n- !!str y\n- &b !<tag:z> [z]\n";
+    let events: Vec<_> =
+        Parser::new(input).map(Result::unwrap).collect();
+
+    let Event::Scalar(scalar) = &events[3] else {
+        panic!("{:?}", events[3]);
+    };
+    assert!(is_borrowed(scalar.anchor.as_ref()));
+    assert!(is_borrowed(scalar.tag.as_ref()));
+    assert_eq!(scalar.tag.as_deref(), Some("!local"));
+
+    let Event::Alias(alias) = &events[4] else {
+        panic!("{:?}", events[4]);
+    };
+    assert!(is_borrowed(Some(&alias.anchor)));
+
+    let Event::Scalar(scalar) = &events[5] else {
+        panic!("{:?}", events[5]);
+    };
+    assert!(!is_borrowed(scalar.tag.as_ref()));
+    assert_eq!(scalar.tag.as_deref(), Some("tag:yaml.org,2002:str"));
+
+    let Event::SequenceStart(sequence) = &events[6] else {
+        panic!("{:?}", events[6]);
+    };
+    assert_eq!(sequence.anchor.as_deref(), Some("b"));
+    assert!(is_borrowed(sequence.anchor.as_ref()));
+    assert!(is_borrowed(sequence.tag.as_ref()));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_same_values() {
+    let input = b"\
+a: b c  # comment
+'d''e': \"f\\\"g\"
+h: >-
+  i
+   j
+k: l
+
+  m
+
+  n
+o: [p q, 'r s',
+  t]
+u: \"v\xE2\x80\xA8w\"
+x: y\xC2\x85  z\xE2\x80\xA8 z
+";
+    let copied: Vec<_> = Parser::from_reader(&input[..])
+        .map(Result::unwrap)
+        .collect();
+    let borrowed: Vec<_> =
+        Parser::new(input).map(Result::unwrap).collect();
+    assert_eq!(copied, borrowed);
+
+    let document = Loader::new(input).next().unwrap().unwrap();
+    let root = document.root().unwrap().as_mapping().unwrap();
+    assert_eq!(root.get("a").unwrap().as_str(), Some("b c"));
+    assert_eq!(root.get("k").unwrap().as_str(), Some("l\nm\nn"));
+    assert_eq!(root.get("u").unwrap().as_str(), Some("v\u{2028}w"));
+    assert_eq!(root.get("x").unwrap().as_str(), Some("y z\u{2028}z"));
+}
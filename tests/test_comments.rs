@@ -0,0 +1,489 @@
+use libyml::checked;
+use libyml::{
+    yaml_emitter_delete, yaml_emitter_set_output, yaml_event_delete,
+    yaml_parser_delete, yaml_parser_set_comments,
+    yaml_parser_set_input_string, yaml_token_delete, YamlBlockEndToken,
+    YamlCommentToken, YamlCommentTypeT, YamlDocumentComment,
+    YamlEmitterT, YamlEventT, YamlKeyToken, YamlLeadingComment,
+    YamlParserT, YamlScalarEvent, YamlScalarToken, YamlStreamEndEvent,
+    YamlStreamEndToken, YamlTokenT, YamlTokenTypeT,
+    YamlTrailingComment,
+};
+use std::ffi::{c_void, CStr};
+use std::mem::MaybeUninit;
+use std::ptr::addr_of_mut;
+use std::slice;
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Comment(YamlCommentTypeT, String, (u64, u64)),
+    Scalar(String),
+    Other(YamlTokenTypeT),
+}
+
+fn scan(input: &str, comments: bool) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    unsafe {
+        let mut parser = MaybeUninit::<YamlParserT>::uninit();
+        let parser = parser.as_mut_ptr();
+        checked::yaml_parser_initialize(parser).unwrap();
+        yaml_parser_set_comments(parser, comments);
+        yaml_parser_set_input_string(
+            parser,
+            input.as_ptr(),
+            input.len() as u64,
+        );
+        let mut token = MaybeUninit::<YamlTokenT>::uninit();
+        let token = token.as_mut_ptr();
+        loop {
+            checked::yaml_parser_scan(parser, token).unwrap();
+            let type_ = (*token).type_;
+            tokens.push(match type_ {
+                YamlCommentToken => {
+                    let comment = (*token).data.comment;
+                    let text = slice::from_raw_parts(
+                        comment.value,
+                        comment.length as usize,
+                    );
+                    Token::Comment(
+                        comment.type_,
+                        String::from_utf8(text.to_vec()).unwrap(),
+                        (
+                            (*token).start_mark.line,
+                            (*token).start_mark.column,
+                        ),
+                    )
+                }
+                YamlScalarToken => {
+                    let scalar = (*token).data.scalar;
+                    let text = slice::from_raw_parts(
+                        scalar.value,
+                        scalar.length as usize,
+                    );
+                    Token::Scalar(
+                        String::from_utf8(text.to_vec()).unwrap(),
+                    )
+                }
+                other => Token::Other(other),
+            });
+            yaml_token_delete(token);
+            if type_ == YamlStreamEndToken {
+                break;
+            }
+        }
+        yaml_parser_delete(parser);
+    }
+    tokens
+}
+
+fn comments(input: &str) -> Vec<Token> {
+    scan(input, true)
+        .into_iter()
+        .filter(|token| matches!(token, Token::Comment(..)))
+        .collect()
+}
+
+fn comment(
+    type_: YamlCommentTypeT,
+    text: &str,
+    line: u64,
+    column: u64,
+) -> Token {
+    Token::Comment(type_, text.to_owned(), (line, column))
+}
+
+#[test]
+fn test_comments_disabled_by_default() {
+    let input =
+        "# header\na: 1 # one\n# about b\nb: |  # literal\n  text\n";
+    let plain = scan(input, false);
+    assert!(!plain
+        .iter()
+        .any(|token| matches!(token, Token::Comment(..))));
+    let with_comments: Vec<_> = scan(input, true)
+        .into_iter()
+        .filter(|token| !matches!(token, Token::Comment(..)))
+        .collect();
+    assert_eq!(plain, with_comments);
+}
+
+#[test]
+fn test_comment_classification() {
+    let input = "\
+# Copyright
+
+# about a
+a: 1 # one
+b:
+  # about c
+  c: [x, # inline
+    y]
+# the end
+";
+    assert_eq!(
+        comments(input),
+        [
+            comment(YamlDocumentComment, " Copyright", 0, 0),
+            comment(YamlLeadingComment, " about a", 2, 0),
+            comment(YamlTrailingComment, " one", 3, 5),
+            comment(YamlLeadingComment, " about c", 5, 2),
+            comment(YamlTrailingComment, " inline", 6, 9),
+            comment(YamlDocumentComment, " the end", 8, 0),
+        ],
+    );
+}
+
+#[test]
+fn test_comment_token_order() {
+    let input = "a:\n  b: 1 # one\n# about c\nc: 2\n";
+    let tokens = scan(input, true);
+    let position = |wanted: &Token| {
+        tokens.iter().position(|token| token == wanted).unwrap()
+    };
+    let one = position(&Token::Scalar("1".to_owned()));
+    let trailing =
+        position(&comment(YamlTrailingComment, " one", 1, 7));
+    let leading =
+        position(&comment(YamlLeadingComment, " about c", 2, 0));
+    let c = position(&Token::Scalar("c".to_owned()));
+    // The inner mapping ends between the two comments.
+    assert_eq!(trailing, one + 1);
+    assert_eq!(tokens[trailing + 1], Token::Other(YamlBlockEndToken),);
+    assert!(leading < c);
+    assert_eq!(tokens[leading + 1], Token::Other(YamlKeyToken),);
+}
+
+#[test]
+fn test_comment_after_indicators() {
+    let input = "%TAG !e! tag:example.com,2000: # tags\n--- # start\nkey: | # header\n  text\n# before end\n...\n";
+    assert_eq!(
+        comments(input),
+        [
+            comment(YamlTrailingComment, " tags", 0, 31),
+            comment(YamlTrailingComment, " start", 1, 4),
+            comment(YamlTrailingComment, " header", 2, 7),
+            comment(YamlDocumentComment, " before end", 4, 0),
+        ],
+    );
+    let tokens = scan(input, true);
+    let scalar = tokens
+        .iter()
+        .position(|token| *token == Token::Scalar("text\n".to_owned()))
+        .unwrap();
+    assert_eq!(
+        tokens[scalar + 1],
+        comment(YamlTrailingComment, " header", 2, 7),
+    );
+}
+
+#[test]
+fn test_parse_ignores_comments() {
+    let input = "# header\n\na: 1 # one\nb: | # two\n  x\n";
+    let values = |comments: bool| unsafe {
+        let mut values = Vec::new();
+        let mut parser = MaybeUninit::<YamlParserT>::uninit();
+        let parser = parser.as_mut_ptr();
+        checked::yaml_parser_initialize(parser).unwrap();
+        yaml_parser_set_comments(parser, comments);
+        yaml_parser_set_input_string(
+            parser,
+            input.as_ptr(),
+            input.len() as u64,
+        );
+        let mut event = MaybeUninit::<YamlEventT>::uninit();
+        let event = event.as_mut_ptr();
+        loop {
+            checked::yaml_parser_parse(parser, event).unwrap();
+            let type_ = (*event).type_;
+            if type_ == YamlScalarEvent {
+                let scalar = (*event).data.scalar;
+                let text = slice::from_raw_parts(
+                    scalar.value,
+                    scalar.length as usize,
+                );
+                values.push(String::from_utf8(text.to_vec()).unwrap());
+            }
+            yaml_event_delete(event);
+            if type_ == YamlStreamEndEvent {
+                break;
+            }
+        }
+        yaml_parser_delete(parser);
+        values
+    };
+    assert_eq!(values(true), values(false));
+    assert_eq!(values(true), ["a", "1", "b", "x\n"]);
+}
+
+unsafe fn write_to_vec(
+    data: *mut c_void,
+    buffer: *mut u8,
+    size: u64,
+) -> i32 {
+    let output = &mut *data.cast::<Vec<u8>>();
+    output.extend_from_slice(slice::from_raw_parts(
+        buffer,
+        size as usize,
+    ));
+    1
+}
+
+fn round_trip(input: &str) -> String {
+    let mut output = Vec::new();
+    unsafe {
+        let mut parser = MaybeUninit::<YamlParserT>::uninit();
+        let parser = parser.as_mut_ptr();
+        checked::yaml_parser_initialize(parser).unwrap();
+        yaml_parser_set_comments(parser, true);
+        yaml_parser_set_input_string(
+            parser,
+            input.as_ptr(),
+            input.len() as u64,
+        );
+        let mut emitter = MaybeUninit::<YamlEmitterT>::uninit();
+        let emitter = emitter.as_mut_ptr();
+        checked::yaml_emitter_initialize(emitter).unwrap();
+        yaml_emitter_set_output(
+            emitter,
+            write_to_vec,
+            addr_of_mut!(output).cast(),
+        );
+        let mut event = MaybeUninit::<YamlEventT>::uninit();
+        let event = event.as_mut_ptr();
+        loop {
+            checked::yaml_parser_parse(parser, event).unwrap();
+            let type_ = (*event).type_;
+            checked::yaml_emitter_emit(emitter, event).unwrap();
+            if type_ == YamlStreamEndEvent {
+                break;
+            }
+        }
+        yaml_emitter_delete(emitter);
+        yaml_parser_delete(parser);
+    }
+    String::from_utf8(output).unwrap()
+}
+
+fn event_comments(input: &str) -> Vec<(String, [Option<String>; 3])> {
+    let text = |comment: *mut u8| unsafe {
+        if comment.is_null() {
+            return None;
+        }
+        let bytes = CStr::from_ptr(comment.cast()).to_bytes();
+        Some(String::from_utf8(bytes.to_vec()).unwrap())
+    };
+    let mut events = Vec::new();
+    unsafe {
+        let mut parser = MaybeUninit::<YamlParserT>::uninit();
+        let parser = parser.as_mut_ptr();
+        checked::yaml_parser_initialize(parser).unwrap();
+        yaml_parser_set_comments(parser, true);
+        yaml_parser_set_input_string(
+            parser,
+            input.as_ptr(),
+            input.len() as u64,
+        );
+        let mut event = MaybeUninit::<YamlEventT>::uninit();
+        let event = event.as_mut_ptr();
+        loop {
+            checked::yaml_parser_parse(parser, event).unwrap();
+            let type_ = (*event).type_;
+            let comments = (*event).comments;
+            let comments = [
+                text(comments.before),
+                text(comments.inline),
+                text(comments.after),
+            ];
+            if comments.iter().any(Option::is_some) {
+                let name = if type_ == YamlScalarEvent {
+                    let scalar = (*event).data.scalar;
+                    let value = slice::from_raw_parts(
+                        scalar.value,
+                        scalar.length as usize,
+                    );
+                    String::from_utf8(value.to_vec()).unwrap()
+                } else {
+                    format!("{type_:?}")
+                };
+                events.push((name, comments));
+            }
+            yaml_event_delete(event);
+            if type_ == YamlStreamEndEvent {
+                break;
+            }
+        }
+        yaml_parser_delete(parser);
+    }
+    events
+}
+
+#[test]
+fn test_comment_events() {
+    let input = "\
+# about a
+a: 1 # one
+
+# detached
+
+b: [x, y] # flow
+c:
+  # about d
+  d: | # literal
+    text
+# the end
+";
+    let some = |text: &str| Some(text.to_owned());
+    assert_eq!(
+        event_comments(input),
+        [
+            (
+                "YamlMappingStartEvent".to_owned(),
+                [some(" about a"), None, None],
+            ),
+            ("1".to_owned(), [None, some(" one"), some(" detached")]),
+            (
+                "YamlSequenceEndEvent".to_owned(),
+                [None, some(" flow"), None],
+            ),
+            (
+                "YamlMappingStartEvent".to_owned(),
+                [some(" about d"), None, None],
+            ),
+            ("text\n".to_owned(), [None, some(" literal"), None]),
+            (
+                "YamlMappingEndEvent".to_owned(),
+                [None, None, some(" the end")],
+            ),
+        ],
+    );
+}
+
+#[test]
+fn test_trailing_comment_after_many_entries() {
+    // Fetching the comment after a '[' grows the token queue at some
+    // numbers of entries before it, which moves the tokens in it.
+    for entries in 0..20 {
+        let mut input = String::new();
+        for i in 0..entries {
+            input += &format!("a{i}: b\n");
+        }
+        input += "k: [ # c\n  x]\n";
+        assert_eq!(
+            event_comments(&input),
+            [(
+                "YamlSequenceStartEvent".to_owned(),
+                [None, Some(" c".to_owned()), None],
+            )],
+            "{entries}",
+        );
+    }
+}
+
+#[test]
+fn test_round_trip_kubernetes_manifest() {
+    let input = "\
+# Copyright 2024 Example
+# SPDX-License-Identifier: Apache-2.0
+
+---
+# The web deployment.
+apiVersion: apps/v1 # API group
+kind: Deployment
+metadata:
+  name: web # the name
+  labels: {app: web, tier: frontend} # selected by the service
+  # annotations follow
+  annotations:
+    note: |  # literal header
+      line one
+      line two
+spec:
+  replicas: 3 # scaled by HPA
+
+  # Containers.
+  template:
+    spec:
+      containers:
+        # first container
+        - name: nginx
+          image: nginx:1.25 # pinned
+          ports: [80, 443] # http and https
+        - name: sidecar
+          args:
+            - --verbose # noisy
+            # trailing item note
+            - --port=9000
+      volumes: # none yet
+...
+";
+    let expected = "\
+# Copyright 2024 Example
+# SPDX-License-Identifier: Apache-2.0
+---
+# The web deployment.
+apiVersion: apps/v1 # API group
+kind: Deployment
+metadata:
+  name: web # the name
+  labels: {app: web, tier: frontend} # selected by the service
+  # annotations follow
+  annotations:
+    note: | # literal header
+      line one
+      line two
+spec:
+  replicas: 3 # scaled by HPA
+  # Containers.
+  template:
+    spec:
+      containers:
+        # first container
+        - name: nginx
+          image: nginx:1.25 # pinned
+          ports: [80, 443] # http and https
+        - name: sidecar
+          args:
+          - --verbose # noisy
+          # trailing item note
+          - --port=9000
+      volumes: # none yet
+...
+";
+    let output = round_trip(input);
+    assert_eq!(output, expected);
+    assert_eq!(round_trip(&output), expected);
+}
+
+#[test]
+fn test_round_trip_is_stable() {
+    for (input, expected) in [
+        (
+            "key: {a: 1, # on a\n  b: 2} # end\n",
+            "key: {a: 1, # on a\n  b: 2} # end\n",
+        ),
+        (
+            "key: [ # open\n  a,\n  # before b\n  b,\n]\n",
+            "key: [ # open\n  a,\n  # before b\n  b]\n",
+        ),
+        (
+            "- [] # empty\n- {} # empty map\n",
+            "- [] # empty\n- {} # empty map\n",
+        ),
+        (
+            "--- # start\nfoo\n... # end\n",
+            "--- foo # start\n... # end\n",
+        ),
+        (
+            "a: &anchor 1 # anchored\nb: *anchor # alias\n",
+            "a: &anchor 1 # anchored\nb: *anchor # alias\n",
+        ),
+        ("# only a comment\n", "# only a comment\n"),
+        ("a: 1\n\n# end\n", "a: 1\n\n# end\n"),
+        ("a: 1\n\n# end\n...\n", "a: 1\n\n# end\n...\n"),
+        ("a: 1\n# about a\n\nb: 2\n", "a: 1\n# about a\n\nb: 2\n"),
+    ] {
+        let once = round_trip(input);
+        assert_eq!(once, expected, "{input:?}");
+        assert_eq!(round_trip(&once), once, "{input:?}");
+    }
+}
@@ -0,0 +1,72 @@
+use libyml::checked;
+use libyml::error::{Error, ErrorKind};
+use libyml::safe::Parser;
+use libyml::{
+    yaml_document_delete, yaml_parser_delete,
+    yaml_parser_set_input_string, yaml_parser_set_max_depth,
+    YamlDocumentT, YamlParserT,
+};
+use std::mem::MaybeUninit;
+
+fn parse(source: &str) -> Result<(), Error> {
+    Parser::new(source.as_bytes()).try_for_each(|event| event.map(drop))
+}
+
+fn load(source: &str, max_depth: u64) -> Result<(), Error> {
+    unsafe {
+        let mut parser = MaybeUninit::<YamlParserT>::uninit();
+        let parser = parser.as_mut_ptr();
+        checked::yaml_parser_initialize(parser).unwrap();
+        yaml_parser_set_max_depth(parser, max_depth);
+        yaml_parser_set_input_string(
+            parser,
+            source.as_ptr(),
+            source.len() as u64,
+        );
+        let mut document = MaybeUninit::<YamlDocumentT>::uninit();
+        let document = document.as_mut_ptr();
+        let result = checked::yaml_parser_load(parser, document);
+        if result.is_ok() {
+            yaml_document_delete(document);
+        }
+        yaml_parser_delete(parser);
+        result
+    }
+}
+
+#[test]
+fn test_default_limit() {
+    let deepest = format!("{}{}", "[".repeat(1000), "]".repeat(1000));
+    parse(&deepest).unwrap();
+
+    let source = "[".repeat(1_000_000);
+    let error = parse(&source).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Parser);
+    assert_eq!(error.message(), "recursion limit exceeded");
+    let mark = error.start_mark().unwrap();
+    assert_eq!((mark.line, mark.column), (0, 1000));
+}
+
+#[test]
+fn test_block_nesting() {
+    let mut source = String::new();
+    for level in 0..1001 {
+        source += &" ".repeat(level);
+        source += "a:\n";
+    }
+    let error = parse(&source).unwrap_err();
+    assert_eq!(error.message(), "recursion limit exceeded");
+    assert_eq!(error.start_mark().unwrap().line, 1000);
+}
+
+#[test]
+fn test_custom_limit() {
+    load("- [a, {b: c}]\n", 3).unwrap();
+    let error = load("- [a, {b: c}]\n", 2).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Parser);
+    assert_eq!(error.message(), "recursion limit exceeded");
+    assert_eq!(error.start_mark().unwrap().column, 6);
+
+    load("scalar\n", 0).unwrap();
+    assert!(load("[]\n", 0).is_err());
+}
@@ -0,0 +1,95 @@
+use libyml::error::{Error, ErrorKind};
+use libyml::safe::{Document, Loader, Parser};
+use libyml::{YamlCoreSchema, YamlFailsafeSchema, YamlSchemaT};
+use std::time::{Duration, Instant};
+
+fn load(input: &[u8], schema: YamlSchemaT) -> Result<Document, Error> {
+    let mut parser = Parser::new(input);
+    parser.set_schema(schema);
+    parser.set_unique_keys(true);
+    Loader::from_parser(parser).next().unwrap()
+}
+
+#[test]
+fn test_scalar() {
+    let error =
+        load(b"a: 1\nb: 2\na: 3\n", YamlFailsafeSchema).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Composer);
+    assert_eq!(error.message(), "found duplicate key");
+    assert_eq!(error.context(), Some("first occurrence of the key"));
+    let mark = error.end_mark().unwrap();
+    assert_eq!((mark.line, mark.column), (2, 0));
+    let mark = error.start_mark().unwrap();
+    assert_eq!((mark.line, mark.column), (0, 0));
+
+    let document =
+        Loader::new(b"{a: 1, a: 2}").next().unwrap().unwrap();
+    let root = document.root().unwrap().as_mapping().unwrap();
+    assert_eq!(root.len(), 2);
+}
+
+#[test]
+fn test_resolved() {
+    for input in [
+        &b"{0x10: a, 16: b}"[..],
+        b"{null: a, ~: b}",
+        b"{true: a, True: b}",
+        b"{1.0: a, 1.: b}",
+        b"{a: 1, 'a': 2}",
+        b"{0x10000000000000000: a, 18446744073709551616: b}",
+    ] {
+        let error = load(input, YamlCoreSchema).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Composer);
+    }
+    for input in [
+        &b"{1: a, '1': b}"[..],
+        b"{1: a, 1.0: b}",
+        b"{.nan: a, .nan: b}",
+        b"{a: 1, !x a: 2}",
+    ] {
+        load(input, YamlCoreSchema).unwrap();
+    }
+    load(b"{0x10: a, 16: b}", YamlFailsafeSchema).unwrap();
+}
+
+#[test]
+fn test_complex() {
+    for input in [
+        &b"? [a, {b: c}]\n: 1\n? [a, {b: c}]\n: 2\n"[..],
+        b"? {a: 1, b: 2}\n: x\n? {b: 2, a: 1}\n: y\n",
+        b"? &k [a]\n: 1\n? *k\n: 2\n",
+        b"- &a [*a]\n- &b [*b]\n- {*a: 1, *b: 2}\n",
+    ] {
+        let error = load(input, YamlFailsafeSchema).unwrap_err();
+        assert_eq!(error.message(), "found duplicate key");
+    }
+    for input in [
+        &b"? [a, b]\n: 1\n? [b, a]\n: 2\n"[..],
+        b"? {a: 1}\n: x\n? {a: 2}\n: y\n",
+        b"? [a]\n: 1\n? {a: ~}\n: 2\n",
+    ] {
+        load(input, YamlFailsafeSchema).unwrap();
+    }
+}
+
+#[test]
+fn test_many_keys() {
+    // Comparing every key with every earlier one would take minutes.
+    let start = Instant::now();
+    for key in ["k", "[k]", "{k: v}"] {
+        let mut input = String::new();
+        for i in 0..20000 {
+            input += &format!(
+                "? {}\n: {}\n",
+                key.replace('k', &i.to_string()),
+                i
+            );
+        }
+        load(input.as_bytes(), YamlCoreSchema).unwrap();
+        input += &format!("? {}\n: x\n", key.replace('k', "0x10"));
+        let error = load(input.as_bytes(), YamlCoreSchema).unwrap_err();
+        let mark = error.start_mark().unwrap();
+        assert_eq!((mark.line, mark.column), (32, 2), "{key}");
+    }
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
@@ -8,11 +8,8 @@ mod run_emitter_test_suite;
 use std::fs;
 use std::path::Path;
 
-fn test(id: &str) {
-    let dir = Path::new("tests")
-        .join("data")
-        .join("yaml-test-suite")
-        .join(id);
+fn test(dir: &str) {
+    let dir = Path::new(dir);
 
     let output = bin::run(
         env!("CARGO_BIN_EXE_run-emitter-test-suite"),
@@ -24,11 +21,13 @@ fn test(id: &str) {
     let stderr = String::from_utf8_lossy(&output.stderr);
     eprint!("{}", stderr);
 
-    let out = if dir.join("out.yaml").exists() {
-        dir.join("out.yaml")
-    } else {
-        dir.join("in.yaml")
-    };
+    // Emitters are held to emit.yaml where the suite gives one, falling
+    // back to the normalized out.yaml and then the input itself.
+    let out = ["emit.yaml", "out.yaml", "in.yaml"]
+        .iter()
+        .map(|file| dir.join(file))
+        .find(|path| path.exists())
+        .unwrap();
     let expected = fs::read_to_string(out).unwrap();
     pretty_assertions::assert_str_eq!(expected, stdout);
     assert!(output.success);
@@ -0,0 +1,70 @@
+use libyml::error::{Error, ErrorKind};
+use libyml::safe::Parser;
+use libyml::YamlMarkT;
+
+fn parse_error(source: &str) -> Error {
+    Parser::new(source.as_bytes())
+        .find_map(Result::err)
+        .expect("expected a parse error")
+}
+
+#[test]
+fn test_scanner_error() {
+    let source = "key: value: other\n";
+    let error = parse_error(source);
+    assert_eq!(error.kind(), ErrorKind::Scanner);
+    assert_eq!(
+        error.message(),
+        "mapping values are not allowed in this context"
+    );
+    assert_eq!(error.context(), None);
+    let mark: YamlMarkT = error.end_mark().unwrap();
+    assert_eq!((mark.line, mark.column), (0, 10));
+}
+
+#[test]
+fn test_context_span() {
+    let error = parse_error("key: [unclosed\n");
+    assert_eq!(error.context(), Some("while parsing a flow sequence"));
+    let start = error.start_mark().unwrap();
+    let end = error.end_mark().unwrap();
+    assert_eq!((start.line, start.column), (0, 5));
+    assert_eq!((end.line, end.column), (1, 0));
+}
+
+#[test]
+fn test_snippet() {
+    let source = "a: 1\nb: [x, y}\n";
+    let error = parse_error(source);
+    let expected = "\
+error: did not find expected ',' or ']'
+ --> 2:9
+  |
+2 | b: [x, y}
+  |         ^
+  |
+  = note: while parsing a flow sequence at 2:4
+";
+    assert_eq!(error.snippet(source).to_string(), expected);
+}
+
+#[test]
+fn test_reader_error() {
+    let source = "key: \"\u{1}\"";
+    let error = parse_error(source);
+    assert_eq!(error.kind(), ErrorKind::Reader);
+    assert_eq!(
+        error.to_string(),
+        "control characters are not allowed at position 6",
+    );
+    let rendered = error.snippet(source).to_string();
+    assert!(rendered.contains(" --> 1:7\n"), "{rendered}");
+}
+
+#[test]
+fn test_source() {
+    let error: Box<dyn std::error::Error> =
+        Box::new(Error::new(ErrorKind::Writer, "write error"));
+    assert_eq!(error.to_string(), "write error");
+    assert!(error.source().is_none());
+}
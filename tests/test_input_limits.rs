@@ -0,0 +1,147 @@
+use libyml::checked;
+use libyml::error::{Error, ErrorKind};
+use libyml::{
+    yaml_event_delete, yaml_parser_delete,
+    yaml_parser_set_input_string, yaml_parser_set_max_input_size,
+    yaml_parser_set_max_name_length, yaml_parser_set_max_queued_tokens,
+    yaml_parser_set_max_scalar_length, YamlEventT, YamlParserT,
+    YamlStreamEndEvent,
+};
+use std::mem::MaybeUninit;
+
+fn parse(
+    source: &str,
+    configure: impl FnOnce(*mut YamlParserT),
+) -> Result<(), Error> {
+    unsafe {
+        let mut parser = MaybeUninit::<YamlParserT>::uninit();
+        let parser = parser.as_mut_ptr();
+        checked::yaml_parser_initialize(parser).unwrap();
+        configure(parser);
+        yaml_parser_set_input_string(
+            parser,
+            source.as_ptr(),
+            source.len() as u64,
+        );
+        let mut event = MaybeUninit::<YamlEventT>::uninit();
+        let event = event.as_mut_ptr();
+        let result = loop {
+            if let Err(error) =
+                checked::yaml_parser_parse(parser, event)
+            {
+                break Err(error);
+            }
+            let done = (*event).type_ == YamlStreamEndEvent;
+            yaml_event_delete(event);
+            if done {
+                break Ok(());
+            }
+        };
+        yaml_parser_delete(parser);
+        result
+    }
+}
+
+fn max_scalar_length(max: u64) -> impl FnOnce(*mut YamlParserT) {
+    move |parser| unsafe {
+        yaml_parser_set_max_scalar_length(parser, max)
+    }
+}
+
+fn max_name_length(max: u64) -> impl FnOnce(*mut YamlParserT) {
+    move |parser| unsafe {
+        yaml_parser_set_max_name_length(parser, max)
+    }
+}
+
+fn position(error: &Error) -> (u64, u64) {
+    let mark = error.end_mark().unwrap();
+    (mark.line, mark.column)
+}
+
+#[test]
+fn test_input_size() {
+    let source = "key: value\n";
+    parse(source, |parser| unsafe {
+        yaml_parser_set_max_input_size(parser, 11);
+    })
+    .unwrap();
+    let error = parse(source, |parser| unsafe {
+        yaml_parser_set_max_input_size(parser, 10);
+    })
+    .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Reader);
+    assert_eq!(error.message(), "input size limit exceeded");
+    assert_eq!(error.start_mark().unwrap().index, 10);
+}
+
+#[test]
+fn test_scalar_length() {
+    for source in [
+        "key: abcdef\n",
+        "key: 'abcdef'\n",
+        "key: \"abcdef\"\n",
+        "key: |-\n  abcdef\n",
+    ] {
+        parse(source, max_scalar_length(6)).unwrap();
+        let error = parse(source, max_scalar_length(5)).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Scanner);
+        assert_eq!(error.message(), "scalar length limit exceeded");
+        let mark = error.end_mark().unwrap();
+        assert_eq!(
+            mark.index as usize,
+            source.find('f').unwrap(),
+            "{source:?}",
+        );
+    }
+}
+
+#[test]
+fn test_scalar_length_counts_value() {
+    // Folding and escapes shrink the value below its source text.
+    parse("a\n  b\n", max_scalar_length(3)).unwrap();
+    parse("\"\\x41\\x42\"\n", max_scalar_length(2)).unwrap();
+
+    let error =
+        parse("\"ab \\u00e9\"\n", max_scalar_length(4)).unwrap_err();
+    assert_eq!(position(&error), (0, 4));
+    assert_eq!(error.context(), Some("while scanning a quoted scalar"));
+
+    // Spaces before the closing quote are part of the value.
+    let error = parse("'ab   '\n", max_scalar_length(4)).unwrap_err();
+    assert_eq!(error.message(), "scalar length limit exceeded");
+}
+
+#[test]
+fn test_name_length() {
+    let source = "base: &anchor 1\ncopy: *anchor\n";
+    parse(source, max_name_length(6)).unwrap();
+    let error = parse(source, max_name_length(5)).unwrap_err();
+    assert_eq!(error.message(), "anchor length limit exceeded");
+    assert_eq!(error.context(), Some("while scanning an anchor"));
+    assert_eq!(position(&error), (0, 12));
+
+    let source = "!!str value\n";
+    parse(source, max_name_length(3)).unwrap();
+    let error = parse(source, max_name_length(2)).unwrap_err();
+    assert_eq!(error.message(), "tag length limit exceeded");
+    assert_eq!(position(&error), (0, 4));
+}
+
+#[test]
+fn test_queued_tokens() {
+    // The scanner reads the whole flow mapping ahead, looking for the ':'
+    // that would make it a key.
+    let source = "{a: 1, b: 2}: value\n";
+    parse(source, |parser| unsafe {
+        yaml_parser_set_max_queued_tokens(parser, 16);
+    })
+    .unwrap();
+    let error = parse(source, |parser| unsafe {
+        yaml_parser_set_max_queued_tokens(parser, 8);
+    })
+    .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Scanner);
+    assert_eq!(error.message(), "token queue limit exceeded");
+    assert_eq!(error.context(), None);
+}
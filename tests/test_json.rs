@@ -0,0 +1,223 @@
+use libyml::error::{Error, ErrorKind};
+use libyml::json::JsonSyntax;
+use libyml::safe::{
+    Event, Loader, Parser, PushEvent, PushParser, Scalar,
+};
+use libyml::{YamlDoubleQuotedScalarStyle, YamlMarkT};
+
+type Events = Vec<(Event<'static>, (u64, u64), (u64, u64))>;
+
+fn span(mark: YamlMarkT) -> (u64, u64) {
+    (mark.line, mark.column)
+}
+
+fn parse(
+    input: &str,
+    json: Option<JsonSyntax>,
+) -> Result<Events, Error> {
+    let mut parser = Parser::new(input.as_bytes());
+    if let Some(syntax) = json {
+        parser.set_json(syntax);
+    }
+    let mut events = Vec::new();
+    while let Some(event) = parser.next_event() {
+        events.push((
+            event?.into_owned(),
+            span(parser.start_mark()),
+            span(parser.end_mark()),
+        ));
+    }
+    Ok(events)
+}
+
+fn json(input: &str) -> Result<Events, Error> {
+    parse(input, Some(JsonSyntax::Strict))
+}
+
+fn scalars(input: &str) -> Vec<String> {
+    json(input)
+        .unwrap()
+        .into_iter()
+        .filter_map(|(event, _, _)| match event {
+            Event::Scalar(scalar) => Some(scalar.value.into_owned()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_same_events_as_yaml() {
+    let inputs = [
+        "{\"a\": [1, -2.5e+3, true, false, null], \"b\": {}}",
+        "  [ ]  \n",
+        "[[], [{}], {\"\": \"\"}]",
+        "{\r\n  \"key\" : \"va\\\"l\\\\ue\",\r\n  \"n\":0\r\n}\r\n",
+        "\"\\b\\f\\n\\r\\t\\u00e9\\u4e2d\"",
+        " -0.0E-0",
+        "\"é 😀\"",
+    ];
+    for input in inputs {
+        assert_eq!(json(input).unwrap(), parse(input, None).unwrap());
+    }
+}
+
+#[test]
+fn test_strings() {
+    assert_eq!(scalars("[\"\\/\", \"\\u0041\\u00DF\"]"), ["/", "Aß"]);
+    assert_eq!(scalars("\"\\ud83d\\ude00\""), ["😀"]);
+
+    let mut parser = Parser::new(b"[\"plain\", \"esc\\naped\"]");
+    parser.set_json(JsonSyntax::Strict);
+    let scalars: Vec<Scalar<'_>> = parser
+        .filter_map(|event| match event.unwrap() {
+            Event::Scalar(scalar) => Some(scalar),
+            _ => None,
+        })
+        .collect();
+    assert!(matches!(scalars[0].value, std::borrow::Cow::Borrowed(_)));
+    assert_eq!(scalars[1].value, "esc\naped");
+    assert_eq!(scalars[1].style, YamlDoubleQuotedScalarStyle);
+}
+
+#[test]
+fn test_comments() {
+    let input =
+        "// leading\n{\"a\": /* inline */ 1 /* multi\nline */}\n";
+    assert_eq!(json(input).unwrap_err().kind(), ErrorKind::Scanner);
+    let events = parse(input, Some(JsonSyntax::Comments)).unwrap();
+    let values: Vec<_> = events
+        .iter()
+        .filter_map(|(event, start, _)| match event {
+            Event::Scalar(scalar) => {
+                Some((scalar.value.as_ref(), *start))
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(values, [("a", (1, 1)), ("1", (1, 19))]);
+
+    let error =
+        parse("[1 /* open", Some(JsonSyntax::Comments)).unwrap_err();
+    assert_eq!(error.message(), "did not find the end of the comment");
+}
+
+#[test]
+fn test_errors() {
+    let cases = [
+        ("", "did not find expected JSON value at line 1 column 1"),
+        (
+            "[1,]",
+            "did not find expected JSON value at line 1 column 4",
+        ),
+        (
+            "{\"a\": 1,}",
+            "did not find expected string key at line 1 column 9",
+        ),
+        (
+            "{1: 2}",
+            "did not find expected string key at line 1 column 2",
+        ),
+        ("{\"a\"}", "did not find expected ':' at line 1 column 5"),
+        (
+            "{\"a\":}",
+            "did not find expected JSON value at line 1 column 6",
+        ),
+        (
+            "[1 2]",
+            "did not find expected ',' or ']' at line 1 column 4",
+        ),
+        ("[1}", "did not find expected ',' or ']' at line 1 column 3"),
+        (
+            "{\"a\": 1]",
+            "did not find expected ',' or '}' at line 1 column 8",
+        ),
+        (
+            "1 2",
+            "found content after the JSON value at line 1 column 3",
+        ),
+        (
+            "01",
+            "found content after the JSON value at line 1 column 2",
+        ),
+        (
+            "['a']",
+            "did not find expected JSON value at line 1 column 2",
+        ),
+        ("tru", "did not find expected JSON value at line 1 column 1"),
+        ("NaN", "did not find expected JSON value at line 1 column 1"),
+        (
+            "[-]",
+            "did not find expected digit at line 1 column 3, \
+             while scanning a JSON number at line 1 column 2",
+        ),
+        (
+            "1.e3",
+            "did not find expected digit at line 1 column 3, \
+             while scanning a JSON number at line 1 column 1",
+        ),
+        (
+            "\"a\tb\"",
+            "found unescaped control character at line 1 column 3, \
+             while scanning a JSON string at line 1 column 1",
+        ),
+        (
+            "\"\\x41\"",
+            "found unknown escape character at line 1 column 2, \
+             while scanning a JSON string at line 1 column 1",
+        ),
+        (
+            "\"\\ud83d\"",
+            "found unpaired surrogate escape at line 1 column 8, \
+             while scanning a JSON string at line 1 column 1",
+        ),
+        (
+            "[\"abc",
+            "found unexpected end of stream at line 1 column 6, \
+             while scanning a JSON string at line 1 column 2",
+        ),
+    ];
+    for (input, expected) in cases {
+        let error = json(input).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Scanner, "{input}");
+        assert_eq!(error.to_string(), expected, "{input}");
+    }
+}
+
+#[test]
+fn test_loader() {
+    let mut parser = Parser::new(b"{\"a\": [1, \"x\"], \"b\": null}");
+    parser.set_json(JsonSyntax::Strict);
+    let mut loader = Loader::from_parser(parser);
+    let document = loader.next_document().unwrap().unwrap();
+    let root = document.root().unwrap().as_mapping().unwrap();
+    let a = root.get("a").unwrap().as_sequence().unwrap();
+    assert_eq!(a.get(1).unwrap().as_str(), Some("x"));
+    assert_eq!(root.get("b").unwrap().as_str(), Some("null"));
+    assert!(loader.next_document().is_none());
+}
+
+#[test]
+fn test_push() {
+    let input = "{\"key\": [\"va\\u00e9lue\", 12.5e3, true]}\n";
+    let expected = json(input).unwrap();
+    for size in [1, 3, input.len()] {
+        let mut parser = PushParser::new();
+        parser.set_json(JsonSyntax::Strict);
+        let mut chunks = input.as_bytes().chunks(size);
+        let mut events = Vec::new();
+        while let Some(event) = parser.next_event() {
+            match event.unwrap() {
+                PushEvent::Event(event) => events.push((
+                    event,
+                    span(parser.start_mark()),
+                    span(parser.end_mark()),
+                )),
+                PushEvent::NeedMoreInput => match chunks.next() {
+                    Some(chunk) => parser.feed(chunk),
+                    None => parser.finish(),
+                },
+            }
+        }
+        assert_eq!(events, expected, "{size}");
+    }
+}
@@ -0,0 +1,86 @@
+#[path = "../src/bin/json2yaml.rs"]
+#[allow(dead_code)]
+mod json2yaml;
+
+use self::json2yaml::{convert, Options};
+use libyml::json::JsonSyntax;
+
+fn json2yaml(input: &str, options: &Options) -> Result<String, String> {
+    let mut output = Vec::new();
+    unsafe { convert(&mut input.as_bytes(), &mut output, options) }
+        .map_err(|error| error.to_string())?;
+    Ok(String::from_utf8(output).unwrap())
+}
+
+fn default(input: &str) -> Result<String, String> {
+    json2yaml(input, &Options::default())
+}
+
+#[test]
+fn test_block_style() {
+    let input = r#"{"name": "x", "list": [1, {"a": null}, [], {}],
+        "nested": {"deep": [true, -1.5e3]}}"#;
+    assert_eq!(
+        default(input).unwrap(),
+        "\
+name: x
+list:
+- 1
+- a: null
+- []
+- {}
+nested:
+  deep:
+  - true
+  - -1.5e3
+",
+    );
+    assert_eq!(default("\"text\"").unwrap(), "text\n");
+    assert_eq!(default("[]").unwrap(), "[]\n");
+}
+
+#[test]
+fn test_strings() {
+    let input = r#"["1", "true", "null", "", "a: b", "- x", "é\n😀"]"#;
+    assert_eq!(
+        default(input).unwrap(),
+        "- '1'\n- 'true'\n- 'null'\n- ''\n- 'a: b'\n- '- x'\n\
+         - 'é\n\n  😀'\n",
+    );
+}
+
+#[test]
+fn test_options() {
+    let options = Options {
+        indent: 4,
+        ..Options::default()
+    };
+    assert_eq!(
+        json2yaml("{\"a\": {\"b\": [1]}}", &options).unwrap(),
+        "a:\n    b:\n    - 1\n",
+    );
+
+    let input = "{\n  // line\n  \"a\": 1 /* block */\n}\n";
+    assert_eq!(
+        default(input).unwrap_err(),
+        "2:3: did not find expected string key",
+    );
+    let options = Options {
+        syntax: JsonSyntax::Comments,
+        ..Options::default()
+    };
+    assert_eq!(json2yaml(input, &options).unwrap(), "a: 1\n");
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        default("[1,\n 2,]").unwrap_err(),
+        "2:4: did not find expected JSON value",
+    );
+    assert_eq!(
+        default("{\"a\": \"b\\q\"}").unwrap_err(),
+        "1:9: found unknown escape character \
+         (while scanning a JSON string at 1:7)",
+    );
+}
@@ -0,0 +1,96 @@
+use libyml::safe::{Event, Loader, Parser};
+use libyml::YamlMarkT;
+
+/// The (offset, utf16_column) spans of the scalar events of `input`.
+fn scalar_spans(input: &[u8]) -> Vec<((u64, u64), (u64, u64))> {
+    let mut parser = Parser::new(input);
+    let mut spans = Vec::new();
+    while let Some(event) = parser.next_event() {
+        if let Event::Scalar(_) = event.unwrap() {
+            let span =
+                |mark: YamlMarkT| (mark.offset, mark.utf16_column);
+            spans.push((
+                span(parser.start_mark()),
+                span(parser.end_mark()),
+            ));
+        }
+    }
+    spans
+}
+
+fn utf16le(input: &str) -> Vec<u8> {
+    let mut bytes = vec![0xFF, 0xFE];
+    for unit in input.encode_utf16() {
+        bytes.extend(unit.to_le_bytes());
+    }
+    bytes
+}
+
+#[test]
+fn test_utf8() {
+    let input = "é: 😀x\nb: [ü]\n";
+    assert_eq!(
+        scalar_spans(input.as_bytes()),
+        [
+            ((0, 0), (2, 1)),
+            ((4, 3), (9, 6)),
+            ((10, 0), (11, 1)),
+            ((14, 4), (16, 5)),
+        ],
+    );
+
+    let mut parser = Parser::new(input.as_bytes());
+    while let Some(event) = parser.next_event() {
+        if let Event::Scalar(scalar) = event.unwrap() {
+            let start = parser.start_mark().offset as usize;
+            let end = parser.end_mark().offset as usize;
+            assert_eq!(&input[start..end], scalar.value);
+        }
+    }
+}
+
+#[test]
+fn test_bom_and_breaks() {
+    assert_eq!(
+        scalar_spans(b"\xEF\xBB\xBFa: b\r\nc: d\n"),
+        [
+            ((3, 0), (4, 1)),
+            ((6, 3), (7, 4)),
+            ((9, 0), (10, 1)),
+            ((12, 3), (13, 4)),
+        ],
+    );
+}
+
+#[test]
+fn test_utf16() {
+    let input = "é: 😀x\nb: [ü]\n";
+    let spans = scalar_spans(&utf16le(input));
+    // Every character is two bytes long after the two-byte BOM, except
+    // for the one outside the Basic Multilingual Plane.
+    assert_eq!(
+        spans,
+        [
+            ((2, 0), (4, 1)),
+            ((8, 3), (14, 6)),
+            ((16, 0), (18, 1)),
+            ((24, 4), (26, 5)),
+        ],
+    );
+}
+
+#[test]
+fn test_nodes() {
+    let input = "- 😀\n- {a: b}\n";
+    let document =
+        Loader::new(input.as_bytes()).next().unwrap().unwrap();
+    let root = document.root().unwrap().as_sequence().unwrap();
+    let spans: Vec<_> = root
+        .iter()
+        .map(|node| (node.start_mark().offset, node.end_mark().offset))
+        .collect();
+    assert_eq!(spans, [(2, 6), (9, 15)]);
+    let mapping = root.iter().nth(1).unwrap();
+    assert_eq!(mapping.start_mark().utf16_column, 2);
+    assert_eq!(mapping.end_mark().utf16_column, 8);
+}
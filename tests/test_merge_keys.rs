@@ -0,0 +1,133 @@
+use libyml::error::ErrorKind;
+use libyml::resolver::MERGE_TAG;
+use libyml::safe::{Document, Loader, Node, Parser};
+use std::time::{Duration, Instant};
+
+fn load(input: &[u8]) -> Document {
+    let mut parser = Parser::new(input);
+    parser.set_merge_keys(true);
+    Loader::from_parser(parser).next().unwrap().unwrap()
+}
+
+fn pairs(document: &Document) -> Vec<(&str, &str, bool)> {
+    let root = document.root().unwrap().as_mapping().unwrap();
+    root.iter()
+        .map(|(key, value)| {
+            let key = key.as_str().unwrap();
+            (key, value.as_str().unwrap(), root.is_merged(key))
+        })
+        .collect()
+}
+
+#[test]
+fn test_single() {
+    let document =
+        load(b"- &base {x: 1, y: 2}\n- <<: *base\n  y: 3\n  z: 4\n");
+    let root = document.root().unwrap().as_sequence().unwrap();
+    let merged = root.iter().nth(1).unwrap().as_mapping().unwrap();
+    let keys: Vec<_> = merged
+        .iter()
+        .map(|(key, _)| key.as_str().unwrap())
+        .collect();
+    assert_eq!(keys, ["x", "y", "z"]);
+    assert_eq!(merged.get("x").unwrap().as_str(), Some("1"));
+    assert_eq!(merged.get("y").unwrap().as_str(), Some("3"));
+    assert!(merged.is_merged("x"));
+    assert!(!merged.is_merged("y"));
+    assert!(!merged.is_merged("w"));
+}
+
+#[test]
+fn test_sequence() {
+    let input = b"\
+a: &a {x: 1, y: 1}
+b: &b {y: 2, z: 2}
+c:
+  <<: [*a, *b]
+  z: 3
+";
+    let document = load(input);
+    let root = document.root().unwrap().as_mapping().unwrap();
+    let c = root.get("c").and_then(Node::as_mapping).unwrap();
+    let pairs: Vec<_> = c
+        .iter()
+        .map(|(key, value)| {
+            (key.as_str().unwrap(), value.as_str().unwrap())
+        })
+        .collect();
+    assert_eq!(pairs, [("x", "1"), ("y", "1"), ("z", "3")]);
+    assert!(c.is_merged("y"));
+    assert!(!c.is_merged("z"));
+}
+
+#[test]
+fn test_nested() {
+    let document =
+        load(b"- &a {x: 1}\n- &b {<<: *a, y: 2}\n- {<<: *b}\n");
+    let root = document.root().unwrap().as_sequence().unwrap();
+    let last = root.iter().nth(2).unwrap().as_mapping().unwrap();
+    assert_eq!(last.get("x").unwrap().as_str(), Some("1"));
+    assert_eq!(last.get("y").unwrap().as_str(), Some("2"));
+    assert!(last.is_merged("x"));
+}
+
+#[test]
+fn test_tagged() {
+    let document = load(b"!!merge '<<': {x: 1}\n'<<': 2\n");
+    assert_eq!(
+        pairs(&document),
+        [("x", "1", true), ("<<", "2", false)]
+    );
+}
+
+#[test]
+fn test_disabled() {
+    let document =
+        Loader::new(b"<<: {x: 1}\n").next().unwrap().unwrap();
+    let root = document.root().unwrap().as_mapping().unwrap();
+    let (key, value) = root.iter().next().unwrap();
+    assert_eq!(key.as_str(), Some("<<"));
+    assert_ne!(key.tag(), Some(MERGE_TAG));
+    assert!(value.as_mapping().is_some());
+    assert!(!root.is_merged("x"));
+}
+
+#[test]
+fn test_invalid() {
+    for input in [
+        &b"<<: 1\n"[..],
+        b"<<: [{x: 1}, 2]\n",
+        b"&a {x: 1, <<: *a}\n",
+        b"&a [{<<: *a}]\n",
+    ] {
+        let mut parser = Parser::new(input);
+        parser.set_merge_keys(true);
+        let error = Loader::from_parser(parser).next().unwrap();
+        let error = error.unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Composer, "{error}");
+    }
+}
+
+#[test]
+fn test_many_keys() {
+    // Looking up every merged key among every own one would take
+    // minutes.
+    let mut input = String::from("- &base\n");
+    for i in 0..20000 {
+        input += &format!("  {i}: base\n");
+    }
+    input += "- <<: *base\n";
+    for i in 10000..30000 {
+        input += &format!("  {i}: own\n");
+    }
+    let start = Instant::now();
+    let document = load(input.as_bytes());
+    assert!(start.elapsed() < Duration::from_secs(10));
+    let root = document.root().unwrap().as_sequence().unwrap();
+    let merged = root.iter().nth(1).unwrap().as_mapping().unwrap();
+    assert_eq!(merged.iter().count(), 30000);
+    assert_eq!(merged.get("0").unwrap().as_str(), Some("base"));
+    assert_eq!(merged.get("10000").unwrap().as_str(), Some("own"));
+    assert!(merged.is_merged("9999"));
+    assert!(!merged.is_merged("10000"));
+}
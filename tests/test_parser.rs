@@ -5,14 +5,20 @@ mod bin;
 #[allow(dead_code)]
 mod run_parser_test_suite;
 
+use libyml::json::JsonSyntax;
+use libyml::resolver::{self, Resolved};
+use libyml::safe::{Loader, Node, Parser};
+use libyml::{
+    YamlCoreSchema, YamlDoubleQuotedScalarStyle, YamlFoldedScalarStyle,
+    YamlLiteralScalarStyle, YamlPlainScalarStyle,
+    YamlSingleQuotedScalarStyle,
+};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
-fn test(id: &str) {
-    let dir = Path::new("tests")
-        .join("data")
-        .join("yaml-test-suite")
-        .join(id);
+fn test(dir: &str) {
+    let dir = Path::new(dir);
 
     let output = bin::run(
         env!("CARGO_BIN_EXE_run-parser-test-suite"),
@@ -27,6 +33,222 @@ fn test(id: &str) {
     let expected = fs::read_to_string(dir.join("test.event")).unwrap();
     pretty_assertions::assert_str_eq!(expected, stdout);
     assert!(output.success);
+
+    if let Ok(json) = fs::read_to_string(dir.join("in.json")) {
+        assert_eq!(json_documents(&json), yaml_documents(&stdout));
+    }
+}
+
+/// The data model shared by YAML documents and in.json values, with
+/// numbers compared by value and mappings by their keys' text.
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Sequence(Vec<Value>),
+    Mapping(BTreeMap<String, Value>),
+}
+
+impl From<Resolved<'_>> for Value {
+    fn from(resolved: Resolved) -> Self {
+        match resolved.value {
+            resolver::Value::Null => Value::Null,
+            resolver::Value::Bool(bool) => Value::Bool(bool),
+            resolver::Value::Int(int) => Value::Number(int as f64),
+            resolver::Value::BigInt(int) => {
+                Value::Number(int.to_string().parse().unwrap())
+            }
+            resolver::Value::Float(float) => Value::Number(float),
+            resolver::Value::Str(str) => Value::String(str.to_owned()),
+            value => panic!("unexpected value {:?}", value),
+        }
+    }
+}
+
+/// Loads the values of an in.json, which holds one per document of the
+/// stream.
+fn json_documents(json: &str) -> Vec<Value> {
+    let mut starts = Vec::new();
+    let mut depth = 0;
+    let mut between = true;
+    let mut chars = json.char_indices();
+    while let Some((i, ch)) = chars.next() {
+        if depth == 0 && between && !ch.is_whitespace() {
+            starts.push(i);
+            between = false;
+        }
+        match ch {
+            '"' => {
+                while let Some((_, ch)) = chars.next() {
+                    match ch {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '[' | '{' => depth += 1,
+            ']' | '}' => depth -= 1,
+            _ => {}
+        }
+        if depth == 0
+            && (ch.is_whitespace() || matches!(ch, '"' | ']' | '}'))
+        {
+            between = true;
+        }
+    }
+
+    let ends = starts.iter().skip(1).copied().chain([json.len()]);
+    starts
+        .iter()
+        .zip(ends)
+        .map(|(&start, end)| {
+            let mut parser = Parser::new(&json.as_bytes()[start..end]);
+            parser.set_json(JsonSyntax::Strict);
+            parser.set_schema(YamlCoreSchema);
+            let document = Loader::from_parser(parser)
+                .next_document()
+                .unwrap()
+                .unwrap();
+            json_value(document.root().unwrap())
+        })
+        .collect()
+}
+
+fn json_value(node: Node) -> Value {
+    match node {
+        Node::Scalar(scalar) => Value::from(scalar.resolve().unwrap()),
+        Node::Sequence(sequence) => {
+            Value::Sequence(sequence.iter().map(json_value).collect())
+        }
+        Node::Mapping(mapping) => Value::Mapping(
+            mapping
+                .iter()
+                .map(|(key, value)| {
+                    (
+                        key.as_str().unwrap().to_owned(),
+                        json_value(value),
+                    )
+                })
+                .collect(),
+        ),
+        _ => unreachable!(),
+    }
+}
+
+/// Builds the documents of a stream from the events printed by the
+/// parser, resolving scalars with the core schema.
+fn yaml_documents(events: &str) -> Vec<Value> {
+    enum Collection {
+        Sequence(Vec<Value>),
+        Mapping(BTreeMap<String, Value>, Option<String>),
+    }
+
+    let mut documents = Vec::new();
+    let mut anchors = BTreeMap::new();
+    let mut stack: Vec<(Collection, Option<&str>)> = Vec::new();
+    for line in events.lines() {
+        let (value, key, anchor) = if line.starts_with("+SEQ")
+            || line.starts_with("+MAP")
+        {
+            let (anchor, _) = properties(&line[4..]);
+            let collection = if line.starts_with("+SEQ") {
+                Collection::Sequence(Vec::new())
+            } else {
+                Collection::Mapping(BTreeMap::new(), None)
+            };
+            stack.push((collection, anchor));
+            continue;
+        } else if line == "-SEQ" || line == "-MAP" {
+            let (collection, anchor) = stack.pop().unwrap();
+            let value = match collection {
+                Collection::Sequence(items) => Value::Sequence(items),
+                Collection::Mapping(pairs, _) => Value::Mapping(pairs),
+            };
+            (value, None, anchor)
+        } else if let Some(rest) = line.strip_prefix("=VAL") {
+            let (anchor, rest) = properties(rest);
+            let (tag, rest) = match rest.strip_prefix(" <") {
+                Some(rest) => {
+                    let end = rest.find('>').unwrap();
+                    (Some(&rest[..end]), &rest[end + 1..])
+                }
+                None => (None, rest),
+            };
+            let style = match &rest[..2] {
+                " :" => YamlPlainScalarStyle,
+                " '" => YamlSingleQuotedScalarStyle,
+                " \"" => YamlDoubleQuotedScalarStyle,
+                " |" => YamlLiteralScalarStyle,
+                " >" => YamlFoldedScalarStyle,
+                _ => panic!("unexpected event {:?}", line),
+            };
+            let text = unescape(&rest[2..]);
+            let resolved =
+                resolver::resolve(YamlCoreSchema, tag, &text, style)
+                    .unwrap();
+            (Value::from(resolved), Some(text), anchor)
+        } else if let Some(alias) = line.strip_prefix("=ALI *") {
+            let (value, key) = anchors.get(alias).cloned().unwrap();
+            (value, key, None)
+        } else {
+            continue;
+        };
+
+        if let Some(anchor) = anchor {
+            anchors.insert(anchor, (value.clone(), key.clone()));
+        }
+        match stack.last_mut() {
+            None => documents.push(value),
+            Some((Collection::Sequence(items), _)) => items.push(value),
+            Some((Collection::Mapping(pairs, pending), _)) => {
+                match pending.take() {
+                    Some(key) => {
+                        pairs.insert(key, value);
+                    }
+                    None => *pending = Some(key.unwrap()),
+                }
+            }
+        }
+    }
+    documents
+}
+
+/// Splits the anchor off the properties of an event.
+fn properties(rest: &str) -> (Option<&str>, &str) {
+    let rest = rest.strip_prefix(" {}").unwrap_or(rest);
+    let rest = rest.strip_prefix(" []").unwrap_or(rest);
+    match rest.strip_prefix(" &") {
+        Some(rest) => {
+            let end = rest.find(' ').unwrap_or(rest.len());
+            (Some(&rest[..end]), &rest[end..])
+        }
+        None => (None, rest),
+    }
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        unescaped.push(if ch == '\\' {
+            match chars.next().unwrap() {
+                '0' => '\0',
+                'b' => '\x08',
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                ch => ch,
+            }
+        } else {
+            ch
+        });
+    }
+    unescaped
 }
 
 libyml_test_suite::test_parser!();
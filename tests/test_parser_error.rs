@@ -7,11 +7,8 @@ mod run_parser_test_suite;
 
 use std::path::Path;
 
-fn test(id: &str) {
-    let dir = Path::new("tests")
-        .join("data")
-        .join("yaml-test-suite")
-        .join(id);
+fn test(dir: &str) {
+    let dir = Path::new(dir);
 
     let output = bin::run(
         env!("CARGO_BIN_EXE_run-parser-test-suite"),
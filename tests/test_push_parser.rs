@@ -0,0 +1,244 @@
+use libyml::error::{Error, ErrorKind};
+use libyml::safe::{Event, Parser, PushEvent, PushParser, Scalar};
+use libyml::YamlMarkT;
+use std::time::{Duration, Instant};
+
+type Events = Vec<(Event<'static>, (u64, u64), (u64, u64))>;
+
+fn span(mark: YamlMarkT) -> (u64, u64) {
+    (mark.offset, mark.utf16_column)
+}
+
+/// Parses `input`, failing with the events produced before the error
+/// along with it.
+fn pull(input: &[u8]) -> Result<Events, (Events, Error)> {
+    let mut parser = Parser::new(input);
+    let mut events = Vec::new();
+    while let Some(event) = parser.next_event() {
+        let event = match event {
+            Ok(event) => event.into_owned(),
+            Err(error) => return Err((events, error)),
+        };
+        events.push((
+            event,
+            span(parser.start_mark()),
+            span(parser.end_mark()),
+        ));
+    }
+    Ok(events)
+}
+
+/// Pushes `input` in chunks of `size` bytes, feeding the next chunk
+/// whenever the parser needs more input.
+fn push(input: &[u8], size: usize) -> Result<Events, (Events, Error)> {
+    let mut parser = PushParser::new();
+    let mut chunks = input.chunks(size);
+    let mut events = Vec::new();
+    while let Some(event) = parser.next_event() {
+        let event = match event {
+            Ok(event) => event,
+            Err(error) => return Err((events, error)),
+        };
+        match event {
+            PushEvent::Event(event) => events.push((
+                event,
+                span(parser.start_mark()),
+                span(parser.end_mark()),
+            )),
+            PushEvent::NeedMoreInput => match chunks.next() {
+                Some(chunk) => parser.feed(chunk),
+                None => parser.finish(),
+            },
+        }
+    }
+    Ok(events)
+}
+
+fn scalar(event: Option<Result<PushEvent, Error>>) -> Scalar<'static> {
+    match event.unwrap().unwrap() {
+        PushEvent::Event(Event::Scalar(scalar)) => scalar,
+        event => panic!("{event:?}"),
+    }
+}
+
+#[test]
+fn test_chunks() {
+    let input = b"\
+%YAML 1.1
+--- &a !!map
+? [a, {b: c}]
+: 'single
+  quoted' # comment
+d: |
+  literal
+e: *a
+... # end
+---
+- plain
+  multi-line
+-
+";
+    let expected = pull(input).unwrap();
+    for size in [1, 2, 5, 16, input.len()] {
+        assert_eq!(push(input, size).unwrap(), expected, "{size}");
+    }
+}
+
+#[test]
+fn test_utf16() {
+    let mut input = vec![0xFE, 0xFF];
+    for unit in "é: [😀, x]\n".encode_utf16() {
+        input.extend(unit.to_be_bytes());
+    }
+    assert_eq!(push(&input, 1).unwrap(), pull(&input).unwrap());
+}
+
+#[test]
+fn test_need_more_input() {
+    let mut parser = PushParser::new();
+    assert_eq!(
+        parser.next_event().unwrap().unwrap(),
+        PushEvent::NeedMoreInput,
+    );
+    parser.feed(b"key: val");
+    // The scalar may go on, and may be a key itself.
+    let mut events = 0;
+    loop {
+        match parser.next_event().unwrap().unwrap() {
+            PushEvent::Event(Event::Scalar(scalar)) => {
+                panic!("{scalar:?}")
+            }
+            PushEvent::Event(_) => events += 1,
+            PushEvent::NeedMoreInput => break,
+        }
+    }
+    assert_eq!(events, 0);
+    parser.feed(b"ue\n");
+    parser.finish();
+    let mut values = Vec::new();
+    while let Some(event) = parser.next_event() {
+        if let PushEvent::Event(Event::Scalar(scalar)) = event.unwrap()
+        {
+            values.push(scalar.value);
+        }
+    }
+    assert_eq!(values, ["key", "value"]);
+    assert!(parser.next_event().is_none());
+}
+
+#[test]
+fn test_long_scalar() {
+    let value = "x".repeat(100_000);
+    let mut parser = PushParser::new();
+    parser.feed(b"- ");
+    for chunk in value.as_bytes().chunks(1000) {
+        parser.feed(chunk);
+        while let Some(event) = parser.next_event() {
+            match event.unwrap() {
+                PushEvent::NeedMoreInput => break,
+                PushEvent::Event(Event::Scalar(scalar)) => {
+                    panic!("{:?}", scalar.value.len())
+                }
+                PushEvent::Event(_) => {}
+            }
+        }
+    }
+    parser.finish();
+    let scalar = loop {
+        if let PushEvent::Event(Event::Scalar(scalar)) =
+            parser.next_event().unwrap().unwrap()
+        {
+            break scalar;
+        }
+    };
+    assert_eq!(scalar.value, value);
+    assert_eq!(parser.end_mark().offset, 100_002);
+    assert!(matches!(
+        parser.next_event().unwrap().unwrap(),
+        PushEvent::Event(Event::SequenceEnd),
+    ));
+}
+
+#[test]
+fn test_long_scalar_small_chunks() {
+    // Scanning the scalar again from its start on every chunk would take
+    // minutes.
+    let value = "x".repeat(1 << 20);
+    let start = Instant::now();
+    let mut parser = PushParser::new();
+    let mut chunks = value.as_bytes().chunks(1024);
+    parser.feed(b"- ");
+    let scalar = loop {
+        match parser.next_event().unwrap().unwrap() {
+            PushEvent::Event(Event::Scalar(scalar)) => break scalar,
+            PushEvent::Event(_) => {}
+            PushEvent::NeedMoreInput => match chunks.next() {
+                Some(chunk) => parser.feed(chunk),
+                None => parser.finish(),
+            },
+        }
+    };
+    assert_eq!(scalar.value.len(), value.len());
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn test_error() {
+    // The events before the error are produced all the same, however
+    // far ahead of them the scanner runs into it.
+    for input in [
+        &b"- a\n- [b\n"[..],
+        b"a: b\nc\n",
+        b"a:\n  b: c\nd\n",
+        b"a:\n- *b\nc\n\n  - d\n",
+        b"a: \xFF",
+    ] {
+        let (expected_events, expected) = pull(input).unwrap_err();
+        for size in [1, 3, input.len()] {
+            let (events, error) = push(input, size).unwrap_err();
+            assert_eq!(events, expected_events, "{size}");
+            assert_eq!(error.kind(), expected.kind());
+            assert_eq!(error.message(), expected.message());
+            assert_eq!(error.to_string(), expected.to_string());
+        }
+    }
+
+    let mut parser = PushParser::new();
+    parser.feed(b"- a\n- 'b\n");
+    parser.finish();
+    let mut events = 0;
+    let error = loop {
+        match parser.next_event().unwrap() {
+            Ok(_) => events += 1,
+            Err(error) => break error,
+        }
+    };
+    assert_eq!(events, 4);
+    assert_eq!(error.kind(), ErrorKind::Scanner);
+    assert!(parser.next_event().is_none());
+}
+
+#[test]
+fn test_scalar_marks() {
+    let mut parser = PushParser::new();
+    parser.feed(b"- abc\n- de");
+    parser.feed(b"f\n");
+    parser.finish();
+    while !matches!(
+        parser.next_event().unwrap().unwrap(),
+        PushEvent::Event(Event::SequenceStart(_))
+    ) {}
+    assert_eq!(scalar(parser.next_event()).value, "abc");
+    assert_eq!(parser.start_mark().offset, 2);
+    assert_eq!(scalar(parser.next_event()).value, "def");
+    assert_eq!(parser.start_mark().offset, 8);
+    assert_eq!(parser.end_mark().offset, 11);
+}
+
+#[test]
+#[should_panic = "input fed after finish"]
+fn test_feed_after_finish() {
+    let mut parser = PushParser::new();
+    parser.finish();
+    parser.feed(b"a");
+}
@@ -0,0 +1,293 @@
+use libyml::error::ErrorKind;
+use libyml::resolver::{
+    resolve, Value, BOOL_TAG, FLOAT_TAG, INT_TAG, NULL_TAG, STR_TAG,
+};
+use libyml::safe::{Loader, Node, Parser};
+use libyml::{
+    YamlCoreSchema, YamlDoubleQuotedScalarStyle, YamlFailsafeSchema,
+    YamlJsonSchema, YamlLiteralScalarStyle, YamlPlainScalarStyle,
+    YamlSchemaT, YamlYaml11Schema,
+};
+
+fn plain(schema: YamlSchemaT, value: &str) -> Value<'_> {
+    resolve(schema, None, value, YamlPlainScalarStyle)
+        .unwrap()
+        .value
+}
+
+#[test]
+fn test_failsafe() {
+    for value in ["null", "true", "12", "1.5", ".inf"] {
+        let resolved = resolve(
+            YamlFailsafeSchema,
+            None,
+            value,
+            YamlPlainScalarStyle,
+        )
+        .unwrap();
+        assert_eq!(resolved.tag, STR_TAG);
+        assert_eq!(resolved.value, Value::Str(value));
+    }
+}
+
+#[test]
+fn test_json() {
+    let schema = YamlJsonSchema;
+    assert_eq!(plain(schema, "null"), Value::Null);
+    assert_eq!(plain(schema, "true"), Value::Bool(true));
+    assert_eq!(plain(schema, "false"), Value::Bool(false));
+    assert_eq!(plain(schema, "-12"), Value::Int(-12));
+    assert_eq!(plain(schema, "0"), Value::Int(0));
+    assert_eq!(plain(schema, "-1.5e3"), Value::Float(-1500.0));
+    assert_eq!(plain(schema, "0.25"), Value::Float(0.25));
+    for value in ["~", "", "True", "+1", "012", ".5", "0x1", ".inf"] {
+        assert_eq!(
+            plain(schema, value),
+            Value::Str(value),
+            "{value:?}"
+        );
+    }
+}
+
+#[test]
+fn test_core() {
+    let schema = YamlCoreSchema;
+    for value in ["null", "Null", "NULL", "~", ""] {
+        assert_eq!(plain(schema, value), Value::Null, "{value:?}");
+    }
+    for value in ["true", "True", "TRUE"] {
+        assert_eq!(plain(schema, value), Value::Bool(true));
+    }
+    assert_eq!(plain(schema, "FALSE"), Value::Bool(false));
+    assert_eq!(plain(schema, "+012"), Value::Int(12));
+    assert_eq!(plain(schema, "-12"), Value::Int(-12));
+    assert_eq!(plain(schema, "0o14"), Value::Int(12));
+    assert_eq!(plain(schema, "0xC"), Value::Int(12));
+    assert_eq!(
+        plain(schema, "-9223372036854775808"),
+        Value::Int(i64::MIN),
+    );
+    assert_eq!(plain(schema, "1."), Value::Float(1.0));
+
+    let Value::BigInt(int) = plain(schema, "-9223372036854775809")
+    else {
+        panic!()
+    };
+    assert!(int.is_negative());
+    assert_eq!(
+        (int.radix(), int.digits()),
+        (10, "9223372036854775809")
+    );
+    assert_eq!(int.to_string(), "-9223372036854775809");
+    let Value::BigInt(int) = plain(schema, "0o1000000000000000000000")
+    else {
+        panic!()
+    };
+    assert_eq!(int.to_string(), "9223372036854775808");
+    assert_eq!(
+        plain(schema, "0x0000FFFFFFFFFFFFFFFFFFFF"),
+        plain(schema, "+1208925819614629174706175"),
+    );
+    assert_ne!(
+        plain(schema, "-18446744073709551616"),
+        plain(schema, "18446744073709551616"),
+    );
+    assert_eq!(plain(schema, ".5"), Value::Float(0.5));
+    assert_eq!(plain(schema, "+1e3"), Value::Float(1000.0));
+    assert_eq!(plain(schema, "1.5E-1"), Value::Float(0.15));
+    assert_eq!(plain(schema, "-.INF"), Value::Float(f64::NEG_INFINITY));
+    assert!(matches!(
+        plain(schema, ".NaN"),
+        Value::Float(float) if float.is_nan(),
+    ));
+    for value in [
+        "yes", "tRUE", "0b1", "1_000", "0o8", "-0x1", ".", "1e",
+        "-.nan", "3:25", "inf", "1.5.1",
+    ] {
+        assert_eq!(
+            plain(schema, value),
+            Value::Str(value),
+            "{value:?}"
+        );
+    }
+}
+
+#[test]
+fn test_yaml11() {
+    let schema = YamlYaml11Schema;
+    assert_eq!(plain(schema, "~"), Value::Null);
+    for value in ["y", "Yes", "ON", "true"] {
+        assert_eq!(plain(schema, value), Value::Bool(true));
+    }
+    for value in ["n", "NO", "off", "False"] {
+        assert_eq!(plain(schema, value), Value::Bool(false));
+    }
+    assert_eq!(plain(schema, "0b1100"), Value::Int(12));
+    assert_eq!(plain(schema, "014"), Value::Int(12));
+    assert_eq!(plain(schema, "-0x_C"), Value::Int(-12));
+    assert_eq!(plain(schema, "1_000"), Value::Int(1000));
+    assert_eq!(plain(schema, "190:20:30"), Value::Int(685230));
+    let value = format!("-0b1_{}", "0".repeat(64));
+    let Value::BigInt(int) = plain(schema, &value) else {
+        panic!()
+    };
+    assert_eq!(int.to_string(), "-18446744073709551616");
+    let error = resolve(
+        schema,
+        None,
+        "10000000000000000:00:00",
+        YamlPlainScalarStyle,
+    )
+    .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Composer);
+    assert_eq!(plain(schema, "-1:00"), Value::Int(-60));
+    assert_eq!(plain(schema, "1_000.5"), Value::Float(1000.5));
+    assert_eq!(plain(schema, "6.8523015e+5"), Value::Float(685230.15));
+    assert_eq!(plain(schema, "190:20:30.15"), Value::Float(685230.15));
+    assert_eq!(plain(schema, ".Inf"), Value::Float(f64::INFINITY));
+    for value in ["0o14", "1e3", "1.0e3", "190:60", "_1", "1:2:"] {
+        assert_eq!(
+            plain(schema, value),
+            Value::Str(value),
+            "{value:?}"
+        );
+    }
+}
+
+#[test]
+fn test_big_ints() {
+    let schema = YamlCoreSchema;
+    let Value::BigInt(int) = plain(schema, "0x3635C9ADC5DEA00000")
+    else {
+        panic!()
+    };
+    assert_eq!(int.to_string(), "1000000000000000000000");
+    let value = format!("0x1{}", "0".repeat(3000));
+    let int = plain(schema, &value);
+    let Value::BigInt(big) = int else { panic!() };
+    let octal = format!("0o1{}", "0".repeat(4000));
+    assert_eq!(plain(schema, &octal), int);
+    assert_eq!(plain(schema, &big.to_string()), int);
+    assert_eq!(
+        plain(YamlYaml11Schema, &format!("0b1{}", "0".repeat(4095))),
+        plain(YamlYaml11Schema, &format!("0x8_{}", "0".repeat(1023))),
+    );
+
+    // Leading zeros do not count towards the limit on digits.
+    let value = format!("{}{}", "0".repeat(100), "9".repeat(4096));
+    assert!(matches!(plain(schema, &value), Value::BigInt(_)));
+    let value = "9".repeat(4097);
+    let error = resolve(schema, None, &value, YamlPlainScalarStyle)
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Composer);
+}
+
+#[test]
+fn test_tags() {
+    let resolved = resolve(
+        YamlCoreSchema,
+        None,
+        "12",
+        YamlDoubleQuotedScalarStyle,
+    )
+    .unwrap();
+    assert_eq!(resolved.tag, STR_TAG);
+    assert_eq!(resolved.value, Value::Str("12"));
+
+    let resolved =
+        resolve(YamlCoreSchema, Some("!"), "12", YamlPlainScalarStyle)
+            .unwrap();
+    assert_eq!(resolved.tag, STR_TAG);
+
+    let resolved = resolve(
+        YamlCoreSchema,
+        Some(INT_TAG),
+        "12",
+        YamlLiteralScalarStyle,
+    )
+    .unwrap();
+    assert_eq!(resolved.value, Value::Int(12));
+
+    let resolved = resolve(
+        YamlCoreSchema,
+        Some(FLOAT_TAG),
+        "12",
+        YamlPlainScalarStyle,
+    )
+    .unwrap();
+    assert_eq!(resolved.value, Value::Float(12.0));
+
+    let resolved = resolve(
+        YamlCoreSchema,
+        Some("!point"),
+        "1,2",
+        YamlPlainScalarStyle,
+    )
+    .unwrap();
+    assert_eq!(resolved.tag, "!point");
+    assert_eq!(resolved.value, Value::Str("1,2"));
+
+    for (tag, value) in [
+        (NULL_TAG, "nil"),
+        (BOOL_TAG, "yes"),
+        (INT_TAG, "1.5"),
+        (FLOAT_TAG, "one"),
+    ] {
+        let error = resolve(
+            YamlCoreSchema,
+            Some(tag),
+            value,
+            YamlPlainScalarStyle,
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Composer);
+    }
+}
+
+#[test]
+fn test_loader() {
+    let input = b"- 12\n- '12'\n- !!str 12\n- yes\n- ~\n";
+
+    let document = Loader::new(input).next().unwrap().unwrap();
+    let root = document.root().unwrap().as_sequence().unwrap();
+    assert!(root.iter().all(|node| node.tag() == Some(STR_TAG)));
+
+    let mut parser = Parser::new(input);
+    parser.set_schema(YamlCoreSchema);
+    assert_eq!(parser.schema(), YamlCoreSchema);
+    let document = Loader::from_parser(parser).next().unwrap().unwrap();
+    let root = document.root().unwrap().as_sequence().unwrap();
+    let tags: Vec<_> =
+        root.iter().map(|node| node.tag().unwrap()).collect();
+    assert_eq!(tags, [INT_TAG, STR_TAG, STR_TAG, STR_TAG, NULL_TAG]);
+    let values: Vec<_> = root
+        .iter()
+        .map(|node| node.as_scalar().unwrap().resolve().unwrap().value)
+        .collect();
+    assert_eq!(
+        values,
+        [
+            Value::Int(12),
+            Value::Str("12"),
+            Value::Str("12"),
+            Value::Str("yes"),
+            Value::Null,
+        ],
+    );
+
+    let mut parser = Parser::new(b"yes\n");
+    parser.set_schema(YamlYaml11Schema);
+    let document = Loader::from_parser(parser).next().unwrap().unwrap();
+    let root = document.root().and_then(Node::as_scalar).unwrap();
+    assert_eq!(root.tag(), Some(BOOL_TAG));
+
+    let mut parser = Parser::new(b"99999999999999999999\n");
+    parser.set_schema(YamlCoreSchema);
+    let document = Loader::from_parser(parser).next().unwrap().unwrap();
+    let root = document.root().and_then(Node::as_scalar).unwrap();
+    assert_eq!(root.tag(), Some(INT_TAG));
+    let Value::BigInt(int) = root.resolve().unwrap().value else {
+        panic!()
+    };
+    assert_eq!(int.digits(), "99999999999999999999");
+}
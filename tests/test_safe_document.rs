@@ -0,0 +1,82 @@
+use libyml::safe::{Document, Loader, Node};
+use libyml::{
+    YamlBlockMappingStyle, YamlDoubleQuotedScalarStyle,
+    YamlFlowSequenceStyle,
+};
+
+fn load(input: &str) -> Vec<Document> {
+    Loader::new(input.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+}
+
+#[test]
+fn test_mapping() {
+    let documents = load("a: 1\nb: \"two\"\nc: [x, y]\n");
+    assert_eq!(documents.len(), 1);
+    let root = documents[0].root().unwrap();
+    assert_eq!(root.tag(), Some("tag:yaml.org,2002:map"));
+    let mapping = root.as_mapping().unwrap();
+    assert_eq!(mapping.style(), YamlBlockMappingStyle);
+    assert_eq!(mapping.len(), 3);
+    let keys: Vec<_> =
+        mapping.iter().filter_map(|(key, _)| key.as_str()).collect();
+    assert_eq!(keys, ["a", "b", "c"]);
+
+    let b = mapping.get("b").unwrap().as_scalar().unwrap();
+    assert_eq!(b.value(), "two");
+    assert_eq!(b.style(), YamlDoubleQuotedScalarStyle);
+    assert_eq!(b.tag(), Some("tag:yaml.org,2002:str"));
+
+    let c = mapping.get("c").unwrap().as_sequence().unwrap();
+    assert_eq!(c.style(), YamlFlowSequenceStyle);
+    assert_eq!(c.len(), 2);
+    assert_eq!(c.get(1).and_then(Node::as_str), Some("y"));
+    assert!(c.get(2).is_none());
+    assert!(mapping.get("missing").is_none());
+}
+
+#[test]
+fn test_aliases_share_nodes() {
+    let documents = load("- &a {k: v}\n- *a\n");
+    let sequence = documents[0].root().unwrap().as_sequence().unwrap();
+    let items: Vec<_> = sequence.iter().collect();
+    assert_eq!(items.len(), 2);
+    for item in items {
+        let mapping = item.as_mapping().unwrap();
+        assert_eq!(mapping.get("k").and_then(Node::as_str), Some("v"));
+    }
+}
+
+#[test]
+fn test_marks() {
+    let documents = load("key:\n  - value\n");
+    let root = documents[0].root().unwrap().as_mapping().unwrap();
+    let value = root.get("key").unwrap();
+    assert_eq!(value.start_mark().line, 1);
+    assert_eq!(value.start_mark().column, 2);
+}
+
+#[test]
+fn test_multiple_documents() {
+    let documents = load("%YAML 1.1\n--- first\n...\n--- second\n");
+    assert_eq!(documents.len(), 2);
+    let version = documents[0].version_directive().unwrap();
+    assert_eq!((version.major, version.minor), (1, 1));
+    assert!(!documents[0].start_implicit());
+    assert_eq!(documents[1].root().unwrap().as_str(), Some("second"));
+    assert!(format!("{:?}", documents[1]).contains("\"second\""));
+}
+
+#[test]
+fn test_empty_stream() {
+    assert!(load("").is_empty());
+}
+
+#[test]
+fn test_load_error() {
+    let mut loader = Loader::new(b"a: *missing\n");
+    let error = loader.next().unwrap().unwrap_err();
+    assert_eq!(error.message(), "found undefined alias");
+    assert!(loader.next().is_none());
+}
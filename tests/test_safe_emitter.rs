@@ -0,0 +1,236 @@
+use libyml::error::ErrorKind;
+use libyml::safe::{
+    Alias, DocumentEnd, DocumentStart, Emitter, EmitterBuilder, Event,
+    FmtSink, MappingStart, Parser, Scalar, SequenceStart, Sink,
+    StreamStart,
+};
+use libyml::{
+    YamlAnyScalarStyle, YamlBlockMappingStyle, YamlFlowSequenceStyle,
+    YamlUtf8Encoding,
+};
+use std::fmt;
+
+fn scalar(value: &str) -> Event<'_> {
+    Event::Scalar(Scalar {
+        anchor: None,
+        tag: None,
+        value: value.into(),
+        plain_implicit: true,
+        quoted_implicit: true,
+        style: YamlAnyScalarStyle,
+    })
+}
+
+fn emit(emitter: &mut Emitter<Vec<u8>>, events: &[Event<'_>]) {
+    for event in events {
+        emitter.emit(event).unwrap();
+    }
+}
+
+fn document(body: Vec<Event<'static>>) -> Vec<Event<'static>> {
+    let mut events = vec![
+        Event::StreamStart(StreamStart {
+            encoding: YamlUtf8Encoding,
+        }),
+        Event::DocumentStart(DocumentStart {
+            implicit: true,
+            ..DocumentStart::default()
+        }),
+    ];
+    events.extend(body);
+    events.push(Event::DocumentEnd(DocumentEnd { implicit: true }));
+    events.push(Event::StreamEnd);
+    events
+}
+
+#[test]
+fn test_emit_mapping() {
+    let events = document(vec![
+        Event::MappingStart(MappingStart {
+            anchor: None,
+            tag: None,
+            implicit: true,
+            style: YamlBlockMappingStyle,
+        }),
+        scalar("key"),
+        Event::SequenceStart(SequenceStart {
+            anchor: Some("a".into()),
+            tag: None,
+            implicit: true,
+            style: YamlFlowSequenceStyle,
+        }),
+        scalar("x"),
+        scalar("y"),
+        Event::SequenceEnd,
+        scalar("other"),
+        Event::Alias(Alias { anchor: "a".into() }),
+        Event::MappingEnd,
+    ]);
+    let mut emitter = Emitter::new(Vec::new());
+    emit(&mut emitter, &events);
+    let output = emitter.into_inner();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "key: &a [x, y]\nother: *a\n",
+    );
+}
+
+#[test]
+fn test_builder_options() {
+    let events = document(vec![
+        Event::MappingStart(MappingStart {
+            anchor: None,
+            tag: None,
+            implicit: true,
+            style: YamlBlockMappingStyle,
+        }),
+        scalar("key"),
+        Event::MappingStart(MappingStart {
+            anchor: None,
+            tag: None,
+            implicit: true,
+            style: YamlBlockMappingStyle,
+        }),
+        scalar("nested"),
+        scalar("é"),
+        Event::MappingEnd,
+        Event::MappingEnd,
+    ]);
+    let mut emitter = EmitterBuilder::new()
+        .indent(4)
+        .unicode(true)
+        .line_break(libyml::YamlCrlnBreak)
+        .build(Vec::new());
+    emit(&mut emitter, &events);
+    assert_eq!(
+        String::from_utf8(emitter.into_inner()).unwrap(),
+        "key:\r\n    nested: é\r\n",
+    );
+}
+
+#[test]
+fn test_round_trip() {
+    let input = "- &x !!str a\n- 'b c'\n- *x\n";
+    let events = Parser::new(input.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let mut emitter = Emitter::new(Vec::new());
+    emit(&mut emitter, &events);
+    assert_eq!(String::from_utf8(emitter.into_inner()).unwrap(), input);
+}
+
+#[test]
+fn test_emit_error() {
+    let mut emitter = Emitter::new(Vec::new());
+    let error = emitter.emit(&Event::StreamEnd).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Emitter);
+    assert_eq!(error.to_string(), "expected STREAM-START");
+
+    let mut emitter = Emitter::new(Vec::new());
+    let error = emitter
+        .emit(&Event::Alias(Alias {
+            anchor: "a\0b".into(),
+        }))
+        .unwrap_err();
+    assert_eq!(error.to_string(), "invalid event");
+}
+
+#[test]
+fn test_fmt_sink() {
+    let events = document(vec![scalar("ünïcödé")]);
+    let mut emitter = EmitterBuilder::new()
+        .unicode(true)
+        .build(FmtSink::new(String::new()));
+    for event in &events {
+        emitter.emit(event).unwrap();
+    }
+    emitter.flush().unwrap();
+    assert_eq!(emitter.into_inner().into_inner(), "ünïcödé\n");
+}
+
+#[test]
+fn test_fmt_sink_split_sequence() {
+    let mut sink = FmtSink::new(String::new());
+    let bytes = "é".as_bytes();
+    sink.write_all(&bytes[..1]).unwrap();
+    sink.write_all(&bytes[1..]).unwrap();
+    sink.flush().unwrap();
+    assert_eq!(sink.into_inner(), "é");
+}
+
+struct Full;
+
+#[derive(Debug)]
+struct FullError;
+
+impl fmt::Display for FullError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("sink is full")
+    }
+}
+
+impl std::error::Error for FullError {}
+
+impl Sink for Full {
+    type Error = FullError;
+
+    fn write_all(&mut self, _bytes: &[u8]) -> Result<(), Self::Error> {
+        Err(FullError)
+    }
+}
+
+#[test]
+fn test_sink_error_is_preserved() {
+    let mut emitter = Emitter::new(Full);
+    let error = document(vec![scalar("x")])
+        .iter()
+        .map(|event| emitter.emit(event))
+        .find_map(Result::err)
+        .unwrap();
+    assert_eq!(error.kind(), ErrorKind::Writer);
+    assert_eq!(error.to_string(), "write error");
+    let source = std::error::Error::source(&error).unwrap();
+    assert_eq!(source.to_string(), "sink is full");
+}
+
+#[test]
+fn test_empty_plain_scalars() {
+    // Written plain where they read back as empty plain scalars, and
+    // quoted elsewhere.
+    for (input, expected) in [
+        ("a:\nb: c\n", "a:\nb: c\n"),
+        ("-\n- a\n", "-\n- a\n"),
+        ("{a: , b: c}\n", "{a: , b: c}\n"),
+        ("? &k\n: v\n", "&k : v\n"),
+        ("? !!null\n: v\n", "!!null : v\n"),
+        ("? \n: v\n", "! '': v\n"),
+        ("[a, {? : b}]\n", "[a, {! '': b}]\n"),
+        ("a: |\n", "a: |-\n"),
+    ] {
+        let events = Parser::new(input.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let mut emitter = Emitter::new(Vec::new());
+        emit(&mut emitter, &events);
+        let output = String::from_utf8(emitter.into_inner()).unwrap();
+        assert_eq!(output, expected, "{input:?}");
+    }
+
+    let events = document(vec![
+        Event::SequenceStart(SequenceStart {
+            anchor: None,
+            tag: None,
+            implicit: true,
+            style: YamlFlowSequenceStyle,
+        }),
+        scalar(""),
+        scalar("a"),
+        Event::SequenceEnd,
+    ]);
+    let mut emitter = Emitter::new(Vec::new());
+    emit(&mut emitter, &events);
+    assert_eq!(
+        String::from_utf8(emitter.into_inner()).unwrap(),
+        "['', a]\n",
+    );
+}
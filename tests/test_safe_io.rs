@@ -0,0 +1,100 @@
+#![cfg(feature = "std")]
+
+use libyml::error::ErrorKind;
+use libyml::safe::{Emitter, Event, IoSink, Loader, Parser};
+use std::io::{self, Read, Write};
+
+/// Yields its input a few bytes at a time, then fails.
+struct Flaky {
+    input: &'static [u8],
+    interrupted: bool,
+}
+
+impl Read for Flaky {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.interrupted {
+            self.interrupted = true;
+            return Err(io::ErrorKind::Interrupted.into());
+        }
+        if self.input.is_empty() {
+            return Err(io::Error::other("disk on fire"));
+        }
+        let n = buf.len().min(self.input.len()).min(3);
+        buf[..n].copy_from_slice(&self.input[..n]);
+        self.input = &self.input[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_read_events() {
+    let input = "- one\n- two\n- three\n".repeat(1000);
+    let count = Parser::from_reader(input.as_bytes())
+        .filter(|event| matches!(event, Ok(Event::Scalar(_))))
+        .count();
+    assert_eq!(count, 3000);
+}
+
+#[test]
+fn test_load_from_reader() {
+    let parser =
+        Parser::from_reader(io::Cursor::new(b"a: [1, 2]\n".to_vec()));
+    let documents = Loader::from_parser(parser)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let root = documents[0].root().unwrap().as_mapping().unwrap();
+    assert_eq!(root.get("a").unwrap().as_sequence().unwrap().len(), 2);
+}
+
+#[test]
+fn test_io_error_is_preserved() {
+    let reader = Flaky {
+        input: b"key: value\n",
+        interrupted: false,
+    };
+    let error =
+        Parser::from_reader(reader).find_map(Result::err).unwrap();
+    assert_eq!(error.kind(), ErrorKind::Reader);
+    assert_eq!(error.message(), "input error");
+    let io_error = error.io_error().unwrap();
+    assert_eq!(io_error.to_string(), "disk on fire");
+    let source = std::error::Error::source(&error).unwrap();
+    assert_eq!(source.to_string(), "disk on fire");
+}
+
+struct Broken;
+
+impl Write for Broken {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::ErrorKind::BrokenPipe.into())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_io_sink() {
+    let mut emitter = Emitter::new(IoSink::new(Vec::new()));
+    for event in Parser::new(b"[a, {b: c}]") {
+        emitter.emit(&event.unwrap()).unwrap();
+    }
+    emitter.flush().unwrap();
+    let output = emitter.into_inner().into_inner();
+    assert_eq!(output, b"[a, {b: c}]\n");
+}
+
+#[test]
+fn test_io_sink_error_is_preserved() {
+    let mut emitter = Emitter::new(IoSink::new(Broken));
+    let error = Parser::new(b"a: b")
+        .map(|event| emitter.emit(&event.unwrap()))
+        .find_map(Result::err)
+        .unwrap();
+    assert_eq!(error.kind(), ErrorKind::Writer);
+    assert_eq!(
+        error.io_error().unwrap().kind(),
+        io::ErrorKind::BrokenPipe,
+    );
+}
@@ -0,0 +1,93 @@
+use libyml::error::ErrorKind;
+use libyml::safe::{Event, Parser};
+use libyml::{YamlBlockMappingStyle, YamlPlainScalarStyle};
+
+fn events(input: &str) -> Vec<Event<'_>> {
+    Parser::new(input.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+}
+
+#[test]
+fn test_event_stream() {
+    let events = events("&a key: !!str value\n");
+    assert_eq!(events.len(), 8);
+    assert!(matches!(events[0], Event::StreamStart(_)));
+    assert!(
+        matches!(events[1], Event::DocumentStart(ref start) if start.implicit)
+    );
+    match &events[2] {
+        Event::MappingStart(mapping) => {
+            assert_eq!(mapping.style, YamlBlockMappingStyle);
+            assert_eq!(mapping.anchor, None);
+        }
+        other => panic!("unexpected event: {other:?}"),
+    }
+    match &events[3] {
+        Event::Scalar(scalar) => {
+            assert_eq!(scalar.anchor.as_deref(), Some("a"));
+            assert_eq!(scalar.value, "key");
+            assert_eq!(scalar.style, YamlPlainScalarStyle);
+            assert!(scalar.plain_implicit);
+        }
+        other => panic!("unexpected event: {other:?}"),
+    }
+    match &events[4] {
+        Event::Scalar(scalar) => {
+            assert_eq!(
+                scalar.tag.as_deref(),
+                Some("tag:yaml.org,2002:str"),
+            );
+            assert_eq!(scalar.value, "value");
+        }
+        other => panic!("unexpected event: {other:?}"),
+    }
+    assert_eq!(events[5], Event::MappingEnd);
+    assert!(matches!(events[6], Event::DocumentEnd(_)));
+    assert_eq!(events[7], Event::StreamEnd);
+}
+
+#[test]
+fn test_alias_and_directives() {
+    let events = events("%YAML 1.1\n--- [&x 1, *x]\n");
+    match &events[1] {
+        Event::DocumentStart(start) => {
+            let version = start.version_directive.unwrap();
+            assert_eq!((version.major, version.minor), (1, 1));
+            assert!(!start.implicit);
+        }
+        other => panic!("unexpected event: {other:?}"),
+    }
+    match &events[4] {
+        Event::Alias(alias) => assert_eq!(alias.anchor, "x"),
+        other => panic!("unexpected event: {other:?}"),
+    }
+}
+
+#[test]
+fn test_marks() {
+    let mut parser = Parser::new(b"a: [b]\n");
+    while let Some(event) = parser.next_event() {
+        if let Event::SequenceStart(_) = event.unwrap() {
+            assert_eq!(parser.start_mark().line, 0);
+            assert_eq!(parser.start_mark().column, 3);
+        }
+    }
+}
+
+#[test]
+fn test_error_ends_iteration() {
+    let mut parser = Parser::new(b"key: [unclosed\n");
+    let error = loop {
+        match parser.next().unwrap() {
+            Ok(_) => {}
+            Err(error) => break error,
+        }
+    };
+    assert_eq!(error.kind(), ErrorKind::Parser);
+    assert_eq!(
+        error.to_string(),
+        "did not find expected ',' or ']' at line 2 column 1, while parsing a flow sequence at line 1 column 6",
+    );
+    assert!(parser.next().is_none());
+}
@@ -0,0 +1,314 @@
+#![cfg(feature = "serde")]
+
+use libyml::error::ErrorKind;
+use libyml::safe::Parser;
+use libyml::{
+    from_slice, from_str, Deserializer, YamlCoreSchema,
+    YamlFailsafeSchema,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Server<'a> {
+    host: &'a str,
+    port: u16,
+    #[serde(default)]
+    tags: Vec<String>,
+    backup: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Config<'a> {
+    #[serde(borrow)]
+    servers: Vec<Server<'a>>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+enum Shape {
+    Point,
+    Circle(f64),
+    Line(i32, i32),
+    Rect { width: u32, height: u32 },
+}
+
+#[test]
+fn test_struct() {
+    let input = "\
+host: example.com
+port: 8080
+tags: [web, 'primary']
+backup: ~
+";
+    let server: Server = from_str(input).unwrap();
+    assert_eq!(
+        server,
+        Server {
+            host: "example.com",
+            port: 8080,
+            tags: vec!["web".to_owned(), "primary".to_owned()],
+            backup: None,
+        },
+    );
+
+    let server: Server =
+        from_slice(b"{host: h, port: 1, backup: true}").unwrap();
+    assert_eq!(server.backup, Some(true));
+    assert!(server.tags.is_empty());
+}
+
+#[test]
+fn test_scalars() {
+    assert_eq!(from_str::<i64>("0x10").unwrap(), 16);
+    assert_eq!(from_str::<f64>("-.inf").unwrap(), f64::NEG_INFINITY);
+    assert!(from_str::<bool>("true").unwrap());
+    assert_eq!(from_str::<String>("0x10").unwrap(), "0x10");
+    assert_eq!(from_str::<String>("[a]").unwrap_err().path(), None);
+    assert_eq!(from_str::<i32>("!!str 3").unwrap_err().kind(), {
+        ErrorKind::Composer
+    });
+    assert_eq!(from_str::<char>("'x'").unwrap(), 'x');
+    assert_eq!(from_str::<Option<u8>>("").unwrap(), None);
+    assert_eq!(from_str::<Option<u8>>("7").unwrap(), Some(7));
+    from_str::<()>("null").unwrap();
+    assert!(from_str::<u8>("300").is_err());
+    assert_eq!(
+        from_str::<u64>("0xFFFFFFFFFFFFFFFF").unwrap(),
+        u64::MAX,
+    );
+    assert_eq!(
+        from_str::<u128>(
+            "0o2000000000000000000000000000000000000000000"
+        )
+        .unwrap(),
+        1 << 127,
+    );
+    assert_eq!(
+        from_str::<i128>("-170141183460469231731687303715884105728")
+            .unwrap(),
+        i128::MIN,
+    );
+    assert!(
+        from_str::<u128>(&format!("0x1{}", "0".repeat(32))).is_err()
+    );
+
+    // An empty value stands for an empty mapping.
+    let map: BTreeMap<String, BTreeMap<String, u8>> =
+        from_str("a:\nb: {c: 1}\n").unwrap();
+    assert!(map["a"].is_empty());
+    assert_eq!(map["b"]["c"], 1);
+
+    // Strings that appear in the input as they are borrow from it.
+    let map: BTreeMap<&str, &str> = from_str("a: 'b'").unwrap();
+    assert_eq!(map["a"], "b");
+    assert!(from_str::<BTreeMap<&str, &str>>("a: \"b\\n\"").is_err());
+    let map: BTreeMap<String, String> =
+        from_str("a: \"b\\n\"").unwrap();
+    assert_eq!(map["a"], "b\n");
+}
+
+#[test]
+fn test_schema() {
+    let mut parser = Parser::new(b"[true, 1]");
+    parser.set_schema(YamlFailsafeSchema);
+    let value = <(String, String)>::deserialize(
+        Deserializer::from_parser(parser),
+    )
+    .unwrap();
+    assert_eq!(value, ("true".to_owned(), "1".to_owned()));
+
+    let mut parser = Parser::new(b"[true, !!int 1]");
+    parser.set_schema(YamlFailsafeSchema);
+    let error =
+        <(bool, i32)>::deserialize(Deserializer::from_parser(parser))
+            .unwrap_err();
+    assert_eq!(error.path(), Some("[0]"));
+}
+
+#[test]
+fn test_enums() {
+    let input = "\
+- Point
+- !Point
+- !Circle 1.5
+- !Line [1, 2]
+- !Rect {width: 3, height: 4}
+- Circle: 2
+- {Rect: {width: 5, height: 6}}
+";
+    let shapes: Vec<Shape> = from_str(input).unwrap();
+    assert_eq!(
+        shapes,
+        [
+            Shape::Point,
+            Shape::Point,
+            Shape::Circle(1.5),
+            Shape::Line(1, 2),
+            Shape::Rect {
+                width: 3,
+                height: 4,
+            },
+            Shape::Circle(2.0),
+            Shape::Rect {
+                width: 5,
+                height: 6,
+            },
+        ],
+    );
+
+    for (input, message) in [
+        (
+            "!Square 1",
+            "unknown variant `Square`, expected one of `Point`, \
+             `Circle`, `Line`, `Rect` at line 1 column 1",
+        ),
+        (
+            "{Point: ~, Circle: 1}",
+            "invalid length 2, expected map containing 1 entry at line 1 \
+             column 1",
+        ),
+        (
+            "[Point]",
+            "invalid type: sequence, expected enum Shape at line 1 \
+             column 1",
+        ),
+        (
+            "Circle: [1]",
+            "Circle: invalid type: sequence, expected f64 at line 1 \
+             column 9",
+        ),
+    ] {
+        let error = from_str::<Shape>(input).unwrap_err();
+        assert_eq!(error.to_string(), message);
+    }
+}
+
+#[test]
+fn test_aliases() {
+    let input = "\
+servers:
+- &local {host: localhost, port: 80}
+- *local
+";
+    let config: Config = from_str(input).unwrap();
+    assert_eq!(config.servers[0], config.servers[1]);
+
+    let error = from_str::<u8>("*a").unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Composer);
+    assert_eq!(
+        error.to_string(),
+        "found undefined alias at line 1 column 1",
+    );
+
+    // Each alias of `b` stands for 13 nodes, 4 per alias of `a`. Only
+    // the aliases in fields that are read count.
+    let input = "\
+a: &a [1, 2, 3]
+b: &b [*a, *a, *a]
+c: [*b, *b]
+";
+    #[derive(Deserialize, Debug)]
+    struct Value {
+        c: Vec<Vec<Vec<u8>>>,
+    }
+    let deserialize = |max_alias_expansion| {
+        let mut parser = Parser::new(input.as_bytes());
+        parser.set_schema(YamlCoreSchema);
+        parser.set_max_alias_expansion(max_alias_expansion);
+        Value::deserialize(Deserializer::from_parser(parser))
+    };
+    assert_eq!(deserialize(26).unwrap().c[1][2], [1, 2, 3]);
+    assert_eq!(
+        deserialize(25).unwrap_err().to_string(),
+        "c[1][2][2]: alias expansion limit exceeded at line 3 column 9",
+    );
+
+    // An alias inside the node it refers to stands for it all over again.
+    #[derive(Deserialize, Debug)]
+    struct Nested(#[allow(dead_code)] Vec<Nested>);
+    let error = from_str::<Nested>("&a [*a]").unwrap_err();
+    assert_eq!(error.message(), "recursion limit exceeded");
+    assert!(error.path().unwrap().starts_with("[0][0][0]"));
+}
+
+#[test]
+fn test_documents() {
+    let input = "--- 1\n--- two\n--- [3]\n";
+    let mut documents = Deserializer::from_str(input);
+    let first = documents.next().unwrap();
+    assert_eq!(u8::deserialize(first).unwrap(), 1);
+    let second = documents.next().unwrap();
+    assert_eq!(String::deserialize(second).unwrap(), "two");
+    let third = documents.next().unwrap();
+    assert_eq!(Vec::<u8>::deserialize(third).unwrap(), [3]);
+    assert!(documents.next().is_none());
+
+    assert_eq!(
+        from_str::<u8>(input).unwrap_err().to_string(),
+        "deserializing from YAML containing more than one document is \
+         not supported at line 2 column 1",
+    );
+    assert_eq!(Deserializer::from_str("").count(), 0);
+
+    // A document that does not parse ends the stream.
+    let mut documents =
+        Deserializer::from_str("--- 1\n--- [2\n--- 3\n");
+    assert_eq!(u8::deserialize(documents.next().unwrap()).unwrap(), 1);
+    let error = u8::deserialize(documents.next().unwrap()).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Parser);
+    assert!(documents.next().is_none());
+}
+
+#[test]
+fn test_errors() {
+    let input = "\
+servers:
+- host: a
+  port: 80
+- host: b
+  port: eighty
+";
+    let error = from_str::<Config>(input).unwrap_err();
+    assert_eq!(error.path(), Some("servers[1].port"));
+    let mark = error.start_mark().unwrap();
+    assert_eq!((mark.line, mark.column), (4, 8));
+    assert_eq!(
+        error.to_string(),
+        "servers[1].port: invalid type: string \"eighty\", expected u16 \
+         at line 5 column 9",
+    );
+
+    let error =
+        from_str::<Config>("servers:\n- host: a\n").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "servers[0]: missing field `port` at line 2 column 3",
+    );
+
+    let error = from_str::<u8>("x").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid type: string \"x\", expected u8 at line 1 column 1",
+    );
+
+    let error = from_str::<Vec<u8>>("[1, 2]\n]").unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Parser);
+    assert_eq!(error.path(), None);
+
+    let error =
+        from_str::<BTreeMap<String, (u8,)>>("a: [1, 2]").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "a: invalid length 2, expected sequence of 1 element at line 1 \
+         column 4",
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_from_reader() {
+    let reader = std::io::Cursor::new("servers: []\n");
+    let value: BTreeMap<String, Vec<u8>> =
+        libyml::from_reader(reader).unwrap();
+    assert!(value["servers"].is_empty());
+}
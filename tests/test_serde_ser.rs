@@ -0,0 +1,230 @@
+#![cfg(feature = "serde")]
+
+use libyml::safe::{EmitterBuilder, FmtSink};
+use libyml::{from_str, to_string, to_vec, Serializer};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Shape {
+    Point,
+    Circle(f64),
+    Line(i32, i32),
+    Rect { width: u32, height: u32 },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Layer {
+    Shape(Shape),
+    Hidden(Option<Box<Layer>>),
+}
+
+struct Bytes(&'static [u8]);
+
+impl Serialize for Bytes {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+/// Serializes `value`, checks the output, and checks that it reads back
+/// as `value`.
+fn round_trip<T>(value: &T, expected: &str)
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let output = to_string(value).unwrap();
+    assert_eq!(output, expected);
+    assert_eq!(from_str::<T>(&output).unwrap(), *value);
+}
+
+#[test]
+fn test_scalars() {
+    round_trip(&true, "true\n");
+    round_trip(&-7_i8, "-7\n");
+    round_trip(&u64::MAX, "18446744073709551615\n");
+    round_trip(
+        &i128::MIN,
+        "-170141183460469231731687303715884105728\n",
+    );
+    round_trip(&1.0_f64, "1.0\n");
+    round_trip(&0.1_f32, "0.1\n");
+    round_trip(&1e300_f64, "1.0e+300\n");
+    round_trip(&-2.5e-7_f64, "-2.5e-7\n");
+    round_trip(&f64::NEG_INFINITY, "-.inf\n");
+    assert!(from_str::<f64>(&to_string(&f64::NAN).unwrap())
+        .unwrap()
+        .is_nan());
+    round_trip(&'x', "x\n");
+    round_trip(&(), "null\n");
+    round_trip(&None::<u8>, "null\n");
+    round_trip(&Some(3_u8), "3\n");
+}
+
+#[test]
+fn test_strings() {
+    for (value, expected) in [
+        ("text", "text\n"),
+        ("", "''\n"),
+        ("null", "'null'\n"),
+        ("~", "'~'\n"),
+        ("true", "'true'\n"),
+        ("yes", "'yes'\n"),
+        ("12", "'12'\n"),
+        ("0x1F", "'0x1F'\n"),
+        ("1_000", "'1_000'\n"),
+        ("1.5", "'1.5'\n"),
+        (".inf", "'.inf'\n"),
+        ("- a", "'- a'\n"),
+        ("a: b", "'a: b'\n"),
+        ("it's", "it's\n"),
+        ("two\nlines\n", "|\n  two\n  lines\n"),
+        ("tab\there", "\"tab\\there\"\n"),
+    ] {
+        round_trip(&value.to_owned(), expected);
+    }
+
+    let map = BTreeMap::from([("".to_owned(), "1".to_owned())]);
+    round_trip(&map, "'': '1'\n");
+    round_trip(&vec!["a\nb".to_owned()], "- |-\n  a\n  b\n");
+}
+
+#[test]
+fn test_collections() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Config {
+        name: String,
+        ports: Vec<u16>,
+        env: BTreeMap<String, String>,
+        limits: (u8, u8),
+        empty: Vec<u8>,
+    }
+    let config = Config {
+        name: "web".to_owned(),
+        ports: vec![80, 443],
+        env: BTreeMap::from([("MODE".to_owned(), "on".to_owned())]),
+        limits: (1, 2),
+        empty: Vec::new(),
+    };
+    round_trip(
+        &config,
+        "\
+name: web
+ports:
+- 80
+- 443
+env:
+  MODE: 'on'
+limits:
+- 1
+- 2
+empty: []
+",
+    );
+
+    let nested = vec![vec![1, 2], vec![]];
+    round_trip(&nested, "- - 1\n  - 2\n- []\n");
+    let keys = BTreeMap::from([((1, 2), "a".to_owned())]);
+    round_trip(&keys, "? - 1\n  - 2\n: a\n");
+    round_trip(&BTreeMap::<u8, u8>::new(), "{}\n");
+
+    assert_eq!(to_vec(&Bytes(&[1, 2])).unwrap(), b"- 1\n- 2\n");
+}
+
+#[test]
+fn test_enums() {
+    let shapes = vec![
+        Shape::Point,
+        Shape::Circle(1.5),
+        Shape::Line(1, 2),
+        Shape::Rect {
+            width: 3,
+            height: 4,
+        },
+    ];
+    round_trip(
+        &shapes,
+        "\
+- Point
+- !Circle 1.5
+- !Line
+  - 1
+  - 2
+- !Rect
+  width: 3
+  height: 4
+",
+    );
+
+    // The value of a variant that is a variant itself gets a mapping.
+    let layers = vec![
+        Layer::Shape(Shape::Circle(2.0)),
+        Layer::Shape(Shape::Point),
+        Layer::Hidden(Some(Box::new(Layer::Shape(Shape::Line(3, 4))))),
+        Layer::Hidden(None),
+    ];
+    round_trip(
+        &layers,
+        "\
+- Shape: !Circle 2.0
+- !Shape Point
+- Hidden:
+    Shape: !Line
+    - 3
+    - 4
+- !Hidden null
+",
+    );
+}
+
+#[test]
+fn test_documents() {
+    let mut serializer = Serializer::new(FmtSink::new(String::new()));
+    Shape::Circle(1.0).serialize(&mut serializer).unwrap();
+    vec!["a"].serialize(&mut serializer).unwrap();
+    "".serialize(&mut serializer).unwrap();
+    let output = serializer.into_inner().unwrap().into_inner();
+    assert_eq!(output, "!Circle 1.0\n---\n- a\n--- ''\n");
+
+    let empty = Serializer::new(Vec::new()).into_inner().unwrap();
+    assert!(empty.is_empty());
+
+    let emitter =
+        EmitterBuilder::new().indent(4).width(20).build(Vec::new());
+    let mut serializer = Serializer::from_emitter(emitter);
+    let value = BTreeMap::from([("text", "aaaa bbbb cccc dddd eeee")]);
+    value.serialize(&mut serializer).unwrap();
+    assert_eq!(
+        serializer.into_inner().unwrap(),
+        b"text: aaaa bbbb cccc dddd\n    eeee\n",
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_to_writer() {
+    let mut output = Vec::new();
+    libyml::to_writer(&mut output, &BTreeMap::from([("a", 1)]))
+        .unwrap();
+    assert_eq!(output, b"a: 1\n");
+
+    struct Broken;
+    impl std::io::Write for Broken {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    let error = libyml::to_writer(Broken, &1).unwrap_err();
+    assert_eq!(
+        error.io_error().unwrap().kind(),
+        std::io::ErrorKind::BrokenPipe,
+    );
+}
@@ -0,0 +1,193 @@
+#[path = "../src/bin/run-parser-test-suite.rs"]
+#[allow(dead_code)]
+mod run_parser_test_suite;
+
+use std::error::Error;
+
+/// Parses `input` into events written as in the YAML test suite,
+/// leaving out the stream start and end.
+fn parse(
+    input: &str,
+    strict: bool,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut output = Vec::new();
+    unsafe {
+        run_parser_test_suite::print_events(
+            &mut input.as_bytes(),
+            &mut output,
+            strict,
+        )?;
+    }
+    Ok(String::from_utf8(output)?
+        .lines()
+        .filter(|event| !event.ends_with("STR"))
+        .map(str::to_owned)
+        .collect())
+}
+
+fn strict(input: &str) -> Vec<String> {
+    parse(input, true).unwrap()
+}
+
+#[test]
+fn test_default_mode() {
+    for input in [
+        "&a: key: value\n",
+        ": a\n",
+        "{\"foo\"\n: \"bar\"}\n",
+        "- -\tbaz\n",
+        "---\na\n...\nb\n",
+        "%FOO bar\n---\na\n",
+        "[ ? : x ]\n",
+        "[ ? , b ]\n",
+    ] {
+        assert!(parse(input, false).is_err(), "{input:?}");
+        parse(input, true).unwrap();
+    }
+}
+
+#[test]
+fn test_anchor_names() {
+    assert_eq!(
+        strict("a: &an:chor x\nb: *an:chor\n"),
+        [
+            "+DOC",
+            "+MAP",
+            "=VAL :a",
+            "=VAL &an:chor :x",
+            "=VAL :b",
+            "=ALI *an:chor",
+            "-MAP",
+            "-DOC",
+        ],
+    );
+    assert_eq!(
+        strict("[&\u{1f600} x, *\u{1f600}]\n")[2],
+        "=VAL &\u{1f600} :x",
+    );
+}
+
+#[test]
+fn test_empty_keys() {
+    assert_eq!(
+        strict(": a\n: b\n"),
+        [
+            "+DOC", "+MAP", "=VAL :", "=VAL :a", "=VAL :", "=VAL :b",
+            "-MAP", "-DOC",
+        ],
+    );
+    assert_eq!(
+        strict("[ : a ]\n"),
+        [
+            "+DOC", "+SEQ []", "+MAP {}", "=VAL :", "=VAL :a", "-MAP",
+            "-SEQ", "-DOC",
+        ],
+    );
+    assert_eq!(
+        strict("{ a:, : b }\n"),
+        [
+            "+DOC", "+MAP {}", "=VAL :a", "=VAL :", "=VAL :",
+            "=VAL :b", "-MAP", "-DOC",
+        ],
+    );
+}
+
+#[test]
+fn test_flow_mapping_keys() {
+    let expected = [
+        "+DOC",
+        "+MAP {}",
+        "=VAL :multi line",
+        "=VAL :value",
+        "-MAP",
+        "-DOC",
+    ];
+    assert_eq!(strict("{ multi\n  line: value }\n"), expected);
+    assert_eq!(
+        strict("{ \"foo\" # comment\n  :bar }\n"),
+        ["+DOC", "+MAP {}", "=VAL \"foo", "=VAL :bar", "-MAP", "-DOC"],
+    );
+    // Keys of single pairs in flow sequences stay on a single line.
+    assert!(parse("[ a\n  : b ]\n", true).is_err());
+}
+
+#[test]
+fn test_flow_colons() {
+    assert_eq!(
+        strict("[ ::vector, http://x, {a: b}:c ]\n"),
+        [
+            "+DOC",
+            "+SEQ []",
+            "=VAL :::vector",
+            "=VAL :http://x",
+            "+MAP {}",
+            "+MAP {}",
+            "=VAL :a",
+            "=VAL :b",
+            "-MAP",
+            "=VAL :c",
+            "-MAP",
+            "-SEQ",
+            "-DOC",
+        ],
+    );
+    assert_eq!(
+        strict("{ a:, \"b\":c }\n"),
+        [
+            "+DOC", "+MAP {}", "=VAL :a", "=VAL :", "=VAL \"b",
+            "=VAL :c", "-MAP", "-DOC",
+        ],
+    );
+}
+
+#[test]
+fn test_tabs() {
+    assert_eq!(
+        strict("- a:\t b\n-\tc\n"),
+        [
+            "+DOC", "+SEQ", "+MAP", "=VAL :a", "=VAL :b", "-MAP",
+            "=VAL :c", "-SEQ", "-DOC",
+        ],
+    );
+    // Tabs still do not indent.
+    assert!(parse("a:\n\tb: c\n", true).is_err());
+}
+
+#[test]
+fn test_block_scalars() {
+    assert_eq!(
+        strict("--- |\n%!PS\n# not a comment\n...\n"),
+        ["+DOC ---", "=VAL |%!PS\\n# not a comment\\n", "-DOC ...",],
+    );
+    assert_eq!(strict("- >\n \t\n x\n")[2], "=VAL >\\t\\nx\\n");
+}
+
+#[test]
+fn test_documents() {
+    assert_eq!(
+        strict("a\n...\n# none\n...\nb\n"),
+        ["+DOC", "=VAL :a", "-DOC ...", "+DOC", "=VAL :b", "-DOC"],
+    );
+    assert_eq!(strict("...\n"), Vec::<String>::new());
+    assert_eq!(
+        strict("%RESERVED x y # ignored\n%YAML 1.3\n--- a\n"),
+        ["+DOC ---", "=VAL :a", "-DOC"],
+    );
+    assert!(parse("%YAML 2.0\n--- a\n", true).is_err());
+}
+
+#[test]
+fn test_rejected() {
+    for input in [
+        "key: \"value\"# comment\n",
+        "[ a, b,# comment\n]\n",
+        "block: ># comment\n  x\n",
+        "---\nkey: value\n%YAML 1.2\n---\n",
+        "---\nflow: [a,\nb]\n",
+        "---\nquoted: \"a\nb\"\n",
+        "block: >\n  \n # x\n",
+    ] {
+        parse(input, false).unwrap();
+        assert!(parse(input, true).is_err(), "{input:?}");
+    }
+}
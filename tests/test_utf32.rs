@@ -0,0 +1,106 @@
+use libyml::error::ErrorKind;
+use libyml::safe::{EmitterBuilder, Event, Parser, StreamStart};
+use libyml::{
+    YamlEncodingT, YamlUtf16leEncoding, YamlUtf32beEncoding,
+    YamlUtf32leEncoding, YamlUtf8Encoding,
+};
+
+const INPUT: &str = "a: [b, é😀]\n";
+
+fn utf32(input: &str, little_endian: bool, bom: bool) -> Vec<u8> {
+    let bom = if bom { Some('\u{FEFF}') } else { None };
+    bom.into_iter()
+        .chain(input.chars())
+        .flat_map(|char| {
+            let char = char as u32;
+            if little_endian {
+                char.to_le_bytes()
+            } else {
+                char.to_be_bytes()
+            }
+        })
+        .collect()
+}
+
+fn events(input: &[u8]) -> Vec<Event<'_>> {
+    Parser::new(input).map(Result::unwrap).collect()
+}
+
+fn encoding(events: &[Event<'_>]) -> YamlEncodingT {
+    match &events[0] {
+        Event::StreamStart(stream_start) => stream_start.encoding,
+        event => panic!("{event:?}"),
+    }
+}
+
+#[test]
+fn test_detection() {
+    let expected = events(INPUT.as_bytes());
+    for (little_endian, encoding_expected) in
+        [(true, YamlUtf32leEncoding), (false, YamlUtf32beEncoding)]
+    {
+        for bom in [true, false] {
+            let input = utf32(INPUT, little_endian, bom);
+            let events = events(&input);
+            assert_eq!(encoding(&events), encoding_expected);
+            assert_eq!(events[1..], expected[1..]);
+        }
+    }
+    // The byte order mark of UTF-16LE followed by a character.
+    let events = events(b"\xFF\xFEa\x00");
+    assert_eq!(encoding(&events), YamlUtf16leEncoding);
+    assert_eq!(encoding(&self::events(b"a")), YamlUtf8Encoding);
+}
+
+#[test]
+fn test_marks() {
+    let input = utf32("é: 😀\n", true, true);
+    let mut parser = Parser::new(&input);
+    let mut offsets = Vec::new();
+    while let Some(event) = parser.next_event() {
+        if let Event::Scalar(_) = event.unwrap() {
+            offsets.push((
+                parser.start_mark().offset,
+                parser.end_mark().offset,
+            ));
+        }
+    }
+    assert_eq!(offsets, [(4, 8), (16, 20)]);
+}
+
+#[test]
+fn test_invalid() {
+    for (input, message) in [
+        (
+            &b"\x00\x00\x00a\x00\x00\xD8\x00"[..],
+            "invalid Unicode character",
+        ),
+        (
+            b"\xFF\xFE\x00\x00a\x00\x00\x00\x00\x00\x11\x00",
+            "invalid Unicode character",
+        ),
+        (b"\xFF\xFE\x00\x00a\x00\x00", "incomplete UTF-32 character"),
+    ] {
+        let error =
+            Parser::new(input).find_map(Result::err).expect("error");
+        assert_eq!(error.kind(), ErrorKind::Reader);
+        assert_eq!(error.message(), message);
+    }
+}
+
+#[test]
+fn test_emit() {
+    for (encoding, little_endian) in
+        [(YamlUtf32leEncoding, true), (YamlUtf32beEncoding, false)]
+    {
+        let mut events = events(INPUT.as_bytes());
+        events[0] = Event::StreamStart(StreamStart { encoding });
+        let mut emitter =
+            EmitterBuilder::new().unicode(true).build(Vec::new());
+        for event in &events {
+            emitter.emit(event).unwrap();
+        }
+        let output = emitter.into_inner();
+        assert_eq!(output, utf32(INPUT, little_endian, true));
+    }
+}
@@ -0,0 +1,158 @@
+#[path = "../src/bin/yaml2json.rs"]
+#[allow(dead_code)]
+mod yaml2json;
+
+use self::yaml2json::{convert, Options, Output};
+use libyml::YamlFailsafeSchema;
+
+fn yaml2json(input: &str, options: &Options) -> Result<String, String> {
+    let mut output = Vec::new();
+    unsafe { convert(&mut input.as_bytes(), &mut output, options) }
+        .map_err(|error| error.to_string())?;
+    Ok(String::from_utf8(output).unwrap())
+}
+
+fn default(input: &str) -> Result<String, String> {
+    yaml2json(input, &Options::default())
+}
+
+#[test]
+fn test_scalars() {
+    let input = "\
+null: [~, null, '']
+bool: [true, False]
+int: [1, 0x1F, 0o17, '2']
+float: [1.5, -.5e3, 1.]
+str: [a b, \"\\t\\u0001\\\"\", !!str 3]
+tagged: !!float 3
+";
+    assert_eq!(
+        default(input).unwrap(),
+        "{\"null\":[null,null,\"\"],\"bool\":[true,false],\
+         \"int\":[1,31,15,\"2\"],\"float\":[1.5,-500.0,1.0],\
+         \"str\":[\"a b\",\"\\t\\u0001\\\"\",\"3\"],\"tagged\":3.0}\n",
+    );
+
+    // Integers too wide for an i64 keep all their digits.
+    assert_eq!(
+        default(
+            "a: 12345678901234567890\n\
+             b: [-9223372036854775809, 0x10000000000000000]\n\
+             0o2000000000000000000000: c\n",
+        )
+        .unwrap(),
+        "{\"a\":12345678901234567890,\
+         \"b\":[-9223372036854775809,18446744073709551616],\
+         \"18446744073709551616\":\"c\"}\n",
+    );
+
+    let options = Options {
+        schema: YamlFailsafeSchema,
+        ..Options::default()
+    };
+    assert_eq!(
+        yaml2json("[1, true, ~]", &options).unwrap(),
+        "[\"1\",\"true\",\"~\"]\n",
+    );
+}
+
+#[test]
+fn test_keys() {
+    assert_eq!(
+        default("1: a\n~: b\ntrue: c\n0x10: d\n1: e\n").unwrap(),
+        "{\"1\":\"a\",\"null\":\"b\",\"true\":\"c\",\"16\":\"d\",\
+         \"1\":\"e\"}\n",
+    );
+    assert_eq!(
+        default("a: 1\n? [b]\n: 2\n").unwrap_err(),
+        "2:3: a sequence or a mapping cannot be a JSON object key",
+    );
+    assert_eq!(
+        default("- &k {a: 1}\n- {*k : 2}\n").unwrap_err(),
+        "2:4: a sequence or a mapping cannot be a JSON object key",
+    );
+}
+
+#[test]
+fn test_documents() {
+    let input = "--- 1\n--- {a: [b]}\n---\n";
+    assert_eq!(default(input).unwrap(), "[1,{\"a\":[\"b\"]},null]\n");
+    assert_eq!(default("a\n").unwrap(), "\"a\"\n");
+    assert_eq!(default("").unwrap(), "[]\n");
+
+    let array = Options {
+        output: Output::Array,
+        ..Options::default()
+    };
+    assert_eq!(yaml2json("a\n", &array).unwrap(), "[\"a\"]\n");
+
+    let ndjson = Options {
+        output: Output::Ndjson,
+        ..Options::default()
+    };
+    assert_eq!(
+        yaml2json(input, &ndjson).unwrap(),
+        "1\n{\"a\":[\"b\"]}\nnull\n",
+    );
+}
+
+#[test]
+fn test_pretty() {
+    let options = Options {
+        pretty: true,
+        ..Options::default()
+    };
+    assert_eq!(
+        yaml2json("a: [1, {}]\nb: []\n", &options).unwrap(),
+        "{\n  \"a\": [\n    1,\n    {}\n  ],\n  \"b\": []\n}\n",
+    );
+}
+
+#[test]
+fn test_aliases() {
+    assert_eq!(
+        default("- &a {x: [1]}\n- *a\n- &a 2\n- *a\n").unwrap(),
+        "[{\"x\":[1]},{\"x\":[1]},2,2]\n",
+    );
+    assert_eq!(
+        default("- *a\n").unwrap_err(),
+        "1:3: unknown anchor \"a\"",
+    );
+
+    let input = "\
+a: &a [x, x, x, x]
+b: &b [*a, *a, *a, *a]
+c: [*b, *b]
+";
+    let options = Options {
+        max_alias_nodes: 40,
+        ..Options::default()
+    };
+    // Each *a stands for 5 nodes and each *b for 25.
+    assert_eq!(
+        yaml2json(input, &options).unwrap_err(),
+        "3:5: aliases stand for more than 40 nodes",
+    );
+    let options = Options {
+        max_alias_nodes: 70,
+        ..Options::default()
+    };
+    yaml2json(input, &options).unwrap();
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        default("a: [b\n").unwrap_err(),
+        "2:1: did not find expected ',' or ']' \
+         (while parsing a flow sequence at 1:4)",
+    );
+    assert_eq!(
+        default("- .nan\n").unwrap_err(),
+        "1:3: \".nan\" has no JSON form",
+    );
+    assert_eq!(
+        default("!!int x\n").unwrap_err(),
+        "1:1: invalid value \"x\" for tag tag:yaml.org,2002:int",
+    );
+}
@@ -0,0 +1,227 @@
+#[path = "../src/bin/yamlfmt.rs"]
+#[allow(dead_code)]
+mod yamlfmt;
+
+use self::yamlfmt::{format, format_file, Options};
+use libyml::YamlCrlnBreak;
+use std::env;
+use std::fs;
+use std::process;
+
+fn yamlfmt(input: &str, options: &Options) -> Result<String, String> {
+    let output = unsafe { format(input.as_bytes(), options) }
+        .map_err(|error| error.to_string())?;
+    Ok(String::from_utf8(output).unwrap())
+}
+
+fn default(input: &str) -> Result<String, String> {
+    yamlfmt(input, &Options::default())
+}
+
+/// Formats `input`, and checks that formatting the output again leaves
+/// it as it is.
+fn stable(input: &str) -> String {
+    let output = default(input).unwrap();
+    assert_eq!(default(&output).unwrap(), output);
+    output
+}
+
+#[test]
+fn test_layout() {
+    let input = "\
+a:   {x: 1,   y: [2,3]}
+b:
+    -   c
+    -    - d
+c:
+        e: f
+";
+    assert_eq!(
+        stable(input),
+        "\
+a: {x: 1, y: [2, 3]}
+b:
+- c
+- - d
+c:
+  e: f
+",
+    );
+}
+
+#[test]
+fn test_scalar_styles() {
+    let input = "\
+plain: text
+single: 'text'
+double: \"text\"
+literal: |
+  two
+  lines
+folded: >-
+  one line
+empty: |
+flow: {a: , b: ''}
+? &key
+: value
+";
+    assert_eq!(
+        stable(input),
+        "\
+plain: text
+single: 'text'
+double: \"text\"
+literal: |
+  two
+  lines
+folded: >-
+  one line
+empty: |-
+flow: {a: , b: ''}
+&key : value
+",
+    );
+}
+
+#[test]
+fn test_document_markers() {
+    for input in [
+        "a\n",
+        "--- a\n",
+        "---\na: 1\n...\n",
+        "a\n--- b\n...\n--- c\n",
+        "%YAML 1.1\n--- a\n",
+        "%TAG !e! tag:example.com,2000:\n--- !e!x a\n",
+    ] {
+        assert_eq!(stable(input), input);
+    }
+    assert_eq!(stable(""), "");
+}
+
+#[test]
+fn test_comments() {
+    let input = "\
+# head
+a: 1  # one
+# before b
+b:
+- 2
+";
+    assert_eq!(
+        stable(input),
+        "# head\na: 1 # one\n# before b\nb:\n- 2\n"
+    );
+}
+
+#[test]
+fn test_comments_formatted_again() {
+    assert_eq!(
+        stable("attributes:\n- &project\n  # comment\n  key: v\n"),
+        "attributes:\n  # comment\n  - &project\n    key: v\n",
+    );
+    assert_eq!(
+        stable("a: # note\n  # above\n  b: c\n"),
+        "a: # note\n  # above\n  b: c\n",
+    );
+    assert_eq!(
+        stable("a: [b\n  # x\n]\n# y\n"),
+        "a: [b]\n\n# x\n# y\n"
+    );
+}
+
+#[test]
+fn test_options() {
+    let input = "a:\n  b: [c, \"é\"]\n";
+    let options = Options {
+        indent: 4,
+        line_break: YamlCrlnBreak,
+        ..Options::default()
+    };
+    assert_eq!(
+        yamlfmt(input, &options).unwrap(),
+        "a:\r\n    b: [c, \"é\"]\r\n",
+    );
+    assert_eq!(stable("k: café\n"), "k: café\n");
+
+    // Plain scalars that have to be quoted stay untagged strings.
+    let options = Options {
+        unicode: false,
+        ..Options::default()
+    };
+    assert_eq!(
+        yamlfmt(input, &options).unwrap(),
+        "a:\n  b: [c, \"\\xE9\"]\n",
+    );
+    assert_eq!(
+        yamlfmt("k: café\n", &options).unwrap(),
+        "k: \"caf\\xE9\"\n",
+    );
+
+    let input = "k: aaaa bbbb cccc dddd\n";
+    let options = Options {
+        width: 10,
+        ..Options::default()
+    };
+    assert_eq!(
+        yamlfmt(input, &options).unwrap(),
+        "k: aaaa bbbb\n  cccc dddd\n",
+    );
+    let options = Options {
+        width: -1,
+        ..Options::default()
+    };
+    assert_eq!(yamlfmt(input, &options).unwrap(), input);
+}
+
+#[test]
+fn test_files() {
+    let dir =
+        env::temp_dir().join(format!("yamlfmt-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let formatted = dir.join("formatted.yaml");
+    let unformatted = dir.join("unformatted.yaml");
+    fs::write(&formatted, "a: [1, 2]\n").unwrap();
+    fs::write(&unformatted, "a:   [1,2]\n").unwrap();
+    let options = Options::default();
+
+    assert!(!format_file(&formatted, &options, true).unwrap());
+    assert!(format_file(&unformatted, &options, true).unwrap());
+    assert_eq!(
+        fs::read_to_string(&unformatted).unwrap(),
+        "a:   [1,2]\n"
+    );
+
+    assert!(!format_file(&formatted, &options, false).unwrap());
+    assert!(format_file(&unformatted, &options, false).unwrap());
+    assert_eq!(
+        fs::read_to_string(&unformatted).unwrap(),
+        "a: [1, 2]\n"
+    );
+    assert!(!format_file(&unformatted, &options, true).unwrap());
+
+    // A file that does not parse is left as it is.
+    let invalid = dir.join("invalid.yaml");
+    fs::write(&invalid, "a: [b\n").unwrap();
+    let error = format_file(&invalid, &options, false).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "2:1: did not find expected ',' or ']' \
+         (while parsing a flow sequence at 1:4)",
+    );
+    assert_eq!(fs::read_to_string(&invalid).unwrap(), "a: [b\n");
+
+    let mut entries: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    entries.sort();
+    assert_eq!(
+        entries,
+        ["formatted.yaml", "invalid.yaml", "unformatted.yaml"],
+    );
+    assert!(format_file(&dir.join("missing.yaml"), &options, false)
+        .unwrap_err()
+        .mark
+        .is_none());
+    fs::remove_dir_all(&dir).unwrap();
+}